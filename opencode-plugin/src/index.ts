@@ -83,7 +83,8 @@ function parseDecision(response: string): { block: boolean; feedback: string } {
 function writeFeedback(directory: string, sessionId: string, feedback: string): void {
   const sessionDir = join(directory, SUPEREGO_DIR, "sessions", sessionId);
   mkdirSync(sessionDir, { recursive: true });
-  writeFileSync(join(sessionDir, "feedback"), feedback);
+  // Same queue format as sg (src/feedback.rs): one JSON entry per line, appended
+  appendFileSync(join(sessionDir, "feedback.jsonl"), JSON.stringify({ message: feedback }) + "\n");
 }
 
 // Format messages for evaluation prompt
@@ -444,7 +445,7 @@ export const Superego: Plugin = async ({ directory, client }) => {
 
           if (block && feedback) {
             writeFeedback(directory, sessionId, feedback);
-            log(superegoDir, `Feedback written to .superego/sessions/${sessionId}/feedback`);
+            log(superegoDir, `Feedback written to .superego/sessions/${sessionId}/feedback.jsonl`);
 
             // Inject feedback into the original session so model sees it
             try {
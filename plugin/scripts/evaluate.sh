@@ -63,9 +63,9 @@ fi
 if [ -n "$SESSION_ID" ] && [ "$SESSION_ID" != "null" ]; then
     SESSION_DIR="$PROJECT_DIR/.superego/sessions/$SESSION_ID"
     mkdir -p "$SESSION_DIR"
-    FEEDBACK_PATH="$SESSION_DIR/feedback"
+    FEEDBACK_PATH="$SESSION_DIR/feedback.jsonl"
 else
-    FEEDBACK_PATH="$PROJECT_DIR/.superego/feedback"
+    FEEDBACK_PATH="$PROJECT_DIR/.superego/feedback.jsonl"
 fi
 
 # Skip if this is superego's own transcript (recursion prevention)
@@ -114,7 +114,8 @@ if [ -s "$FEEDBACK_PATH" ]; then
         log "Feedback already claimed by another hook"
         exit 0
     fi
-    FEEDBACK=$(cat "$TEMP_FEEDBACK")
+    # One JSON entry per line; deliver all of them (see src/feedback.rs)
+    FEEDBACK=$(jq -rs 'map(.message) | join("\n\n---\n\n")' "$TEMP_FEEDBACK")
     log "Blocking with feedback: ${FEEDBACK:0:100}..."
     rm -f "$TEMP_FEEDBACK"
 
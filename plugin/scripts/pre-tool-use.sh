@@ -47,7 +47,7 @@ else
     SESSION_DIR="$PROJECT_DIR/.superego"
     SESSION_ID=""
 fi
-FEEDBACK_PATH="$SESSION_DIR/feedback"
+FEEDBACK_PATH="$SESSION_DIR/feedback.jsonl"
 PENDING_CHANGE_PATH="$SESSION_DIR/pending_change.txt"
 LOCK_FILE="$SESSION_DIR/eval.lock"
 
@@ -100,7 +100,8 @@ run_eval() {
         local temp_feedback="$FEEDBACK_PATH.$$"
         if mv "$FEEDBACK_PATH" "$temp_feedback" 2>/dev/null; then
             local feedback
-            feedback=$(cat "$temp_feedback")
+            # One JSON entry per line; deliver all of them (see src/feedback.rs)
+            feedback=$(jq -rs 'map(.message) | join("\n\n---\n\n")' "$temp_feedback")
             rm -f "$temp_feedback"
             log "Blocking with feedback: ${feedback:0:100}..."
 
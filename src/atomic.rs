@@ -0,0 +1,80 @@
+//! Atomic file replacement for state that must never be left half-written
+//!
+//! Content goes to a temp file next to the target, which is then renamed over
+//! it. Rename is atomic on the same filesystem, so readers see either the old
+//! file or the complete new one - never a truncated mix.
+//! AIDEV-NOTE: Used for state.json and decision journal entries. Logs and the
+//! feedback queue are append-only and don't need this.
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Distinguishes concurrent writes from threads of the same process
+static WRITE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Temp file for `path`: hidden, same directory, unique per process and write
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        name,
+        std::process::id(),
+        WRITE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Replace `path` with whatever `write` produces
+/// If `write` fails, the target is untouched and the temp file is removed.
+pub fn write_with<F>(path: &Path, write: F) -> std::io::Result<()>
+where
+    F: FnOnce(&mut dyn Write) -> std::io::Result<()>,
+{
+    let temp = temp_path(path);
+    let result = (|| {
+        let mut writer = BufWriter::new(File::create(&temp)?);
+        write(&mut writer)?;
+        writer
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+        fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Replace `path` with `contents`
+pub fn write(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    write_with(path, |w| w.write_all(contents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failed_write_leaves_target_intact() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        write(&path, b"{\"complete\": true}").unwrap();
+
+        // Crash mid-serialization: half the bytes written, then an error
+        let err = write_with(&path, |w| {
+            w.write_all(b"{\"compl")?;
+            Err(std::io::Error::other("simulated crash"))
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "simulated crash");
+
+        assert_eq!(fs::read(&path).unwrap(), b"{\"complete\": true}");
+        let leftovers: Vec<_> = fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(leftovers.len(), 1, "temp file cleaned up");
+    }
+}
@@ -4,10 +4,10 @@
 
 use chrono::{DateTime, Utc};
 use serde::Serialize;
-use std::collections::HashSet;
+use std::collections::HashMap;
 
 use crate::claude::{self, ClaudeError, ClaudeOptions};
-use crate::decision::Decision;
+use crate::decision::{Decision, DecisionType};
 
 /// Statistics about decisions
 #[derive(Debug, Clone, Serialize)]
@@ -16,6 +16,21 @@ pub struct AuditStats {
     pub start_date: Option<DateTime<Utc>>,
     pub end_date: Option<DateTime<Utc>>,
     pub session_count: usize,
+    /// BLOCKs withheld for being below `min_confidence`
+    pub suppressed: usize,
+    /// BLOCKs not re-queued for repeating recent feedback (`dedupe_window_minutes`)
+    pub duplicates: usize,
+    /// Decisions per session, most first
+    pub per_session: Vec<SessionStat>,
+}
+
+/// Decision counts for one session
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionStat {
+    pub session_id: String,
+    pub decision_count: usize,
+    pub first: DateTime<Utc>,
+    pub last: DateTime<Utc>,
 }
 
 /// Full audit result with stats and analysis
@@ -25,34 +40,66 @@ pub struct AuditResult {
     pub analysis: String,
 }
 
-/// Calculate statistics from decisions
-pub fn calculate_stats(decisions: &[Decision]) -> AuditStats {
+/// Calculate statistics from decisions, each paired with the session
+/// directory it was read from (as returned by `decision::read_sessions_between`)
+/// AIDEV-NOTE: Group by the directory id, not `Decision::session_id` - that is
+/// the evaluator's per-call id, which is new on every evaluation.
+pub fn calculate_stats(decisions: &[(String, Decision)]) -> AuditStats {
     if decisions.is_empty() {
         return AuditStats {
             total: 0,
             start_date: None,
             end_date: None,
             session_count: 0,
+            suppressed: 0,
+            duplicates: 0,
+            per_session: Vec::new(),
         };
     }
 
-    // Count unique sessions
-    let sessions: HashSet<_> = decisions
-        .iter()
-        .filter_map(|d| d.session_id.as_ref())
-        .collect();
+    let mut sessions: HashMap<&str, SessionStat> = HashMap::new();
+    for (id, d) in decisions {
+        let stat = sessions.entry(id).or_insert_with(|| SessionStat {
+            session_id: id.to_string(),
+            decision_count: 0,
+            first: d.timestamp,
+            last: d.timestamp,
+        });
+        stat.decision_count += 1;
+        stat.first = stat.first.min(d.timestamp);
+        stat.last = stat.last.max(d.timestamp);
+    }
+    let mut per_session: Vec<SessionStat> = sessions.into_values().collect();
+    per_session.sort_by(|a, b| {
+        b.decision_count
+            .cmp(&a.decision_count)
+            .then(a.first.cmp(&b.first))
+    });
 
     // Decisions are already sorted by timestamp
     AuditStats {
         total: decisions.len(),
-        start_date: decisions.first().map(|d| d.timestamp),
-        end_date: decisions.last().map(|d| d.timestamp),
-        session_count: sessions.len(),
+        start_date: decisions.first().map(|(_, d)| d.timestamp),
+        end_date: decisions.last().map(|(_, d)| d.timestamp),
+        session_count: per_session.len(),
+        suppressed: decisions
+            .iter()
+            .filter(|(_, d)| d.decision_type == DecisionType::FeedbackSuppressed)
+            .count(),
+        duplicates: decisions
+            .iter()
+            .filter(|(_, d)| d.decision_type == DecisionType::FeedbackDuplicate)
+            .count(),
+        per_session,
     }
 }
 
 /// Build the prompt for Claude to analyze decisions
-fn build_audit_prompt(decisions: &[Decision]) -> String {
+/// `labels` maps session directory ids to human-readable labels (from `sg sessions label`)
+fn build_audit_prompt(
+    decisions: &[(String, Decision)],
+    labels: &HashMap<String, String>,
+) -> String {
     let mut prompt = String::from(
         "You are analyzing superego's decision history for a project.\n\n\
          Superego is a metacognitive advisor that monitors Claude Code sessions \
@@ -60,23 +107,36 @@ fn build_audit_prompt(decisions: &[Decision]) -> String {
          Below are all recorded decisions (feedback given to Claude Code):\n\n",
     );
 
-    for (i, decision) in decisions.iter().enumerate() {
+    for (i, (session, decision)) in decisions.iter().enumerate() {
         prompt.push_str(&format!("--- Decision {} ---\n", i + 1));
         prompt.push_str(&format!(
             "Timestamp: {}\n",
             decision.timestamp.format("%Y-%m-%d %H:%M UTC")
         ));
 
-        if let Some(session) = &decision.session_id {
-            // Truncate session ID for readability
-            let short_session = if session.len() > 8 {
-                &session[..8]
-            } else {
-                session
-            };
-            prompt.push_str(&format!("Session: {}...\n", short_session));
+        // Truncate session ID for readability
+        let short_session = if session.len() > 8 {
+            &session[..8]
         } else {
-            prompt.push_str("Session: (unknown)\n");
+            session
+        };
+        match labels.get(session) {
+            Some(label) => prompt.push_str(&format!("Session: {} ({}...)\n", label, short_session)),
+            None => prompt.push_str(&format!("Session: {}...\n", short_session)),
+        }
+
+        if decision.decision_type == DecisionType::FeedbackSuppressed {
+            prompt.push_str("Status: suppressed (below min_confidence, not shown to Claude)\n");
+        }
+        if decision.decision_type == DecisionType::FeedbackDuplicate {
+            prompt.push_str("Status: duplicate (repeated recent feedback, not shown again)\n");
+        }
+
+        if let Some(confidence) = &decision.confidence {
+            prompt.push_str(&format!("Confidence: {}\n", confidence));
+        }
+        if decision.drift == Some(true) {
+            prompt.push_str("Drift: off the current task\n");
         }
 
         if let Some(context) = &decision.context {
@@ -100,16 +160,21 @@ fn build_audit_prompt(decisions: &[Decision]) -> String {
 }
 
 /// Analyze decisions using Claude LLM
-pub fn analyze_decisions(decisions: &[Decision]) -> Result<String, ClaudeError> {
+pub fn analyze_decisions(
+    decisions: &[(String, Decision)],
+    labels: &HashMap<String, String>,
+    tools: Option<Vec<String>>,
+) -> Result<String, ClaudeError> {
     if decisions.is_empty() {
         return Ok("No decisions to analyze.".to_string());
     }
 
-    let prompt = build_audit_prompt(decisions);
+    let prompt = build_audit_prompt(decisions, labels);
 
     let options = ClaudeOptions {
         model: None,
         no_session_persistence: true,
+        tools,
         ..Default::default()
     };
 
@@ -120,10 +185,143 @@ pub fn analyze_decisions(decisions: &[Decision]) -> Result<String, ClaudeError>
     Ok(response.result)
 }
 
-/// Run full audit: calculate stats and analyze with LLM
-pub fn run_audit(decisions: &[Decision]) -> Result<AuditResult, ClaudeError> {
+/// `analysis` when the LLM pass is skipped (`sg audit --no-llm`)
+pub const NO_LLM_ANALYSIS: &str = "LLM analysis skipped (--no-llm).";
+
+/// Run full audit: calculate stats and, if `with_llm`, analyze with LLM
+/// (limited to `tools`, config `superego_tools`)
+pub fn run_audit(
+    decisions: &[(String, Decision)],
+    labels: &HashMap<String, String>,
+    with_llm: bool,
+    tools: Option<Vec<String>>,
+) -> Result<AuditResult, ClaudeError> {
     let stats = calculate_stats(decisions);
-    let analysis = analyze_decisions(decisions)?;
+    let analysis = if with_llm {
+        analyze_decisions(decisions, labels, tools)?
+    } else {
+        NO_LLM_ANALYSIS.to_string()
+    };
 
     Ok(AuditResult { stats, analysis })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decision::{self, Journal};
+    use crate::sessions;
+    use tempfile::tempdir;
+
+    fn decision(session: Option<&str>, ts: &str) -> Decision {
+        Decision {
+            timestamp: ts.parse().unwrap(),
+            session_id: session.map(|s| s.to_string()),
+            decision_type: DecisionType::FeedbackDelivered,
+            context: None,
+            trigger: None,
+            confidence: None,
+            drift: None,
+            tool_uses: Vec::new(),
+        }
+    }
+
+    fn in_session(dir: &str, d: Decision) -> (String, Decision) {
+        (dir.to_string(), d)
+    }
+
+    #[test]
+    fn test_calculate_stats_per_session() {
+        let decisions = vec![
+            in_session("a", decision(Some("e1"), "2025-01-10T09:00:00Z")),
+            in_session("b", decision(Some("e2"), "2025-01-10T10:00:00Z")),
+            in_session("b", decision(None, "2025-01-10T11:00:00Z")),
+            in_session("b", decision(Some("e3"), "2025-01-11T12:00:00Z")),
+        ];
+
+        let stats = calculate_stats(&decisions);
+        assert_eq!(stats.total, 4);
+        assert_eq!(stats.session_count, 2);
+        let per_session: Vec<_> = stats
+            .per_session
+            .iter()
+            .map(|s| (s.session_id.as_str(), s.decision_count))
+            .collect();
+        assert_eq!(per_session, vec![("b", 3), ("a", 1)]);
+        assert_eq!(stats.per_session[0].first, decisions[1].1.timestamp);
+        assert_eq!(stats.per_session[0].last, decisions[3].1.timestamp);
+        assert!(calculate_stats(&[]).per_session.is_empty());
+
+        let duplicate = Decision::feedback_duplicate(None, "Again".to_string(), None);
+        assert_eq!(calculate_stats(&[in_session("a", duplicate)]).duplicates, 1);
+    }
+
+    /// Decisions from real session dirs group by the dir, not the evaluator id
+    #[test]
+    fn test_calculate_stats_groups_by_session_dir() {
+        let dir = tempdir().unwrap();
+        let superego_dir = dir.path();
+        let journal = |id: &str| Journal::new(&superego_dir.join("sessions").join(id));
+        for (i, ts) in ["09", "10", "11"].iter().enumerate() {
+            let d = decision(
+                Some(&format!("eval-a{}", i)),
+                &format!("2025-01-10T{}:00:00Z", ts),
+            );
+            journal("session-a").write(&d).unwrap();
+        }
+        for (i, ts) in ["12", "13"].iter().enumerate() {
+            let d = decision(
+                Some(&format!("eval-b{}", i)),
+                &format!("2025-01-10T{}:00:00Z", ts),
+            );
+            journal("session-b").write(&d).unwrap();
+        }
+
+        let decisions = decision::read_sessions_between(superego_dir, None, None).unwrap();
+        let stats = calculate_stats(&decisions);
+        assert_eq!(stats.total, 5);
+        assert_eq!(stats.session_count, 2);
+        let per_session: Vec<_> = stats
+            .per_session
+            .iter()
+            .map(|s| (s.session_id.as_str(), s.decision_count))
+            .collect();
+        assert_eq!(per_session, vec![("session-a", 3), ("session-b", 2)]);
+    }
+
+    /// A label set on a session dir shows up in the audit prompt and stats
+    #[test]
+    fn test_labeled_session_dir_in_audit() {
+        let dir = tempdir().unwrap();
+        let superego_dir = dir.path();
+        let session_dir = superego_dir.join("sessions").join("session-abcdef123");
+        Journal::new(&session_dir)
+            .write(&decision(Some("eval-1"), "2025-01-10T09:00:00Z"))
+            .unwrap();
+        sessions::set_label(superego_dir, "session-abcdef123", Some("auth refactor")).unwrap();
+
+        let decisions = decision::read_sessions_between(superego_dir, None, None).unwrap();
+        let labels = sessions::labels(superego_dir);
+        let prompt = build_audit_prompt(&decisions, &labels);
+        assert!(prompt.contains("Session: auth refactor (session-...)"));
+
+        let stats = calculate_stats(&decisions);
+        assert_eq!(
+            labels
+                .get(&stats.per_session[0].session_id)
+                .map(String::as_str),
+            Some("auth refactor")
+        );
+    }
+
+    #[test]
+    fn test_run_audit_without_llm() {
+        let decisions = vec![in_session(
+            "a",
+            decision(Some("e1"), "2025-01-10T09:00:00Z"),
+        )];
+        let result = run_audit(&decisions, &HashMap::new(), false, None).unwrap();
+        assert_eq!(result.stats.total, 1);
+        assert_eq!(result.analysis, NO_LLM_ANALYSIS);
+    }
+}
@@ -0,0 +1,358 @@
+//! Batch evaluation of a directory of transcripts
+//!
+//! `sg evaluate-dir` reviews every Claude Code, Codex, Gemini or aider transcript
+//! modified since a cutoff and emits an aggregate report - e.g. a nightly CI job over
+//! yesterday's agent sessions.
+//! AIDEV-NOTE: Batch runs are stateless. Each file is evaluated in full; no
+//! last_evaluated cursor, feedback queue or decision journal is touched.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::config::Config;
+use crate::evaluate;
+use crate::llm::{self, InvokeOptions};
+use crate::prompts;
+use crate::transcript;
+
+/// Error type for batch evaluation
+#[derive(Debug)]
+pub enum BatchError {
+    Io(std::io::Error),
+    InvalidSince(String),
+}
+
+impl std::fmt::Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchError::Io(e) => write!(f, "IO error: {}", e),
+            BatchError::InvalidSince(s) => write!(
+                f,
+                "Invalid --since '{}' (use RFC 3339, YYYY-MM-DD, or a duration like 24h/7d)",
+                s
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+impl From<std::io::Error> for BatchError {
+    fn from(e: std::io::Error) -> Self {
+        BatchError::Io(e)
+    }
+}
+
+/// Result of evaluating one transcript file
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReport {
+    pub path: String,
+    pub has_concerns: bool,
+    pub feedback: String,
+    pub cost_usd: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Aggregate report over all evaluated files
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchReport {
+    pub files: Vec<FileReport>,
+    pub total_files: usize,
+    pub files_with_concerns: usize,
+    pub errors: usize,
+    pub total_cost_usd: f64,
+}
+
+/// LLM backend: (system_prompt, message) -> (response text, cost in USD)
+pub type Backend = dyn Fn(&str, &str) -> Result<(String, f64), String> + Sync;
+
+/// Default backend: the config `backend` CLI, invoked with the same options
+/// as `sg review` (model, timeout, retries, prompt cache, `superego_tools`)
+pub fn llm_backend(config: &Config) -> impl Fn(&str, &str) -> Result<(String, f64), String> + Sync {
+    let backend = config.backend;
+    let options = InvokeOptions {
+        model: config.model.clone(),
+        timeout_ms: config.timeout_ms,
+        no_session_persistence: true,
+        disable_prompt_cache: !config.prompt_cache,
+        max_retries: config.max_retries,
+        tools: config.superego_tools.clone(),
+        ..Default::default()
+    };
+    move |system_prompt, message| {
+        llm::for_backend(backend)
+            .invoke(system_prompt, message, &options)
+            .map(|r| (r.result, r.cost_usd))
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Parse `--since`: RFC 3339, a date (midnight UTC), or a relative duration (30m, 24h, 7d)
+pub fn parse_since(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, BatchError> {
+    let value = value.trim();
+    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        if let Some(midnight) = date.and_hms_opt(0, 0, 0) {
+            return Ok(midnight.and_utc());
+        }
+    }
+
+    let invalid = || BatchError::InvalidSince(value.to_string());
+    let (split, _) = value.char_indices().last().ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let duration = match unit {
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        _ => return Err(invalid()),
+    };
+    Ok(now - duration)
+}
+
+/// Find transcripts under `dir` modified at or after `since`, sorted by path:
+/// .jsonl files (Claude Code, Codex), Gemini sessions and aider chat histories
+pub fn find_transcripts(dir: &Path, since: Option<DateTime<Utc>>) -> std::io::Result<Vec<PathBuf>> {
+    fn visit(dir: &Path, since: Option<SystemTime>, out: &mut Vec<PathBuf>) -> std::io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                visit(&path, since, out)?;
+            } else if path.extension().is_some_and(|e| e == "jsonl")
+                || transcript::gemini::is_gemini_format(&path)
+                || transcript::aider::is_aider_format(&path)
+            {
+                let modified = path.metadata()?.modified()?;
+                if since.is_none_or(|s| modified >= s) {
+                    out.push(path);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    visit(dir, since.map(SystemTime::from), &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+/// Load the full conversation from a transcript, auto-detecting its format
+/// (as evaluate-llm does). Returns (agent name, formatted context); empty
+/// context means nothing to review.
+fn load_context(
+    path: &Path,
+    config: &Config,
+) -> Result<(&'static str, String), transcript::TranscriptError> {
+    if let Some(agent) = evaluate::read_agent_context(path, &config.context_limits())? {
+        return Ok((agent.agent, agent.context));
+    }
+
+    let entries = transcript::read_transcript(path)?;
+    let mut messages = transcript::get_messages_since(&entries, None, None);
+    if config.exclude_subagents {
+        messages = transcript::exclude_subagents(messages);
+    }
+    let (context, _) = transcript::format_context_within(
+        &messages,
+        config.max_context_chars,
+        &transcript::FeedbackEchoes::default(),
+    );
+    Ok(("Claude Code", context))
+}
+
+/// Evaluate a single transcript file
+fn evaluate_file(
+    path: &Path,
+    system_prompt: &str,
+    config: &Config,
+    backend: &Backend,
+) -> FileReport {
+    let mut report = FileReport {
+        path: path.display().to_string(),
+        has_concerns: false,
+        feedback: "No concerns.".to_string(),
+        cost_usd: 0.0,
+        error: None,
+    };
+
+    let (agent, context) = match load_context(path, config) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            report.error = Some(e.to_string());
+            return report;
+        }
+    };
+    if context.trim().is_empty() {
+        return report;
+    }
+
+    let message = format!(
+        "Review the following {} conversation and provide feedback.\n\n\
+        --- CONVERSATION ---\n{}\n--- END CONVERSATION ---",
+        agent, context
+    );
+
+    match backend(system_prompt, &message) {
+        Ok((response, cost)) => {
+            let (has_concerns, feedback, _) = evaluate::parse_decision_response(response.trim());
+            report.has_concerns = has_concerns;
+            report.feedback = feedback;
+            report.cost_usd = cost;
+        }
+        Err(e) => report.error = Some(e),
+    }
+    report
+}
+
+/// Evaluate every transcript in `dir` modified since `since`
+/// AIDEV-NOTE: Bounded worker pool (scoped threads pulling from a shared index);
+/// report order matches the sorted file order regardless of completion order.
+pub fn evaluate_dir(
+    superego_dir: &Path,
+    dir: &Path,
+    since: Option<DateTime<Utc>>,
+    concurrency: usize,
+    backend: &Backend,
+) -> Result<BatchReport, BatchError> {
+    let files = find_transcripts(dir, since)?;
+
+    let config = Config::load(superego_dir);
+    let system_prompt = evaluate::load_system_prompt(superego_dir)?;
+    let system_prompt = prompts::with_focus(&system_prompt, config.default_focus.as_deref());
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<FileReport>>> = Mutex::new(vec![None; files.len()]);
+    let workers = concurrency.clamp(1, files.len().max(1));
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::SeqCst);
+                let Some(path) = files.get(idx) else {
+                    break;
+                };
+                let report = evaluate_file(path, &system_prompt, &config, backend);
+                results.lock().unwrap()[idx] = Some(report);
+            });
+        }
+    });
+
+    let files: Vec<FileReport> = results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect();
+
+    Ok(BatchReport {
+        total_files: files.len(),
+        files_with_concerns: files.iter().filter(|f| f.has_concerns).count(),
+        errors: files.iter().filter(|f| f.error.is_some()).count(),
+        total_cost_usd: files.iter().map(|f| f.cost_usd).sum(),
+        files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const CLAUDE_FIXTURE: &str = r#"{"type":"user","uuid":"a","sessionId":"s1","timestamp":"2025-01-15T10:00:00Z","message":{"role":"user","content":"Delete prod with rm -rf"}}"#;
+    const CODEX_FIXTURE: &str = r#"{"timestamp":"2025-11-04T00:16:00.102Z","type":"event_msg","payload":{"type":"user_message","message":"Add a unit test","images":[]}}"#;
+    const AIDER_FIXTURE: &str =
+        "# aider chat started at 2025-01-15 10:00:00\n\n#### Drop the cache with rm -rf\n\nDone.\n";
+
+    fn mock_backend(_system: &str, message: &str) -> Result<(String, f64), String> {
+        if message.contains("rm -rf") {
+            Ok(("DECISION: BLOCK\n\nDestructive command.".to_string(), 0.02))
+        } else {
+            Ok(("DECISION: ALLOW\n\nLooks fine.".to_string(), 0.01))
+        }
+    }
+
+    #[test]
+    fn test_evaluate_dir_aggregates_reports() {
+        let superego = tempdir().unwrap();
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("claude.jsonl"), CLAUDE_FIXTURE).unwrap();
+        fs::create_dir_all(dir.path().join("nested")).unwrap();
+        fs::write(dir.path().join("nested/codex.jsonl"), CODEX_FIXTURE).unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a transcript").unwrap();
+
+        fs::write(dir.path().join(".aider.chat.history.md"), AIDER_FIXTURE).unwrap();
+
+        let report = evaluate_dir(superego.path(), dir.path(), None, 2, &mock_backend).unwrap();
+
+        assert_eq!(report.total_files, 3);
+        assert_eq!(report.files_with_concerns, 2);
+        assert_eq!(report.errors, 0);
+        assert!((report.total_cost_usd - 0.05).abs() < 1e-9);
+
+        assert!(report.files[0].path.ends_with(".aider.chat.history.md"));
+        assert!(report.files[0].has_concerns);
+        assert!(report.files[1].path.ends_with("claude.jsonl"));
+        assert!(report.files[1].has_concerns);
+        assert_eq!(report.files[1].feedback, "Destructive command.");
+        assert!(report.files[2].path.ends_with("codex.jsonl"));
+        assert!(!report.files[2].has_concerns);
+    }
+
+    #[test]
+    fn test_evaluate_dir_records_backend_errors() {
+        let superego = tempdir().unwrap();
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("claude.jsonl"), CLAUDE_FIXTURE).unwrap();
+
+        let failing =
+            |_: &str, _: &str| -> Result<(String, f64), String> { Err("timed out".to_string()) };
+        let report = evaluate_dir(superego.path(), dir.path(), None, 4, &failing).unwrap();
+        assert_eq!(report.errors, 1);
+        assert_eq!(report.files[0].error.as_deref(), Some("timed out"));
+    }
+
+    #[test]
+    fn test_find_transcripts_since_filters_old_files() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.jsonl"), CLAUDE_FIXTURE).unwrap();
+
+        let future = Utc::now() + Duration::hours(1);
+        assert!(find_transcripts(dir.path(), Some(future))
+            .unwrap()
+            .is_empty());
+        assert_eq!(find_transcripts(dir.path(), None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let now = DateTime::parse_from_rfc3339("2025-01-15T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            parse_since("24h", now).unwrap().to_rfc3339(),
+            "2025-01-14T12:00:00+00:00"
+        );
+        assert_eq!(
+            parse_since("2025-01-10", now).unwrap().to_rfc3339(),
+            "2025-01-10T00:00:00+00:00"
+        );
+        assert_eq!(
+            parse_since("2025-01-15T08:00:00Z", now)
+                .unwrap()
+                .to_rfc3339(),
+            "2025-01-15T08:00:00+00:00"
+        );
+        assert!(parse_since("yesterday", now).is_err());
+        assert!(parse_since("", now).is_err());
+        assert!(parse_since("7д", now).is_err());
+    }
+}
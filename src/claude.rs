@@ -6,10 +6,15 @@
 
 use serde::Deserialize;
 use serde_json::Value;
+use std::io::{BufRead, BufReader, Read};
 use std::process::{Command, Stdio};
 use std::thread;
 use std::time::{Duration, Instant};
 
+use crate::diag;
+use crate::llm::{InvokeOptions, LlmBackend, LlmError, LlmResponse};
+use crate::signals;
+
 /// Response from Claude CLI in JSON format
 #[derive(Debug, Clone, Deserialize)]
 pub struct ClaudeResponse {
@@ -80,6 +85,12 @@ pub enum ClaudeError {
     ParseError(serde_json::Error),
     IoError(std::io::Error),
     Timeout(Duration),
+    /// SIGTERM/SIGINT arrived while waiting; the CLI was killed
+    Interrupted(i32),
+    /// API rate limit or plan usage limit hit
+    RateLimited {
+        resets_in_seconds: Option<u64>,
+    },
 }
 
 impl std::fmt::Display for ClaudeError {
@@ -89,6 +100,14 @@ impl std::fmt::Display for ClaudeError {
             ClaudeError::ParseError(e) => write!(f, "Failed to parse Claude response: {}", e),
             ClaudeError::IoError(e) => write!(f, "IO error: {}", e),
             ClaudeError::Timeout(d) => write!(f, "Claude timed out after {:?}", d),
+            ClaudeError::Interrupted(sig) => write!(f, "Interrupted by signal {}", sig),
+            ClaudeError::RateLimited { resets_in_seconds } => {
+                if let Some(secs) = resets_in_seconds {
+                    write!(f, "Rate limited (resets in {} minutes)", secs.div_ceil(60))
+                } else {
+                    write!(f, "Rate limited")
+                }
+            }
         }
     }
 }
@@ -107,9 +126,20 @@ impl From<serde_json::Error> for ClaudeError {
     }
 }
 
+/// Tools superego's Claude calls may use unless config `superego_tools` says otherwise
+/// AIDEV-NOTE: Bash lets the evaluator run commands (tests, git log); Read,Glob,Grep
+/// alone keeps inspection read-only.
+pub const DEFAULT_TOOLS: &[&str] = &["Bash", "Read", "Glob", "Grep"];
+
 /// Default timeout: 5 minutes
 const DEFAULT_TIMEOUT_MS: u64 = 300_000;
 
+/// First retry delay; doubles per attempt up to `MAX_RETRY_DELAY`
+const BASE_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Cap on the delay between retries
+pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
 /// Options for Claude invocation
 #[derive(Debug, Clone, Default)]
 pub struct ClaudeOptions {
@@ -120,10 +150,170 @@ pub struct ClaudeOptions {
     pub no_session_persistence: bool,
     /// Timeout in milliseconds (default: 5 minutes)
     pub timeout_ms: Option<u64>,
+    /// Opt out of Anthropic prompt caching (config `prompt_cache: false`)
+    pub disable_prompt_cache: bool,
+    /// Extra attempts after a timeout or transient failure (default: 0)
+    pub max_retries: u32,
+    /// Tools the CLI may use (default: DEFAULT_TOOLS)
+    pub tools: Option<Vec<String>>,
+    /// Echo the CLI's progress (assistant text, tool calls) to stderr as it
+    /// arrives; for interactive commands, hooks stay quiet (default: false)
+    pub stream: bool,
+}
+
+/// Recognize a rate-limit failure in the CLI's error text
+/// AIDEV-NOTE: Two shapes seen: API 429s ("API Error: 429 {...rate_limit_error...}")
+/// and the plan usage limit, "Claude AI usage limit reached|<reset epoch seconds>".
+/// Only a real 429 status or a `rate_limit_error` in the JSON error counts: a
+/// bare "429" also appears in ordinary errors ("prompt is too long: 204290
+/// tokens"), and misreading those would skip the same context forever.
+fn parse_rate_limit(error_text: &str, now_epoch: i64) -> Option<ClaudeError> {
+    let lower = error_text.to_lowercase();
+    let limited = lower.contains("usage limit reached")
+        || has_http_429(&lower)
+        || json_errors(error_text).iter().any(is_rate_limit_error);
+    if !limited {
+        return None;
+    }
+
+    let leading_number = |s: &str| -> Option<u64> {
+        s.trim_start()
+            .split(|c: char| !c.is_ascii_digit())
+            .next()
+            .and_then(|n| n.parse().ok())
+    };
+    let resets_in_seconds = if let Some(idx) = lower.find("resets_in_seconds\":") {
+        leading_number(&lower[idx + "resets_in_seconds\":".len()..])
+    } else if let Some(idx) = lower.find("usage limit reached|") {
+        leading_number(&lower[idx + "usage limit reached|".len()..])
+            .map(|reset_at| (reset_at as i64 - now_epoch).max(0) as u64)
+    } else {
+        None
+    };
+    Some(ClaudeError::RateLimited { resets_in_seconds })
+}
+
+/// Whether lowercased error text reports an HTTP 429 status, as opposed to
+/// merely containing the digits (token counts, byte sizes, IDs)
+fn has_http_429(lower: &str) -> bool {
+    [
+        "api error: 429",
+        "429 too many requests",
+        "http 429",
+        "status 429",
+        "status: 429",
+        "status code 429",
+        "status code: 429",
+        "\"status\":429",
+        "\"status\": 429",
+    ]
+    .iter()
+    .any(|sig| lower.contains(sig))
+}
+
+/// JSON objects embedded in the error text, e.g. the API body after "API Error: 429"
+fn json_errors(error_text: &str) -> Vec<Value> {
+    error_text
+        .match_indices('{')
+        .filter_map(|(idx, _)| {
+            serde_json::Deserializer::from_str(&error_text[idx..])
+                .into_iter::<Value>()
+                .next()
+                .and_then(Result::ok)
+        })
+        .collect()
+}
+
+/// Whether a JSON error body carries a `rate_limit_error` type or code
+fn is_rate_limit_error(body: &Value) -> bool {
+    let error = body.get("error").unwrap_or(body);
+    ["type", "code"]
+        .iter()
+        .any(|key| error.get(key).and_then(Value::as_str) == Some("rate_limit_error"))
+        || body.get("status").and_then(Value::as_u64) == Some(429)
+}
+
+/// Check if an error is worth retrying: a timeout, or a failure that looks
+/// like a network hiccup or an overloaded API rather than a bad request
+fn is_transient(err: &ClaudeError) -> bool {
+    match err {
+        ClaudeError::Timeout(_) => true,
+        ClaudeError::CommandFailed(msg) => {
+            let lower = msg.to_lowercase();
+            [
+                "overloaded",
+                "internal server error",
+                "bad gateway",
+                "service unavailable",
+                "gateway timeout",
+                "econnreset",
+                "econnrefused",
+                "etimedout",
+                "socket hang up",
+                "network error",
+                "connection error",
+            ]
+            .iter()
+            .any(|sig| lower.contains(sig))
+        }
+        _ => false,
+    }
+}
+
+/// Delay before retry number `attempt` (0-based): exponential, capped
+fn retry_delay(attempt: u32) -> Duration {
+    BASE_RETRY_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_RETRY_DELAY)
+}
+
+/// Sleep for `delay`, returning early with `Interrupted` on SIGTERM/SIGINT
+fn wait_for_retry(delay: Duration) -> Result<(), ClaudeError> {
+    let start = Instant::now();
+    while start.elapsed() < delay {
+        if let Some(signal) = signals::interrupted() {
+            return Err(ClaudeError::Interrupted(signal));
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    Ok(())
+}
+
+/// Run `call`, retrying transient failures up to `max_retries` times
+fn with_retries<F, W>(
+    max_retries: u32,
+    mut call: F,
+    mut wait: W,
+) -> Result<ClaudeResponse, ClaudeError>
+where
+    F: FnMut() -> Result<ClaudeResponse, ClaudeError>,
+    W: FnMut(Duration) -> Result<(), ClaudeError>,
+{
+    let mut attempt = 0;
+    loop {
+        match call() {
+            Err(e) if attempt < max_retries && is_transient(&e) => {
+                let delay = retry_delay(attempt);
+                diag::warn(&format!(
+                    "{} - retrying in {}s ({}/{})",
+                    e,
+                    delay.as_secs(),
+                    attempt + 1,
+                    max_retries
+                ));
+                wait(delay)?;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
 }
 
 /// Invoke Claude CLI with a system prompt and user message
 ///
+/// Timeouts and transient failures are retried `options.max_retries` times
+/// with exponential backoff.
+///
 /// # Arguments
 /// * `system_prompt` - System prompt for Claude
 /// * `message` - User message / context
@@ -137,18 +327,41 @@ pub fn invoke(
     message: &str,
     options: ClaudeOptions,
 ) -> Result<ClaudeResponse, ClaudeError> {
+    let max_retries = options.max_retries;
+    with_retries(
+        max_retries,
+        || invoke_once(system_prompt, message, options.clone()),
+        wait_for_retry,
+    )
+}
+
+/// Build the Claude CLI command for an invocation
+fn build_command(system_prompt: &str, message: &str, options: &ClaudeOptions) -> Command {
     let mut cmd = Command::new("claude");
 
     // Non-interactive mode with JSON output
-    cmd.arg("-p").arg("--output-format").arg("json");
+    // AIDEV-NOTE: Plain json is written only on exit; stream-json emits one
+    // event per line as the CLI works (and requires --verbose with -p).
+    if options.stream {
+        cmd.arg("-p")
+            .arg("--output-format")
+            .arg("stream-json")
+            .arg("--verbose");
+    } else {
+        cmd.arg("-p").arg("--output-format").arg("json");
+    }
 
     // Enable tools for superego to inspect the codebase
-    cmd.arg("--tools").arg("Bash,Read,Glob,Grep");
+    let tools = match &options.tools {
+        Some(tools) => tools.join(","),
+        None => DEFAULT_TOOLS.join(","),
+    };
+    cmd.arg("--tools").arg(tools);
 
     // System prompt
     cmd.arg("--system-prompt").arg(system_prompt);
 
-    if let Some(model) = options.model {
+    if let Some(model) = &options.model {
         cmd.arg("--model").arg(model);
     }
 
@@ -173,48 +386,148 @@ pub fn invoke(
     // Without this, wm compile runs on sg's "Review the following..." prompts.
     cmd.env("WM_DISABLED", "1");
 
+    // AIDEV-NOTE: The Claude CLI applies prompt caching automatically (the
+    // system prompt is a stable prefix across evaluations); it has no flag to
+    // request it, only this env var to turn it off.
+    if options.disable_prompt_cache {
+        cmd.env("DISABLE_PROMPT_CACHING", "1");
+    }
+
     // AIDEV-NOTE: Must pipe stdout/stderr to capture output, otherwise
     // wait_with_output() returns empty and JSON parsing fails with EOF.
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
     cmd.stdin(Stdio::null());
 
+    // Own process group, so an interrupt can take down the CLI's children too
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
+    cmd
+}
+
+/// One line of progress for a stream-json event: assistant text, or a tool call
+/// with its main argument (None for events worth no line, e.g. tool results)
+fn progress_line(event: &Value) -> Option<String> {
+    if event.get("type")?.as_str()? != "assistant" {
+        return None;
+    }
+    let lines: Vec<String> = event
+        .get("message")?
+        .get("content")?
+        .as_array()?
+        .iter()
+        .filter_map(|block| match block.get("type")?.as_str()? {
+            "text" => Some(block.get("text")?.as_str()?.trim().to_string()),
+            "tool_use" => {
+                let name = block.get("name")?.as_str()?;
+                let input = block.get("input");
+                let arg = ["command", "file_path", "pattern", "path"]
+                    .iter()
+                    .find_map(|key| input?.get(key)?.as_str());
+                Some(match arg {
+                    Some(arg) => format!("→ {}: {}", name, arg),
+                    None => format!("→ {}", name),
+                })
+            }
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Read stream-json events until EOF, echoing progress to stderr
+/// Returns the events as a JSON array, the shape `parse_claude_response` takes.
+fn stream_events(stdout: impl Read) -> String {
+    let mut events = Vec::new();
+    for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+        let Ok(event) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        if let Some(progress) = progress_line(&event) {
+            eprintln!("{}", progress);
+        }
+        events.push(event);
+    }
+    Value::Array(events).to_string()
+}
+
+/// The `result` text of a CLI response, single object or event array
+fn response_result(stdout: &str) -> Option<String> {
+    let json = match serde_json::from_str::<Value>(stdout).ok()? {
+        Value::Array(events) => events
+            .into_iter()
+            .rev()
+            .find(|e| e.get("type").and_then(|t| t.as_str()) == Some("result"))?,
+        json => json,
+    };
+    json.get("result")?.as_str().map(|r| r.to_string())
+}
+
+/// Single Claude CLI invocation (no retry)
+fn invoke_once(
+    system_prompt: &str,
+    message: &str,
+    options: ClaudeOptions,
+) -> Result<ClaudeResponse, ClaudeError> {
+    let mut cmd = build_command(system_prompt, message, &options);
+
     // Execute with timeout (default 5 minutes)
     let timeout = Duration::from_millis(options.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
     let mut child = cmd.spawn()?;
     let start = Instant::now();
 
+    // Streaming: a reader thread echoes progress and collects the events
+    let mut streamed = if options.stream {
+        child
+            .stdout
+            .take()
+            .map(|stdout| thread::spawn(move || stream_events(stdout)))
+    } else {
+        None
+    };
+
     // Poll for completion with timeout
     loop {
         match child.try_wait()? {
             Some(status) => {
                 // Process exited - collect output
                 let output = child.wait_with_output()?;
-                let stdout = String::from_utf8_lossy(&output.stdout);
+                let stdout = match streamed.take() {
+                    Some(reader) => reader.join().unwrap_or_default(),
+                    None => String::from_utf8_lossy(&output.stdout).to_string(),
+                };
                 let stderr = String::from_utf8_lossy(&output.stderr);
 
                 if !status.success() {
                     // Claude CLI returns errors in JSON stdout with is_error: true
                     // Try to parse stdout to get a more helpful error message
-                    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&stdout) {
-                        if let Some(result) = json.get("result").and_then(|r| r.as_str()) {
-                            return Err(ClaudeError::CommandFailed(result.to_string()));
-                        }
-                    }
+                    let json_result = response_result(&stdout);
                     // Fall back to stderr if we can't parse stdout
-                    let error_msg = if stderr.is_empty() {
-                        stdout.to_string()
-                    } else {
-                        stderr.to_string()
+                    let error_msg = match json_result {
+                        Some(result) => result,
+                        None if stderr.is_empty() => stdout.to_string(),
+                        None => stderr.to_string(),
                     };
+                    let now = chrono::Utc::now().timestamp();
+                    if let Some(limited) = parse_rate_limit(&error_msg, now) {
+                        return Err(limited);
+                    }
                     return Err(ClaudeError::CommandFailed(error_msg));
                 }
                 return parse_claude_response(&stdout);
             }
             None => {
+                if let Some(signal) = signals::interrupted() {
+                    signals::kill_process_group(&mut child);
+                    return Err(ClaudeError::Interrupted(signal));
+                }
                 if start.elapsed() > timeout {
-                    let _ = child.kill();
-                    let _ = child.wait(); // Reap the process
+                    signals::kill_process_group(&mut child);
                     return Err(ClaudeError::Timeout(timeout));
                 }
                 thread::sleep(Duration::from_millis(100));
@@ -223,10 +536,129 @@ pub fn invoke(
     }
 }
 
+/// The Claude Code CLI (`claude -p`)
+pub struct ClaudeBackend;
+
+impl LlmBackend for ClaudeBackend {
+    fn invoke(
+        &self,
+        system_prompt: &str,
+        message: &str,
+        options: &InvokeOptions,
+    ) -> Result<LlmResponse, LlmError> {
+        let options = ClaudeOptions {
+            model: options.model.clone(),
+            session_id: None,
+            no_session_persistence: options.no_session_persistence,
+            timeout_ms: options.timeout_ms,
+            disable_prompt_cache: options.disable_prompt_cache,
+            max_retries: options.max_retries,
+            tools: options.tools.clone(),
+            stream: options.stream,
+        };
+        let response = invoke(system_prompt, message, options)?;
+        Ok(LlmResponse {
+            result: response.result,
+            session_id: response.session_id,
+            cost_usd: response.total_cost_usd,
+            tokens: None,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn has_env(cmd: &Command, key: &str) -> bool {
+        cmd.get_envs().any(|(k, v)| k == key && v.is_some())
+    }
+
+    #[test]
+    fn test_build_command_prompt_cache_opt_out() {
+        let cached = build_command("sys", "msg", &ClaudeOptions::default());
+        assert!(!has_env(&cached, "DISABLE_PROMPT_CACHING"));
+        assert!(has_env(&cached, "SUPEREGO_DISABLED"));
+
+        let options = ClaudeOptions {
+            disable_prompt_cache: true,
+            ..Default::default()
+        };
+        let uncached = build_command("sys", "msg", &options);
+        assert!(has_env(&uncached, "DISABLE_PROMPT_CACHING"));
+    }
+
+    #[test]
+    fn test_build_command_tools() {
+        let tools_arg = |options: &ClaudeOptions| -> String {
+            let cmd = build_command("sys", "msg", options);
+            let args: Vec<_> = cmd.get_args().collect();
+            let idx = args.iter().position(|a| *a == "--tools").unwrap();
+            args[idx + 1].to_string_lossy().to_string()
+        };
+        assert_eq!(tools_arg(&ClaudeOptions::default()), "Bash,Read,Glob,Grep");
+
+        let read_only = ClaudeOptions {
+            tools: Some(vec![
+                "Read".to_string(),
+                "Glob".to_string(),
+                "Grep".to_string(),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(tools_arg(&read_only), "Read,Glob,Grep");
+    }
+
+    #[test]
+    fn test_build_command_stream() {
+        let args = |options: &ClaudeOptions| -> Vec<String> {
+            build_command("sys", "msg", options)
+                .get_args()
+                .map(|a| a.to_string_lossy().to_string())
+                .collect()
+        };
+        let quiet = args(&ClaudeOptions::default());
+        assert!(quiet.windows(2).any(|w| w == ["--output-format", "json"]));
+
+        let streaming = args(&ClaudeOptions {
+            stream: true,
+            ..Default::default()
+        });
+        assert!(streaming
+            .windows(3)
+            .any(|w| w == ["--output-format", "stream-json", "--verbose"]));
+    }
+
+    #[test]
+    fn test_stream_events() {
+        let stdout = concat!(
+            r#"{"type":"system","subtype":"init","session_id":"s1"}"#,
+            "\n",
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Checking the tests."},{"type":"tool_use","name":"Bash","input":{"command":"cargo test"}}]}}"#,
+            "\n",
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","content":"ok"}]}}"#,
+            "\nnot json\n",
+            r#"{"type":"result","result":"DECISION: ALLOW","session_id":"s1","total_cost_usd":0.02}"#,
+            "\n"
+        );
+
+        let events: Vec<Value> = stdout
+            .lines()
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .collect();
+        let progress: Vec<String> = events.iter().filter_map(progress_line).collect();
+        assert_eq!(progress, vec!["Checking the tests.\n→ Bash: cargo test"]);
+
+        let collected = stream_events(stdout.as_bytes());
+        let response = parse_claude_response(&collected).unwrap();
+        assert_eq!(response.result, "DECISION: ALLOW");
+        assert_eq!(response.session_id, "s1");
+        assert_eq!(
+            response_result(&collected).as_deref(),
+            Some("DECISION: ALLOW")
+        );
+    }
+
     /// Test that stdout is properly captured when piped.
     /// This verifies the fix for the EOF parsing bug where stdout wasn't piped.
     #[test]
@@ -417,6 +849,124 @@ mod tests {
         assert_eq!(response.session_id, "s1");
     }
 
+    /// Test that rate limits are recognized, with the reset window when given
+    #[test]
+    fn test_parse_rate_limit() {
+        let now = 1_700_000_000;
+
+        let usage = format!("Claude AI usage limit reached|{}", now + 3600);
+        match parse_rate_limit(&usage, now) {
+            Some(ClaudeError::RateLimited { resets_in_seconds }) => {
+                assert_eq!(resets_in_seconds, Some(3600))
+            }
+            other => panic!("expected RateLimited, got {:?}", other),
+        }
+
+        let api = r#"API Error: 429 {"type":"error","error":{"type":"rate_limit_error","resets_in_seconds": 90}}"#;
+        let err = parse_rate_limit(api, now).unwrap();
+        assert_eq!(err.to_string(), "Rate limited (resets in 2 minutes)");
+
+        let bare = parse_rate_limit("API Error: 429 Too Many Requests", now).unwrap();
+        assert_eq!(bare.to_string(), "Rate limited");
+
+        let typed = r#"{"type":"error","error":{"type":"rate_limit_error","message":"slow down"}}"#;
+        assert!(parse_rate_limit(typed, now).is_some());
+
+        assert!(parse_rate_limit("Prompt is too long", now).is_none());
+    }
+
+    /// Test that ordinary errors which merely mention "429" or rate limits are
+    /// not mistaken for a rate limit
+    #[test]
+    fn test_parse_rate_limit_ignores_incidental_429() {
+        let now = 1_700_000_000;
+        for text in [
+            "prompt is too long: 204290 tokens > 200000 maximum",
+            "Error: file src/429.rs not found",
+            "request 4291 failed: invalid_request_error",
+            r#"API Error: 400 {"type":"error","error":{"type":"invalid_request_error","message":"max_tokens: 4290 > 4096"}}"#,
+            "Rate limit settings could not be read from config",
+        ] {
+            assert!(
+                parse_rate_limit(text, now).is_none(),
+                "misread as rate limit: {}",
+                text
+            );
+        }
+    }
+
+    /// Test that timeouts and transient failures are retried with capped backoff
+    #[test]
+    fn test_with_retries_transient_failures() {
+        assert!(is_transient(&ClaudeError::Timeout(Duration::from_secs(1))));
+        assert!(is_transient(&ClaudeError::CommandFailed(
+            "API Error: 529 Overloaded".to_string()
+        )));
+        assert!(!is_transient(&ClaudeError::CommandFailed(
+            "Prompt is too long".to_string()
+        )));
+        assert!(!is_transient(&ClaudeError::Interrupted(15)));
+
+        assert_eq!(retry_delay(0), Duration::from_secs(2));
+        assert_eq!(retry_delay(2), Duration::from_secs(8));
+        assert_eq!(retry_delay(10), MAX_RETRY_DELAY);
+
+        let ok = || {
+            Ok(ClaudeResponse {
+                result: "ok".to_string(),
+                session_id: String::new(),
+                total_cost_usd: 0.0,
+            })
+        };
+
+        // Two timeouts, then success
+        let mut calls = 0;
+        let mut waits = Vec::new();
+        let result = with_retries(
+            2,
+            || {
+                calls += 1;
+                if calls < 3 {
+                    Err(ClaudeError::Timeout(Duration::from_secs(1)))
+                } else {
+                    ok()
+                }
+            },
+            |d| {
+                waits.push(d);
+                Ok(())
+            },
+        );
+        assert_eq!(result.unwrap().result, "ok");
+        assert_eq!(waits, vec![Duration::from_secs(2), Duration::from_secs(4)]);
+
+        // Retries exhausted: the last error is returned
+        let mut calls = 0;
+        let result = with_retries(
+            1,
+            || {
+                calls += 1;
+                Err(ClaudeError::Timeout(Duration::from_secs(1)))
+            },
+            |_| Ok(()),
+        );
+        assert!(matches!(result, Err(ClaudeError::Timeout(_))));
+        assert_eq!(calls, 2);
+
+        // Non-transient failures and the default (0 retries) fail immediately
+        let mut calls = 0;
+        let _ = with_retries(
+            3,
+            || {
+                calls += 1;
+                Err(ClaudeError::CommandFailed("Prompt is too long".to_string()))
+            },
+            |_| Ok(()),
+        );
+        assert_eq!(calls, 1);
+        assert_eq!(ClaudeOptions::default().max_retries, 0);
+    }
+
     /// Test that invalid JSON returns parse error
     #[test]
     fn test_parse_invalid_json() {
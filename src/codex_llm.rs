@@ -4,15 +4,19 @@
 //! This allows Codex users to run superego without needing Claude CLI installed.
 
 use serde::Deserialize;
+use std::path::Path;
 use std::process::{Command, Stdio};
 use std::thread;
 use std::time::{Duration, Instant};
 
+use crate::llm::{InvokeOptions, LlmBackend, LlmError, LlmResponse};
+use crate::signals;
+
 /// Response from Codex exec
 #[derive(Debug, Clone)]
 pub struct CodexLlmResponse {
     pub result: String,
-    #[allow(dead_code)]
+    /// Codex thread id; pass back to `invoke` to resume the thread
     pub session_id: String,
     pub total_tokens: u64,
 }
@@ -25,7 +29,11 @@ pub enum CodexLlmError {
     IoError(std::io::Error),
     Timeout(Duration),
     NotInstalled,
-    RateLimited { resets_in_seconds: Option<u64> },
+    RateLimited {
+        resets_in_seconds: Option<u64>,
+    },
+    /// SIGTERM/SIGINT arrived while waiting; the CLI was killed
+    Interrupted(i32),
 }
 
 impl std::fmt::Display for CodexLlmError {
@@ -43,6 +51,7 @@ impl std::fmt::Display for CodexLlmError {
                     write!(f, "Rate limited")
                 }
             }
+            CodexLlmError::Interrupted(sig) => write!(f, "Interrupted by signal {}", sig),
         }
     }
 }
@@ -66,6 +75,10 @@ struct CodexEvent {
     thread_id: Option<String>,
     #[serde(default)]
     usage: Option<CodexUsage>,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    error: Option<CodexErrorDetail>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -74,6 +87,14 @@ struct CodexItem {
     item_type: String,
     #[serde(default)]
     text: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CodexErrorDetail {
+    #[serde(default)]
+    message: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -84,8 +105,54 @@ struct CodexUsage {
     output_tokens: u64,
 }
 
+/// Number of trailing output lines included in parse errors for debugging
+const ERROR_SAMPLE_LINES: usize = 3;
+
 /// Default timeout: 3 minutes
-const DEFAULT_TIMEOUT_MS: u64 = 180_000;
+pub const DEFAULT_TIMEOUT_MS: u64 = 180_000;
+
+/// Per-session file holding the Codex thread to resume (codex_resume_thread)
+pub const THREAD_FILE: &str = "codex_thread";
+
+/// Thread id saved by `save_thread` for this session, if any
+pub fn load_thread(session_dir: &Path) -> Option<String> {
+    let id = std::fs::read_to_string(session_dir.join(THREAD_FILE)).ok()?;
+    let id = id.trim();
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+/// Remember the thread to resume on this session's next evaluation
+pub fn save_thread(session_dir: &Path, thread_id: &str) -> std::io::Result<()> {
+    std::fs::create_dir_all(session_dir)?;
+    std::fs::write(session_dir.join(THREAD_FILE), thread_id)
+}
+
+/// Forget the saved thread (e.g. after resuming it failed)
+pub fn clear_thread(session_dir: &Path) {
+    let _ = std::fs::remove_file(session_dir.join(THREAD_FILE));
+}
+
+/// `codex` arguments for a JSONL exec reading the prompt from stdin
+/// Non-interactive exec mode; the git repo check is skipped since we run as
+/// a meta-evaluator, and "-" reads the prompt from stdin (avoids CLI arg
+/// length limits). With a thread id, `exec resume` continues that thread.
+fn exec_args(model: Option<&str>, thread_id: Option<&str>) -> Vec<String> {
+    let mut args = vec![
+        "exec".to_string(),
+        "--json".to_string(),
+        "--skip-git-repo-check".to_string(),
+    ];
+    if let Some(model) = model {
+        args.push("--model".to_string());
+        args.push(model.to_string());
+    }
+    if let Some(thread_id) = thread_id {
+        args.push("resume".to_string());
+        args.push(thread_id.to_string());
+    }
+    args.push("-".to_string());
+    args
+}
 
 /// Check if Codex CLI is available
 pub fn is_available() -> bool {
@@ -99,24 +166,21 @@ pub fn is_available() -> bool {
 }
 
 /// Invoke Codex exec with a prompt for evaluation
+/// `model` is passed as `--model`; None uses the Codex CLI default.
+/// `thread_id` resumes an earlier thread (see `CodexLlmResponse::session_id`).
 pub fn invoke(
     system_prompt: &str,
     message: &str,
     timeout_ms: Option<u64>,
+    model: Option<&str>,
+    thread_id: Option<&str>,
 ) -> Result<CodexLlmResponse, CodexLlmError> {
     if !is_available() {
         return Err(CodexLlmError::NotInstalled);
     }
 
     let mut cmd = Command::new("codex");
-
-    // Non-interactive exec mode with JSONL output
-    // Skip git repo check since we're running as a meta-evaluator
-    // Use "-" to read prompt from stdin (avoids CLI arg length limits)
-    cmd.arg("exec")
-        .arg("--json")
-        .arg("--skip-git-repo-check")
-        .arg("-");
+    cmd.args(exec_args(model, thread_id));
 
     // Combine system prompt and message
     let full_prompt = format!(
@@ -132,6 +196,13 @@ pub fn invoke(
     // hooks/skills that call superego again.
     cmd.env("SUPEREGO_DISABLED", "1");
 
+    // Own process group, so an interrupt can take down the CLI's children too
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
     let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
     let mut child = cmd.spawn()?;
 
@@ -174,9 +245,12 @@ pub fn invoke(
                 return parse_codex_output(&stdout);
             }
             None => {
+                if let Some(signal) = signals::interrupted() {
+                    signals::kill_process_group(&mut child);
+                    return Err(CodexLlmError::Interrupted(signal));
+                }
                 if start.elapsed() > timeout {
-                    let _ = child.kill();
-                    let _ = child.wait();
+                    signals::kill_process_group(&mut child);
                     return Err(CodexLlmError::Timeout(timeout));
                 }
                 thread::sleep(Duration::from_millis(100));
@@ -185,11 +259,42 @@ pub fn invoke(
     }
 }
 
+/// Extract the error message from a failure event, if this is one
+/// AIDEV-NOTE: codex exec reports failures as `error`, `thread.error`, `turn.failed`
+/// or `item.failed` events, or as an `item.completed` whose item type is "error".
+fn event_error_message(event: &CodexEvent) -> Option<String> {
+    let message = match event.event_type.as_str() {
+        "error" | "thread.error" | "turn.failed" | "item.failed" => event
+            .message
+            .clone()
+            .or_else(|| event.error.as_ref().and_then(|e| e.message.clone()))
+            .or_else(|| event.item.as_ref().and_then(|i| i.message.clone()))
+            .unwrap_or_else(|| format!("{} event without message", event.event_type)),
+        "item.completed" => {
+            let item = event.item.as_ref().filter(|i| i.item_type == "error")?;
+            item.message
+                .clone()
+                .or_else(|| item.text.clone())
+                .unwrap_or_else(|| "error item without message".to_string())
+        }
+        _ => return None,
+    };
+    Some(message)
+}
+
+/// Last few non-empty lines of output, for debugging parse failures
+fn output_sample(output: &str) -> String {
+    let lines: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
+    let start = lines.len().saturating_sub(ERROR_SAMPLE_LINES);
+    lines[start..].join("\n")
+}
+
 /// Parse JSONL output from codex exec --json
 fn parse_codex_output(output: &str) -> Result<CodexLlmResponse, CodexLlmError> {
     let mut result_text = String::new();
     let mut thread_id = String::new();
     let mut total_tokens: u64 = 0;
+    let mut errors: Vec<String> = Vec::new();
 
     for line in output.lines() {
         if line.trim().is_empty() {
@@ -197,8 +302,12 @@ fn parse_codex_output(output: &str) -> Result<CodexLlmResponse, CodexLlmError> {
         }
 
         if let Ok(event) = serde_json::from_str::<CodexEvent>(line) {
-            if let Some(tid) = event.thread_id {
-                thread_id = tid;
+            if let Some(tid) = &event.thread_id {
+                thread_id = tid.clone();
+            }
+
+            if let Some(msg) = event_error_message(&event) {
+                errors.push(msg);
             }
 
             if event.event_type == "item.completed" {
@@ -218,9 +327,18 @@ fn parse_codex_output(output: &str) -> Result<CodexLlmResponse, CodexLlmError> {
     }
 
     if result_text.is_empty() {
-        return Err(CodexLlmError::ParseError(
-            "No agent_message found in output".to_string(),
-        ));
+        let reason = if errors.is_empty() {
+            "No agent_message found in output".to_string()
+        } else {
+            format!("Codex reported error: {}", errors.join("; "))
+        };
+        let sample = output_sample(output);
+        let msg = if sample.is_empty() {
+            format!("{} (output was empty)", reason)
+        } else {
+            format!("{}\nLast output lines:\n{}", reason, sample)
+        };
+        return Err(CodexLlmError::ParseError(msg));
     }
 
     Ok(CodexLlmResponse {
@@ -229,3 +347,124 @@ fn parse_codex_output(output: &str) -> Result<CodexLlmResponse, CodexLlmError> {
         total_tokens,
     })
 }
+
+/// The Codex CLI (`codex exec`), always on a new thread
+pub struct CodexBackend;
+
+impl LlmBackend for CodexBackend {
+    fn invoke(
+        &self,
+        system_prompt: &str,
+        message: &str,
+        options: &InvokeOptions,
+    ) -> Result<LlmResponse, LlmError> {
+        let response = invoke(
+            system_prompt,
+            message,
+            options.timeout_ms,
+            options.model.as_deref(),
+            options.resume_thread.as_deref(),
+        )?;
+        Ok(LlmResponse {
+            result: response.result,
+            session_id: response.session_id,
+            cost_usd: 0.0,
+            tokens: Some(response.total_tokens),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_agent_message() {
+        let output = r#"{"type":"thread.started","thread_id":"t-1"}
+{"type":"item.completed","item":{"type":"agent_message","text":"DECISION: ALLOW"}}
+{"type":"turn.completed","usage":{"input_tokens":10,"output_tokens":5}}"#;
+        let response = parse_codex_output(output).unwrap();
+        assert_eq!(response.result, "DECISION: ALLOW");
+        assert_eq!(response.session_id, "t-1");
+        assert_eq!(response.total_tokens, 15);
+    }
+
+    #[test]
+    fn test_exec_args() {
+        assert_eq!(
+            exec_args(None, None),
+            ["exec", "--json", "--skip-git-repo-check", "-"]
+        );
+        assert_eq!(
+            exec_args(Some("o3"), Some("t-1")),
+            [
+                "exec",
+                "--json",
+                "--skip-git-repo-check",
+                "--model",
+                "o3",
+                "resume",
+                "t-1",
+                "-"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_thread_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let session_dir = dir.path().join("sessions").join("s1");
+        assert_eq!(load_thread(&session_dir), None);
+
+        save_thread(&session_dir, "t-1").unwrap();
+        assert_eq!(load_thread(&session_dir).as_deref(), Some("t-1"));
+
+        clear_thread(&session_dir);
+        assert_eq!(load_thread(&session_dir), None);
+    }
+
+    #[test]
+    fn test_parse_surfaces_turn_failed_message() {
+        let output = r#"{"type":"thread.started","thread_id":"t-1"}
+{"type":"turn.failed","error":{"message":"model overloaded"}}"#;
+        let err = parse_codex_output(output).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("model overloaded"), "got: {}", msg);
+        assert!(msg.contains("turn.failed"), "sample missing: {}", msg);
+    }
+
+    #[test]
+    fn test_parse_surfaces_error_and_item_failed() {
+        let output = r#"{"type":"error","message":"stream disconnected"}
+{"type":"item.failed","item":{"type":"command_execution","message":"sandbox denied"}}"#;
+        let msg = parse_codex_output(output).unwrap_err().to_string();
+        assert!(msg.contains("stream disconnected"), "got: {}", msg);
+        assert!(msg.contains("sandbox denied"), "got: {}", msg);
+    }
+
+    #[test]
+    fn test_parse_surfaces_error_item() {
+        let output =
+            r#"{"type":"item.completed","item":{"type":"error","message":"context too long"}}"#;
+        let msg = parse_codex_output(output).unwrap_err().to_string();
+        assert!(msg.contains("context too long"), "got: {}", msg);
+    }
+
+    #[test]
+    fn test_parse_no_agent_message_includes_sample() {
+        let output = "line one\nline two\nline three\nline four\n";
+        let msg = parse_codex_output(output).unwrap_err().to_string();
+        assert!(msg.contains("No agent_message found"));
+        assert!(msg.contains("line four"));
+        assert!(
+            !msg.contains("line one"),
+            "sample should be last lines only"
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_output() {
+        let msg = parse_codex_output("").unwrap_err().to_string();
+        assert!(msg.contains("output was empty"), "got: {}", msg);
+    }
+}
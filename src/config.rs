@@ -2,8 +2,12 @@
 //!
 //! Reads settings from .superego/config.yaml
 
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::evaluate::Confidence;
+use crate::transcript::ContextLimits;
 
 /// Evaluation mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -32,6 +36,200 @@ impl Mode {
     }
 }
 
+/// What to do when the agent is still in plan mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlanMode {
+    /// Evaluate, telling superego to judge the plan rather than missing changes
+    #[default]
+    Evaluate,
+    /// Skip evaluation until the agent leaves plan mode
+    Skip,
+}
+
+impl PlanMode {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "evaluate" => Some(PlanMode::Evaluate),
+            "skip" => Some(PlanMode::Skip),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlanMode::Evaluate => "evaluate",
+            PlanMode::Skip => "skip",
+        }
+    }
+}
+
+/// Which LLM CLI evaluations and reviews call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// Claude Code CLI (`claude -p`)
+    #[default]
+    Claude,
+    /// Codex CLI (`codex exec`)
+    Codex,
+}
+
+impl Backend {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "claude" => Some(Backend::Claude),
+            "codex" => Some(Backend::Codex),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Backend::Claude => "claude",
+            Backend::Codex => "codex",
+        }
+    }
+}
+
+/// Task tracker that supplies the current tasks for drift detection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TaskProviderKind {
+    /// Whichever of ba, then bd, is initialized
+    #[default]
+    Auto,
+    Ba,
+    Bd,
+}
+
+impl TaskProviderKind {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "auto" => Some(TaskProviderKind::Auto),
+            "ba" => Some(TaskProviderKind::Ba),
+            "bd" => Some(TaskProviderKind::Bd),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskProviderKind::Auto => "auto",
+            TaskProviderKind::Ba => "ba",
+            TaskProviderKind::Bd => "bd",
+        }
+    }
+}
+
+/// Line format of codex.log and eval.log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// `<timestamp> <message>` lines for humans
+    #[default]
+    Text,
+    /// Newline-delimited JSON records for log tooling
+    Json,
+}
+
+impl LogFormat {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "text" => Some(LogFormat::Text),
+            "json" => Some(LogFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogFormat::Text => "text",
+            LogFormat::Json => "json",
+        }
+    }
+}
+
+/// Keys `sg config get/set` can read and write, in config.yaml order
+/// AIDEV-NOTE: Keep in sync with the match in `Config::parse` and
+/// `Config::value`. Sections (`SECTIONS`) are edited by hand.
+pub const KEYS: &[&str] = &[
+    "mode",
+    "base_prompt",
+    "backend",
+    "model",
+    "review_model",
+    "review_base_branch",
+    "timeout_ms",
+    "max_retries",
+    "superego_tools",
+    "carryover_decision_count",
+    "carryover_window_minutes",
+    "default_focus",
+    "explain_decision",
+    "min_confidence",
+    "second_opinion_model",
+    "plan_mode",
+    "min_messages_to_evaluate",
+    "exclude_subagents",
+    "codex_resume_thread",
+    "task_provider",
+    "notify_webhook_url",
+    "feedback_cooldown_minutes",
+    "dedupe_window_minutes",
+    "max_feedback_chars",
+    "include_repo_overview",
+    "prompt_cache",
+    "log_max_bytes",
+    "log_format",
+    "max_tool_output_chars",
+    "max_message_chars",
+    "max_context_chars",
+    "max_diff_bytes",
+    "oh_endeavor_id",
+    "oh_api_url",
+    "oh_api_key",
+    "oh_ca_cert",
+    "oh_timeout_seconds",
+    "oh_cache_ttl_minutes",
+];
+
+/// Keys that start a section of indented `name: value` lines
+pub const SECTIONS: &[&str] = &["oh_endeavor_map", "prompt_vars"];
+
+/// Error type for `sg config get/set`
+#[derive(Debug)]
+pub enum ConfigError {
+    UnknownKey(String),
+    /// The value doesn't parse for this key (it would be ignored)
+    InvalidValue {
+        key: String,
+        value: String,
+    },
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::UnknownKey(key) => write!(
+                f,
+                "Unknown config key '{}'. Valid keys: {}",
+                key,
+                KEYS.join(", ")
+            ),
+            ConfigError::InvalidValue { key, value } => {
+                write!(f, "Invalid value for {}: '{}'", key, value)
+            }
+            ConfigError::Io(e) => write!(f, "IO error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(e: std::io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
 /// Superego configuration
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -41,6 +239,81 @@ pub struct Config {
     pub carryover_decision_count: usize,
     /// Minutes of recent messages to include in carryover context (default: 5)
     pub carryover_window_minutes: i64,
+    /// Topic superego should pay particular attention to (default: none)
+    pub default_focus: Option<String>,
+    /// Ask for (and journal) a one-line rationale on ALLOW (default: false)
+    pub explain_decision: bool,
+    /// Size at which codex.log/eval.log/hook.log are rotated, 0 = never (default: 1 MiB)
+    pub log_max_bytes: u64,
+    /// codex.log/eval.log line format: text or json (default: text)
+    pub log_format: LogFormat,
+    /// Let the Claude backend use Anthropic prompt caching (default: true)
+    pub prompt_cache: bool,
+    /// Cap on queued/displayed feedback length in characters (default: none)
+    pub max_feedback_chars: Option<usize>,
+    /// Minutes to skip evaluations after delivering feedback, 0 = off (default: 0)
+    pub feedback_cooldown_minutes: i64,
+    /// Minutes in which a BLOCK repeating the last delivered feedback isn't re-queued, 0 = off (default: 0)
+    pub dedupe_window_minutes: i64,
+    /// Second model consulted on every evaluation; see `combine_opinions` (default: none)
+    pub second_opinion_model: Option<String>,
+    /// Prepend a cached repo overview to a session's first evaluation (default: false)
+    pub include_repo_overview: bool,
+    /// Behavior while the agent is in plan mode: evaluate or skip (default: evaluate)
+    pub plan_mode: PlanMode,
+    /// Skip evaluation until this many new messages have accumulated (default: 1)
+    pub min_messages_to_evaluate: usize,
+    /// Leave Claude Code sub-agent (Task tool) turns out of evaluations (default: false)
+    pub exclude_subagents: bool,
+    /// `sg evaluate-codex` resumes the session's previous Codex thread (default: false)
+    pub codex_resume_thread: bool,
+    /// Task tracker for the current tasks: auto, ba or bd (default: auto)
+    pub task_provider: TaskProviderKind,
+    /// BLOCKs below this confidence are journaled but not delivered (default: none = all)
+    pub min_confidence: Option<Confidence>,
+    /// Base prompt name from `sg prompt switch` (default: none = code)
+    pub base_prompt: Option<String>,
+    /// LLM CLI for evaluations and reviews (default: claude)
+    pub backend: Backend,
+    /// Model for evaluations and reviews, named for `backend` (default: CLI default)
+    pub model: Option<String>,
+    /// Model for `sg review`, falling back to `model` (default: none)
+    pub review_model: Option<String>,
+    /// Base branch for `sg review pr`, skipping detection; --base overrides (default: none)
+    pub review_base_branch: Option<String>,
+    /// LLM CLI timeout in milliseconds; --timeout-ms overrides (default: 5 min Claude, 3 min Codex)
+    pub timeout_ms: Option<u64>,
+    /// Retries after a Claude CLI timeout or transient failure (default: 0)
+    pub max_retries: u32,
+    /// Tools superego's Claude calls may use, comma-separated in config.yaml
+    /// (default: none = `claude::DEFAULT_TOOLS`, Bash,Read,Glob,Grep)
+    pub superego_tools: Option<Vec<String>>,
+    /// URL to POST each queued concern to as JSON; see `notify` (default: none)
+    pub notify_webhook_url: Option<String>,
+    /// Open Horizons endeavor to log decisions to (default: none)
+    pub oh_endeavor_id: Option<String>,
+    /// Open Horizons API URL (default: none, see `OhConfig`)
+    pub oh_api_url: Option<String>,
+    /// Open Horizons API key (default: none)
+    pub oh_api_key: Option<String>,
+    /// PEM bundle of extra CAs to trust for OH requests; OH_CA_CERT overrides (default: none)
+    pub oh_ca_cert: Option<PathBuf>,
+    /// Timeout for each OH API call in seconds (default: none, 5s; 10s for retro pushes)
+    pub oh_timeout_seconds: Option<u64>,
+    /// Path pattern -> OH endeavor, in file order; see `oh::resolve_endeavor_id` (default: empty)
+    pub oh_endeavor_map: Vec<(String, String)>,
+    /// Values for `{{name}}` tokens in the system prompt; see `prompts::substitute_vars` (default: empty)
+    pub prompt_vars: Vec<(String, String)>,
+    /// Minutes to reuse cached OH endeavor details/guardrails, 0 = always refetch (default: 10)
+    pub oh_cache_ttl_minutes: i64,
+    /// Cap on each Codex/Gemini tool output in characters, 0 = none (default: 500)
+    pub max_tool_output_chars: usize,
+    /// Cap on each Codex/Gemini message in characters, 0 = none (default: 2000)
+    pub max_message_chars: usize,
+    /// Cap on Claude Code context per evaluation; see `format_context_within`, 0 = none (default: 100k)
+    pub max_context_chars: usize,
+    /// `sg review` diffs larger than this are reviewed file-by-file in parts, 0 = none (default: 100k)
+    pub max_diff_bytes: usize,
 }
 
 impl Default for Config {
@@ -49,6 +322,43 @@ impl Default for Config {
             mode: Mode::Always,
             carryover_decision_count: 2,
             carryover_window_minutes: 5,
+            default_focus: None,
+            explain_decision: false,
+            log_max_bytes: 1024 * 1024,
+            log_format: LogFormat::Text,
+            prompt_cache: true,
+            max_feedback_chars: None,
+            feedback_cooldown_minutes: 0,
+            dedupe_window_minutes: 0,
+            second_opinion_model: None,
+            include_repo_overview: false,
+            plan_mode: PlanMode::Evaluate,
+            min_messages_to_evaluate: 1,
+            exclude_subagents: false,
+            codex_resume_thread: false,
+            task_provider: TaskProviderKind::Auto,
+            min_confidence: None,
+            base_prompt: None,
+            backend: Backend::Claude,
+            model: None,
+            review_model: None,
+            review_base_branch: None,
+            timeout_ms: None,
+            max_retries: 0,
+            superego_tools: None,
+            notify_webhook_url: None,
+            oh_endeavor_id: None,
+            oh_api_url: None,
+            oh_api_key: None,
+            oh_ca_cert: None,
+            oh_timeout_seconds: None,
+            oh_endeavor_map: Vec::new(),
+            prompt_vars: Vec::new(),
+            oh_cache_ttl_minutes: 10,
+            max_tool_output_chars: ContextLimits::default().max_tool_output_chars,
+            max_message_chars: ContextLimits::default().max_message_chars,
+            max_context_chars: 100_000,
+            max_diff_bytes: 100_000,
         }
     }
 }
@@ -62,20 +372,43 @@ impl Config {
             return Config::default();
         }
 
-        let content = match fs::read_to_string(&config_path) {
-            Ok(c) => c,
-            Err(_) => return Config::default(),
-        };
+        match fs::read_to_string(&config_path) {
+            Ok(content) => Self::parse(&content),
+            Err(_) => Config::default(),
+        }
+    }
 
+    /// Parse config.yaml contents; unknown keys and unparsable values are ignored
+    pub fn parse(content: &str) -> Self {
         let mut config = Config::default();
 
         // Simple line-by-line parsing (no YAML crate dependency)
-        for line in content.lines() {
-            let line = line.trim();
+        // AIDEV-NOTE: The nested sections (SECTIONS) are oh_endeavor_map and
+        // prompt_vars; their entries are the indented `name: value` lines after
+        // the section key. Any unindented line ends a section.
+        let mut section: Option<&str> = None;
+        for raw_line in content.lines() {
+            let line = strip_inline_comment(raw_line.trim());
             if line.starts_with('#') || line.is_empty() {
                 continue;
             }
 
+            let indented = raw_line.starts_with([' ', '\t']);
+            if let (Some(section), true) = (section, indented) {
+                if let Some((name, value)) = line.split_once(':') {
+                    if let (Some(name), Some(value)) =
+                        (string_value(name.trim()), string_value(value.trim()))
+                    {
+                        match section {
+                            "prompt_vars" => config.prompt_vars.push((name, value)),
+                            _ => config.oh_endeavor_map.push((name, value)),
+                        }
+                    }
+                }
+                continue;
+            }
+            section = None;
+
             if let Some((key, value)) = line.split_once(':') {
                 let key = key.trim();
                 let value = value.trim();
@@ -96,6 +429,145 @@ impl Config {
                             config.carryover_window_minutes = v;
                         }
                     }
+                    "default_focus" => config.default_focus = string_value(value),
+                    "explain_decision" => {
+                        if let Ok(v) = value.parse() {
+                            config.explain_decision = v;
+                        }
+                    }
+                    "log_max_bytes" => {
+                        if let Ok(v) = value.parse() {
+                            config.log_max_bytes = v;
+                        }
+                    }
+                    "log_format" => {
+                        if let Some(f) = LogFormat::from_str(value) {
+                            config.log_format = f;
+                        }
+                    }
+                    "prompt_cache" => {
+                        if let Ok(v) = value.parse() {
+                            config.prompt_cache = v;
+                        }
+                    }
+                    "max_feedback_chars" => {
+                        if let Ok(v) = value.parse() {
+                            config.max_feedback_chars = Some(v);
+                        }
+                    }
+                    "feedback_cooldown_minutes" => {
+                        if let Ok(v) = value.parse() {
+                            config.feedback_cooldown_minutes = v;
+                        }
+                    }
+                    "dedupe_window_minutes" => {
+                        if let Ok(v) = value.parse() {
+                            config.dedupe_window_minutes = v;
+                        }
+                    }
+                    "second_opinion_model" => config.second_opinion_model = string_value(value),
+                    "include_repo_overview" => {
+                        if let Ok(v) = value.parse() {
+                            config.include_repo_overview = v;
+                        }
+                    }
+                    "plan_mode" => {
+                        if let Some(p) = PlanMode::from_str(value) {
+                            config.plan_mode = p;
+                        }
+                    }
+                    "task_provider" => {
+                        if let Some(t) = TaskProviderKind::from_str(value) {
+                            config.task_provider = t;
+                        }
+                    }
+                    "min_messages_to_evaluate" => {
+                        if let Ok(v) = value.parse() {
+                            config.min_messages_to_evaluate = v;
+                        }
+                    }
+                    "exclude_subagents" => {
+                        if let Ok(v) = value.parse() {
+                            config.exclude_subagents = v;
+                        }
+                    }
+                    "codex_resume_thread" => {
+                        if let Ok(v) = value.parse() {
+                            config.codex_resume_thread = v;
+                        }
+                    }
+                    "min_confidence" => {
+                        if let Some(c) = Confidence::from_str(value) {
+                            config.min_confidence = Some(c);
+                        }
+                    }
+                    "base_prompt" => config.base_prompt = string_value(value),
+                    "backend" => {
+                        if let Some(b) = Backend::from_str(value) {
+                            config.backend = b;
+                        }
+                    }
+                    "model" => config.model = string_value(value),
+                    "review_model" => config.review_model = string_value(value),
+                    "review_base_branch" => config.review_base_branch = string_value(value),
+                    "superego_tools" => {
+                        let tools: Vec<String> = value
+                            .trim_matches('"')
+                            .trim_matches('\'')
+                            .split(',')
+                            .map(|t| t.trim().to_string())
+                            .filter(|t| !t.is_empty())
+                            .collect();
+                        config.superego_tools = (!tools.is_empty()).then_some(tools);
+                    }
+                    "timeout_ms" => {
+                        if let Ok(v) = value.parse() {
+                            config.timeout_ms = Some(v);
+                        }
+                    }
+                    "max_retries" => {
+                        if let Ok(v) = value.parse() {
+                            config.max_retries = v;
+                        }
+                    }
+                    "notify_webhook_url" => config.notify_webhook_url = string_value(value),
+                    "oh_endeavor_id" => config.oh_endeavor_id = string_value(value),
+                    "oh_api_url" => config.oh_api_url = string_value(value),
+                    "oh_api_key" => config.oh_api_key = string_value(value),
+                    "oh_ca_cert" => config.oh_ca_cert = string_value(value).map(PathBuf::from),
+                    "oh_timeout_seconds" => {
+                        if let Ok(v) = value.parse() {
+                            config.oh_timeout_seconds = Some(v);
+                        }
+                    }
+                    "oh_endeavor_map" | "prompt_vars" => {
+                        section = value.is_empty().then_some(key);
+                    }
+                    "oh_cache_ttl_minutes" => {
+                        if let Ok(v) = value.parse() {
+                            config.oh_cache_ttl_minutes = v;
+                        }
+                    }
+                    "max_tool_output_chars" => {
+                        if let Ok(v) = value.parse() {
+                            config.max_tool_output_chars = v;
+                        }
+                    }
+                    "max_message_chars" => {
+                        if let Ok(v) = value.parse() {
+                            config.max_message_chars = v;
+                        }
+                    }
+                    "max_diff_bytes" => {
+                        if let Ok(v) = value.parse() {
+                            config.max_diff_bytes = v;
+                        }
+                    }
+                    "max_context_chars" => {
+                        if let Ok(v) = value.parse() {
+                            config.max_context_chars = v;
+                        }
+                    }
                     _ => {} // Ignore unknown keys
                 }
             }
@@ -103,6 +575,280 @@ impl Config {
 
         config
     }
+
+    /// Effective value of a `KEYS` entry as text (None = unset); Err for unknown keys
+    pub fn value(&self, key: &str) -> Result<Option<String>, ConfigError> {
+        fn num(n: impl ToString) -> Option<String> {
+            Some(n.to_string())
+        }
+        Ok(match key {
+            "mode" => num(self.mode.as_str()),
+            "base_prompt" => self.base_prompt.clone(),
+            "backend" => num(self.backend.as_str()),
+            "model" => self.model.clone(),
+            "review_model" => self.review_model.clone(),
+            "review_base_branch" => self.review_base_branch.clone(),
+            "superego_tools" => self.superego_tools.as_ref().map(|t| t.join(",")),
+            "timeout_ms" => self.timeout_ms.and_then(num),
+            "max_retries" => num(self.max_retries),
+            "carryover_decision_count" => num(self.carryover_decision_count),
+            "carryover_window_minutes" => num(self.carryover_window_minutes),
+            "default_focus" => self.default_focus.clone(),
+            "explain_decision" => num(self.explain_decision),
+            "min_confidence" => self.min_confidence.and_then(num),
+            "second_opinion_model" => self.second_opinion_model.clone(),
+            "plan_mode" => num(self.plan_mode.as_str()),
+            "min_messages_to_evaluate" => num(self.min_messages_to_evaluate),
+            "exclude_subagents" => num(self.exclude_subagents),
+            "codex_resume_thread" => num(self.codex_resume_thread),
+            "task_provider" => num(self.task_provider.as_str()),
+            "feedback_cooldown_minutes" => num(self.feedback_cooldown_minutes),
+            "dedupe_window_minutes" => num(self.dedupe_window_minutes),
+            "max_feedback_chars" => self.max_feedback_chars.and_then(num),
+            "include_repo_overview" => num(self.include_repo_overview),
+            "prompt_cache" => num(self.prompt_cache),
+            "log_max_bytes" => num(self.log_max_bytes),
+            "log_format" => num(self.log_format.as_str()),
+            "max_tool_output_chars" => num(self.max_tool_output_chars),
+            "max_message_chars" => num(self.max_message_chars),
+            "max_context_chars" => num(self.max_context_chars),
+            "max_diff_bytes" => num(self.max_diff_bytes),
+            "notify_webhook_url" => self.notify_webhook_url.clone(),
+            "oh_endeavor_id" => self.oh_endeavor_id.clone(),
+            "oh_api_url" => self.oh_api_url.clone(),
+            "oh_api_key" => self.oh_api_key.clone(),
+            "oh_ca_cert" => self.oh_ca_cert.as_ref().map(|p| p.display().to_string()),
+            "oh_timeout_seconds" => self.oh_timeout_seconds.and_then(num),
+            "oh_cache_ttl_minutes" => num(self.oh_cache_ttl_minutes),
+            _ => return Err(ConfigError::UnknownKey(key.to_string())),
+        })
+    }
+
+    /// Truncation limits for Codex/Gemini transcript context
+    pub fn context_limits(&self) -> ContextLimits {
+        ContextLimits {
+            max_tool_output_chars: self.max_tool_output_chars,
+            max_message_chars: self.max_message_chars,
+        }
+    }
+}
+
+/// Set `key: value` in config.yaml, keeping comments and every other line
+/// AIDEV-NOTE: The value is validated by parsing the result: if the key's
+/// effective value doesn't come out as written, the parser would silently
+/// ignore it, so it's rejected instead. An empty value unsets optional keys.
+/// The last active `key:` line is replaced (it's the one that wins); a new
+/// key goes after its commented-out example from `sg init`, else at the end.
+pub fn set_value(superego_dir: &Path, key: &str, value: &str) -> Result<(), ConfigError> {
+    Config::default().value(key)?;
+    let value = value.trim();
+
+    let path = superego_dir.join("config.yaml");
+    let content = if path.exists() {
+        fs::read_to_string(&path)?
+    } else {
+        String::new()
+    };
+    let new_line = if value.is_empty() {
+        format!("{}:", key)
+    } else {
+        format!("{}: {}", key, value)
+    };
+
+    let key_of =
+        |line: &str| -> Option<String> { line.split_once(':').map(|(k, _)| k.trim().to_string()) };
+    let mut lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+    let active = lines.iter().rposition(|l| {
+        let t = l.trim();
+        !t.starts_with('#') && key_of(t).as_deref() == Some(key)
+    });
+    let example = || {
+        lines.iter().position(|l| {
+            l.trim()
+                .strip_prefix('#')
+                .and_then(|rest| key_of(rest.trim()))
+                .as_deref()
+                == Some(key)
+        })
+    };
+    match (active, example()) {
+        (Some(i), _) => lines[i] = new_line,
+        (None, Some(i)) => lines.insert(i + 1, new_line),
+        (None, None) => lines.push(new_line),
+    }
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+
+    if !takes_effect(&Config::parse(&updated), key, value)? {
+        return Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+    }
+
+    fs::write(&path, updated)?;
+    Ok(())
+}
+
+/// Whether `key`'s effective value in `config` is `written` (i.e. the parser
+/// didn't ignore it). Case, quotes and whitespace are ignored, so "Read, Grep"
+/// matches the stored "Read,Grep".
+fn takes_effect(config: &Config, key: &str, written: &str) -> Result<bool, ConfigError> {
+    let normalize = |v: &str| {
+        v.trim_matches('"')
+            .trim_matches('\'')
+            .to_lowercase()
+            .split_whitespace()
+            .collect::<String>()
+    };
+    let effective = config.value(key)?;
+    Ok(effective.as_deref().map(normalize) == string_value(written).as_deref().map(normalize))
+}
+
+/// How serious a `ConfigIssue` is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    /// The line is ignored or its value makes no sense
+    Error,
+    /// Works, but probably not as intended
+    Warning,
+}
+
+/// A problem `validate` found on one line of config.yaml
+#[derive(Debug, Clone)]
+pub struct ConfigIssue {
+    pub severity: IssueSeverity,
+    /// 1-based line number
+    pub line: usize,
+    /// The line as written
+    pub text: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            IssueSeverity::Error => "error",
+            IssueSeverity::Warning => "warning",
+        };
+        write!(
+            f,
+            "config.yaml:{}: {}: {}\n    {} | {}",
+            self.line, severity, self.message, self.line, self.text
+        )
+    }
+}
+
+/// Minimum for numeric keys where a smaller value is meaningless
+fn minimum(key: &str) -> Option<i64> {
+    match key {
+        "carryover_window_minutes"
+        | "feedback_cooldown_minutes"
+        | "dedupe_window_minutes"
+        | "oh_cache_ttl_minutes" => Some(0),
+        "timeout_ms" | "oh_timeout_seconds" => Some(1),
+        _ => None,
+    }
+}
+
+/// Check config.yaml contents line by line
+/// AIDEV-NOTE: `Config::parse` silently ignores unknown keys and values it
+/// can't parse (falling back to defaults); this reports them instead. A value
+/// is valid if parsing its line alone gives it back, the same test
+/// `set_value` uses, so the two can't disagree.
+pub fn validate(content: &str) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    let mut section: Option<&str> = None;
+    for (i, raw_line) in content.lines().enumerate() {
+        let line = strip_inline_comment(raw_line.trim());
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        let mut issue = |severity, message: String| {
+            issues.push(ConfigIssue {
+                severity,
+                line: i + 1,
+                text: raw_line.to_string(),
+                message,
+            })
+        };
+
+        let Some((key, value)) = line.split_once(':') else {
+            issue(IssueSeverity::Error, "expected `key: value`".to_string());
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+
+        if let (Some(section), true) = (section, raw_line.starts_with([' ', '\t'])) {
+            if string_value(key).is_none() || string_value(value).is_none() {
+                issue(
+                    IssueSeverity::Error,
+                    format!("{} entries need `name: value`", section),
+                );
+            }
+            continue;
+        }
+        section = SECTIONS.iter().find(|s| **s == key).copied();
+        if section.is_some() {
+            continue;
+        }
+
+        if !KEYS.contains(&key) {
+            issue(IssueSeverity::Error, format!("unknown key '{}'", key));
+            continue;
+        }
+        if let Some(first) = seen.insert(key, i + 1) {
+            issue(
+                IssueSeverity::Warning,
+                format!(
+                    "{} is also set on line {}; this later line wins",
+                    key, first
+                ),
+            );
+        }
+
+        let config = Config::parse(line);
+        if !takes_effect(&config, key, value).unwrap_or(false) {
+            issue(
+                IssueSeverity::Error,
+                format!(
+                    "invalid value '{}' for {} (it would be ignored)",
+                    value, key
+                ),
+            );
+            continue;
+        }
+        let number = config
+            .value(key)
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<i64>().ok());
+        if let (Some(min), Some(n)) = (minimum(key), number) {
+            if n < min {
+                issue(
+                    IssueSeverity::Error,
+                    format!("{} must be at least {}", key, min),
+                );
+            }
+        }
+    }
+    issues
+}
+
+/// A line without a trailing `# comment`
+/// As in YAML, `#` only starts a comment after whitespace, so URL fragments
+/// like `https://host/#/path` are kept.
+fn strip_inline_comment(line: &str) -> &str {
+    line.match_indices('#')
+        .find(|(i, _)| line[..*i].ends_with([' ', '\t']))
+        .map_or(line, |(i, _)| line[..i].trim_end())
+}
+
+/// A string value with surrounding quotes removed; None if empty
+fn string_value(value: &str) -> Option<String> {
+    let value = value.trim_matches('"').trim_matches('\'');
+    (!value.is_empty()).then(|| value.to_string())
 }
 
 #[cfg(test)]
@@ -116,6 +862,7 @@ mod tests {
         assert_eq!(config.mode, Mode::Always);
         assert_eq!(config.carryover_decision_count, 2);
         assert_eq!(config.carryover_window_minutes, 5);
+        assert_eq!(config.min_messages_to_evaluate, 1);
     }
 
     #[test]
@@ -153,6 +900,306 @@ mod tests {
         assert_eq!(config.carryover_window_minutes, 7);
     }
 
+    #[test]
+    fn test_load_default_focus() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("config.yaml");
+        fs::write(&config_path, "default_focus: \"test coverage\"\n").unwrap();
+
+        let config = Config::load(dir.path());
+        assert_eq!(config.default_focus.as_deref(), Some("test coverage"));
+        assert_eq!(config.second_opinion_model, None);
+    }
+
+    #[test]
+    fn test_load_boolean_and_size_options() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("config.yaml");
+        fs::write(
+            &config_path,
+            "explain_decision: true\nlog_max_bytes: 4096\nprompt_cache: false\nmax_feedback_chars: 280\nfeedback_cooldown_minutes: 10\ndedupe_window_minutes: 30\ninclude_repo_overview: true\nexclude_subagents: true\ncodex_resume_thread: true\nmax_tool_output_chars: 0\nmax_message_chars: 800\nmax_context_chars: 5000\nmax_diff_bytes: 0\n",
+        )
+        .unwrap();
+
+        let config = Config::load(dir.path());
+        assert!(config.explain_decision);
+        assert_eq!(config.log_max_bytes, 4096);
+        assert!(!config.prompt_cache);
+        assert_eq!(config.max_feedback_chars, Some(280));
+        assert_eq!(config.feedback_cooldown_minutes, 10);
+        assert_eq!(config.dedupe_window_minutes, 30);
+        assert!(config.include_repo_overview);
+        assert!(config.exclude_subagents);
+        assert!(config.codex_resume_thread);
+        assert_eq!(config.context_limits().max_tool_output_chars, 0);
+        assert_eq!(config.context_limits().max_message_chars, 800);
+        assert_eq!(config.max_context_chars, 5000);
+        assert_eq!(Config::default().max_context_chars, 100_000);
+        assert_eq!(config.max_diff_bytes, 0);
+        assert_eq!(Config::default().max_diff_bytes, 100_000);
+        assert!(!Config::default().explain_decision);
+    }
+
+    #[test]
+    fn test_load_string_options() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("config.yaml"),
+            "base_prompt: writing\n\
+             model: 'opus'\n\
+             review_model: opus\n\
+             review_base_branch: develop\n\
+             superego_tools: Read, Glob,Grep\n\
+             timeout_ms: 60000\n\
+             max_retries: 2\n\
+             notify_webhook_url: https://ntfy.sh/my-superego\n\
+             \x20 oh_endeavor_id:   \"initiative:abc123\"  \n\
+             oh_api_url: http://localhost:3001\n\
+             oh_api_key: \n\
+             oh_ca_cert: /etc/ssl/corp-ca.pem\n\
+             oh_timeout_seconds: 30\n\
+             oh_cache_ttl_minutes: 0\n\
+             # model: haiku\n",
+        )
+        .unwrap();
+
+        let config = Config::load(dir.path());
+        assert_eq!(config.base_prompt.as_deref(), Some("writing"));
+        assert_eq!(config.model.as_deref(), Some("opus"));
+        assert_eq!(config.review_model.as_deref(), Some("opus"));
+        assert_eq!(config.review_base_branch.as_deref(), Some("develop"));
+        assert_eq!(
+            config.superego_tools,
+            Some(vec![
+                "Read".to_string(),
+                "Glob".to_string(),
+                "Grep".to_string()
+            ])
+        );
+        assert_eq!(Config::default().superego_tools, None);
+        assert_eq!(config.timeout_ms, Some(60000));
+        assert_eq!(config.max_retries, 2);
+        assert_eq!(
+            config.notify_webhook_url.as_deref(),
+            Some("https://ntfy.sh/my-superego")
+        );
+        assert_eq!(config.oh_endeavor_id.as_deref(), Some("initiative:abc123"));
+        assert_eq!(config.oh_api_url.as_deref(), Some("http://localhost:3001"));
+        assert_eq!(config.oh_api_key, None, "empty value");
+        assert_eq!(
+            config.oh_ca_cert.as_deref(),
+            Some(Path::new("/etc/ssl/corp-ca.pem"))
+        );
+        assert_eq!(config.oh_cache_ttl_minutes, 0);
+        assert_eq!(config.oh_timeout_seconds, Some(30));
+        assert_eq!(Config::default().oh_cache_ttl_minutes, 10);
+    }
+
+    #[test]
+    fn test_load_oh_endeavor_map() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("config.yaml"),
+            "oh_endeavor_map:  # first match wins\n\
+             \x20 \"crates/api/**\": initiative:api\n\
+             \x20 # web first\n\
+             \x20 web/: 'initiative:web'\n\
+             \x20 broken\n\
+             model: opus\n\
+             \x20 docs/: initiative:docs\n",
+        )
+        .unwrap();
+
+        let config = Config::load(dir.path());
+        assert_eq!(
+            config.oh_endeavor_map,
+            vec![
+                ("crates/api/**".to_string(), "initiative:api".to_string()),
+                ("web/".to_string(), "initiative:web".to_string()),
+            ]
+        );
+        assert_eq!(config.model.as_deref(), Some("opus"));
+        assert!(Config::default().oh_endeavor_map.is_empty());
+    }
+
+    #[test]
+    fn test_load_prompt_vars() {
+        let config = Config::parse(
+            "prompt_vars:\n\
+             \x20 project: billing-api\n\
+             \x20 standards_url: https://example.com/std#naming  # kept: no space before #\n\
+             oh_endeavor_map:\n\
+             \x20 web/: initiative:web\n",
+        );
+        assert_eq!(
+            config.prompt_vars,
+            vec![
+                ("project".to_string(), "billing-api".to_string()),
+                (
+                    "standards_url".to_string(),
+                    "https://example.com/std#naming".to_string()
+                ),
+            ]
+        );
+        assert_eq!(config.oh_endeavor_map.len(), 1);
+        assert!(validate("prompt_vars:\n  project: x\n  empty:\n")[0]
+            .message
+            .contains("prompt_vars entries need"));
+    }
+
+    #[test]
+    fn test_every_key_has_a_value() {
+        let config = Config::default();
+        for key in KEYS {
+            assert!(config.value(key).is_ok(), "{}", key);
+        }
+        assert!(matches!(
+            config.value("eval_interval"),
+            Err(ConfigError::UnknownKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_inline_comments_ignored() {
+        let config = Config::parse(
+            "carryover_decision_count: 4    # recent decisions\n\
+             review_model: opus  # sg review only\n\
+             notify_webhook_url: https://example.com/#/hook\n",
+        );
+        assert_eq!(config.carryover_decision_count, 4);
+        assert_eq!(config.review_model.as_deref(), Some("opus"));
+        assert_eq!(
+            config.notify_webhook_url.as_deref(),
+            Some("https://example.com/#/hook")
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_issues_with_lines() {
+        let issues = validate(
+            "# comment\n\
+             mode: pull\n\
+             mdoe: pull\n\
+             explain_decision: yes\n\
+             feedback_cooldown_minutes: -5\n\
+             model:\n\
+             oh_endeavor_map:\n  web/: initiative:web\n  broken\n\
+             mode: always  # later wins\n\
+             no colon here\n",
+        );
+        let summary: Vec<_> = issues
+            .iter()
+            .map(|i| (i.line, i.severity, i.message.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (3, IssueSeverity::Error, "unknown key 'mdoe'"),
+                (
+                    4,
+                    IssueSeverity::Error,
+                    "invalid value 'yes' for explain_decision (it would be ignored)"
+                ),
+                (
+                    5,
+                    IssueSeverity::Error,
+                    "feedback_cooldown_minutes must be at least 0"
+                ),
+                (9, IssueSeverity::Error, "expected `key: value`"),
+                (
+                    10,
+                    IssueSeverity::Warning,
+                    "mode is also set on line 2; this later line wins"
+                ),
+                (11, IssueSeverity::Error, "expected `key: value`"),
+            ]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "config.yaml:3: error: unknown key 'mdoe'\n    3 | mdoe: pull"
+        );
+        assert!(validate("mode: always\ntimeout_ms: 60000\n").is_empty());
+    }
+
+    #[test]
+    fn test_set_value_keeps_comments() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(
+            &path,
+            "# Superego configuration\n\
+             mode: always\n\
+             # model: opus\n\
+             # timeout_ms: 30000\n",
+        )
+        .unwrap();
+
+        set_value(dir.path(), "mode", "pull").unwrap();
+        set_value(dir.path(), "model", "sonnet").unwrap();
+        set_value(dir.path(), "max_retries", "3").unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# Superego configuration\n\
+             mode: pull\n\
+             # model: opus\n\
+             model: sonnet\n\
+             # timeout_ms: 30000\n\
+             max_retries: 3\n"
+        );
+
+        let config = Config::load(dir.path());
+        assert_eq!(config.value("mode").unwrap().as_deref(), Some("pull"));
+        assert_eq!(config.value("model").unwrap().as_deref(), Some("sonnet"));
+        assert_eq!(config.value("timeout_ms").unwrap(), None);
+
+        // Unset an optional key
+        set_value(dir.path(), "model", "").unwrap();
+        assert_eq!(Config::load(dir.path()).model, None);
+
+        // Lists accept spaces after commas
+        set_value(dir.path(), "superego_tools", "Read, Glob, Grep").unwrap();
+        assert_eq!(
+            Config::load(dir.path())
+                .value("superego_tools")
+                .unwrap()
+                .as_deref(),
+            Some("Read,Glob,Grep")
+        );
+
+        // Values the parser would ignore are rejected, file untouched
+        let before = fs::read_to_string(&path).unwrap();
+        for (key, value) in [
+            ("max_retries", "lots"),
+            ("explain_decision", "yes"),
+            ("max_retries", ""),
+        ] {
+            assert!(matches!(
+                set_value(dir.path(), key, value),
+                Err(ConfigError::InvalidValue { .. })
+            ));
+        }
+        assert!(matches!(
+            set_value(dir.path(), "eval_interval", "5"),
+            Err(ConfigError::UnknownKey(_))
+        ));
+        assert_eq!(fs::read_to_string(&path).unwrap(), before);
+    }
+
+    #[test]
+    fn test_commented_example_config_uses_defaults() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("config.yaml"),
+            "# model: opus\n# timeout_ms: 30000\n# oh_endeavor_id: initiative:abc123\n",
+        )
+        .unwrap();
+
+        let config = Config::load(dir.path());
+        assert_eq!(config.model, None);
+        assert_eq!(config.timeout_ms, None);
+        assert_eq!(config.oh_endeavor_id, None);
+    }
+
     #[test]
     fn test_mode_parsing() {
         assert_eq!(Mode::from_str("always"), Some(Mode::Always));
@@ -167,9 +1214,23 @@ mod tests {
     fn test_load_pull_mode() {
         let dir = tempdir().unwrap();
         let config_path = dir.path().join("config.yaml");
-        fs::write(&config_path, "mode: pull\n").unwrap();
+        fs::write(
+            &config_path,
+            "mode: pull\nplan_mode: skip\nmin_confidence: medium\nmin_messages_to_evaluate: 4\nbackend: codex\ntask_provider: bd\nlog_format: JSON\n",
+        )
+        .unwrap();
 
         let config = Config::load(dir.path());
         assert_eq!(config.mode, Mode::Pull);
+        assert_eq!(config.plan_mode, PlanMode::Skip);
+        assert_eq!(config.min_messages_to_evaluate, 4);
+        assert_eq!(config.backend, Backend::Codex);
+        assert_eq!(Config::default().backend, Backend::Claude);
+        assert_eq!(config.task_provider, TaskProviderKind::Bd);
+        assert_eq!(Config::default().task_provider, TaskProviderKind::Auto);
+        assert_eq!(config.log_format, LogFormat::Json);
+        assert_eq!(Config::default().log_format, LogFormat::Text);
+        assert_eq!(config.min_confidence, Some(Confidence::Medium));
+        assert_eq!(Config::default().min_confidence, None);
     }
 }
@@ -0,0 +1,175 @@
+//! Evaluation cost ledger
+//!
+//! Every completed LLM evaluation appends one line to `.superego/costs.jsonl`;
+//! `sg costs` sums them up by day and by session.
+//! AIDEV-NOTE: Append-only JSONL so concurrent hooks can't clobber each other's
+//! records. Codex reports tokens rather than dollars, so its records carry
+//! `tokens` with a cost of 0.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use crate::diag;
+
+/// Ledger file within .superego/
+pub const COSTS_FILE: &str = "costs.jsonl";
+
+/// One evaluation's spend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostRecord {
+    pub timestamp: DateTime<Utc>,
+    pub session_id: Option<String>,
+    pub cost_usd: f64,
+    /// Model used; None = the CLI's default
+    pub model: Option<String>,
+    /// Token count, when the backend reports tokens instead of cost
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<u64>,
+}
+
+impl CostRecord {
+    /// A record timestamped now
+    pub fn new(session_id: Option<&str>, cost_usd: f64, model: Option<&str>) -> Self {
+        CostRecord {
+            timestamp: Utc::now(),
+            session_id: session_id.map(|s| s.to_string()),
+            cost_usd,
+            model: model.map(|m| m.to_string()),
+            tokens: None,
+        }
+    }
+}
+
+/// Append a record to the ledger
+pub fn append(superego_dir: &Path, record: &CostRecord) -> std::io::Result<()> {
+    let line = serde_json::to_string(record)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(superego_dir.join(COSTS_FILE))?;
+    writeln!(file, "{}", line)
+}
+
+/// Read all records, oldest first; empty if there is no ledger yet
+pub fn read_all(superego_dir: &Path) -> std::io::Result<Vec<CostRecord>> {
+    let path = superego_dir.join(COSTS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut records: Vec<CostRecord> = fs::read_to_string(&path)?
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| match serde_json::from_str(l) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                // Skip a torn or hand-edited line rather than failing
+                diag::warn(&format!("skipping malformed cost record: {}", e));
+                None
+            }
+        })
+        .collect();
+    records.sort_by_key(|r| r.timestamp);
+    Ok(records)
+}
+
+/// Spend for one day or one session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostBucket {
+    pub key: String,
+    pub evaluations: usize,
+    pub cost_usd: f64,
+}
+
+/// Totals for `sg costs`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostSummary {
+    pub evaluations: usize,
+    pub total_usd: f64,
+    /// By UTC day (YYYY-MM-DD), oldest first
+    pub by_day: Vec<CostBucket>,
+    /// By session, most expensive first
+    pub by_session: Vec<CostBucket>,
+}
+
+/// Sum records into buckets keyed by `key`
+fn bucket(records: &[CostRecord], key: impl Fn(&CostRecord) -> String) -> Vec<CostBucket> {
+    let mut buckets: BTreeMap<String, CostBucket> = BTreeMap::new();
+    for record in records {
+        let k = key(record);
+        let b = buckets.entry(k.clone()).or_insert(CostBucket {
+            key: k,
+            evaluations: 0,
+            cost_usd: 0.0,
+        });
+        b.evaluations += 1;
+        b.cost_usd += record.cost_usd;
+    }
+    buckets.into_values().collect()
+}
+
+/// Summarize records by day and by session
+pub fn summarize(records: &[CostRecord]) -> CostSummary {
+    let by_day = bucket(records, |r| r.timestamp.format("%Y-%m-%d").to_string());
+    let mut by_session = bucket(records, |r| {
+        r.session_id.clone().unwrap_or_else(|| "(none)".to_string())
+    });
+    by_session.sort_by(|a, b| b.cost_usd.total_cmp(&a.cost_usd));
+
+    CostSummary {
+        evaluations: records.len(),
+        total_usd: records.iter().map(|r| r.cost_usd).sum(),
+        by_day,
+        by_session,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_read_and_summarize() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(read_all(dir.path()).unwrap().is_empty());
+
+        let record = |ts: &str, session: &str, cost: f64| CostRecord {
+            timestamp: ts.parse().unwrap(),
+            ..CostRecord::new(Some(session), cost, Some("opus"))
+        };
+        append(dir.path(), &record("2025-01-11T09:00:00Z", "b", 0.5)).unwrap();
+        append(dir.path(), &record("2025-01-10T23:00:00Z", "a", 0.25)).unwrap();
+        append(dir.path(), &record("2025-01-11T10:00:00Z", "a", 0.125)).unwrap();
+        fs::write(
+            dir.path().join(COSTS_FILE),
+            fs::read_to_string(dir.path().join(COSTS_FILE)).unwrap() + "{torn\n",
+        )
+        .unwrap();
+
+        let records = read_all(dir.path()).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].session_id.as_deref(), Some("a"));
+
+        let summary = summarize(&records);
+        assert_eq!(summary.evaluations, 3);
+        assert_eq!(summary.total_usd, 0.875);
+        let days: Vec<_> = summary
+            .by_day
+            .iter()
+            .map(|b| (b.key.as_str(), b.evaluations, b.cost_usd))
+            .collect();
+        assert_eq!(
+            days,
+            vec![("2025-01-10", 1, 0.25), ("2025-01-11", 2, 0.625)]
+        );
+        let sessions: Vec<_> = summary
+            .by_session
+            .iter()
+            .map(|b| (b.key.as_str(), b.cost_usd))
+            .collect();
+        assert_eq!(sessions, vec![("b", 0.5), ("a", 0.375)]);
+    }
+}
@@ -4,12 +4,14 @@
 //! for audit trail and context recovery.
 //! AIDEV-NOTE: Simplified - constructor methods removed, just read existing files.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::fs::{self, File};
-use std::io::{BufWriter, Write};
+use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::atomic;
+use crate::diag;
+
 /// Types of decisions that can be recorded
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -17,8 +19,20 @@ pub enum DecisionType {
     OverrideGranted,
     FeedbackDelivered,
     PrecompactSnapshot,
+    /// Informational: why superego allowed the work (only with explain_decision)
+    AllowRationale,
+    /// A BLOCK below `min_confidence`: journaled, but not delivered to the agent
+    FeedbackSuppressed,
+    /// A BLOCK repeating feedback delivered within `dedupe_window_minutes`: journaled, not re-queued
+    FeedbackDuplicate,
 }
 
+/// Journal file name format (second precision; the file holds the exact time)
+const FILENAME_FORMAT: &str = "%Y-%m-%dT%H-%M-%SZ.json";
+
+/// Character budget for a one-line context summary (`sg history --short`)
+const SHORT_CONTEXT_CHARS: usize = 100;
+
 /// A decision record stored in the journal
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Decision {
@@ -28,6 +42,16 @@ pub struct Decision {
     pub decision_type: DecisionType,
     pub context: Option<String>,
     pub trigger: Option<String>,
+    /// Evaluator confidence (HIGH/MEDIUM/LOW) when the LLM gave one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<String>,
+    /// Whether the evaluator judged the work to have drifted from the current
+    /// ba task (only set when there was a task to compare against)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drift: Option<bool>,
+    /// The agent's most recent tool calls when feedback was given, e.g. "Edit(foo.rs)"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_uses: Vec<String>,
 }
 
 impl Decision {
@@ -39,6 +63,94 @@ impl Decision {
             decision_type: DecisionType::FeedbackDelivered,
             context: Some(feedback),
             trigger: None,
+            confidence: None,
+            drift: None,
+            tool_uses: Vec::new(),
+        }
+    }
+
+    /// First non-empty line of the context, capped at SHORT_CONTEXT_CHARS
+    /// ("…" marks anything dropped)
+    pub fn short_context(&self) -> Option<String> {
+        let context = self.context.as_deref()?;
+        let mut lines = context.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
+        let first = lines.next()?;
+        let truncated = first.chars().count() > SHORT_CONTEXT_CHARS;
+        let mut short: String = first.chars().take(SHORT_CONTEXT_CHARS).collect();
+        if truncated || lines.next().is_some() {
+            short.push('…');
+        }
+        Some(short)
+    }
+
+    /// Every field, with the full context last, for `sg explain`
+    /// `session` names the session when the record doesn't (e.g. its label).
+    pub fn format_detail(&self, session: Option<&str>) -> String {
+        let mut out = format!("Timestamp: {}\n", self.timestamp.to_rfc3339());
+        if let Some(session) = session.or(self.session_id.as_deref()) {
+            out.push_str(&format!("Session: {}\n", session));
+        }
+        out.push_str(&format!("Type: {:?}\n", self.decision_type));
+        if let Some(confidence) = &self.confidence {
+            out.push_str(&format!("Confidence: {}\n", confidence));
+        }
+        if let Some(drift) = self.drift {
+            out.push_str(&format!("Drift: {}\n", if drift { "yes" } else { "no" }));
+        }
+        if let Some(trigger) = &self.trigger {
+            out.push_str(&format!("Trigger: {}\n", trigger));
+        }
+        if !self.tool_uses.is_empty() {
+            out.push_str(&format!("Recent tools: {}\n", self.tool_uses.join(", ")));
+        }
+        match &self.context {
+            Some(context) => out.push_str(&format!("Context:\n{}\n", context.trim_end())),
+            None => out.push_str("Context: (none)\n"),
+        }
+        out
+    }
+
+    /// Create a record of feedback withheld for being below `min_confidence`
+    pub fn feedback_suppressed(
+        session_id: Option<String>,
+        feedback: String,
+        confidence: Option<String>,
+    ) -> Self {
+        Decision {
+            timestamp: Utc::now(),
+            session_id,
+            decision_type: DecisionType::FeedbackSuppressed,
+            context: Some(feedback),
+            trigger: None,
+            confidence,
+            drift: None,
+            tool_uses: Vec::new(),
+        }
+    }
+
+    /// Create a record of feedback not re-queued because it repeats recent feedback
+    pub fn feedback_duplicate(
+        session_id: Option<String>,
+        feedback: String,
+        confidence: Option<String>,
+    ) -> Self {
+        Decision {
+            decision_type: DecisionType::FeedbackDuplicate,
+            ..Self::feedback_suppressed(session_id, feedback, confidence)
+        }
+    }
+
+    /// Create an informational record of why an evaluation was allowed
+    pub fn allow_rationale(session_id: Option<String>, rationale: String) -> Self {
+        Decision {
+            timestamp: Utc::now(),
+            session_id,
+            decision_type: DecisionType::AllowRationale,
+            context: Some(rationale),
+            trigger: None,
+            confidence: None,
+            drift: None,
+            tool_uses: Vec::new(),
         }
     }
 }
@@ -73,25 +185,159 @@ impl From<serde_json::Error> for JournalError {
     }
 }
 
-/// Read decisions from all session directories
+/// Decisions as a pretty-printed JSON array (`[]` when empty), for `sg history --json`
+/// Timestamps serialize as RFC3339; the full context is always included.
+pub fn to_json_array(decisions: &[Decision]) -> String {
+    serde_json::to_string_pretty(decisions).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Read decisions from all session directories, paired with the session
+/// directory name they were read from (sorted by timestamp), limited to
+/// those at or after `start` and before `end` (either bound optional)
 /// AIDEV-NOTE: Used by audit and history commands to aggregate all decisions
-pub fn read_all_sessions(superego_dir: &Path) -> Result<Vec<Decision>, JournalError> {
+pub fn read_sessions_between(
+    superego_dir: &Path,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> Result<Vec<(String, Decision)>, JournalError> {
     let mut all = Vec::new();
 
     let sessions_dir = superego_dir.join("sessions");
     if sessions_dir.exists() {
         for entry in fs::read_dir(&sessions_dir)? {
-            let path = entry?.path();
+            let entry = entry?;
+            let path = entry.path();
             if path.is_dir() {
-                all.extend(Journal::new(&path).read_all()?);
+                let id = entry.file_name().to_string_lossy().to_string();
+                all.extend(
+                    Journal::new(&path)
+                        .read_between(start, end)?
+                        .into_iter()
+                        .map(|d| (id.clone(), d)),
+                );
             }
         }
     }
 
-    all.sort_by_key(|d| d.timestamp);
+    all.sort_by_key(|(_, d)| d.timestamp);
     Ok(all)
 }
 
+/// Journal file name for a decision reference: a file name (with or without
+/// .json) or a timestamp, as RFC3339 or as `sg history` prints it
+/// ("2025-01-15 10:30:00.123 UTC"). None if it's neither.
+fn reference_file_name(reference: &str) -> Option<String> {
+    let reference = reference.trim();
+    let stem = reference.strip_suffix(".json").unwrap_or(reference);
+    if NaiveDateTime::parse_from_str(&format!("{}.json", stem), FILENAME_FORMAT).is_ok() {
+        return Some(format!("{}.json", stem));
+    }
+    let timestamp =
+        NaiveDateTime::parse_from_str(reference.trim_end_matches(" UTC"), "%Y-%m-%d %H:%M:%S%.f")
+            .map(|t| t.and_utc())
+            .or_else(|_| DateTime::parse_from_rfc3339(reference).map(|t| t.with_timezone(&Utc)))
+            .ok()?;
+    Some(timestamp.format(FILENAME_FORMAT).to_string())
+}
+
+/// Journal files matching `reference` (a path to a decision file, its file
+/// name, or its timestamp), paired with the session directory they're in
+/// (None for the legacy unnamespaced journal)
+/// AIDEV-NOTE: File names only have whole seconds, so a timestamp can match
+/// decisions in several sessions; callers decide what to do with more than one.
+pub fn locate(
+    superego_dir: &Path,
+    reference: &str,
+) -> Result<Vec<(Option<String>, PathBuf)>, JournalError> {
+    let path = Path::new(reference);
+    if path.is_file() {
+        let session = path
+            .parent()
+            .filter(|dir| dir.ends_with("decisions"))
+            .and_then(Path::parent)
+            .filter(|dir| dir.parent().is_some_and(|p| p.ends_with("sessions")))
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().to_string());
+        return Ok(vec![(session, path.to_path_buf())]);
+    }
+    let Some(file_name) = reference_file_name(reference) else {
+        return Ok(Vec::new());
+    };
+
+    let mut found = Vec::new();
+    let sessions_dir = superego_dir.join("sessions");
+    if sessions_dir.exists() {
+        for entry in fs::read_dir(&sessions_dir)? {
+            let entry = entry?;
+            let candidate = entry.path().join("decisions").join(&file_name);
+            if candidate.is_file() {
+                let id = entry.file_name().to_string_lossy().to_string();
+                found.push((Some(id), candidate));
+            }
+        }
+    }
+    let legacy = superego_dir.join("decisions").join(&file_name);
+    if legacy.is_file() {
+        found.push((None, legacy));
+    }
+    found.sort();
+    Ok(found)
+}
+
+/// Read one decision file
+pub fn read_file(path: &Path) -> Result<Decision, JournalError> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Minimum files per reader thread; below this, spawning costs more than it saves
+const FILES_PER_THREAD: usize = 32;
+
+/// Read and parse decision files in parallel, in no particular order
+/// AIDEV-NOTE: Malformed files are skipped with a warning rather than failing
+/// the whole read (decision journal is audit data, not critical state).
+/// Callers sort the result.
+pub fn read_files(paths: &[PathBuf]) -> std::io::Result<Vec<Decision>> {
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(paths.len().div_ceil(FILES_PER_THREAD))
+        .max(1);
+    let chunk_size = paths.len().div_ceil(threads).max(1);
+
+    let read_chunk = |chunk: &[PathBuf]| -> std::io::Result<Vec<Decision>> {
+        let mut decisions = Vec::with_capacity(chunk.len());
+        for path in chunk {
+            let content = fs::read_to_string(path)?;
+            match serde_json::from_str::<Decision>(&content) {
+                Ok(decision) => decisions.push(decision),
+                Err(e) => {
+                    diag::warn(&format!(
+                        "skipping malformed decision file {:?}: {}",
+                        path, e
+                    ));
+                }
+            }
+        }
+        Ok(decisions)
+    };
+
+    if threads == 1 {
+        return read_chunk(paths);
+    }
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = paths
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || read_chunk(chunk)))
+            .collect();
+        let mut all = Vec::with_capacity(paths.len());
+        for handle in handles {
+            all.extend(handle.join().expect("decision reader thread panicked")?);
+        }
+        Ok(all)
+    })
+}
+
 /// Decision journal - manages reading and writing decision records
 pub struct Journal {
     decisions_dir: PathBuf,
@@ -116,16 +362,11 @@ impl Journal {
         self.ensure_dir()?;
 
         // Format timestamp for filename: 2024-01-15T10-30-00Z.json
-        let filename = decision
-            .timestamp
-            .format("%Y-%m-%dT%H-%M-%SZ.json")
-            .to_string();
+        let filename = decision.timestamp.format(FILENAME_FORMAT).to_string();
         let path = self.decisions_dir.join(&filename);
 
-        let file = File::create(&path)?;
-        let mut writer = BufWriter::new(file);
         let json = serde_json::to_string_pretty(decision)?;
-        writer.write_all(json.as_bytes())?;
+        atomic::write(&path, json.as_bytes())?;
 
         Ok(path)
     }
@@ -134,33 +375,53 @@ impl Journal {
     /// AIDEV-NOTE: Only reads .json files. Old .yaml files from pre-0.4 are ignored
     /// (decision journal is audit data, not critical state).
     pub fn read_all(&self) -> Result<Vec<Decision>, JournalError> {
+        self.read_between(None, None)
+    }
+
+    /// Read decisions at or after `start` and before `end`, sorted by timestamp
+    /// AIDEV-NOTE: Files whose name puts them out of range are skipped unread;
+    /// names only have whole seconds, so the parsed timestamp is checked too.
+    pub fn read_between(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Decision>, JournalError> {
+        let in_range = |t: DateTime<Utc>| start.is_none_or(|s| t >= s) && end.is_none_or(|e| t < e);
+
         if !self.decisions_dir.exists() {
             return Ok(Vec::new());
         }
 
-        let mut decisions = Vec::new();
+        let mut paths = Vec::new();
 
         for entry in fs::read_dir(&self.decisions_dir)? {
             let entry = entry?;
             let path = entry.path();
 
             if path.extension().is_some_and(|ext| ext == "json") {
-                let content = fs::read_to_string(&path)?;
-                match serde_json::from_str::<Decision>(&content) {
-                    Ok(decision) => decisions.push(decision),
-                    Err(e) => {
-                        // AIDEV-NOTE: Skip malformed files rather than failing
-                        eprintln!(
-                            "Warning: skipping malformed decision file {:?}: {}",
-                            path, e
-                        );
+                let name_time = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|n| NaiveDateTime::parse_from_str(n, FILENAME_FORMAT).ok())
+                    .map(|t| t.and_utc());
+                if let Some(t) = name_time {
+                    // Anything within the file's second may still be in range
+                    let second_end = t + chrono::Duration::seconds(1);
+                    if start.is_some_and(|s| second_end <= s) || end.is_some_and(|e| t >= e) {
+                        continue;
                     }
                 }
+                paths.push(path);
             }
         }
 
+        let mut decisions: Vec<Decision> = read_files(&paths)?
+            .into_iter()
+            .filter(|d| in_range(d.timestamp))
+            .collect();
+
         // Sort by timestamp (oldest first)
-        decisions.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        decisions.sort_by_key(|d| d.timestamp);
 
         Ok(decisions)
     }
@@ -171,6 +432,65 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_locate_by_path_name_or_timestamp() {
+        let dir = tempdir().unwrap();
+        let superego_dir = dir.path();
+        let mut decision = Decision::feedback_delivered(None, "Line one.\n\nLine two.".to_string());
+        decision.timestamp = DateTime::parse_from_rfc3339("2025-01-15T10:30:00.250Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let path = Journal::new(&superego_dir.join("sessions").join("s1"))
+            .write(&decision)
+            .unwrap();
+        Journal::new(&superego_dir.join("sessions").join("s2"))
+            .write(&decision)
+            .unwrap();
+
+        let by_path = locate(superego_dir, path.to_str().unwrap()).unwrap();
+        assert_eq!(by_path, vec![(Some("s1".to_string()), path.clone())]);
+
+        for reference in [
+            "2025-01-15T10-30-00Z",
+            "2025-01-15T10-30-00Z.json",
+            "2025-01-15T10:30:00.250Z",
+            "2025-01-15 10:30:00.250 UTC",
+        ] {
+            let found = locate(superego_dir, reference).unwrap();
+            let sessions: Vec<_> = found.iter().map(|(s, _)| s.as_deref()).collect();
+            assert_eq!(sessions, vec![Some("s1"), Some("s2")], "{}", reference);
+        }
+        assert!(locate(superego_dir, "2025-01-16T00-00-00Z")
+            .unwrap()
+            .is_empty());
+        assert!(locate(superego_dir, "not a decision").unwrap().is_empty());
+
+        let detail = read_file(&path).unwrap().format_detail(Some("s1"));
+        assert_eq!(
+            detail,
+            "Timestamp: 2025-01-15T10:30:00.250+00:00\n\
+             Session: s1\n\
+             Type: FeedbackDelivered\n\
+             Context:\nLine one.\n\nLine two.\n"
+        );
+    }
+
+    #[test]
+    fn test_short_context_for_long_feedback() {
+        let long_line = "x".repeat(250);
+        let decision =
+            Decision::feedback_delivered(None, format!("\n{}\n\nMore detail here.", long_line));
+        let short = decision.short_context().unwrap();
+        assert_eq!(short.chars().count(), SHORT_CONTEXT_CHARS + 1);
+        assert!(short.ends_with('…'));
+
+        let multi = Decision::feedback_delivered(None, "Missing tests.\nSee foo.rs".to_string());
+        assert_eq!(multi.short_context().unwrap(), "Missing tests.…");
+
+        let single = Decision::feedback_delivered(None, "Looks fine.".to_string());
+        assert_eq!(single.short_context().unwrap(), "Looks fine.");
+    }
+
     #[test]
     fn test_write_and_read_decision() {
         let dir = tempdir().unwrap();
@@ -182,6 +502,9 @@ mod tests {
             decision_type: DecisionType::FeedbackDelivered,
             context: Some("test feedback".to_string()),
             trigger: None,
+            confidence: Some("HIGH".to_string()),
+            drift: Some(true),
+            tool_uses: vec!["Bash(cargo test)".to_string()],
         };
 
         journal.write(&decision).unwrap();
@@ -189,5 +512,118 @@ mod tests {
         let read_back = journal.read_all().unwrap();
         assert_eq!(read_back.len(), 1);
         assert_eq!(read_back[0].decision_type, DecisionType::FeedbackDelivered);
+        assert_eq!(read_back[0].confidence.as_deref(), Some("HIGH"));
+        assert_eq!(read_back[0].drift, Some(true));
+        assert_eq!(read_back[0].tool_uses, vec!["Bash(cargo test)"]);
+    }
+
+    #[test]
+    fn test_read_decision_without_confidence() {
+        // Journals written before confidence was recorded
+        let json = r#"{"timestamp":"2025-12-17T22:16:39Z","session_id":null,"type":"feedback_delivered","context":"x","trigger":null}"#;
+        let decision: Decision = serde_json::from_str(json).unwrap();
+        assert_eq!(decision.confidence, None);
+        assert_eq!(decision.drift, None);
+        assert!(decision.tool_uses.is_empty());
+    }
+
+    #[test]
+    fn test_read_sessions_between() {
+        let dir = tempdir().unwrap();
+        let write = |session: &str, ts: &str, context: &str| {
+            Journal::new(&dir.path().join("sessions").join(session))
+                .write(&Decision {
+                    timestamp: ts.parse().unwrap(),
+                    session_id: Some(session.to_string()),
+                    decision_type: DecisionType::FeedbackDelivered,
+                    context: Some(context.to_string()),
+                    trigger: None,
+                    confidence: None,
+                    drift: None,
+                    tool_uses: Vec::new(),
+                })
+                .unwrap();
+        };
+        write("a", "2025-01-01T12:00:00Z", "old");
+        write("a", "2025-01-08T00:00:00.500Z", "start");
+        write("b", "2025-01-10T09:00:00Z", "middle");
+        write("b", "2025-01-15T00:00:00Z", "end");
+
+        let contexts = |start: Option<&str>, end: Option<&str>| -> Vec<String> {
+            read_sessions_between(
+                dir.path(),
+                start.map(|s| s.parse().unwrap()),
+                end.map(|e| e.parse().unwrap()),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(_, d)| d.context.unwrap())
+            .collect()
+        };
+
+        assert_eq!(contexts(None, None).len(), 4);
+        assert_eq!(
+            contexts(
+                Some("2025-01-08T00:00:00.200Z"),
+                Some("2025-01-15T00:00:00Z")
+            ),
+            vec!["start", "middle"]
+        );
+        // Same file second as "start", but earlier than start
+        assert_eq!(
+            contexts(Some("2025-01-08T00:00:00.800Z"), None),
+            vec!["middle", "end"]
+        );
+    }
+
+    #[test]
+    fn test_read_many_files_in_parallel() {
+        let dir = tempdir().unwrap();
+        let journal = Journal::new(dir.path());
+        let base: DateTime<Utc> = "2025-01-01T00:00:00Z".parse().unwrap();
+        // Written newest first so directory order doesn't match timestamp order
+        for i in (0..300).rev() {
+            journal
+                .write(&Decision {
+                    timestamp: base + chrono::Duration::seconds(i),
+                    session_id: None,
+                    decision_type: DecisionType::FeedbackDelivered,
+                    context: Some(i.to_string()),
+                    trigger: None,
+                    confidence: None,
+                    drift: None,
+                    tool_uses: Vec::new(),
+                })
+                .unwrap();
+        }
+        for i in 0..5 {
+            fs::write(
+                dir.path()
+                    .join("decisions")
+                    .join(format!("broken-{}.json", i)),
+                "{not json",
+            )
+            .unwrap();
+        }
+
+        let decisions = journal.read_all().unwrap();
+        assert_eq!(decisions.len(), 300);
+        assert!(decisions
+            .windows(2)
+            .all(|w| w[0].timestamp < w[1].timestamp));
+        assert_eq!(decisions[0].context.as_deref(), Some("0"));
+        assert_eq!(decisions[299].context.as_deref(), Some("299"));
+    }
+
+    #[test]
+    fn test_to_json_array() {
+        assert_eq!(to_json_array(&[]), "[]");
+
+        let json = r#"{"timestamp":"2025-12-17T22:16:39Z","session_id":"s1","type":"feedback_delivered","context":"x","trigger":null}"#;
+        let decision: Decision = serde_json::from_str(json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&to_json_array(&[decision])).unwrap();
+        assert_eq!(parsed[0]["timestamp"], "2025-12-17T22:16:39Z");
+        assert_eq!(parsed[0]["type"], "feedback_delivered");
+        assert_eq!(parsed[0]["session_id"], "s1");
     }
 }
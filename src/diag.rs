@@ -0,0 +1,69 @@
+//! Leveled stderr diagnostics
+//!
+//! `sg --quiet` hides warnings and progress notes; `sg --verbose` adds debug
+//! detail (e.g. each evaluation log line as it's written). Errors that end a
+//! command are printed directly and never hidden.
+//! AIDEV-NOTE: Process-wide level, set once from the CLI flags in main before
+//! the command runs. Library code calls `warn`/`info`/`debug` instead of eprintln.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How much diagnostic output goes to stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Errors only
+    Quiet = 0,
+    /// Warnings and progress notes (default)
+    Normal = 1,
+    /// Also debug detail
+    Verbose = 2,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+/// Set the process-wide verbosity
+pub fn set_verbosity(verbosity: Verbosity) {
+    LEVEL.store(verbosity as u8, Ordering::Relaxed);
+}
+
+/// The current verbosity
+pub fn verbosity() -> Verbosity {
+    match LEVEL.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        _ => Verbosity::Verbose,
+    }
+}
+
+/// A recoverable problem, as "Warning: <msg>" (hidden by --quiet)
+pub fn warn(msg: &str) {
+    if verbosity() >= Verbosity::Normal {
+        eprintln!("Warning: {}", msg);
+    }
+}
+
+/// A progress note (hidden by --quiet)
+pub fn info(msg: &str) {
+    if verbosity() >= Verbosity::Normal {
+        eprintln!("{}", msg);
+    }
+}
+
+/// Debug detail (only with --verbose)
+pub fn debug(msg: &str) {
+    if verbosity() >= Verbosity::Verbose {
+        eprintln!("{}", msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbosity_ordering() {
+        assert!(Verbosity::Quiet < Verbosity::Normal);
+        assert!(Verbosity::Normal < Verbosity::Verbose);
+        assert_eq!(verbosity(), Verbosity::Normal);
+    }
+}
@@ -0,0 +1,269 @@
+//! `sg doctor`: health checks for superego's external dependencies
+//!
+//! Missing CLIs or unreachable services otherwise surface as confusing errors
+//! deep inside an evaluation. Each check reports pass/warn/fail with a hint.
+//! AIDEV-NOTE: Only `.superego/` and the claude CLI are critical (Fail); codex,
+//! the task tracker and Open Horizons are optional integrations, so they can only Warn.
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::{self, Config, IssueSeverity};
+use crate::oh::{OhClient, OhConfig};
+use crate::tasks;
+
+/// Outcome of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    /// Optional dependency missing or unhealthy
+    Warn,
+    /// Superego can't work until this is fixed
+    Fail,
+}
+
+impl Status {
+    fn marker(&self) -> &'static str {
+        match self {
+            Status::Pass => "✓",
+            Status::Warn => "!",
+            Status::Fail => "✗",
+        }
+    }
+}
+
+/// One line of the checklist
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+    /// How to fix it (shown unless the check passed)
+    pub hint: Option<&'static str>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: Status::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn problem(
+        name: &'static str,
+        status: Status,
+        detail: impl Into<String>,
+        hint: &'static str,
+    ) -> Self {
+        Check {
+            name,
+            status,
+            detail: detail.into(),
+            hint: Some(hint),
+        }
+    }
+}
+
+/// Whether `.superego/` exists with a prompt
+fn check_superego_dir(superego_dir: &Path) -> Check {
+    const NAME: &str = ".superego";
+    const HINT: &str = "run 'sg init' in the project root";
+    if !superego_dir.is_dir() {
+        return Check::problem(NAME, Status::Fail, "not initialized", HINT);
+    }
+    if !superego_dir.join("prompt.md").exists() {
+        return Check::problem(NAME, Status::Fail, "prompt.md is missing", HINT);
+    }
+    Check::pass(NAME, "initialized")
+}
+
+/// Whether `claude --version` runs
+fn check_claude() -> Check {
+    const NAME: &str = "claude CLI";
+    const HINT: &str = "install Claude Code and make sure 'claude' is on PATH";
+    match Command::new("claude").arg("--version").output() {
+        Ok(out) if out.status.success() => Check::pass(
+            NAME,
+            String::from_utf8_lossy(&out.stdout).trim().to_string(),
+        ),
+        Ok(out) => Check::problem(
+            NAME,
+            Status::Fail,
+            format!(
+                "'claude --version' failed: {}",
+                String::from_utf8_lossy(&out.stderr).trim()
+            ),
+            HINT,
+        ),
+        Err(e) => Check::problem(NAME, Status::Fail, format!("not found ({})", e), HINT),
+    }
+}
+
+/// Whether the codex CLI is available (only needed for Codex evaluation/review)
+fn check_codex() -> Check {
+    const NAME: &str = "codex CLI";
+    if crate::codex_llm::is_available() {
+        Check::pass(NAME, "available")
+    } else {
+        Check::problem(
+            NAME,
+            Status::Warn,
+            "not found",
+            "only needed for 'sg review-codex'/'sg evaluate-codex': npm install -g @openai/codex",
+        )
+    }
+}
+
+/// Whether config.yaml has lines the parser would ignore (see `config::validate`)
+fn check_config(superego_dir: &Path) -> Check {
+    const NAME: &str = "config.yaml";
+    let Ok(content) = std::fs::read_to_string(superego_dir.join("config.yaml")) else {
+        return Check::pass(NAME, "not present (defaults)");
+    };
+    let issues = config::validate(&content);
+    let errors: Vec<_> = issues
+        .iter()
+        .filter(|i| i.severity == IssueSeverity::Error)
+        .collect();
+    match errors.first() {
+        None if issues.is_empty() => Check::pass(NAME, "valid"),
+        None => Check::pass(NAME, format!("valid, {} warning(s)", issues.len())),
+        Some(first) => Check::problem(
+            NAME,
+            Status::Warn,
+            format!(
+                "{} problem(s), first on line {}: {}",
+                errors.len(),
+                first.line,
+                first.message
+            ),
+            "run 'sg config validate' for details; ignored lines fall back to defaults",
+        ),
+    }
+}
+
+/// Fix hint for a missing task tracker
+const TASKS_HINT: &str =
+    "optional; run 'ba init' or 'bd init' (see task_provider) to give superego current-task context";
+
+/// Whether the configured task tracker (ba/bd) is set up for task-drift context
+fn check_tasks(superego_dir: &Path) -> Check {
+    const NAME: &str = "task tracker";
+    let kind = Config::load(superego_dir).task_provider;
+    match tasks::provider(kind) {
+        Some(provider) if provider.is_initialized() => {
+            Check::pass(NAME, format!("{} initialized", provider.name()))
+        }
+        Some(provider) => Check::problem(
+            NAME,
+            Status::Warn,
+            format!("{} not installed or not initialized", provider.name()),
+            TASKS_HINT,
+        ),
+        // Only auto finds no provider
+        None => Check::problem(
+            NAME,
+            Status::Warn,
+            "neither ba nor bd is initialized",
+            TASKS_HINT,
+        ),
+    }
+}
+
+/// Whether the configured OH API answers (skipped when OH isn't configured)
+fn check_oh(superego_dir: &Path) -> Option<Check> {
+    const NAME: &str = "Open Horizons";
+    let config = OhConfig::from_config(superego_dir)?;
+    let url = config.api_url.clone();
+    let result = OhClient::with_config(config).and_then(|client| client.check_connection());
+    Some(match result {
+        Ok(()) => Check::pass(NAME, format!("reachable at {}", url)),
+        Err(e) => Check::problem(
+            NAME,
+            Status::Warn,
+            e.to_string(),
+            "check oh_api_url/oh_api_key (or 'sg setup-oh'), proxies and oh_ca_cert",
+        ),
+    })
+}
+
+/// Run every check
+pub fn run_checks(superego_dir: &Path) -> Vec<Check> {
+    let mut checks = vec![
+        check_superego_dir(superego_dir),
+        check_config(superego_dir),
+        check_claude(),
+        check_codex(),
+        check_tasks(superego_dir),
+    ];
+    checks.extend(check_oh(superego_dir));
+    checks
+}
+
+/// Whether any critical check failed
+pub fn has_failures(checks: &[Check]) -> bool {
+    checks.iter().any(|c| c.status == Status::Fail)
+}
+
+/// The checklist as printed by `sg doctor`
+pub fn format_report(checks: &[Check]) -> String {
+    let width = checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for check in checks {
+        out.push_str(&format!(
+            "{} {:width$}  {}\n",
+            check.status.marker(),
+            check.name,
+            check.detail,
+            width = width
+        ));
+        if let Some(hint) = check.hint.filter(|_| check.status != Status::Pass) {
+            out.push_str(&format!("  {:width$}  → {}\n", "", hint, width = width));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_superego_dir_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let superego_dir = dir.path().join(".superego");
+        assert_eq!(check_superego_dir(&superego_dir).status, Status::Fail);
+
+        std::fs::create_dir(&superego_dir).unwrap();
+        let check = check_superego_dir(&superego_dir);
+        assert_eq!(check.status, Status::Fail);
+        assert_eq!(check.detail, "prompt.md is missing");
+
+        std::fs::write(superego_dir.join("prompt.md"), "prompt").unwrap();
+        assert_eq!(check_superego_dir(&superego_dir).status, Status::Pass);
+    }
+
+    #[test]
+    fn test_report_and_failures() {
+        let checks = vec![
+            Check::pass("claude CLI", "2.0.1"),
+            Check::problem("ba", Status::Warn, "not initialized", "run 'ba init'"),
+        ];
+        assert!(!has_failures(&checks));
+        assert_eq!(
+            format_report(&checks),
+            "✓ claude CLI  2.0.1\n! ba          not initialized\n              → run 'ba init'\n"
+        );
+
+        let failed = vec![Check::problem(
+            ".superego",
+            Status::Fail,
+            "missing",
+            "sg init",
+        )];
+        assert!(has_failures(&failed));
+    }
+}
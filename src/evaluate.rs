@@ -2,18 +2,29 @@
 //!
 //! LLM-based evaluation with natural language feedback.
 
+use std::collections::HashSet;
 use std::fs;
 use std::path::Path;
 
 use chrono::Duration;
+use serde_json::json;
 
-use crate::ba;
-use crate::claude::{self, ClaudeOptions};
-use crate::config::Config;
+use crate::claude;
+use crate::codex_llm;
+use crate::config::{Backend, Config, PlanMode};
+use crate::costs::{self, CostRecord};
 use crate::decision::{Decision, DecisionType, Journal};
+use crate::diag;
 use crate::feedback::{Feedback, FeedbackQueue};
+use crate::llm::{self, InvokeOptions, LlmError, LlmResponse};
+use crate::lock::{self, ScopedLock};
+use crate::logfile;
+use crate::notify::{self, FeedbackNotification};
 use crate::oh::OhIntegration;
+use crate::overview;
+use crate::prompts;
 use crate::state::StateManager;
+use crate::tasks;
 use crate::transcript;
 
 /// Error type for evaluation
@@ -22,6 +33,7 @@ use crate::transcript;
 pub enum EvaluateError {
     TranscriptError(transcript::TranscriptError),
     ClaudeError(claude::ClaudeError),
+    CodexError(codex_llm::CodexLlmError),
     IoError(std::io::Error),
 }
 
@@ -30,6 +42,7 @@ impl std::fmt::Display for EvaluateError {
         match self {
             EvaluateError::TranscriptError(e) => write!(f, "Transcript error: {}", e),
             EvaluateError::ClaudeError(e) => write!(f, "Claude error: {}", e),
+            EvaluateError::CodexError(e) => write!(f, "Codex error: {}", e),
             EvaluateError::IoError(e) => write!(f, "IO error: {}", e),
         }
     }
@@ -37,6 +50,19 @@ impl std::fmt::Display for EvaluateError {
 
 impl std::error::Error for EvaluateError {}
 
+impl EvaluateError {
+    /// The signal, if the LLM CLI was killed by SIGTERM/SIGINT
+    pub fn interrupted(&self) -> Option<i32> {
+        match self {
+            EvaluateError::ClaudeError(claude::ClaudeError::Interrupted(signal))
+            | EvaluateError::CodexError(codex_llm::CodexLlmError::Interrupted(signal)) => {
+                Some(*signal)
+            }
+            _ => None,
+        }
+    }
+}
+
 impl From<transcript::TranscriptError> for EvaluateError {
     fn from(e: transcript::TranscriptError) -> Self {
         EvaluateError::TranscriptError(e)
@@ -49,6 +75,15 @@ impl From<claude::ClaudeError> for EvaluateError {
     }
 }
 
+impl From<LlmError> for EvaluateError {
+    fn from(e: LlmError) -> Self {
+        match e {
+            LlmError::Claude(e) => EvaluateError::ClaudeError(e),
+            LlmError::Codex(e) => EvaluateError::CodexError(e),
+        }
+    }
+}
+
 impl From<std::io::Error> for EvaluateError {
     fn from(e: std::io::Error) -> Self {
         EvaluateError::IoError(e)
@@ -63,6 +98,26 @@ pub enum Confidence {
     Low,
 }
 
+impl Confidence {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.trim().to_uppercase().as_str() {
+            "HIGH" => Some(Confidence::High),
+            "MEDIUM" => Some(Confidence::Medium),
+            "LOW" => Some(Confidence::Low),
+            _ => None,
+        }
+    }
+
+    /// Ordering key: LOW < MEDIUM < HIGH
+    fn rank(self) -> u8 {
+        match self {
+            Confidence::Low => 0,
+            Confidence::Medium => 1,
+            Confidence::High => 2,
+        }
+    }
+}
+
 impl std::fmt::Display for Confidence {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -81,10 +136,428 @@ pub struct LlmEvaluationResult {
     /// Whether there were concerns
     pub has_concerns: bool,
     /// Confidence level of the evaluation (included in feedback, exposed for callers)
-    #[allow(dead_code)]
     pub confidence: Option<Confidence>,
     /// Cost of the LLM call
     pub cost_usd: f64,
+    /// Cost of the second-opinion call (config `second_opinion_model`)
+    pub second_opinion_cost_usd: Option<f64>,
+    /// Tokens used, when the backend reports tokens instead of cost (Codex)
+    pub tokens: Option<u64>,
+    /// Why no evaluation was made (e.g. "disabled", "locked"); None if evaluated
+    pub skip_reason: Option<&'static str>,
+    /// Transcript entries in the evaluated context (0 for plain text)
+    pub context_entries: usize,
+    /// Size of the evaluated conversation context in bytes
+    pub context_bytes: usize,
+    /// Wall-clock time of the LLM call(s); None if no call was made
+    pub elapsed_secs: Option<f32>,
+}
+
+impl LlmEvaluationResult {
+    /// Nothing to evaluate: no LLM call, no concerns
+    fn no_concerns() -> Self {
+        LlmEvaluationResult {
+            feedback: "No concerns.".to_string(),
+            has_concerns: false,
+            confidence: None,
+            cost_usd: 0.0,
+            second_opinion_cost_usd: None,
+            tokens: None,
+            skip_reason: None,
+            context_entries: 0,
+            context_bytes: 0,
+            elapsed_secs: None,
+        }
+    }
+
+    /// Evaluation skipped for `reason`, explained by `feedback`
+    fn skipped(reason: &'static str, feedback: String) -> Self {
+        LlmEvaluationResult {
+            feedback,
+            skip_reason: Some(reason),
+            ..Self::no_concerns()
+        }
+    }
+}
+
+/// A Gemini, aider or Codex conversation, formatted as evaluation context
+pub struct AgentContext {
+    /// Display name of the agent, e.g. "Codex"
+    pub agent: &'static str,
+    /// Transcript entries read (0 = nothing to evaluate)
+    pub entries: usize,
+    pub context: String,
+}
+
+/// Read a Gemini, aider or Codex transcript whole, auto-detecting the format
+/// None for Claude Code transcripts, which are read from the last-evaluated cursor.
+/// AIDEV-NOTE: Shared by evaluate_llm and evaluate-dir so both detect formats alike.
+pub fn read_agent_context(
+    path: &Path,
+    limits: &transcript::ContextLimits,
+) -> Result<Option<AgentContext>, transcript::TranscriptError> {
+    let context = if transcript::gemini::is_gemini_format(path) {
+        let entries = transcript::gemini::read_gemini_transcript(path)?;
+        AgentContext {
+            agent: "Gemini",
+            entries: entries.len(),
+            context: transcript::gemini::format_gemini_context(&entries, limits),
+        }
+    } else if transcript::aider::is_aider_format(path) {
+        // Latest session in the file
+        let entries = transcript::aider::read_aider_transcript(path)?;
+        AgentContext {
+            agent: "aider",
+            entries: entries.len(),
+            context: transcript::aider::format_aider_context(&entries, limits),
+        }
+    } else if transcript::codex::is_codex_format(path) {
+        let entries = transcript::codex::read_codex_transcript(path)?;
+        AgentContext {
+            agent: "Codex",
+            entries: entries.len(),
+            context: transcript::codex::format_codex_context(&entries, limits),
+        }
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(context))
+}
+
+/// Options for LLM evaluation
+#[derive(Debug, Clone, Default)]
+pub struct EvaluateOptions {
+    /// Topic to pay particular attention to (overrides config `default_focus`)
+    pub focus: Option<String>,
+    /// Accept a plain-text/markdown conversation export instead of JSONL
+    pub plain: bool,
+    /// Request and journal a one-line rationale on ALLOW (or config `explain_decision`)
+    pub explain_decision: bool,
+    /// Cap on queued/displayed feedback length (overrides config `max_feedback_chars`)
+    pub max_feedback_chars: Option<usize>,
+    /// Claude Code transcript already read (`--stdin`); the path is then not read
+    pub transcript: Option<Vec<transcript::TranscriptEntry>>,
+    /// When `transcript` was read, used as the new read cursor (default: now)
+    pub transcript_read_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// LLM CLI timeout in milliseconds (overrides config `timeout_ms`)
+    pub timeout_ms: Option<u64>,
+    /// LLM backend (overrides config `backend`)
+    pub backend: Option<Backend>,
+}
+
+/// Truncate feedback to at most `max` characters (char-safe, marked with "…")
+fn truncate_feedback(feedback: &str, max: usize) -> String {
+    if feedback.chars().count() <= max {
+        return feedback.to_string();
+    }
+    let mut truncated: String = feedback.chars().take(max.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Queue feedback for the agent and record it in the decision journal
+/// AIDEV-NOTE: The queue gets `displayed` (possibly truncated by
+/// max_feedback_chars); the journal always keeps the full `feedback`.
+fn deliver_feedback(
+    session_dir: &Path,
+    session_id: Option<String>,
+    feedback: &str,
+    displayed: &str,
+    confidence: Option<Confidence>,
+    drift: Option<bool>,
+    tool_uses: Vec<String>,
+) -> Decision {
+    let queue = FeedbackQueue::new(session_dir);
+    // Include confidence in feedback so agent sees it
+    let feedback_with_confidence = if let Some(conf) = confidence {
+        format!("CONFIDENCE: {}\n\n{}", conf, displayed)
+    } else {
+        displayed.to_string()
+    };
+    let fb = Feedback::warning(&feedback_with_confidence);
+    if let Err(e) = queue.write(&fb) {
+        eprintln!("ERROR: failed to write feedback file: {}", e);
+        eprintln!("FEEDBACK CONTENT (fallback):\n{}", feedback_with_confidence);
+    }
+    // Record to decision journal for audit trail (session-namespaced per user requirement)
+    let journal = Journal::new(session_dir);
+    let mut decision = Decision::feedback_delivered(session_id, feedback.to_string());
+    decision.confidence = confidence.map(|c| c.to_string());
+    decision.drift = drift;
+    decision.tool_uses = tool_uses;
+    if let Err(e) = journal.write(&decision) {
+        diag::warn(&format!("failed to write decision journal: {}", e));
+    }
+    decision
+}
+
+/// Whether a BLOCK's confidence falls below the configured minimum
+/// AIDEV-NOTE: A response without a CONFIDENCE line is never suppressed -
+/// unknown confidence shouldn't silently drop concerns.
+fn below_min_confidence(confidence: Option<Confidence>, min: Option<Confidence>) -> bool {
+    match (confidence, min) {
+        (Some(confidence), Some(min)) => confidence.rank() < min.rank(),
+        _ => false,
+    }
+}
+
+/// Journal a BLOCK that was withheld for low confidence (not queued)
+fn record_suppressed_feedback(
+    session_dir: &Path,
+    session_id: Option<String>,
+    feedback: &str,
+    confidence: Option<Confidence>,
+    drift: Option<bool>,
+) {
+    let decision = Decision {
+        drift,
+        ..Decision::feedback_suppressed(
+            session_id,
+            feedback.to_string(),
+            confidence.map(|c| c.to_string()),
+        )
+    };
+    if let Err(e) = Journal::new(session_dir).write(&decision) {
+        diag::warn(&format!("failed to write decision journal: {}", e));
+    }
+}
+
+/// Journal a BLOCK that repeated recent feedback (not re-queued)
+fn record_duplicate_feedback(
+    session_dir: &Path,
+    session_id: Option<String>,
+    feedback: &str,
+    confidence: Option<Confidence>,
+    drift: Option<bool>,
+) {
+    let decision = Decision {
+        drift,
+        ..Decision::feedback_duplicate(
+            session_id,
+            feedback.to_string(),
+            confidence.map(|c| c.to_string()),
+        )
+    };
+    if let Err(e) = Journal::new(session_dir).write(&decision) {
+        diag::warn(&format!("failed to write decision journal: {}", e));
+    }
+}
+
+/// Share of distinct words two pieces of feedback must have in common to be a repeat
+const DUPLICATE_SIMILARITY: f64 = 0.8;
+
+/// Whether two pieces of feedback say substantially the same thing
+/// (overlap of their lowercase words; punctuation and formatting ignored)
+fn is_same_feedback(a: &str, b: &str) -> bool {
+    let words = |text: &str| -> HashSet<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let (a, b) = (words(a), words(b));
+    let union = a.union(&b).count();
+    union > 0 && a.intersection(&b).count() as f64 / union as f64 >= DUPLICATE_SIMILARITY
+}
+
+/// Whether feedback repeats the most recently delivered feedback within the window
+/// AIDEV-NOTE: Only the latest delivery counts - once the advice changed, going
+/// back to an older concern is news again. `minutes` 0 disables deduplication.
+fn repeats_recent_feedback(
+    feedback: &str,
+    decisions: &[Decision],
+    now: chrono::DateTime<chrono::Utc>,
+    minutes: i64,
+) -> bool {
+    if minutes <= 0 {
+        return false;
+    }
+    decisions
+        .iter()
+        .rev()
+        .find(|d| d.decision_type == DecisionType::FeedbackDelivered)
+        .filter(|d| now - d.timestamp < Duration::minutes(minutes))
+        .and_then(|d| d.context.as_deref())
+        .is_some_and(|previous| is_same_feedback(feedback, previous))
+}
+
+/// Common words that say nothing about whether feedback was acted on
+const FEEDBACK_STOPWORDS: &[&str] = &[
+    "about", "after", "again", "because", "before", "being", "could", "consider", "every", "might",
+    "other", "should", "still", "their", "there", "these", "thing", "those", "which", "where",
+    "while", "would",
+];
+
+/// Heuristic: did activity after the feedback address it?
+///
+/// Some(true) if at least a third of the feedback's distinctive words show up
+/// afterwards, None if there is no later activity to judge by.
+/// AIDEV-NOTE: Cheap stand-in for retro's LLM-judged `accepted`. The feedback
+/// text itself is removed first, since the hook injects it into the transcript.
+fn feedback_accepted(feedback: &str, later_context: &str) -> Option<bool> {
+    let later = later_context
+        .to_lowercase()
+        .replace(&feedback.to_lowercase(), "");
+    if later.trim().is_empty() {
+        return None;
+    }
+
+    let keywords: std::collections::BTreeSet<String> = feedback
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|w| w.chars().count() >= 5)
+        .map(|w| w.to_lowercase())
+        .filter(|w| !FEEDBACK_STOPWORDS.contains(&w.as_str()))
+        .collect();
+    if keywords.is_empty() {
+        return None;
+    }
+
+    let hits = keywords
+        .iter()
+        .filter(|k| later.contains(k.as_str()))
+        .count();
+    Some(hits * 3 >= keywords.len())
+}
+
+/// Prompt section describing the most recent feedback and its outcome
+fn previous_feedback_section(decision: &Decision, later_context: &str) -> String {
+    let feedback = decision.context.as_deref().unwrap_or("(no context)");
+    let status = match feedback_accepted(feedback, later_context) {
+        Some(true) => "likely addressed (later activity refers to it)",
+        Some(false) => "likely not addressed (later activity doesn't refer to it)",
+        None => "unclear (no activity since)",
+    };
+    format!(
+        "--- PREVIOUS FEEDBACK (and whether it was addressed) ---\n\
+        [{}]: {}\n\
+        Status: {}\n\
+        --- END PREVIOUS FEEDBACK ---\n\n",
+        decision.timestamp.format("%H:%M:%S"),
+        feedback,
+        status
+    )
+}
+
+/// Combine primary and second-opinion evaluations
+///
+/// ALLOW if both allow, BLOCK if both block. When they disagree the concerns
+/// are still delivered, but flagged as disputed and at LOW confidence.
+/// AIDEV-NOTE: Disagreement escalates rather than silently allowing - dropping
+/// one model's concern would defeat the point of asking for a second opinion.
+fn combine_opinions(
+    primary: (bool, String, Option<Confidence>),
+    second: (bool, String, Option<Confidence>),
+    second_model: &str,
+) -> (bool, String, Option<Confidence>) {
+    match (primary.0, second.0) {
+        (false, false) => primary,
+        (true, true) => (
+            true,
+            format!(
+                "{}\n\nSecond opinion ({}) agrees:\n{}",
+                primary.1, second_model, second.1
+            ),
+            primary.2,
+        ),
+        (true, false) => (
+            true,
+            format!(
+                "MODELS DISAGREE: the second opinion ({}) found no concerns. \
+                Weigh this feedback accordingly.\n\n{}",
+                second_model, primary.1
+            ),
+            Some(Confidence::Low),
+        ),
+        (false, true) => (
+            true,
+            format!(
+                "MODELS DISAGREE: only the second opinion ({}) raised concerns. \
+                Weigh this feedback accordingly.\n\n{}",
+                second_model, second.1
+            ),
+            Some(Confidence::Low),
+        ),
+    }
+}
+
+/// How many of the agent's latest tool calls to journal with feedback
+const RECENT_TOOL_USES: usize = 5;
+
+/// How many recent deliveries to strip from the context as echoes
+const RECENT_FEEDBACK_ECHOES: usize = 10;
+
+/// Per-session lock held while evaluating (in the session dir)
+const EVALUATE_LOCK_FILE: &str = "evaluate.lock";
+
+/// Evaluation log in .superego (the Claude path's counterpart to codex.log)
+const EVAL_LOG_FILE: &str = "eval.log";
+
+/// Appended to the message when the agent is still in plan mode
+const PLAN_MODE_NOTE: &str = "\n\nNOTE: The agent is in plan mode and has not changed anything \
+yet. Judge the plan itself; don't flag missing implementation, tests or edits.";
+
+/// Appended to the system prompt when explain_decision is enabled
+const EXPLAIN_ALLOW_INSTRUCTION: &str = "## Explain ALLOW\n\n\
+When your decision is ALLOW, still include a one-line rationale explaining \
+why the work is sound.\n";
+
+/// Appended to the system prompt when a ba task is in progress
+const TASK_DRIFT_INSTRUCTION: &str = "## Task Drift\n\n\
+The message names the CURRENT TASK(S) in progress. Assess whether the \
+conversation is still working toward one of them or has drifted to unrelated \
+work. Directly after the DECISION line (and CONFIDENCE, if given), add:\n\n\
+DRIFT: YES|NO\n\n\
+Drift alone is not a reason to BLOCK; mention it in the feedback when it matters.\n";
+
+/// Remove the DRIFT line from a response, returning its value and the rest
+/// AIDEV-NOTE: Taken out before parse_decision_response so the line can't end
+/// up in the feedback or hide a CONFIDENCE line. Unrecognized values → None.
+fn take_drift(response: &str) -> (Option<bool>, String) {
+    let mut found = false;
+    let mut drift = None;
+    let rest: Vec<&str> = response
+        .lines()
+        .filter(|line| {
+            if found {
+                return true;
+            }
+            let Some(value) = strip_markdown_prefix(line).strip_prefix("DRIFT:") else {
+                return true;
+            };
+            found = true;
+            let value = value.trim_start_matches('*').trim().to_lowercase();
+            drift = match value.split_whitespace().next() {
+                Some("yes" | "true") => Some(true),
+                Some("no" | "false") => Some(false),
+                _ => None,
+            };
+            false
+        })
+        .collect();
+    (drift, rest.join("\n"))
+}
+
+/// Extract the one-line rationale from ALLOW feedback (first non-empty line)
+fn allow_rationale(feedback: &str) -> Option<String> {
+    feedback
+        .lines()
+        .map(|l| strip_markdown_prefix(l).trim_end_matches('*').trim())
+        .find(|l| !l.is_empty())
+        .map(|l| l.to_string())
+}
+
+/// Journal an ALLOW rationale as an informational decision
+/// AIDEV-NOTE: Not FeedbackDelivered - carryover and retro only consider concerns.
+fn record_allow_rationale(
+    session_dir: &Path,
+    session_id: Option<String>,
+    feedback: &str,
+) -> Option<Decision> {
+    let decision = Decision::allow_rationale(session_id, allow_rationale(feedback)?);
+    if let Err(e) = Journal::new(session_dir).write(&decision) {
+        diag::warn(&format!("failed to write decision journal: {}", e));
+    }
+    Some(decision)
 }
 
 /// Strip common markdown formatting from a line
@@ -106,7 +579,7 @@ fn strip_markdown_prefix(line: &str) -> &str {
 /// Returns (has_concerns, feedback_text, confidence)
 /// AIDEV-NOTE: If parsing fails, defaults to BLOCK to be safe.
 /// AIDEV-NOTE: Handles markdown variations like "## DECISION:" or "**DECISION:**"
-fn parse_decision_response(response: &str) -> (bool, String, Option<Confidence>) {
+pub fn parse_decision_response(response: &str) -> (bool, String, Option<Confidence>) {
     let lines: Vec<&str> = response.lines().collect();
 
     if lines.is_empty() {
@@ -132,12 +605,7 @@ fn parse_decision_response(response: &str) -> (bool, String, Option<Confidence>)
                     }
                     // First non-empty line: either CONFIDENCE or start of feedback
                     if let Some(c) = trimmed.strip_prefix("CONFIDENCE:") {
-                        confidence = match c.trim().to_uppercase().as_str() {
-                            "HIGH" => Some(Confidence::High),
-                            "MEDIUM" => Some(Confidence::Medium),
-                            "LOW" => Some(Confidence::Low),
-                            _ => None,
-                        };
+                        confidence = Confidence::from_str(c);
                         if confidence.is_some() {
                             confidence_line_idx = Some(idx + offset);
                         }
@@ -163,10 +631,10 @@ fn parse_decision_response(response: &str) -> (bool, String, Option<Confidence>)
                 "ALLOW" => return (false, feedback, confidence),
                 "BLOCK" => return (true, feedback, confidence),
                 _ => {
-                    eprintln!(
-                        "Warning: Unknown decision '{}', defaulting to BLOCK",
+                    diag::warn(&format!(
+                        "Unknown decision '{}', defaulting to BLOCK",
                         decision
-                    );
+                    ));
                     return (true, feedback, confidence);
                 }
             }
@@ -180,17 +648,82 @@ fn parse_decision_response(response: &str) -> (bool, String, Option<Confidence>)
     (has_concerns, response.to_string(), None)
 }
 
+/// Load the evaluation system prompt: prompt.md (else the embedded prompt for
+/// the configured base) followed by the prompt.d layers, with `{{name}}`
+/// tokens replaced from config `prompt_vars`
+pub fn load_system_prompt(superego_dir: &Path) -> std::io::Result<String> {
+    let prompt_path = superego_dir.join("prompt.md");
+    let base = if prompt_path.exists() {
+        fs::read_to_string(&prompt_path)?
+    } else {
+        prompts::get_current_base(superego_dir)
+            .unwrap_or(prompts::PromptType::Code)
+            .content()
+            .to_string()
+    };
+    let prompt = prompts::with_layers(superego_dir, &base)?;
+    let (prompt, unknown) =
+        prompts::substitute_vars(&prompt, &Config::load(superego_dir).prompt_vars);
+    for name in unknown {
+        diag::warn(&format!(
+            "prompt variable {{{{{}}}}} has no value in prompt_vars; left as is",
+            name
+        ));
+    }
+    Ok(prompt)
+}
+
 /// Evaluate conversation using LLM with natural language feedback
 ///
-/// AIDEV-NOTE: This calls Claude with the superego prompt and gets
-/// rich natural language feedback that Claude can reason about.
+/// AIDEV-NOTE: This calls the config `backend` (Claude by default) with the
+/// superego prompt and gets rich natural language feedback the agent can reason about.
 /// Context is everything since last_evaluated - not an arbitrary window.
 /// When session_id is provided, uses session-namespaced paths for state isolation.
 pub fn evaluate_llm(
     transcript_path: &Path,
     superego_dir: &Path,
     session_id: Option<&str>,
+    options: &EvaluateOptions,
 ) -> Result<LlmEvaluationResult, EvaluateError> {
+    let backend = llm::for_backend(
+        options
+            .backend
+            .unwrap_or_else(|| Config::load(superego_dir).backend),
+    );
+    evaluate_llm_with(
+        transcript_path,
+        superego_dir,
+        session_id,
+        options,
+        |system_prompt, message, invoke_options| {
+            backend.invoke(system_prompt, message, invoke_options)
+        },
+    )
+}
+
+/// `evaluate_llm` with the LLM call injected (tests substitute a fake)
+fn evaluate_llm_with<F>(
+    transcript_path: &Path,
+    superego_dir: &Path,
+    session_id: Option<&str>,
+    options: &EvaluateOptions,
+    mut invoke: F,
+) -> Result<LlmEvaluationResult, EvaluateError>
+where
+    F: FnMut(&str, &str, &InvokeOptions) -> Result<LlmResponse, LlmError>,
+{
+    // Paused via `sg disable` (root state, not per-session)
+    if StateManager::new(superego_dir)
+        .load()
+        .unwrap_or_default()
+        .disabled
+    {
+        return Ok(LlmEvaluationResult::skipped(
+            "disabled",
+            "Skipped: superego is disabled (run 'sg enable' to resume).".to_string(),
+        ));
+    }
+
     // Use session-namespaced directory for state if session_id provided
     let session_dir = if let Some(sid) = session_id {
         superego_dir.join("sessions").join(sid)
@@ -203,55 +736,206 @@ pub fn evaluate_llm(
         fs::create_dir_all(&session_dir)?;
     }
 
+    // The hooks only read feedback.jsonl: queue feedback left in the legacy file
+    FeedbackQueue::new(&session_dir).migrate_legacy();
+
+    // Load config for carryover/cooldown settings
+    let config = Config::load(superego_dir);
+    let timeout_ms = options.timeout_ms.or(config.timeout_ms);
+    let backend = options.backend.unwrap_or(config.backend);
+
+    // AIDEV-NOTE: Hooks usually discard stderr, so eval.log is where this path
+    // is debugged (`sg tail`). Same format and rotation as codex.log.
+    let log = |event: &str, msg: &str, fields: serde_json::Value| {
+        diag::debug(msg);
+        let line = logfile::format_line(config.log_format, event, msg, fields);
+        let _ = logfile::append(
+            &superego_dir.join(EVAL_LOG_FILE),
+            &line,
+            config.log_max_bytes,
+        );
+    };
+    log(
+        "start",
+        &format!("evaluate-llm started: {}", transcript_path.display()),
+        json!({"session": session_id, "transcript": transcript_path.display().to_string()}),
+    );
+
+    // One evaluation per session at a time; overlapping ones would race on state
+    // AIDEV-NOTE: Not "eval.lock" - the plugin hooks already mkdir that as their own lock.
+    // A lock is only stale once the LLM calls (with retries and any second
+    // opinion) could no longer be running.
+    let stale_after = lock::stale_after_calls(
+        timeout_ms.map_or(lock::STALE_AFTER, std::time::Duration::from_millis),
+        config.max_retries,
+        1 + u32::from(config.second_opinion_model.is_some()),
+    );
+    let Some(_lock) =
+        ScopedLock::acquire(&session_dir.join(EVALUATE_LOCK_FILE), stale_after, &|msg| {
+            log("lock", msg, json!({}));
+            diag::info(msg)
+        })
+    else {
+        log(
+            "skip",
+            "SKIP: Another evaluation of this session in progress",
+            json!({"reason": "locked"}),
+        );
+        return Ok(LlmEvaluationResult::skipped(
+            "locked",
+            "Skipped: another evaluation of this session is in progress.".to_string(),
+        ));
+    };
+
     // Load state to get last_evaluated timestamp (from session dir)
     let state_mgr = StateManager::new(&session_dir);
     let state = state_mgr.load().unwrap_or_default();
 
+    // Give the agent time to act on the last BLOCK before evaluating again
+    // AIDEV-NOTE: last_evaluated is left alone, so whatever happens during the
+    // cooldown is evaluated once it ends.
+    if state.in_feedback_cooldown(chrono::Utc::now(), config.feedback_cooldown_minutes) {
+        log(
+            "skip",
+            "SKIP: Feedback cooldown active",
+            json!({"reason": "cooldown"}),
+        );
+        return Ok(LlmEvaluationResult::skipped(
+            "cooldown",
+            "Skipped: feedback cooldown active.".to_string(),
+        ));
+    }
+
     // AIDEV-NOTE: Capture read timestamp NOW, before reading transcript.
     // This creates a barrier: "we've evaluated everything as of this moment".
     // Messages written during LLM eval will be caught by next evaluation.
     // Using Utc::now() at read time (not finish time) prevents race conditions.
-    let transcript_read_at = chrono::Utc::now();
+    // A preloaded (--stdin) transcript was read earlier, by the caller.
+    let transcript_read_at = options.transcript_read_at.unwrap_or_else(chrono::Utc::now);
+
+    let decisions = Journal::new(&session_dir).read_all().ok();
+    // Our own recent feedback, stripped from Claude Code context so it isn't re-evaluated
+    let echoes = transcript::FeedbackEchoes::new(
+        decisions
+            .iter()
+            .flatten()
+            .rev()
+            .filter(|d| d.decision_type == DecisionType::FeedbackDelivered)
+            .take(RECENT_FEEDBACK_ECHOES)
+            .filter_map(|d| d.context.as_deref()),
+    );
 
     // Auto-detect transcript format and load appropriately
     // AIDEV-NOTE: transcript_entries is kept around for carryover context (avoids double read)
-    let (context, transcript_entries) = if transcript::codex::is_codex_format(transcript_path) {
-        // Codex format
-        let entries = transcript::codex::read_codex_transcript(transcript_path)?;
-        if entries.is_empty() {
-            return Ok(LlmEvaluationResult {
-                feedback: "No concerns.".to_string(),
-                has_concerns: false,
-                confidence: None,
-                cost_usd: 0.0,
-            });
-        }
-        (
-            transcript::codex::format_codex_context(&entries),
-            Vec::new(),
-        )
+    let plain_context = if options.plain {
+        match transcript::read_plain_context(transcript_path)? {
+            Some(text) => Some(text),
+            None => {
+                log(
+                    "skip",
+                    "Plain transcript is empty",
+                    json!({"reason": "empty"}),
+                );
+                return Ok(LlmEvaluationResult::no_concerns());
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut plan_mode_note = "";
+    // What the agent was doing, journaled with any feedback (Claude Code transcripts only)
+    let mut recent_tools = Vec::new();
+    let mut context_entries = 0;
+    let (context, transcript_entries) = if let Some(text) = plain_context {
+        // Plain-text export: whole file is the conversation (no timestamps to filter on)
+        (text, Vec::new())
+    } else if let Some(agent) = read_agent_context(transcript_path, &config.context_limits())? {
+        // Gemini, aider or Codex: the whole conversation
+        if agent.entries == 0 {
+            log(
+                "skip",
+                "No entries in transcript",
+                json!({"reason": "empty"}),
+            );
+            return Ok(LlmEvaluationResult::no_concerns());
+        }
+        context_entries = agent.entries;
+        (agent.context, Vec::new())
     } else {
         // Claude Code format
-        let entries = transcript::read_transcript(transcript_path)?;
+        let entries = match &options.transcript {
+            Some(entries) => entries.clone(),
+            None => transcript::read_transcript(transcript_path)?,
+        };
 
         // Get messages since last evaluation, filtered by session_id to prevent cross-session bleed
-        let messages = transcript::get_messages_since(&entries, state.last_evaluated, session_id);
+        let mut messages =
+            transcript::get_messages_since(&entries, state.last_evaluated, session_id);
+        if config.exclude_subagents {
+            messages = transcript::exclude_subagents(messages);
+        }
+
+        log(
+            "messages",
+            &format!("Messages since last evaluation: {}", messages.len()),
+            json!({"messages": messages.len()}),
+        );
 
         // Skip if nothing new to evaluate
         if messages.is_empty() {
-            return Ok(LlmEvaluationResult {
-                feedback: "No concerns.".to_string(),
-                has_concerns: false,
-                confidence: None,
-                cost_usd: 0.0,
-            });
+            return Ok(LlmEvaluationResult::no_concerns());
         }
 
-        (transcript::format_context(&messages), entries)
-    };
+        // Too little new activity to be worth a call
+        // AIDEV-NOTE: Like the empty case, this leaves last_evaluated alone, so
+        // the messages are still "new" (and evaluated) once enough accumulate.
+        if messages.len() < config.min_messages_to_evaluate {
+            log(
+                "skip",
+                "SKIP: Below min_messages_to_evaluate",
+                json!({"reason": "min_messages", "messages": messages.len()}),
+            );
+            return Ok(LlmEvaluationResult::skipped(
+                "min_messages",
+                format!(
+                    "Skipped: {} new message(s), below min_messages_to_evaluate ({}).",
+                    messages.len(),
+                    config.min_messages_to_evaluate
+                ),
+            ));
+        }
 
-    // Load config for carryover settings
-    let config = Config::load(superego_dir);
+        // Still planning: skip, or let superego know it's reviewing a plan
+        // AIDEV-NOTE: Skipping leaves last_evaluated alone, so the planning
+        // conversation is evaluated once the agent leaves plan mode.
+        if transcript::in_plan_mode(&messages) {
+            if config.plan_mode == PlanMode::Skip {
+                log(
+                    "skip",
+                    "SKIP: Agent is in plan mode",
+                    json!({"reason": "plan_mode"}),
+                );
+                return Ok(LlmEvaluationResult::skipped(
+                    "plan_mode",
+                    "Skipped: agent is in plan mode.".to_string(),
+                ));
+            }
+            plan_mode_note = PLAN_MODE_NOTE;
+        }
+
+        context_entries = messages.len();
+        recent_tools = transcript::recent_tool_uses(&messages, RECENT_TOOL_USES);
+        let (context, trimmed) =
+            transcript::format_context_within(&messages, config.max_context_chars, &echoes);
+        if trimmed > 0 {
+            diag::info(&format!(
+                "Note: trimmed {} characters of older context (max_context_chars: {})",
+                trimmed, config.max_context_chars
+            ));
+        }
+        (context, entries)
+    };
 
     // Build carryover context for continuity (replaces session resumption)
     // AIDEV-NOTE: Instead of resuming Claude sessions (which accumulates unbounded context),
@@ -261,8 +945,7 @@ pub fn evaluate_llm(
         let mut parts = Vec::new();
 
         // Get recent decisions from journal (sorted oldest first, so reverse and take N)
-        let journal = Journal::new(&session_dir);
-        if let Ok(decisions) = journal.read_all() {
+        if let Some(decisions) = &decisions {
             let recent: Vec<_> = decisions
                 .iter()
                 .rev()
@@ -288,16 +971,19 @@ pub fn evaluate_llm(
         // Uses transcript_entries loaded earlier (avoids double read)
         if let Some(cutoff) = state.last_evaluated {
             let window_start = cutoff - Duration::minutes(config.carryover_window_minutes);
-            let recent_messages = transcript::get_messages_in_window(
+            let mut recent_messages = transcript::get_messages_in_window(
                 &transcript_entries,
                 window_start,
                 cutoff,
                 session_id,
             );
+            if config.exclude_subagents {
+                recent_messages = transcript::exclude_subagents(recent_messages);
+            }
 
             if !recent_messages.is_empty() {
                 parts.push("Recent activity (before current evaluation window):".to_string());
-                parts.push(transcript::format_context(&recent_messages));
+                parts.push(transcript::format_context_within(&recent_messages, 0, &echoes).0);
             }
         }
 
@@ -311,24 +997,53 @@ pub fn evaluate_llm(
         }
     };
 
+    // Most recent feedback and whether the agent acted on it (reduces repeat nagging)
+    let previous_feedback = decisions
+        .as_ref()
+        .and_then(|ds| {
+            ds.iter()
+                .rev()
+                .find(|d| d.decision_type == DecisionType::FeedbackDelivered)
+        })
+        .map(|d| {
+            let later =
+                transcript::get_messages_since(&transcript_entries, Some(d.timestamp), session_id);
+            previous_feedback_section(d, &transcript::format_context(&later))
+        })
+        .unwrap_or_default();
+
     // Load system prompt
-    let prompt_path = superego_dir.join("prompt.md");
-    let system_prompt = if prompt_path.exists() {
-        fs::read_to_string(&prompt_path)?
+    let system_prompt = load_system_prompt(superego_dir)?;
+    let focus = options.focus.as_deref().or(config.default_focus.as_deref());
+    let system_prompt = prompts::with_focus(&system_prompt, focus);
+    let explain_decision = options.explain_decision || config.explain_decision;
+    let system_prompt = if explain_decision {
+        format!(
+            "{}\n\n{}",
+            system_prompt.trim_end(),
+            EXPLAIN_ALLOW_INSTRUCTION
+        )
     } else {
-        include_str!("../default_prompt.md").to_string()
+        system_prompt
     };
 
-    // Get ba task context (only include if there IS a task - for drift detection)
-    let ba_context = match ba::evaluate() {
-        Ok(eval) => {
-            if let Some(task) = eval.current_task {
-                format!("CURRENT TASK: {} - {}\n\n", task.id, task.title)
-            } else {
-                String::new() // No task = no context (don't prime workflow concerns)
-            }
-        }
-        Err(_) => String::new(),
+    let system_prompt = match options.max_feedback_chars.or(config.max_feedback_chars) {
+        Some(max) => format!(
+            "{}\n\n## Feedback Length\n\nKeep your feedback under {} characters.\n",
+            system_prompt.trim_end(),
+            max
+        ),
+        None => system_prompt,
+    };
+
+    // Get task context (only include if there IS a task - for drift detection)
+    // No task = no context (don't prime workflow concerns)
+    let current_tasks = tasks::current_tasks(config.task_provider);
+    let task_context = tasks::context(&current_tasks);
+    let system_prompt = if !current_tasks.is_empty() {
+        format!("{}\n\n{}", system_prompt.trim_end(), TASK_DRIFT_INSTRUCTION)
+    } else {
+        system_prompt
     };
 
     // Get OH endeavor context (optional - graceful degradation if unavailable)
@@ -353,79 +1068,1409 @@ pub fn evaluate_llm(
         String::new()
     };
 
-    // Build message for superego - include carryover, ba context, OH context, and pending change
+    // One-time project grounding for the session's first evaluation
+    let overview_context = if config.include_repo_overview && state.last_evaluated.is_none() {
+        let repo_root = match superego_dir.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p,
+            _ => Path::new("."),
+        };
+        match overview::cached_overview(superego_dir, repo_root) {
+            Ok(o) if !o.trim().is_empty() => {
+                format!("--- REPO OVERVIEW ---\n{}--- END REPO OVERVIEW ---\n\n", o)
+            }
+            _ => String::new(),
+        }
+    } else {
+        String::new()
+    };
+
+    // Build message for superego - include carryover, task context, OH context, and pending change
     // AIDEV-NOTE: carryover_context provides continuity without session resumption
     let message = format!(
         "Review the following Claude Code conversation and provide feedback.\n\n\
-        {}{}{}--- CONVERSATION ---\n\
+        {}{}{}{}{}--- CONVERSATION ---\n\
         {}\n\
-        --- END CONVERSATION ---{}",
-        carryover_context, ba_context, oh_context, context, pending_context
+        --- END CONVERSATION ---{}{}",
+        overview_context,
+        carryover_context,
+        previous_feedback,
+        task_context,
+        oh_context,
+        context,
+        pending_context,
+        plan_mode_note
     );
 
-    // Call Claude - each evaluation is isolated (no session resumption)
+    // Call the LLM - each evaluation is isolated (no session resumption)
     // AIDEV-NOTE: Session resumption was removed because it accumulates context unboundedly,
     // eventually causing "Prompt is too long" errors. Carryover context provides continuity instead.
-    let options = ClaudeOptions {
-        model: None,
-        session_id: None, // No resumption - isolated evaluations
+    // The one exception is Codex's opt-in `codex_resume_thread`.
+    let resume_thread = (backend == Backend::Codex && config.codex_resume_thread)
+        .then(|| codex_llm::load_thread(&session_dir))
+        .flatten();
+    let invoke_options = InvokeOptions {
+        model: config.model.clone(),
         no_session_persistence: true,
-        timeout_ms: None,
+        timeout_ms,
+        disable_prompt_cache: !config.prompt_cache,
+        max_retries: config.max_retries,
+        tools: config.superego_tools.clone(),
+        // Hook path: nothing watches stderr
+        stream: false,
+        resume_thread: resume_thread.clone(),
+    };
+
+    let second_opinion_options = config
+        .second_opinion_model
+        .as_ref()
+        .map(|model| InvokeOptions {
+            model: Some(model.clone()),
+            resume_thread: None,
+            ..invoke_options.clone()
+        });
+
+    // On SIGTERM/SIGINT the CLI has been killed; keep the read-time cursor
+    // so the next run doesn't re-evaluate the same context
+    let on_interrupt = |e: &LlmError| {
+        if e.is_interrupted() {
+            let _ = state_mgr.update(|s| s.mark_evaluated_at(transcript_read_at));
+        }
     };
 
-    let response = claude::invoke(&system_prompt, &message, options)?;
+    // AIDEV-NOTE: Rate limits are a skip, not a failure (as on the Codex path);
+    // last_evaluated stays put so the context is evaluated once the limit resets.
+    let rate_limited = |e: &LlmError| {
+        log(
+            "skip",
+            &format!("SKIP: {}", e),
+            json!({"reason": "rate_limited"}),
+        );
+        LlmEvaluationResult::skipped("rate_limited", format!("Skipped: {}", e))
+    };
+    let on_error = |e: &LlmError| log("error", &format!("ERROR: {}", e), json!({}));
+
+    log(
+        "invoke",
+        &format!(
+            "Calling {} (model {})...",
+            backend.as_str(),
+            config.model.as_deref().unwrap_or("default")
+        ),
+        json!({
+            "backend": backend.as_str(),
+            "model": config.model,
+            "context_chars": message.len(),
+        }),
+    );
+    let start_time = std::time::Instant::now();
+    let mut outcome = invoke(&system_prompt, &message, &invoke_options);
+    if let (Some(tid), Err(LlmError::Codex(codex_llm::CodexLlmError::CommandFailed(e)))) =
+        (&resume_thread, &outcome)
+    {
+        log(
+            "warn",
+            &format!(
+                "WARN: resuming thread {} failed ({}), starting a new one",
+                tid,
+                e.trim()
+            ),
+            json!({}),
+        );
+        codex_llm::clear_thread(&session_dir);
+        let fresh = InvokeOptions {
+            resume_thread: None,
+            ..invoke_options.clone()
+        };
+        outcome = invoke(&system_prompt, &message, &fresh);
+    }
+    let response = match outcome {
+        Err(e) if e.is_rate_limited() => return Ok(rate_limited(&e)),
+        result => result.inspect_err(on_interrupt).inspect_err(on_error)?,
+    };
+    if backend == Backend::Codex && config.codex_resume_thread && !response.session_id.is_empty() {
+        if let Err(e) = codex_llm::save_thread(&session_dir, &response.session_id) {
+            log(
+                "warn",
+                &format!("WARN: Could not save Codex thread: {}", e),
+                json!({}),
+            );
+        }
+    }
+    let second_response = match &second_opinion_options {
+        Some(opts) => match invoke(&system_prompt, &message, opts) {
+            Err(e) if e.is_rate_limited() => return Ok(rate_limited(&e)),
+            result => Some(result.inspect_err(on_interrupt).inspect_err(on_error)?),
+        },
+        None => None,
+    };
+    let elapsed = start_time.elapsed().as_secs_f32();
+    let cost_usd = response.cost_usd + second_response.as_ref().map_or(0.0, |r| r.cost_usd);
+    log(
+        "response",
+        &format!("Response in {:.1}s, cost=${:.4}", elapsed, cost_usd),
+        json!({
+            "elapsed_secs": elapsed,
+            "cost_usd": cost_usd,
+            "tokens": response.tokens,
+        }),
+    );
 
     // Update last_evaluated to transcript read time (not completion time!)
     // This ensures messages written during LLM eval are caught next time.
     if let Err(e) = state_mgr.update(|s| s.mark_evaluated_at(transcript_read_at)) {
-        eprintln!("Warning: failed to update state: {}", e);
+        diag::warn(&format!("failed to update state: {}", e));
+    }
+
+    // Record spend in .superego/costs.jsonl (best effort)
+    let cost_record = |response: &LlmResponse, model: Option<&str>| CostRecord {
+        tokens: response.tokens,
+        ..CostRecord::new(
+            session_id,
+            response.cost_usd,
+            model.or((backend == Backend::Codex).then_some("codex")),
+        )
+    };
+    let mut cost_records = vec![cost_record(&response, config.model.as_deref())];
+    if let Some(second) = &second_response {
+        cost_records.push(cost_record(second, config.second_opinion_model.as_deref()));
+    }
+    for record in &cost_records {
+        if let Err(e) = costs::append(superego_dir, record) {
+            diag::warn(&format!("failed to record cost: {}", e));
+        }
     }
 
     // Parse the structured response: "DECISION: ALLOW|BLOCK\nCONFIDENCE: ...\n\n<feedback>"
-    let response_text = response.result.trim();
-    let (has_concerns, feedback, confidence) = parse_decision_response(response_text);
+    // plus an optional "DRIFT: YES|NO" line when there was a task to drift from
+    let (drift, response_text) = take_drift(response.result.trim());
+    let second_text = second_response
+        .as_ref()
+        .map(|second| take_drift(second.result.trim()));
+    let drift = drift
+        .or(second_text.as_ref().and_then(|(d, _)| *d))
+        .filter(|_| !current_tasks.is_empty());
+    let (has_concerns, feedback, confidence) = match (&second_text, &config.second_opinion_model) {
+        (Some((_, second)), Some(model)) => combine_opinions(
+            parse_decision_response(&response_text),
+            parse_decision_response(second),
+            model,
+        ),
+        _ => parse_decision_response(&response_text),
+    };
+
+    // Low-confidence BLOCKs are journaled but don't interrupt the agent
+    let suppressed = has_concerns && below_min_confidence(confidence, config.min_confidence);
+    let has_concerns = has_concerns && !suppressed;
+    // Repeats of the feedback just delivered are journaled but not queued again
+    let duplicate = has_concerns
+        && repeats_recent_feedback(
+            &feedback,
+            decisions.as_deref().unwrap_or_default(),
+            chrono::Utc::now(),
+            config.dedupe_window_minutes,
+        );
+    let has_concerns = has_concerns && !duplicate;
+
+    let (outcome, outcome_msg) = if has_concerns {
+        ("block", "BLOCK - concerns found")
+    } else if duplicate {
+        ("duplicate", "BLOCK - repeats recent feedback, not queued")
+    } else if suppressed {
+        ("suppressed", "BLOCK - below min_confidence, not delivered")
+    } else {
+        ("allow", "ALLOW - no concerns")
+    };
+    log(
+        "decision",
+        outcome_msg,
+        json!({
+            "decision": outcome,
+            "confidence": confidence.map(|c| c.to_string()),
+            "drift": drift,
+        }),
+    );
 
     // Write to feedback queue (session-namespaced) and decision journal if there are concerns
+    let max_feedback_chars = options.max_feedback_chars.or(config.max_feedback_chars);
+    let displayed_feedback = match max_feedback_chars {
+        Some(max) => truncate_feedback(&feedback, max),
+        None => feedback.clone(),
+    };
     if has_concerns {
-        let queue = FeedbackQueue::new(&session_dir);
-        // Include confidence in feedback so agent sees it
-        let feedback_with_confidence = if let Some(conf) = confidence {
-            format!("CONFIDENCE: {}\n\n{}", conf, feedback)
-        } else {
-            feedback.clone()
-        };
-        let fb = Feedback::warning(&feedback_with_confidence);
-        if let Err(e) = queue.write(&fb) {
-            eprintln!("ERROR: failed to write feedback file: {}", e);
-            eprintln!("FEEDBACK CONTENT (fallback):\n{}", feedback_with_confidence);
+        let decision = deliver_feedback(
+            &session_dir,
+            Some(response.session_id.clone()),
+            &feedback,
+            &displayed_feedback,
+            confidence,
+            drift,
+            recent_tools,
+        );
+        if let Err(e) = state_mgr.update(|s| s.mark_blocked_at(decision.timestamp)) {
+            diag::warn(&format!("failed to update state: {}", e));
         }
-        // Record to decision journal for audit trail (session-namespaced per user requirement)
-        let journal = Journal::new(&session_dir);
-        let decision =
-            Decision::feedback_delivered(Some(response.session_id.clone()), feedback.clone());
-        if let Err(e) = journal.write(&decision) {
-            eprintln!("Warning: failed to write decision journal: {}", e);
+
+        if let Some(url) = &config.notify_webhook_url {
+            let notification = FeedbackNotification::new(
+                session_id,
+                confidence.map(|c| c.to_string()),
+                &feedback,
+                decision.timestamp,
+            );
+            if let Err(e) = notify::send_webhook(url, &notification) {
+                diag::warn(&format!("failed to notify webhook: {}", e));
+            }
         }
 
         // Log to Open Horizons if configured (optional integration)
         if let Some(oh) = OhIntegration::new(superego_dir) {
-            if let Err(e) = oh.log_feedback(&feedback) {
-                eprintln!("Warning: failed to log to Open Horizons: {}", e);
+            if let Err(e) = oh.log_feedback(&feedback, decision.timestamp) {
+                diag::warn(&format!("failed to log to Open Horizons: {}", e));
             }
         }
+    } else if duplicate {
+        record_duplicate_feedback(
+            &session_dir,
+            Some(response.session_id.clone()),
+            &feedback,
+            confidence,
+            drift,
+        );
+    } else if suppressed {
+        record_suppressed_feedback(
+            &session_dir,
+            Some(response.session_id.clone()),
+            &feedback,
+            confidence,
+            drift,
+        );
+    } else if explain_decision {
+        record_allow_rationale(&session_dir, Some(response.session_id.clone()), &feedback);
     }
 
     Ok(LlmEvaluationResult {
-        feedback,
+        feedback: displayed_feedback,
         has_concerns,
         confidence,
-        cost_usd: response.total_cost_usd,
+        cost_usd: response.cost_usd,
+        tokens: response.tokens,
+        second_opinion_cost_usd: second_response.map(|r| r.cost_usd),
+        skip_reason: None,
+        context_entries,
+        context_bytes: context.len(),
+        elapsed_secs: Some(elapsed),
     })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::PathBuf;
+
+    /// A Claude Code user message in session s1, timestamped now
+    fn user_line(text: &str) -> String {
+        json!({
+            "type": "user",
+            "uuid": "u",
+            "sessionId": "s1",
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "message": {"role": "user", "content": text},
+        })
+        .to_string()
+            + "\n"
+    }
+
+    /// Create `dir/.superego` and a transcript holding one user message `text`;
+    /// returns (superego_dir, transcript_path)
+    fn setup_transcript(dir: &Path, text: &str) -> (PathBuf, PathBuf) {
+        let superego_dir = dir.join(".superego");
+        fs::create_dir_all(&superego_dir).unwrap();
+        let transcript_path = dir.join("transcript.jsonl");
+        fs::write(&transcript_path, user_line(text)).unwrap();
+        (superego_dir, transcript_path)
+    }
+
+    #[test]
+    fn test_message_written_during_eval_is_picked_up_next_time() {
+        // AIDEV-NOTE: Mirrors the documented race timeline end to end:
+        //   Message A written -> eval 1 reads transcript -> Message B written
+        //   while the LLM is running -> eval 1 finishes -> eval 2 must see B.
+        // last_evaluated has to be eval 1's read time, not its finish time.
+        let dir = tempfile::tempdir().unwrap();
+        let superego_dir = dir.path().join(".superego");
+        fs::create_dir_all(&superego_dir).unwrap();
+        let transcript_path = dir.path().join("transcript.jsonl");
+
+        let user_line = |text: &str, at: chrono::DateTime<chrono::Utc>| {
+            format!(
+                r#"{{"type":"user","uuid":"{}","sessionId":"s1","timestamp":"{}","message":{{"role":"user","content":"{}"}}}}"#,
+                text,
+                at.to_rfc3339_opts(chrono::SecondsFormat::Nanos, true),
+                text
+            ) + "\n"
+        };
+        let allow = || LlmResponse {
+            result: "DECISION: ALLOW\n\nNo concerns.".to_string(),
+            session_id: "llm".to_string(),
+            cost_usd: 0.0,
+            tokens: None,
+        };
+        let conversation = |message: &str| {
+            message
+                .split("--- CONVERSATION ---")
+                .nth(1)
+                .unwrap()
+                .to_string()
+        };
+
+        fs::write(
+            &transcript_path,
+            user_line("Message A", chrono::Utc::now() - Duration::seconds(10)),
+        )
+        .unwrap();
+
+        let mut first_conversation = String::new();
+        let mut message_b_at = None;
+        let before_eval = chrono::Utc::now();
+        evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, message, _| {
+                first_conversation = conversation(message);
+                // Slow LLM call: the agent keeps working meanwhile
+                std::thread::sleep(std::time::Duration::from_millis(20));
+                let written_at = chrono::Utc::now();
+                message_b_at = Some(written_at);
+                let mut transcript = fs::read_to_string(&transcript_path).unwrap();
+                transcript.push_str(&user_line("Message B", written_at));
+                fs::write(&transcript_path, transcript).unwrap();
+                Ok(allow())
+            },
+        )
+        .unwrap();
+        assert!(first_conversation.contains("Message A"));
+        assert!(!first_conversation.contains("Message B"));
+
+        let state = StateManager::new(&superego_dir.join("sessions").join("s1"))
+            .load()
+            .unwrap();
+        let last_evaluated = state.last_evaluated.unwrap();
+        assert!(last_evaluated >= before_eval);
+        assert!(
+            last_evaluated < message_b_at.unwrap(),
+            "last_evaluated must be the read time, not the completion time"
+        );
+
+        let mut second_conversation = String::new();
+        evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, message, _| {
+                second_conversation = conversation(message);
+                Ok(allow())
+            },
+        )
+        .unwrap();
+        assert!(second_conversation.contains("Message B"));
+        assert!(!second_conversation.contains("Message A"));
+    }
+
+    #[test]
+    fn test_evaluation_never_persists_or_resumes_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let (superego_dir, transcript_path) = setup_transcript(dir.path(), "hello");
+
+        for _ in 0..2 {
+            evaluate_llm_with(
+                &transcript_path,
+                &superego_dir,
+                Some("s1"),
+                &EvaluateOptions::default(),
+                |_, _, options| {
+                    // InvokeOptions has no session id, so resuming can't be requested
+                    assert!(options.no_session_persistence);
+                    Ok(LlmResponse {
+                        result: "DECISION: BLOCK\n\nConcern.".to_string(),
+                        session_id: "llm-session".to_string(),
+                        cost_usd: 0.0,
+                        tokens: None,
+                    })
+                },
+            )
+            .unwrap();
+        }
+
+        // No superego session id is stored anywhere for later resumption
+        let session_dir = superego_dir.join("sessions").join("s1");
+        assert!(!session_dir.join("superego_session").exists());
+        let state = fs::read_to_string(session_dir.join("state.json")).unwrap();
+        assert!(!state.contains("llm-session"));
+    }
+
+    #[test]
+    fn test_eval_skipped_during_feedback_cooldown() {
+        let dir = tempfile::tempdir().unwrap();
+        let (superego_dir, transcript_path) = setup_transcript(dir.path(), "hello");
+        let session_dir = superego_dir.join("sessions").join("s1");
+        fs::create_dir_all(&session_dir).unwrap();
+        fs::write(
+            superego_dir.join("config.yaml"),
+            "feedback_cooldown_minutes: 5\n",
+        )
+        .unwrap();
+        StateManager::new(&session_dir)
+            .update(|s| s.mark_blocked_at(chrono::Utc::now() - Duration::minutes(1)))
+            .unwrap();
+
+        let result = evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, _, _| panic!("LLM must not be called during cooldown"),
+        )
+        .unwrap();
+        assert!(!result.has_concerns);
+
+        let state = StateManager::new(&session_dir).load().unwrap();
+        assert!(state.last_evaluated.is_none(), "cooldown must not advance");
+    }
+
+    #[test]
+    fn test_eval_skipped_while_session_locked() {
+        let dir = tempfile::tempdir().unwrap();
+        let (superego_dir, transcript_path) = setup_transcript(dir.path(), "hello");
+        let session_dir = superego_dir.join("sessions").join("s1");
+        fs::create_dir_all(&session_dir).unwrap();
+
+        let held = ScopedLock::acquire(
+            &session_dir.join(EVALUATE_LOCK_FILE),
+            lock::STALE_AFTER,
+            &|_| {},
+        )
+        .unwrap();
+        let result = evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, _, _| panic!("LLM must not be called while another evaluation runs"),
+        )
+        .unwrap();
+        assert!(!result.has_concerns);
+        assert!(StateManager::new(&session_dir)
+            .load()
+            .unwrap()
+            .last_evaluated
+            .is_none());
+
+        drop(held);
+        let result = evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, _, _| {
+                Ok(LlmResponse {
+                    result: "DECISION: ALLOW\n\nFine.".to_string(),
+                    session_id: "llm".to_string(),
+                    cost_usd: 0.0,
+                    tokens: None,
+                })
+            },
+        )
+        .unwrap();
+        assert!(!result.has_concerns);
+        assert!(
+            !session_dir.join(EVALUATE_LOCK_FILE).exists(),
+            "lock released after evaluating"
+        );
+    }
+
+    #[test]
+    fn test_delivered_feedback_echo_is_not_reevaluated() {
+        let dir = tempfile::tempdir().unwrap();
+        let superego_dir = dir.path().join(".superego");
+        let session_dir = superego_dir.join("sessions").join("s1");
+        fs::create_dir_all(&session_dir).unwrap();
+        let feedback =
+            "The retry loop swallows every error; surface the final failure to the caller.";
+        Journal::new(&session_dir)
+            .write(&Decision::feedback_delivered(
+                Some("s1".to_string()),
+                feedback.to_string(),
+            ))
+            .unwrap();
+
+        // The Stop hook injects the feedback, then the agent quotes it back
+        let now = chrono::Utc::now().to_rfc3339();
+        let transcript_path = dir.path().join("transcript.jsonl");
+        let hook = format!(
+            r#"{{"type":"user","uuid":"u","sessionId":"s1","timestamp":"{}","message":{{"role":"user","content":"SUPEREGO FEEDBACK: Please critically evaluate this feedback.\n\n{}"}}}}"#,
+            now, feedback
+        );
+        let quote = format!(
+            r#"{{"type":"assistant","uuid":"a","sessionId":"s1","timestamp":"{}","message":{{"role":"assistant","content":[{{"type":"text","text":"Agreed:\n\n> {}\n\nReturning the last error now."}}]}}}}"#,
+            now, feedback
+        );
+        fs::write(&transcript_path, format!("{}\n{}\n", hook, quote)).unwrap();
+
+        let mut sent = String::new();
+        evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, message, _| {
+                sent = message.to_string();
+                Ok(LlmResponse {
+                    result: "DECISION: ALLOW\n\nNo concerns.".to_string(),
+                    session_id: "llm".to_string(),
+                    cost_usd: 0.0,
+                    tokens: None,
+                })
+            },
+        )
+        .unwrap();
+        let conversation = sent.split("--- CONVERSATION ---").nth(1).unwrap();
+        assert!(!conversation.contains("swallows every error"));
+        assert!(conversation.contains("[superego feedback omitted]"));
+        assert!(conversation.contains("SUPEREGO FEEDBACK: Please critically evaluate"));
+        assert!(conversation.contains("Returning the last error now."));
+    }
+
+    #[test]
+    fn test_eval_skipped_while_disabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let (superego_dir, transcript_path) = setup_transcript(dir.path(), "hello");
+        StateManager::new(&superego_dir)
+            .update(|s| s.disable(None))
+            .unwrap();
+
+        let result = evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, _, _| panic!("LLM must not be called while disabled"),
+        )
+        .unwrap();
+        assert!(!result.has_concerns);
+        assert!(result.feedback.contains("disabled"));
+        assert_eq!(result.skip_reason, Some("disabled"));
+    }
+
+    #[test]
+    fn test_codex_backend_resumes_saved_thread_and_falls_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let superego_dir = dir.path().join(".superego");
+        let session_dir = superego_dir.join("sessions").join("c1");
+        fs::create_dir_all(&session_dir).unwrap();
+        fs::write(
+            superego_dir.join("config.yaml"),
+            "codex_resume_thread: true
+",
+        )
+        .unwrap();
+        codex_llm::save_thread(&session_dir, "t-old").unwrap();
+
+        let options = EvaluateOptions {
+            backend: Some(Backend::Codex),
+            ..Default::default()
+        };
+        let mut resumed = Vec::new();
+        let result = evaluate_llm_with(
+            &transcript::fixture_path("codex_session.jsonl"),
+            &superego_dir,
+            Some("c1"),
+            &options,
+            |_, _, invoke_options| {
+                resumed.push(invoke_options.resume_thread.clone());
+                if invoke_options.resume_thread.is_some() {
+                    return Err(LlmError::Codex(codex_llm::CodexLlmError::CommandFailed(
+                        "thread not found".to_string(),
+                    )));
+                }
+                Ok(LlmResponse {
+                    result: "DECISION: ALLOW\n\nNo concerns.".to_string(),
+                    session_id: "t-new".to_string(),
+                    cost_usd: 0.0,
+                    tokens: Some(1200),
+                })
+            },
+        )
+        .unwrap();
+
+        assert_eq!(resumed, vec![Some("t-old".to_string()), None]);
+        assert_eq!(result.tokens, Some(1200));
+        assert_eq!(result.skip_reason, None);
+        assert!(result.context_entries > 0);
+        assert!(result.context_bytes > 0);
+        assert!(result.elapsed_secs.is_some());
+        assert_eq!(
+            codex_llm::load_thread(&session_dir).as_deref(),
+            Some("t-new")
+        );
+        let recorded = costs::read_all(&superego_dir).unwrap();
+        assert_eq!(recorded[0].model.as_deref(), Some("codex"));
+    }
+
+    #[test]
+    fn test_plain_transcript_evaluated_as_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let superego_dir = dir.path().join(".superego");
+        fs::create_dir_all(&superego_dir).unwrap();
+        // A JSON line that would parse as a Claude Code entry must stay text
+        let transcript_path = dir.path().join("chat.log");
+        let json_line = r#"{"type":"user","uuid":"u","sessionId":"s1","message":{"role":"user","content":"hidden"}}"#;
+        fs::write(
+            &transcript_path,
+            format!(
+                "User: why does this fail?\n{}\nAssistant: checking.\n",
+                json_line
+            ),
+        )
+        .unwrap();
+
+        let options = EvaluateOptions {
+            plain: true,
+            ..Default::default()
+        };
+        let mut messages = Vec::new();
+        let result = evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            None,
+            &options,
+            |_, message, _| {
+                messages.push(message.to_string());
+                Ok(LlmResponse {
+                    result: "DECISION: ALLOW\n\nNo concerns.".to_string(),
+                    session_id: "llm".to_string(),
+                    cost_usd: 0.0,
+                    tokens: None,
+                })
+            },
+        )
+        .unwrap();
+
+        assert!(!result.has_concerns);
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].contains("User: why does this fail?"));
+        assert!(messages[0].contains(json_line), "{}", messages[0]);
+        assert!(messages[0].contains("Assistant: checking."));
+
+        fs::write(&transcript_path, "\n").unwrap();
+        let result = evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            None,
+            &options,
+            |_, _, _| panic!("empty plain transcript must not be evaluated"),
+        )
+        .unwrap();
+        assert_eq!(result.feedback, "No concerns.");
+    }
+
+    #[test]
+    fn test_combine_opinions() {
+        let allow = |text: &str| (false, text.to_string(), None);
+        let block = |text: &str| (true, text.to_string(), Some(Confidence::High));
+
+        assert_eq!(
+            combine_opinions(allow("ok"), allow("fine"), "opus"),
+            allow("ok")
+        );
+
+        let (concerns, feedback, confidence) =
+            combine_opinions(block("Missing tests."), block("No tests."), "opus");
+        assert!(concerns);
+        assert!(feedback.starts_with("Missing tests."));
+        assert!(feedback.contains("Second opinion (opus) agrees:\nNo tests."));
+        assert_eq!(confidence, Some(Confidence::High));
+
+        let (concerns, feedback, confidence) =
+            combine_opinions(allow("ok"), block("Deletes prod data."), "opus");
+        assert!(concerns);
+        assert!(feedback.starts_with("MODELS DISAGREE: only the second opinion (opus)"));
+        assert!(feedback.contains("Deletes prod data."));
+        assert_eq!(confidence, Some(Confidence::Low));
+
+        let (concerns, feedback, _) = combine_opinions(block("Risky."), allow("ok"), "opus");
+        assert!(concerns);
+        assert!(feedback.contains("MODELS DISAGREE"));
+        assert!(feedback.contains("Risky."));
+    }
+
+    #[test]
+    fn test_second_opinion_uses_configured_model_and_reports_both_costs() {
+        let dir = tempfile::tempdir().unwrap();
+        let (superego_dir, transcript_path) = setup_transcript(dir.path(), "hello");
+        fs::write(
+            superego_dir.join("config.yaml"),
+            "second_opinion_model: opus\n",
+        )
+        .unwrap();
+
+        let mut models = Vec::new();
+        let result = evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, _, options| {
+                models.push(options.model.clone());
+                let second = options.model.is_some();
+                Ok(LlmResponse {
+                    result: if second {
+                        "DECISION: BLOCK\n\nDeletes prod data.".to_string()
+                    } else {
+                        "DECISION: ALLOW\n\nNo concerns.".to_string()
+                    },
+                    session_id: "llm".to_string(),
+                    cost_usd: if second { 0.5 } else { 0.25 },
+                    tokens: None,
+                })
+            },
+        )
+        .unwrap();
+
+        assert_eq!(models, vec![None, Some("opus".to_string())]);
+        assert!(result.has_concerns);
+        assert!(result.feedback.contains("MODELS DISAGREE"));
+        assert_eq!(result.cost_usd, 0.25);
+        assert_eq!(result.second_opinion_cost_usd, Some(0.5));
+
+        let recorded = costs::read_all(&superego_dir).unwrap();
+        let recorded: Vec<_> = recorded
+            .iter()
+            .map(|r| (r.session_id.as_deref(), r.cost_usd, r.model.as_deref()))
+            .collect();
+        assert_eq!(
+            recorded,
+            vec![(Some("s1"), 0.25, None), (Some("s1"), 0.5, Some("opus"))]
+        );
+    }
+
+    #[test]
+    fn test_timeout_flag_overrides_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let superego_dir = dir.path().join(".superego");
+        fs::create_dir_all(&superego_dir).unwrap();
+        fs::write(superego_dir.join("config.yaml"), "timeout_ms: 60000\n").unwrap();
+        let transcript_path = dir.path().join("transcript.jsonl");
+
+        let mut timeouts = Vec::new();
+        for options in [
+            EvaluateOptions::default(),
+            EvaluateOptions {
+                timeout_ms: Some(5000),
+                ..Default::default()
+            },
+        ] {
+            fs::write(&transcript_path, user_line("hello")).unwrap();
+            evaluate_llm_with(
+                &transcript_path,
+                &superego_dir,
+                Some("s1"),
+                &options,
+                |_, _, invoke_options| {
+                    timeouts.push(invoke_options.timeout_ms);
+                    Ok(LlmResponse {
+                        result: "DECISION: ALLOW\n\nNo concerns.".to_string(),
+                        session_id: "llm".to_string(),
+                        cost_usd: 0.0,
+                        tokens: None,
+                    })
+                },
+            )
+            .unwrap();
+            // Next message must be newer than the read cursor
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        assert_eq!(timeouts, vec![Some(60000), Some(5000)]);
+    }
+
+    #[test]
+    fn test_eval_log_records_evaluation() {
+        let dir = tempfile::tempdir().unwrap();
+        let (superego_dir, transcript_path) = setup_transcript(dir.path(), "hello");
+        fs::write(
+            superego_dir.join("config.yaml"),
+            "log_format: json\nmodel: sonnet\n",
+        )
+        .unwrap();
+
+        evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, _, _| {
+                Ok(LlmResponse {
+                    result: "DECISION: BLOCK\nCONFIDENCE: HIGH\n\nAdd a test.".to_string(),
+                    session_id: "llm".to_string(),
+                    cost_usd: 0.02,
+                    tokens: Some(900),
+                })
+            },
+        )
+        .unwrap();
+
+        let records: Vec<serde_json::Value> = fs::read_to_string(superego_dir.join(EVAL_LOG_FILE))
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let events: Vec<_> = records
+            .iter()
+            .map(|r| r["event"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            events,
+            vec!["start", "messages", "invoke", "response", "decision"]
+        );
+        assert_eq!(records[0]["session"], "s1");
+        assert_eq!(records[1]["messages"], 1);
+        assert_eq!(records[2]["model"], "sonnet");
+        assert_eq!(records[3]["cost_usd"], 0.02);
+        assert_eq!(records[3]["tokens"], 900);
+        assert_eq!(records[4]["decision"], "block");
+        assert_eq!(records[4]["confidence"], "HIGH");
+    }
+
+    #[test]
+    fn test_repo_overview_only_on_first_eval() {
+        let dir = tempfile::tempdir().unwrap();
+        let superego_dir = dir.path().join(".superego");
+        fs::create_dir_all(&superego_dir).unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]\n").unwrap();
+        fs::write(
+            superego_dir.join("config.yaml"),
+            "include_repo_overview: true\n",
+        )
+        .unwrap();
+        let transcript_path = dir.path().join("transcript.jsonl");
+        let write_message = |text: &str| {
+            let mut transcript = fs::read_to_string(&transcript_path).unwrap_or_default();
+            transcript.push_str(&format!(
+                r#"{{"type":"user","uuid":"{}","sessionId":"s1","timestamp":"{}","message":{{"role":"user","content":"{}"}}}}"#,
+                text,
+                chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Nanos, true),
+                text
+            ));
+            transcript.push('\n');
+            fs::write(&transcript_path, transcript).unwrap();
+        };
+
+        let mut messages = Vec::new();
+        for text in ["first", "second"] {
+            write_message(text);
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            evaluate_llm_with(
+                &transcript_path,
+                &superego_dir,
+                Some("s1"),
+                &EvaluateOptions::default(),
+                |_, message, _| {
+                    messages.push(message.to_string());
+                    Ok(LlmResponse {
+                        result: "DECISION: ALLOW\n\nNo concerns.".to_string(),
+                        session_id: "llm".to_string(),
+                        cost_usd: 0.0,
+                        tokens: None,
+                    })
+                },
+            )
+            .unwrap();
+        }
+
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].contains("--- REPO OVERVIEW ---"));
+        assert!(messages[0].contains("Stack: Rust"));
+        assert!(!messages[1].contains("REPO OVERVIEW"));
+        assert!(superego_dir.join("repo_overview.md").exists());
+    }
+
+    #[test]
+    fn test_evaluate_preloaded_transcript() {
+        let dir = tempfile::tempdir().unwrap();
+        let superego_dir = dir.path().join(".superego");
+        fs::create_dir_all(&superego_dir).unwrap();
+        let line = user_line("Piped in from stdin");
+        let read_at = chrono::Utc::now();
+        let options = EvaluateOptions {
+            transcript: Some(
+                transcript::read_transcript_from_reader(std::io::Cursor::new(line)).unwrap(),
+            ),
+            transcript_read_at: Some(read_at),
+            ..Default::default()
+        };
+
+        let mut sent = String::new();
+        evaluate_llm_with(
+            Path::new("-"),
+            &superego_dir,
+            Some("s1"),
+            &options,
+            |_, message, _| {
+                sent = message.to_string();
+                Ok(LlmResponse {
+                    result: "DECISION: ALLOW\n\nNo concerns.".to_string(),
+                    session_id: "llm".to_string(),
+                    cost_usd: 0.0,
+                    tokens: None,
+                })
+            },
+        )
+        .unwrap();
+        assert!(sent.contains("USER: Piped in from stdin"));
+        // The cursor is when stdin was read, not when evaluation got to it
+        let state = StateManager::new(&superego_dir.join("sessions").join("s1"))
+            .load()
+            .unwrap();
+        assert_eq!(state.last_evaluated, Some(read_at));
+    }
+
+    #[test]
+    fn test_plan_mode_skip_and_evaluate() {
+        let dir = tempfile::tempdir().unwrap();
+        let (superego_dir, transcript_path) = setup_transcript(
+            dir.path(),
+            "<system-reminder>Plan mode is active.</system-reminder>Plan the migration",
+        );
+        let allow = || LlmResponse {
+            result: "DECISION: ALLOW\n\nNo concerns.".to_string(),
+            session_id: "llm".to_string(),
+            cost_usd: 0.0,
+            tokens: None,
+        };
+
+        fs::write(superego_dir.join("config.yaml"), "plan_mode: skip\n").unwrap();
+        let result = evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, _, _| panic!("LLM must not be called in plan mode with plan_mode: skip"),
+        )
+        .unwrap();
+        assert!(result.feedback.contains("plan mode"));
+
+        fs::write(superego_dir.join("config.yaml"), "plan_mode: evaluate\n").unwrap();
+        let mut sent = String::new();
+        evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, message, _| {
+                sent = message.to_string();
+                Ok(allow())
+            },
+        )
+        .unwrap();
+        assert!(sent.contains("The agent is in plan mode"));
+    }
+
+    #[test]
+    fn test_min_messages_gate_keeps_messages_for_later() {
+        let dir = tempfile::tempdir().unwrap();
+        let superego_dir = dir.path().join(".superego");
+        fs::create_dir_all(&superego_dir).unwrap();
+        fs::write(
+            superego_dir.join("config.yaml"),
+            "min_messages_to_evaluate: 2\n",
+        )
+        .unwrap();
+        let transcript_path = dir.path().join("transcript.jsonl");
+        let line = |uuid: &str, text: &str| {
+            format!(
+                r#"{{"type":"user","uuid":"{}","sessionId":"s1","timestamp":"{}","message":{{"role":"user","content":"{}"}}}}"#,
+                uuid,
+                chrono::Utc::now().to_rfc3339(),
+                text
+            )
+        };
+        fs::write(&transcript_path, line("u1", "Add a cache layer")).unwrap();
+
+        let result = evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, _, _| panic!("LLM must not be called below min_messages_to_evaluate"),
+        )
+        .unwrap();
+        assert!(!result.has_concerns);
+        assert!(result.feedback.contains("min_messages_to_evaluate"));
+
+        fs::write(
+            &transcript_path,
+            format!(
+                "{}\n{}\n",
+                line("u1", "Add a cache layer"),
+                line("u2", "Use Redis for it")
+            ),
+        )
+        .unwrap();
+        let mut sent = String::new();
+        evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, message, _| {
+                sent = message.to_string();
+                Ok(LlmResponse {
+                    result: "DECISION: ALLOW\n\nNo concerns.".to_string(),
+                    session_id: "llm".to_string(),
+                    cost_usd: 0.0,
+                    tokens: None,
+                })
+            },
+        )
+        .unwrap();
+        assert!(sent.contains("Add a cache layer"));
+        assert!(sent.contains("Use Redis for it"));
+    }
+
+    #[test]
+    fn test_interrupted_eval_records_read_cursor() {
+        let dir = tempfile::tempdir().unwrap();
+        let (superego_dir, transcript_path) = setup_transcript(dir.path(), "hello");
+
+        let before = chrono::Utc::now();
+        let result = evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, _, _| Err(claude::ClaudeError::Interrupted(15).into()),
+        );
+        assert!(matches!(
+            result,
+            Err(EvaluateError::ClaudeError(
+                claude::ClaudeError::Interrupted(15)
+            ))
+        ));
+
+        let state = StateManager::new(&superego_dir.join("sessions").join("s1"))
+            .load()
+            .unwrap();
+        assert!(state.last_evaluated.unwrap() >= before);
+    }
+
+    #[test]
+    fn test_rate_limited_eval_is_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let (superego_dir, transcript_path) = setup_transcript(dir.path(), "hello");
+
+        let result = evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, _, _| {
+                Err(claude::ClaudeError::RateLimited {
+                    resets_in_seconds: Some(600),
+                }
+                .into())
+            },
+        )
+        .unwrap();
+        assert!(!result.has_concerns);
+        assert_eq!(
+            result.feedback,
+            "Skipped: Rate limited (resets in 10 minutes)"
+        );
+
+        // Not marked evaluated: the same context is retried next time
+        let state = StateManager::new(&superego_dir.join("sessions").join("s1"))
+            .load()
+            .unwrap();
+        assert!(state.last_evaluated.is_none());
+    }
+
+    #[test]
+    fn test_feedback_accepted_heuristic() {
+        let feedback = "The migration drops the users table without a backup.";
+        assert_eq!(feedback_accepted(feedback, ""), None);
+        // The injected feedback itself doesn't count as a response
+        assert_eq!(feedback_accepted(feedback, feedback), None);
+        assert_eq!(
+            feedback_accepted(
+                feedback,
+                "ASSISTANT: Added a backup step before the migration runs."
+            ),
+            Some(true)
+        );
+        assert_eq!(
+            feedback_accepted(feedback, "ASSISTANT: Now styling the login page."),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_previous_feedback_included_in_message() {
+        let dir = tempfile::tempdir().unwrap();
+        let superego_dir = dir.path().join(".superego");
+        let session_dir = superego_dir.join("sessions").join("s1");
+        fs::create_dir_all(&session_dir).unwrap();
+
+        let mut decision = Decision::feedback_delivered(
+            None,
+            "The migration drops the users table without a backup.".to_string(),
+        );
+        decision.timestamp = chrono::Utc::now() - Duration::seconds(30);
+        Journal::new(&session_dir).write(&decision).unwrap();
+
+        let transcript_path = dir.path().join("transcript.jsonl");
+        let at = chrono::Utc::now() - Duration::seconds(10);
+        fs::write(
+            &transcript_path,
+            format!(
+                r#"{{"type":"assistant","uuid":"a","sessionId":"s1","timestamp":"{}","message":{{"role":"assistant","content":[{{"type":"text","text":"Added a backup step before the migration."}}]}}}}"#,
+                at.to_rfc3339()
+            ),
+        )
+        .unwrap();
+
+        let mut sent = String::new();
+        evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, message, _| {
+                sent = message.to_string();
+                Ok(LlmResponse {
+                    result: "DECISION: ALLOW\n\nNo concerns.".to_string(),
+                    session_id: "llm".to_string(),
+                    cost_usd: 0.0,
+                    tokens: None,
+                })
+            },
+        )
+        .unwrap();
+
+        assert!(sent.contains("PREVIOUS FEEDBACK (and whether it was addressed)"));
+        assert!(sent.contains("drops the users table"));
+        assert!(sent.contains("Status: likely addressed"));
+    }
+
+    #[test]
+    fn test_truncate_feedback_char_safe() {
+        assert_eq!(truncate_feedback("short", 10), "short");
+        assert_eq!(truncate_feedback("héllo wörld", 6), "héllo…");
+        assert_eq!(truncate_feedback("héllo wörld", 6).chars().count(), 6);
+    }
+
+    #[test]
+    fn test_deliver_feedback_bounds_queue_not_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let full = "Concern ".repeat(50);
+        let displayed = truncate_feedback(&full, 40);
+
+        deliver_feedback(dir.path(), None, &full, &displayed, None, None, Vec::new());
+
+        let queued = FeedbackQueue::new(dir.path()).get_and_clear().unwrap();
+        assert_eq!(queued.chars().count(), 40);
+
+        let journaled = Journal::new(dir.path()).read_all().unwrap();
+        assert_eq!(journaled[0].context.as_deref(), Some(full.as_str()));
+        assert_eq!(journaled[0].confidence, None);
+    }
+
+    #[test]
+    fn test_low_confidence_block_suppressed() {
+        let dir = tempfile::tempdir().unwrap();
+        let (superego_dir, transcript_path) = setup_transcript(dir.path(), "hello");
+        fs::write(superego_dir.join("config.yaml"), "min_confidence: medium\n").unwrap();
+        let block = |confidence: &str| LlmResponse {
+            result: format!(
+                "DECISION: BLOCK\nCONFIDENCE: {}\n\nMaybe a hunch.",
+                confidence
+            ),
+            session_id: "llm".to_string(),
+            cost_usd: 0.0,
+            tokens: None,
+        };
+        let session_dir = superego_dir.join("sessions").join("s1");
+
+        let result = evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, _, _| Ok(block("LOW")),
+        )
+        .unwrap();
+        assert!(!result.has_concerns);
+        assert!(!FeedbackQueue::new(&session_dir).has_feedback());
+        let journaled = Journal::new(&session_dir).read_all().unwrap();
+        assert_eq!(journaled.len(), 1);
+        assert_eq!(journaled[0].decision_type, DecisionType::FeedbackSuppressed);
+        assert_eq!(journaled[0].confidence.as_deref(), Some("LOW"));
+
+        // At or above the threshold (or unstated) it is delivered as usual
+        let mut transcript = fs::read_to_string(&transcript_path).unwrap();
+        transcript.push_str(&format!(
+            "\n{}",
+            format_args!(
+                r#"{{"type":"user","uuid":"u2","sessionId":"s1","timestamp":"{}","message":{{"role":"user","content":"again"}}}}"#,
+                (chrono::Utc::now() + Duration::seconds(1)).to_rfc3339()
+            )
+        ));
+        fs::write(&transcript_path, transcript).unwrap();
+        let result = evaluate_llm_with(
+            &transcript_path,
+            &superego_dir,
+            Some("s1"),
+            &EvaluateOptions::default(),
+            |_, _, _| Ok(block("MEDIUM")),
+        )
+        .unwrap();
+        assert!(result.has_concerns);
+        assert!(FeedbackQueue::new(&session_dir).has_feedback());
+        assert!(!below_min_confidence(None, Some(Confidence::High)));
+        assert!(!below_min_confidence(Some(Confidence::Low), None));
+    }
+
+    #[test]
+    fn test_repeated_block_not_requeued() {
+        let dir = tempfile::tempdir().unwrap();
+        let superego_dir = dir.path().join(".superego");
+        fs::create_dir_all(&superego_dir).unwrap();
+        fs::write(
+            superego_dir.join("config.yaml"),
+            "dedupe_window_minutes: 30\n",
+        )
+        .unwrap();
+        let transcript_path = dir.path().join("transcript.jsonl");
+        let session_dir = superego_dir.join("sessions").join("s1");
+        let block = |text: &str| LlmResponse {
+            result: format!("DECISION: BLOCK\n\n{}", text),
+            session_id: "llm".to_string(),
+            cost_usd: 0.0,
+            tokens: None,
+        };
+        let evaluate = |i: i64, text: &str| {
+            let mut transcript = fs::read_to_string(&transcript_path).unwrap_or_default();
+            transcript.push_str(&format!(
+                r#"{{"type":"user","uuid":"u{}","sessionId":"s1","timestamp":"{}","message":{{"role":"user","content":"step {}"}}}}"#,
+                i,
+                (chrono::Utc::now() + Duration::seconds(i)).to_rfc3339(),
+                i
+            ));
+            transcript.push('\n');
+            fs::write(&transcript_path, transcript).unwrap();
+            let result = evaluate_llm_with(
+                &transcript_path,
+                &superego_dir,
+                Some("s1"),
+                &EvaluateOptions::default(),
+                |_, _, _| Ok(block(text)),
+            )
+            .unwrap();
+            let queued = FeedbackQueue::new(&session_dir).get_and_clear().is_some();
+            assert_eq!(queued, result.has_concerns);
+            result.has_concerns
+        };
+
+        let last_type = || {
+            Journal::new(&session_dir)
+                .read_all()
+                .unwrap()
+                .pop()
+                .map(|d| d.decision_type)
+        };
+
+        assert!(evaluate(0, "Tests for the parser are missing."));
+        assert!(!evaluate(1, "**Tests for the parser are missing!**"));
+        assert_eq!(last_type(), Some(DecisionType::FeedbackDuplicate));
+        assert!(evaluate(
+            2,
+            "The migration drops a column without a backup."
+        ));
+    }
+
+    #[test]
+    fn test_repeats_recent_feedback_window() {
+        let delivered = Decision::feedback_delivered(None, "Add tests for fetch()".to_string());
+        let now = delivered.timestamp;
+        let decisions = vec![delivered];
+        assert!(repeats_recent_feedback(
+            "add tests for fetch",
+            &decisions,
+            now,
+            5
+        ));
+        assert!(!repeats_recent_feedback(
+            "Add tests for fetch()",
+            &decisions,
+            now + Duration::minutes(5),
+            5
+        ));
+        assert!(
+            !repeats_recent_feedback("Add tests for fetch()", &decisions, now, 0),
+            "0 disables"
+        );
+        assert!(!repeats_recent_feedback(
+            "Rename the fetch() helper",
+            &decisions,
+            now,
+            5
+        ));
+    }
+
+    #[test]
+    fn test_deliver_feedback_records_confidence() {
+        let dir = tempfile::tempdir().unwrap();
+        deliver_feedback(
+            dir.path(),
+            None,
+            "Concern",
+            "Concern",
+            Some(Confidence::Low),
+            None,
+            vec!["Edit(src/lib.rs)".to_string()],
+        );
+
+        let queued = FeedbackQueue::new(dir.path()).get_and_clear().unwrap();
+        assert!(queued.starts_with("CONFIDENCE: LOW"));
+        let journaled = Journal::new(dir.path()).read_all().unwrap();
+        assert_eq!(journaled[0].confidence.as_deref(), Some("LOW"));
+        assert_eq!(journaled[0].tool_uses, vec!["Edit(src/lib.rs)"]);
+    }
+
+    #[test]
+    fn test_allow_rationale_journaled() {
+        let dir = tempfile::tempdir().unwrap();
+        let response = "DECISION: ALLOW\n\n**Tests cover the new parser path.**\nMore detail.";
+        let (has_concerns, feedback, _) = parse_decision_response(response);
+        assert!(!has_concerns);
+
+        let decision = record_allow_rationale(dir.path(), Some("s1".to_string()), &feedback)
+            .expect("rationale should be recorded");
+        assert_eq!(
+            decision.context.as_deref(),
+            Some("Tests cover the new parser path.")
+        );
+
+        let journaled = Journal::new(dir.path()).read_all().unwrap();
+        assert_eq!(journaled.len(), 1);
+        assert_eq!(journaled[0].decision_type, DecisionType::AllowRationale);
+
+        // Terse ALLOW with no rationale records nothing
+        assert!(record_allow_rationale(dir.path(), None, "  \n").is_none());
+    }
 
     #[test]
     fn test_parse_decision_allow() {
@@ -463,6 +2508,29 @@ mod tests {
         assert_eq!(confidence, Some(Confidence::Low));
     }
 
+    #[test]
+    fn test_take_drift() {
+        let response = "DECISION: BLOCK\nCONFIDENCE: HIGH\nDRIFT: YES\n\nThis is off-task.";
+        let (drift, rest) = take_drift(response);
+        assert_eq!(drift, Some(true));
+        let (has_concerns, feedback, confidence) = parse_decision_response(&rest);
+        assert!(has_concerns);
+        assert_eq!(feedback, "This is off-task.");
+        assert_eq!(confidence, Some(Confidence::High));
+
+        // Before CONFIDENCE and in markdown: still removed, CONFIDENCE still parsed
+        let (drift, rest) = take_drift("DECISION: ALLOW\n**DRIFT:** no\nCONFIDENCE: LOW\n\nFine.");
+        assert_eq!(drift, Some(false));
+        let (_, feedback, confidence) = parse_decision_response(&rest);
+        assert_eq!(feedback, "Fine.");
+        assert_eq!(confidence, Some(Confidence::Low));
+
+        let (drift, rest) = take_drift("DECISION: ALLOW\n\nNo concerns.");
+        assert_eq!(drift, None);
+        assert_eq!(rest, "DECISION: ALLOW\n\nNo concerns.");
+        assert_eq!(take_drift("DECISION: ALLOW\nDRIFT: maybe\n\nOk.").0, None);
+    }
+
     #[test]
     fn test_parse_decision_case_insensitive() {
         let response = "DECISION: allow\n\nLooks good.";
@@ -1,14 +1,31 @@
 //! Feedback queue for superego
 //!
-//! Async evaluation writes feedback here, hooks check and retrieve it.
+//! Async evaluation appends feedback here, hooks check and retrieve it.
 //! AIDEV-NOTE: Simplified to just message. No severity levels -
 //! all feedback is informational, Claude decides how to act on it.
-
-use std::fs;
+//! Entries are appended as JSON lines to `feedback.jsonl`, so an evaluation
+//! finishing before the previous feedback was picked up doesn't overwrite it.
+//! The plugin hooks read the same file (`jq '.message'`); keep the two in sync.
+//! A plain-text `feedback` file left by older versions is moved into the queue
+//! the first time the queue is touched, so upgrading doesn't lose it.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
+use serde::{Deserialize, Serialize};
+
+/// Queue file name (one JSON `Feedback` per line)
+const QUEUE_FILE: &str = "feedback.jsonl";
+
+/// Pre-JSONL queue file (the raw message of a single entry)
+const LEGACY_QUEUE_FILE: &str = "feedback";
+
+/// Separator between entries when several are retrieved at once
+pub const ENTRY_SEPARATOR: &str = "\n\n---\n\n";
+
 /// Feedback entry
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Feedback {
     pub message: String,
 }
@@ -34,33 +51,104 @@ pub struct FeedbackQueue {
 impl FeedbackQueue {
     pub fn new(superego_dir: &Path) -> Self {
         FeedbackQueue {
-            feedback_path: superego_dir.join("feedback"),
+            feedback_path: superego_dir.join(QUEUE_FILE),
         }
     }
 
-    /// Check if there's pending feedback (instant, no parsing)
+    /// Move a legacy plain-text `feedback` file into the queue
+    /// AIDEV-NOTE: The legacy file is claimed by rename (so two processes can't
+    /// both migrate it) and its message appended like any other entry, so a
+    /// concurrent `write` is never overwritten. It lands after entries already
+    /// queued; on failure the file is put back for the next attempt.
+    pub fn migrate_legacy(&self) {
+        let legacy = self.feedback_path.with_file_name(LEGACY_QUEUE_FILE);
+        if !legacy.exists() {
+            return;
+        }
+        let claimed = legacy.with_extension(format!("{}.migrating", std::process::id()));
+        if fs::rename(&legacy, &claimed).is_err() {
+            return;
+        }
+        let migrated = fs::read_to_string(&claimed).and_then(|message| {
+            if message.trim().is_empty() {
+                return Ok(());
+            }
+            self.append(&Feedback::new(message.trim()))
+        });
+        match migrated {
+            Ok(()) => {
+                let _ = fs::remove_file(&claimed);
+            }
+            Err(_) => {
+                let _ = fs::rename(&claimed, &legacy);
+            }
+        }
+    }
+
+    /// Check if there's pending feedback (instant, no parsing: entries are whole lines)
     pub fn has_feedback(&self) -> bool {
+        self.migrate_legacy();
         self.feedback_path.exists()
             && fs::metadata(&self.feedback_path)
                 .map(|m| m.len() > 0)
                 .unwrap_or(false)
     }
 
-    /// Write feedback to queue (overwrites existing)
+    /// Append feedback to the queue (earlier entries are kept)
     pub fn write(&self, feedback: &Feedback) -> std::io::Result<()> {
-        fs::write(&self.feedback_path, &feedback.message)
+        self.migrate_legacy();
+        self.append(feedback)
     }
 
-    /// Get feedback and clear queue
+    /// Append one entry to the queue file
+    fn append(&self, feedback: &Feedback) -> std::io::Result<()> {
+        let mut line = serde_json::to_string(feedback)?;
+        line.push('\n');
+        // One write per entry, so concurrent appends don't interleave
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.feedback_path)?
+            .write_all(line.as_bytes())
+    }
+
+    /// Get all pending feedback (oldest first, joined by ENTRY_SEPARATOR) and clear queue
+    /// AIDEV-NOTE: The file is renamed before reading (like the hooks' `mv`), so
+    /// an entry appended meanwhile lands in a fresh queue instead of being lost.
     pub fn get_and_clear(&self) -> Option<String> {
         if !self.has_feedback() {
             return None;
         }
 
-        let content = fs::read_to_string(&self.feedback_path).ok()?;
-        let _ = fs::remove_file(&self.feedback_path);
-        Some(content)
+        let claimed = self
+            .feedback_path
+            .with_extension(format!("jsonl.{}", std::process::id()));
+        fs::rename(&self.feedback_path, &claimed).ok()?;
+        let content = fs::read_to_string(&claimed);
+        let _ = fs::remove_file(&claimed);
+        join_entries(&content.ok()?)
+    }
+
+    /// Get all pending feedback like `get_and_clear`, but leave the queue as is
+    pub fn peek(&self) -> Option<String> {
+        if !self.has_feedback() {
+            return None;
+        }
+        join_entries(&fs::read_to_string(&self.feedback_path).ok()?)
+    }
+}
+
+/// Messages of a queue file's entries joined by ENTRY_SEPARATOR (None if there are none)
+fn join_entries(content: &str) -> Option<String> {
+    let messages: Vec<String> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Feedback>(line).ok())
+        .map(|fb| fb.message)
+        .collect();
+    if messages.is_empty() {
+        return None;
     }
+    Some(messages.join(ENTRY_SEPARATOR))
 }
 
 #[cfg(test)]
@@ -90,4 +178,67 @@ mod tests {
         assert!(content.contains("No task in progress"));
         assert!(!queue.has_feedback());
     }
+
+    #[test]
+    fn test_entries_appended_not_overwritten() {
+        let dir = tempdir().unwrap();
+        let queue = FeedbackQueue::new(dir.path());
+
+        queue.write(&Feedback::new("First concern")).unwrap();
+        queue.write(&Feedback::new("Second\nconcern")).unwrap();
+
+        let raw = fs::read_to_string(dir.path().join(QUEUE_FILE)).unwrap();
+        assert_eq!(raw.lines().count(), 2, "one JSON line per entry");
+        assert_eq!(
+            queue.get_and_clear().unwrap(),
+            format!("First concern{}Second\nconcern", ENTRY_SEPARATOR)
+        );
+        assert!(queue.get_and_clear().is_none());
+    }
+
+    #[test]
+    fn test_legacy_feedback_file_migrated_once() {
+        let dir = tempdir().unwrap();
+        let legacy = dir.path().join(LEGACY_QUEUE_FILE);
+        fs::write(&legacy, "Old concern from before the upgrade\n").unwrap();
+        let queue = FeedbackQueue::new(dir.path());
+        // Queued before the migration runs (e.g. by another process)
+        let queued = serde_json::to_string(&Feedback::new("Queued concern")).unwrap();
+        fs::write(dir.path().join(QUEUE_FILE), format!("{}\n", queued)).unwrap();
+
+        queue.write(&Feedback::new("New concern")).unwrap();
+        assert!(!legacy.exists(), "legacy file drained");
+        assert_eq!(
+            queue.get_and_clear().unwrap(),
+            [
+                "Queued concern",
+                "Old concern from before the upgrade",
+                "New concern"
+            ]
+            .join(ENTRY_SEPARATOR)
+        );
+        assert_eq!(
+            fs::read_dir(dir.path()).unwrap().count(),
+            0,
+            "no claimed files left behind"
+        );
+
+        // An empty leftover is just removed
+        fs::write(&legacy, "").unwrap();
+        assert!(!queue.has_feedback());
+        assert!(!legacy.exists());
+    }
+
+    #[test]
+    fn test_peek_leaves_queue_in_place() {
+        let dir = tempdir().unwrap();
+        let queue = FeedbackQueue::new(dir.path());
+        assert!(queue.peek().is_none());
+
+        queue.write(&Feedback::new("Pending concern")).unwrap();
+        assert_eq!(queue.peek().as_deref(), Some("Pending concern"));
+        assert_eq!(queue.peek().as_deref(), Some("Pending concern"));
+        assert!(queue.has_feedback());
+        assert_eq!(queue.get_and_clear().as_deref(), Some("Pending concern"));
+    }
 }
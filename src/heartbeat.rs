@@ -0,0 +1,199 @@
+//! Liveness heartbeat for long-running modes
+//!
+//! A running watcher (`sg tail`) rewrites `.superego/watch.alive` (its PID) every
+//! interval, so the file's mtime is the last heartbeat. `sg watch --status` reads it back.
+//! AIDEV-NOTE: The file is removed when the `Heartbeat` is dropped (graceful
+//! exit). A file left behind by a crashed watcher is reported as stale.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+/// Heartbeat file name within .superego/
+pub const HEARTBEAT_FILE: &str = "watch.alive";
+
+/// Default interval between heartbeats
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Missed heartbeats before a watcher is considered stale
+const STALE_AFTER_INTERVALS: u32 = 3;
+
+/// Granularity at which the heartbeat thread checks for shutdown
+const POLL: Duration = Duration::from_millis(100);
+
+/// Path of the heartbeat file
+pub fn heartbeat_path(superego_dir: &Path) -> PathBuf {
+    superego_dir.join(HEARTBEAT_FILE)
+}
+
+/// Background heartbeat; stops and removes its file on drop
+pub struct Heartbeat {
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Heartbeat {
+    /// Write the first heartbeat and keep touching it every `interval`
+    pub fn start(path: &Path, interval: Duration) -> std::io::Result<Self> {
+        beat(path)?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let thread_path = path.to_path_buf();
+        let handle = thread::spawn(move || {
+            let mut since_beat = Duration::ZERO;
+            while !thread_stop.load(Ordering::Relaxed) {
+                thread::sleep(POLL);
+                since_beat += POLL;
+                if since_beat >= interval {
+                    // Best effort - a missed beat just shows up as staleness
+                    let _ = beat(&thread_path);
+                    since_beat = Duration::ZERO;
+                }
+            }
+        });
+
+        Ok(Heartbeat {
+            path: path.to_path_buf(),
+            stop,
+            handle: Some(handle),
+        })
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Rewrite the heartbeat file, updating its mtime
+fn beat(path: &Path) -> std::io::Result<()> {
+    fs::write(path, std::process::id().to_string())
+}
+
+/// Watcher liveness as seen from the heartbeat file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchStatus {
+    /// No heartbeat file
+    NotRunning,
+    /// Heartbeat within the staleness window
+    Alive { age: Duration },
+    /// Heartbeat file exists but hasn't been touched recently
+    Stale { age: Duration },
+}
+
+/// Classify a heartbeat by its age
+pub fn classify(last_beat: Option<SystemTime>, now: SystemTime, interval: Duration) -> WatchStatus {
+    let Some(last_beat) = last_beat else {
+        return WatchStatus::NotRunning;
+    };
+    // Clock skew (mtime in the future) counts as a fresh beat
+    let age = now.duration_since(last_beat).unwrap_or(Duration::ZERO);
+    if age > interval * STALE_AFTER_INTERVALS {
+        WatchStatus::Stale { age }
+    } else {
+        WatchStatus::Alive { age }
+    }
+}
+
+/// Read the heartbeat file and report watcher status
+pub fn status(superego_dir: &Path, interval: Duration) -> WatchStatus {
+    let last_beat = fs::metadata(heartbeat_path(superego_dir))
+        .and_then(|m| m.modified())
+        .ok();
+    classify(last_beat, SystemTime::now(), interval)
+}
+
+/// One-line report for `sg watch --status`
+pub fn describe(status: &WatchStatus) -> String {
+    match status {
+        WatchStatus::NotRunning => "No watcher running".to_string(),
+        WatchStatus::Alive { age } => {
+            format!("Watcher running (last heartbeat {}s ago)", age.as_secs())
+        }
+        WatchStatus::Stale { age } => format!(
+            "Watcher stale (last heartbeat {}s ago) - it may have crashed",
+            age.as_secs()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_staleness() {
+        let now = SystemTime::now();
+        let interval = Duration::from_secs(10);
+
+        assert_eq!(classify(None, now, interval), WatchStatus::NotRunning);
+        assert_eq!(
+            classify(Some(now - Duration::from_secs(5)), now, interval),
+            WatchStatus::Alive {
+                age: Duration::from_secs(5)
+            }
+        );
+        assert_eq!(
+            classify(Some(now - Duration::from_secs(31)), now, interval),
+            WatchStatus::Stale {
+                age: Duration::from_secs(31)
+            }
+        );
+        // Future mtime (clock skew) is not stale
+        assert_eq!(
+            classify(Some(now + Duration::from_secs(5)), now, interval),
+            WatchStatus::Alive {
+                age: Duration::ZERO
+            }
+        );
+    }
+
+    #[test]
+    fn test_heartbeat_removed_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = heartbeat_path(dir.path());
+
+        let heartbeat = Heartbeat::start(&path, DEFAULT_INTERVAL).unwrap();
+        assert!(matches!(
+            status(dir.path(), DEFAULT_INTERVAL),
+            WatchStatus::Alive { .. }
+        ));
+
+        assert!(describe(&status(dir.path(), DEFAULT_INTERVAL)).starts_with("Watcher running"));
+
+        drop(heartbeat);
+        assert!(!path.exists());
+        assert_eq!(
+            status(dir.path(), DEFAULT_INTERVAL),
+            WatchStatus::NotRunning
+        );
+        assert_eq!(describe(&WatchStatus::NotRunning), "No watcher running");
+    }
+
+    #[test]
+    fn test_heartbeat_keeps_beating() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = heartbeat_path(dir.path());
+        let interval = Duration::from_millis(200);
+
+        let _heartbeat = Heartbeat::start(&path, interval).unwrap();
+        let first = fs::metadata(&path).unwrap().modified().unwrap();
+        thread::sleep(interval * 3);
+        let later = fs::metadata(&path).unwrap().modified().unwrap();
+        assert!(later > first, "heartbeat file is touched every interval");
+        assert!(matches!(
+            status(dir.path(), interval),
+            WatchStatus::Alive { .. }
+        ));
+    }
+}
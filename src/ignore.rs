@@ -0,0 +1,225 @@
+//! `.superego-ignore` support
+//!
+//! Gitignore-style patterns for paths superego should leave out of reviews
+//! (generated code, vendored files), applied on top of `.gitignore`.
+//! AIDEV-NOTE: Supported syntax is the common gitignore subset: `#` comments,
+//! `!` negation (last match wins), trailing `/` for directories, leading `/`
+//! to anchor at the root, and `*`, `?`, `**` wildcards. Patterns without a
+//! slash match a file or directory name at any depth.
+
+use std::fs;
+use std::path::Path;
+
+/// Ignore file name, found by walking up from the working directory
+pub const IGNORE_FILE: &str = ".superego-ignore";
+
+#[derive(Debug, Clone)]
+struct Pattern {
+    glob: String,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+}
+
+/// Parsed `.superego-ignore` patterns
+#[derive(Debug, Clone, Default)]
+pub struct IgnorePatterns {
+    patterns: Vec<Pattern>,
+    /// Directory of the ignore file relative to the repo root ("" = root)
+    base: String,
+}
+
+impl IgnorePatterns {
+    /// Parse patterns from file contents
+    pub fn parse(content: &str) -> Self {
+        let patterns = content
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(Pattern::parse)
+            .collect();
+        IgnorePatterns {
+            patterns,
+            base: String::new(),
+        }
+    }
+
+    /// Find the nearest `.superego-ignore` from `start` up to the repo root;
+    /// empty if there is none
+    /// AIDEV-NOTE: A file below the root only applies to paths under its own
+    /// directory, like a nested .gitignore.
+    pub fn discover(start: &Path) -> Self {
+        let start = fs::canonicalize(start).unwrap_or_else(|_| start.to_path_buf());
+        let root = start.ancestors().find(|d| d.join(".git").exists());
+
+        for dir in start.ancestors() {
+            if let Ok(content) = fs::read_to_string(dir.join(IGNORE_FILE)) {
+                let base = root
+                    .and_then(|r| dir.strip_prefix(r).ok())
+                    .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+                    .unwrap_or_default();
+                return IgnorePatterns {
+                    base,
+                    ..Self::parse(&content)
+                };
+            }
+            if Some(dir) == root {
+                break;
+            }
+        }
+        Self::default()
+    }
+
+    /// Whether a repo-relative file path is ignored
+    pub fn is_ignored(&self, path: &str) -> bool {
+        let path = path.trim_start_matches("./");
+        let path = if self.base.is_empty() {
+            path
+        } else {
+            match path
+                .strip_prefix(self.base.as_str())
+                .and_then(|p| p.strip_prefix('/'))
+            {
+                Some(rest) => rest,
+                None => return false,
+            }
+        };
+        let mut ignored = false;
+        for pattern in &self.patterns {
+            if pattern.matches(path) {
+                ignored = !pattern.negated;
+            }
+        }
+        ignored
+    }
+}
+
+/// Whether a repo-relative path matches a single gitignore-style pattern
+/// (same syntax as `.superego-ignore`, minus negation)
+pub fn path_matches(pattern: &str, path: &str) -> bool {
+    Pattern::parse(pattern.trim()).matches(path.trim_start_matches("./"))
+}
+
+impl Pattern {
+    /// Parse one non-comment pattern line
+    fn parse(line: &str) -> Self {
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let (anchored, glob) = match line.strip_prefix('/') {
+            Some(rest) => (true, rest),
+            // A slash in the middle also anchors, as in gitignore
+            None => (line.contains('/'), line),
+        };
+        Pattern {
+            glob: glob.to_string(),
+            negated,
+            dir_only,
+            anchored,
+        }
+    }
+
+    /// Match against the file path or any of its parent directories
+    fn matches(&self, path: &str) -> bool {
+        let components: Vec<&str> = path.split('/').collect();
+        (1..=components.len()).any(|n| {
+            // The full path is a file; only its prefixes are directories
+            if self.dir_only && n == components.len() {
+                return false;
+            }
+            let prefix = components[..n].join("/");
+            if self.anchored {
+                glob_match(&self.glob, &prefix)
+            } else {
+                glob_match(&self.glob, components[n - 1])
+            }
+        })
+    }
+}
+
+/// Match a glob against text; `*` and `?` don't cross `/`, `**` does
+fn glob_match(glob: &str, text: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    glob_match_from(&glob, &text)
+}
+
+fn glob_match_from(glob: &[char], text: &[char]) -> bool {
+    match glob.first() {
+        None => text.is_empty(),
+        Some('*') if glob.get(1) == Some(&'*') => {
+            // "**/" also matches zero directories
+            let rest = &glob[2..];
+            let rest_after_slash = rest.strip_prefix(&['/']).unwrap_or(rest);
+            (0..=text.len()).any(|i| {
+                glob_match_from(rest, &text[i..]) || glob_match_from(rest_after_slash, &text[i..])
+            })
+        }
+        Some('*') => {
+            let rest = &glob[1..];
+            (0..=text.len())
+                .take_while(|&i| i == 0 || text[i - 1] != '/')
+                .any(|i| glob_match_from(rest, &text[i..]))
+        }
+        Some('?') => {
+            matches!(text.first(), Some(c) if *c != '/') && glob_match_from(&glob[1..], &text[1..])
+        }
+        Some(c) => text.first() == Some(c) && glob_match_from(&glob[1..], &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns() {
+        let ignore = IgnorePatterns::parse(
+            "# generated\n\
+             generated/\n\
+             *.gen.ts\n\
+             /dist\n\
+             src/**/routes.ts\n\
+             !keep.gen.ts\n",
+        );
+
+        assert!(ignore.is_ignored("generated/api.rs"));
+        assert!(ignore.is_ignored("web/generated/client.ts"));
+        assert!(!ignore.is_ignored("generated"), "dir pattern vs file");
+        assert!(ignore.is_ignored("web/api.gen.ts"));
+        assert!(!ignore.is_ignored("web/keep.gen.ts"), "negation");
+        assert!(ignore.is_ignored("dist/bundle.js"));
+        assert!(!ignore.is_ignored("web/dist/bundle.js"), "anchored");
+        assert!(ignore.is_ignored("src/routes.ts"));
+        assert!(ignore.is_ignored("src/app/admin/routes.ts"));
+        assert!(!ignore.is_ignored("src/main.rs"));
+    }
+
+    #[test]
+    fn test_discover_walks_up_to_repo_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("web/src")).unwrap();
+        fs::write(root.join(IGNORE_FILE), "generated/\n").unwrap();
+
+        let ignore = IgnorePatterns::discover(&root.join("web/src"));
+        assert!(ignore.is_ignored("web/generated/client.ts"));
+
+        // A nested ignore file wins and only covers its own directory
+        fs::write(root.join("web").join(IGNORE_FILE), "*.gen.ts\n").unwrap();
+        let ignore = IgnorePatterns::discover(&root.join("web/src"));
+        assert!(ignore.is_ignored("web/src/api.gen.ts"));
+        assert!(!ignore.is_ignored("api.gen.ts"));
+
+        // Nothing above the repo root is consulted
+        let nested = root.join("web/src");
+        fs::create_dir(nested.join(".git")).unwrap();
+        assert!(!IgnorePatterns::discover(&nested).is_ignored("x.gen.ts"));
+    }
+}
@@ -81,14 +81,101 @@ mode: always
 # carryover_decision_count: 2    # Number of recent decisions to include
 # carryover_window_minutes: 5    # Minutes of recent messages before current window
 
+# Topic to pay particular attention to (overridden by --focus)
+# default_focus: test coverage
+
+# Journal a one-line rationale on ALLOW decisions (extra tokens per evaluation)
+# explain_decision: false
+
+# Rotate codex.log/eval.log/hook.log past this size (0 = never)
+# log_max_bytes: 1048576
+
+# codex.log/eval.log line format: text, or json (one JSON record per line, for log tooling)
+# log_format: text
+
+# Anthropic prompt caching on the Claude backend (Codex: not configurable)
+# prompt_cache: true
+
+# Keep queued feedback short, e.g. for status lines (journal keeps full text)
+# max_feedback_chars: 500
+
+# Skip evaluations for this many minutes after a BLOCK, so the agent can react (0 = off)
+# feedback_cooldown_minutes: 0
+
+# Don't re-queue a BLOCK that repeats the last delivered feedback within this many minutes (0 = off)
+# dedupe_window_minutes: 0
+
+# Journal but don't deliver BLOCKs below this confidence: low, medium or high
+# (unset = deliver everything)
+# min_confidence: medium
+
+# Consult a second model on every evaluation (doubles cost). BLOCK when both
+# object; disagreement is delivered flagged as "MODELS DISAGREE".
+# second_opinion_model: opus
+
+# Ground a session's first evaluation in a short repo overview (cached in .superego/)
+# include_repo_overview: false
+
+# Truncate Codex/Gemini tool outputs and messages to this many characters (0 = no limit)
+# max_tool_output_chars: 500
+# max_message_chars: 2000
+
+# Cap the Claude Code context sent per evaluation; oldest tool results go first (0 = no limit)
+# max_context_chars: 100000
+
+# sg review: diffs larger than this are split by file and reviewed in parts (0 = no limit)
+# max_diff_bytes: 100000
+
+# While the agent is in plan mode (nothing edited yet): evaluate the plan, or skip
+# plan_mode: evaluate
+
+# Wait for at least this many new messages before evaluating (they accumulate, none are lost)
+# min_messages_to_evaluate: 1
+
+# Leave sub-agent (Task tool) turns out of evaluations, focusing on the main conversation
+# exclude_subagents: false
+
+# sg evaluate-codex: resume the session's previous Codex thread for cross-eval memory
+# (off by default: evaluations are isolated, and a resumed thread grows every time)
+# codex_resume_thread: false
+
+# Task tracker for the in-progress tasks (drift detection): auto (ba, then bd), ba or bd
+# task_provider: auto
+
+# LLM CLI for evaluate-llm and review: claude or codex (model keys then name that backend's models)
+# backend: claude
+
+# Values for {{name}} tokens in prompt.md and prompt.d/*.md (unknown tokens are left as is)
+# prompt_vars:
+#   project: my-service
+#   standards_url: https://example.com/coding-standards
+
 # Model and timeout (uncomment to override)
 # model: opus
+# review_model: opus  # sg review only (default: model); --model overrides
+# review_base_branch: develop  # sg review pr base (default: detected); --base overrides
 # timeout_ms: 30000
 
+# Tools superego's Claude calls may use (default: Bash,Read,Glob,Grep).
+# Read,Glob,Grep keeps its inspection read-only - no commands run.
+# superego_tools: Read,Glob,Grep
+
+# Retry Claude CLI timeouts and transient API errors (exponential backoff, max 30s apart)
+# max_retries: 2
+
+# POST each queued concern as JSON (session_id, confidence, feedback) - best-effort
+# notify_webhook_url: https://ntfy.sh/your-topic
+
 # Open Horizons integration (for cross-project visibility)
 # oh_endeavor_id: initiative:abc123  # Endeavor to link this project to
+# oh_endeavor_map:                   # Monorepos: route by changed paths (falls back to oh_endeavor_id)
+#   crates/api/**: initiative:api
+#   web/: initiative:web
 # oh_api_url: http://localhost:3001  # OH API URL (default: localhost:3001)
 # oh_api_key: your-api-key-here      # OH API key (or set OH_API_KEY env var)
+# oh_ca_cert: /etc/ssl/corp-ca.pem   # Extra CA bundle (or OH_CA_CERT); proxies via HTTPS_PROXY
+# oh_timeout_seconds: 30             # Per-request OH timeout (default 5s, 10s for retro pushes)
+# oh_cache_ttl_minutes: 10           # Reuse endeavor/guardrails this long (0 = refetch)
 "#,
     )?;
 
@@ -125,6 +212,7 @@ fn update_gitignore(base_dir: &Path) -> Result<(), InitError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config;
     use tempfile::tempdir;
 
     #[test]
@@ -139,6 +227,36 @@ mod tests {
         assert!(dir.path().join(".superego/config.yaml").exists());
     }
 
+    #[test]
+    fn test_config_template_examples_are_valid() {
+        let dir = tempdir().unwrap();
+        init_at(dir.path(), false).unwrap();
+        let template = fs::read_to_string(dir.path().join(".superego/config.yaml")).unwrap();
+        assert!(config::validate(&template).is_empty());
+
+        // Every commented-out example must still be valid once uncommented
+        let uncommented: String = template
+            .lines()
+            .filter_map(|line| {
+                let example = line.strip_prefix("# ")?;
+                let key = example.trim().split_once(':')?.0;
+                (config::KEYS.contains(&key)
+                    || config::SECTIONS.contains(&key)
+                    || example.starts_with("  "))
+                .then(|| format!("{}\n", example))
+            })
+            .collect();
+        assert!(uncommented.contains("review_model: opus"));
+        let issues = config::validate(&uncommented);
+        assert!(
+            issues
+                .iter()
+                .all(|i| i.severity == config::IssueSeverity::Warning),
+            "{:?}",
+            issues
+        );
+    }
+
     #[test]
     fn test_init_fails_if_exists() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,132 @@
+//! Pluggable LLM backend for evaluations and reviews
+//!
+//! `evaluate_llm`, `review` and `evaluate-dir` call whichever CLI config `backend`
+//! names (`claude` or `codex`) through `LlmBackend`, instead of per-backend code paths.
+//! AIDEV-NOTE: audit and retro still call Claude directly. `sg evaluate-codex`
+//! only finds the latest Codex session and formats skill output; the evaluation
+//! itself is `evaluate_llm` with the Codex backend.
+
+use crate::claude::{ClaudeBackend, ClaudeError};
+use crate::codex_llm::{CodexBackend, CodexLlmError};
+use crate::config::Backend;
+
+/// Options for one LLM call; backends ignore what they can't honor
+#[derive(Debug, Clone, Default)]
+pub struct InvokeOptions {
+    /// Model name for the backend's CLI (None = CLI default)
+    pub model: Option<String>,
+    /// Timeout in milliseconds (None = backend default)
+    pub timeout_ms: Option<u64>,
+    /// Claude only: don't persist the session to disk
+    pub no_session_persistence: bool,
+    /// Claude only: opt out of prompt caching (config `prompt_cache: false`)
+    pub disable_prompt_cache: bool,
+    /// Claude only: extra attempts after a timeout or transient failure
+    pub max_retries: u32,
+    /// Claude only: tools the CLI may use (None = `claude::DEFAULT_TOOLS`)
+    pub tools: Option<Vec<String>>,
+    /// Claude only: echo progress to stderr while the CLI works
+    pub stream: bool,
+    /// Codex only: thread to resume (config `codex_resume_thread`)
+    pub resume_thread: Option<String>,
+}
+
+/// Backend-neutral response
+#[derive(Debug, Clone)]
+pub struct LlmResponse {
+    pub result: String,
+    /// Claude session id / Codex thread id
+    pub session_id: String,
+    /// Spend in USD (0 for Codex, which reports tokens instead)
+    pub cost_usd: f64,
+    /// Token count, when the backend reports tokens
+    pub tokens: Option<u64>,
+}
+
+/// Error from whichever backend was called
+#[derive(Debug)]
+pub enum LlmError {
+    Claude(ClaudeError),
+    Codex(CodexLlmError),
+}
+
+impl LlmError {
+    /// Whether this is a rate/usage limit (a skip, not a failure)
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(
+            self,
+            LlmError::Claude(ClaudeError::RateLimited { .. })
+                | LlmError::Codex(CodexLlmError::RateLimited { .. })
+        )
+    }
+
+    /// Whether the call was killed by SIGTERM/SIGINT
+    pub fn is_interrupted(&self) -> bool {
+        matches!(
+            self,
+            LlmError::Claude(ClaudeError::Interrupted(_))
+                | LlmError::Codex(CodexLlmError::Interrupted(_))
+        )
+    }
+}
+
+impl std::fmt::Display for LlmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LlmError::Claude(e) => write!(f, "{}", e),
+            LlmError::Codex(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for LlmError {}
+
+impl From<ClaudeError> for LlmError {
+    fn from(e: ClaudeError) -> Self {
+        LlmError::Claude(e)
+    }
+}
+
+impl From<CodexLlmError> for LlmError {
+    fn from(e: CodexLlmError) -> Self {
+        LlmError::Codex(e)
+    }
+}
+
+/// An LLM CLI that answers a system prompt + message
+pub trait LlmBackend {
+    fn invoke(
+        &self,
+        system_prompt: &str,
+        message: &str,
+        options: &InvokeOptions,
+    ) -> Result<LlmResponse, LlmError>;
+}
+
+/// The backend implementation for a config `backend` value
+pub fn for_backend(backend: Backend) -> &'static dyn LlmBackend {
+    match backend {
+        Backend::Claude => &ClaudeBackend,
+        Backend::Codex => &CodexBackend,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_classification() {
+        let limited = LlmError::from(CodexLlmError::RateLimited {
+            resets_in_seconds: Some(60),
+        });
+        assert!(limited.is_rate_limited());
+        assert!(!limited.is_interrupted());
+
+        let interrupted = LlmError::from(ClaudeError::Interrupted(15));
+        assert!(interrupted.is_interrupted());
+        assert!(!interrupted.is_rate_limited());
+        assert_eq!(interrupted.to_string(), "Interrupted by signal 15");
+        assert!(LlmError::from(CodexLlmError::Interrupted(2)).is_interrupted());
+    }
+}
@@ -0,0 +1,240 @@
+//! Lock files that keep evaluations of the same target from overlapping
+//!
+//! A lock is a file holding its owner's PID, a nonce and its creation time,
+//! removed when the `ScopedLock` is dropped. Locks older than the stale timeout
+//! are left over from crashed processes and are replaced.
+//! AIDEV-NOTE: The file is created with create_new, so of two processes racing
+//! for a free lock only one wins. If it can't be created for another reason
+//! (e.g. unwritable dir) the caller proceeds unlocked rather than never evaluating.
+//! Drop only removes a file still holding its own token: if the lock was judged
+//! stale and taken over, the new owner's lock is left alone. Likewise a stale
+//! lock is only removed if it still holds what was read when judging it stale.
+
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::claude::MAX_RETRY_DELAY;
+
+/// Matches the Claude CLI timeout (5 min) - older locks are from crashed processes
+pub const STALE_AFTER: Duration = Duration::from_secs(300);
+
+/// Distinguishes locks taken by the same process
+static NONCE: AtomicU64 = AtomicU64::new(0);
+
+/// How long a lock guarding `calls` serial LLM calls may legitimately be held:
+/// each call can time out `max_retries + 1` times, with a backoff between attempts
+pub fn stale_after_calls(timeout: Duration, max_retries: u32, calls: u32) -> Duration {
+    let per_call = timeout
+        .saturating_mul(max_retries.saturating_add(1))
+        .saturating_add(MAX_RETRY_DELAY.saturating_mul(max_retries));
+    per_call.saturating_mul(calls.max(1)).max(STALE_AFTER)
+}
+
+/// A held lock file, removed when dropped (scope guard)
+#[derive(Debug)]
+pub struct ScopedLock {
+    path: PathBuf,
+    /// What we wrote into the file (None if it couldn't be created)
+    token: Option<String>,
+}
+
+impl ScopedLock {
+    /// Take the lock at `path`, or None if another process holds a live one
+    pub fn acquire(path: &Path, stale_after: Duration, log: &dyn Fn(&str)) -> Option<Self> {
+        if is_stale(path, stale_after) {
+            let seen = fs::read_to_string(path).ok();
+            if remove_stale(path, stale_after, seen.as_deref()) {
+                log(&format!(
+                    "Removed stale lock (>{}s old)",
+                    stale_after.as_secs()
+                ));
+            }
+        }
+
+        let token = format!(
+            "{} {} {}",
+            std::process::id(),
+            NONCE.fetch_add(1, Ordering::Relaxed),
+            chrono::Utc::now().to_rfc3339()
+        );
+        let token = match OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(mut file) => match file.write_all(token.as_bytes()) {
+                Ok(()) => Some(token),
+                Err(e) => {
+                    // An empty lock would block the target until it goes stale
+                    log(&format!("WARN: Could not write lock file: {}", e));
+                    let _ = fs::remove_file(path);
+                    None
+                }
+            },
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => return None,
+            Err(e) => {
+                log(&format!("WARN: Could not create lock file: {}", e));
+                None
+            }
+        };
+        Some(ScopedLock {
+            path: path.to_path_buf(),
+            token,
+        })
+    }
+
+    /// Run `f` while holding the lock; it is released on every return path,
+    /// before the caller gets a chance to `process::exit`
+    pub fn run<T>(self, f: impl FnOnce() -> T) -> T {
+        let _held = self;
+        f()
+    }
+}
+
+impl Drop for ScopedLock {
+    fn drop(&mut self) {
+        let owned = self
+            .token
+            .as_ref()
+            .is_some_and(|token| fs::read_to_string(&self.path).ok().as_ref() == Some(token));
+        if owned {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Remove the stale lock at `path` if it still holds `seen` (what we read when
+/// judging it stale). Returns whether it was removed.
+/// AIDEV-NOTE: Two processes can judge the same lock stale; the first replaces it
+/// with a fresh one. Renaming claims whatever is at `path` atomically, so the
+/// second checks what it actually claimed and puts a live lock back.
+fn remove_stale(path: &Path, stale_after: Duration, seen: Option<&str>) -> bool {
+    let mut claimed = path.as_os_str().to_owned();
+    claimed.push(format!(
+        ".stale-{}-{}",
+        std::process::id(),
+        NONCE.fetch_add(1, Ordering::Relaxed)
+    ));
+    let claimed = PathBuf::from(claimed);
+    if fs::rename(path, &claimed).is_err() {
+        return false;
+    }
+
+    if fs::read_to_string(&claimed).ok().as_deref() == seen && is_stale(&claimed, stale_after) {
+        let _ = fs::remove_file(&claimed);
+        true
+    } else {
+        // Not the lock we judged stale: restore it, unless yet another is in place
+        let _ = fs::hard_link(&claimed, path);
+        let _ = fs::remove_file(&claimed);
+        false
+    }
+}
+
+/// Whether an existing lock at `path` is older than `stale_after`
+fn is_stale(path: &Path, stale_after: Duration) -> bool {
+    match path.metadata().and_then(|m| m.modified()) {
+        Ok(modified) => modified.elapsed().unwrap_or(stale_after) >= stale_after,
+        // Gone (nothing to remove), or unreadable: treat as stale like before
+        Err(e) => e.kind() != ErrorKind::NotFound,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_released_on_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join("codex.lock");
+
+        let lock = ScopedLock::acquire(&lock_path, STALE_AFTER, &|_| {}).unwrap();
+        let result: Result<(), ()> = lock.run(|| {
+            assert!(lock_path.exists(), "lock held while running");
+            Err(())
+        });
+
+        assert!(result.is_err());
+        assert!(
+            !lock_path.exists(),
+            "error path must not leave a stale lock"
+        );
+    }
+
+    #[test]
+    fn test_live_lock_blocks_stale_lock_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join("eval.lock");
+
+        let held = ScopedLock::acquire(&lock_path, STALE_AFTER, &|_| {}).unwrap();
+        assert!(ScopedLock::acquire(&lock_path, STALE_AFTER, &|_| {}).is_none());
+
+        // With a zero timeout the held lock counts as left over from a crash
+        let logged = std::cell::RefCell::new(Vec::new());
+        let replaced = ScopedLock::acquire(&lock_path, Duration::ZERO, &|msg| {
+            logged.borrow_mut().push(msg.to_string())
+        });
+        assert!(replaced.is_some());
+        assert_eq!(logged.borrow().len(), 1, "stale removal is logged");
+
+        // The original holder finishing must not delete its successor's lock
+        drop(held);
+        assert!(
+            lock_path.exists(),
+            "taken-over lock survives old owner's drop"
+        );
+        drop(replaced);
+        assert!(!lock_path.exists());
+    }
+
+    #[test]
+    fn test_stale_takeover_race_keeps_fresh_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join("eval.lock");
+        let stale_after = Duration::from_secs(60);
+        fs::write(&lock_path, "123 0 old").unwrap();
+        let old = std::time::SystemTime::now() - Duration::from_secs(3600);
+        fs::File::options()
+            .write(true)
+            .open(&lock_path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        // B judges the lock stale and reads it...
+        assert!(is_stale(&lock_path, stale_after));
+        let seen_by_b = fs::read_to_string(&lock_path).ok();
+
+        // ...but A takes it over first
+        let a = ScopedLock::acquire(&lock_path, stale_after, &|_| {}).unwrap();
+        let a_token = a.token.clone().unwrap();
+
+        // B must not remove A's fresh lock
+        assert!(!remove_stale(&lock_path, stale_after, seen_by_b.as_deref()));
+        assert_eq!(fs::read_to_string(&lock_path).unwrap(), a_token);
+        assert!(ScopedLock::acquire(&lock_path, stale_after, &|_| {}).is_none());
+        assert_eq!(
+            fs::read_dir(dir.path()).unwrap().count(),
+            1,
+            "no claimed files left behind"
+        );
+
+        drop(a);
+        assert!(!lock_path.exists());
+    }
+
+    #[test]
+    fn test_stale_after_calls_covers_retries_and_second_opinion() {
+        let timeout = Duration::from_secs(600);
+        assert_eq!(stale_after_calls(timeout, 0, 1), timeout);
+        assert_eq!(
+            stale_after_calls(timeout, 2, 2),
+            (timeout * 3 + MAX_RETRY_DELAY * 2) * 2
+        );
+        // Never shorter than the default
+        assert_eq!(
+            stale_after_calls(Duration::from_secs(10), 0, 1),
+            STALE_AFTER
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! Append-only log files with size-based rotation
+//!
+//! Used for `.superego/codex.log` and `.superego/eval.log` (written here) and
+//! `.superego/hook.log` (appended by the plugin shell scripts, rotated from
+//! `sg evaluate-llm`).
+//! AIDEV-NOTE: Keeps two generations: `<log>.1` (previous) and `<log>.2` (oldest).
+//! codex.log/eval.log lines come from `format_line` (config `log_format`).
+//! `sg tail` follows the evaluation logs with `Follower`.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use crate::config::LogFormat;
+
+/// Evaluation logs in .superego that `sg tail` follows
+pub const EVAL_LOGS: &[&str] = &[
+    "eval.log",
+    "codex.log",
+    "gemini.log",
+    "aider.log",
+    "hook.log",
+];
+
+/// Number of rotated generations kept alongside the live log
+const GENERATIONS: u32 = 2;
+
+/// Path of a rotated generation, e.g. codex.log -> codex.log.1
+fn generation_path(path: &Path, n: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Rotate the log if it has reached `max_bytes` (0 disables rotation)
+///
+/// Returns true if the log was rotated.
+pub fn rotate_if_needed(path: &Path, max_bytes: u64) -> std::io::Result<bool> {
+    if max_bytes == 0 {
+        return Ok(false);
+    }
+
+    let size = match fs::metadata(path) {
+        Ok(meta) => meta.len(),
+        Err(_) => return Ok(false), // Nothing to rotate yet
+    };
+    if size < max_bytes {
+        return Ok(false);
+    }
+
+    // Shift older generations up, dropping the oldest
+    for n in (1..GENERATIONS).rev() {
+        let from = generation_path(path, n);
+        if from.exists() {
+            fs::rename(&from, generation_path(path, n + 1))?;
+        }
+    }
+    fs::rename(path, generation_path(path, 1))?;
+    Ok(true)
+}
+
+/// Append a line to the log, rotating first if it is over the cap
+pub fn append(path: &Path, line: &str, max_bytes: u64) -> std::io::Result<()> {
+    rotate_if_needed(path, max_bytes)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())
+}
+
+/// The last `n` lines of a log (empty if it doesn't exist)
+pub fn last_lines(path: &Path, n: usize) -> std::io::Result<Vec<String>> {
+    let content = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.len().saturating_sub(n);
+    Ok(lines[start..].iter().map(|l| l.to_string()).collect())
+}
+
+/// Reads the lines appended to a log since the last poll
+/// AIDEV-NOTE: A file shorter than what was already read has been rotated
+/// (or truncated), so reading restarts at its beginning. A trailing partial
+/// line is held back until its newline is written.
+pub struct Follower {
+    path: PathBuf,
+    offset: u64,
+    partial: String,
+}
+
+impl Follower {
+    /// Follow `path` from its current end (it may not exist yet)
+    pub fn new(path: &Path) -> Self {
+        Follower {
+            path: path.to_path_buf(),
+            offset: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+            partial: String::new(),
+        }
+    }
+
+    /// Complete lines appended since the last call
+    pub fn poll(&mut self) -> std::io::Result<Vec<String>> {
+        let mut file = match File::open(&self.path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+        }
+        if len == self.offset {
+            return Ok(Vec::new());
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        self.offset += buf.len() as u64;
+        self.partial.push_str(&String::from_utf8_lossy(&buf));
+
+        let Some(end) = self.partial.rfind('\n') else {
+            return Ok(Vec::new());
+        };
+        let rest = self.partial.split_off(end + 1);
+        let lines = self.partial.lines().map(|l| l.to_string()).collect();
+        self.partial = rest;
+        Ok(lines)
+    }
+}
+
+/// One log line (newline included)
+///
+/// Text is `<timestamp> <message>`. JSON is a record with timestamp, level,
+/// event, message and the entries of `fields` (an object); the level comes
+/// from the message's ERROR/WARN prefix, which is dropped from the message.
+pub fn format_line(format: LogFormat, event: &str, message: &str, fields: Value) -> String {
+    let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ");
+    if format == LogFormat::Text {
+        return format!("{} {}\n", timestamp, message);
+    }
+
+    let (level, message) = if let Some(rest) = message.strip_prefix("ERROR") {
+        ("error", rest.trim_start_matches(':').trim_start())
+    } else if let Some(rest) = message.strip_prefix("WARN:") {
+        ("warn", rest.trim_start())
+    } else {
+        (
+            "info",
+            message
+                .strip_prefix("SKIP:")
+                .unwrap_or(message)
+                .trim_start(),
+        )
+    };
+    let mut record = json!({
+        "timestamp": timestamp.to_string(),
+        "level": level,
+        "event": event,
+        "message": message,
+    });
+    if let (Some(record), Value::Object(fields)) = (record.as_object_mut(), fields) {
+        record.extend(fields);
+    }
+    format!("{}\n", record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_append_rotates_past_cap() {
+        let dir = tempdir().unwrap();
+        let log = dir.path().join("codex.log");
+
+        append(&log, "0123456789\n", 16).unwrap();
+        assert!(!generation_path(&log, 1).exists());
+
+        // Log is 11 bytes - under cap, keeps appending
+        append(&log, "abcdefghij\n", 16).unwrap();
+        assert_eq!(fs::read_to_string(&log).unwrap().len(), 22);
+
+        // Now over cap - rotated before this write
+        append(&log, "third\n", 16).unwrap();
+        assert_eq!(fs::read_to_string(&log).unwrap(), "third\n");
+        assert_eq!(
+            fs::read_to_string(generation_path(&log, 1)).unwrap(),
+            "0123456789\nabcdefghij\n"
+        );
+
+        // Second rotation shifts .1 to .2
+        append(&log, "fourth-line-long\n", 16).unwrap();
+        append(&log, "fifth\n", 16).unwrap();
+        assert_eq!(fs::read_to_string(&log).unwrap(), "fifth\n");
+        assert!(generation_path(&log, 2).exists());
+    }
+
+    #[test]
+    fn test_follower_reads_appends_and_rotation() {
+        let dir = tempdir().unwrap();
+        let log = dir.path().join("codex.log");
+        assert!(last_lines(&log, 5).unwrap().is_empty());
+
+        fs::write(&log, "old 1\nold 2\nold 3\n").unwrap();
+        assert_eq!(last_lines(&log, 2).unwrap(), vec!["old 2", "old 3"]);
+
+        // Starts at the end: existing lines aren't repeated
+        let mut follower = Follower::new(&log);
+        assert!(follower.poll().unwrap().is_empty());
+
+        append(&log, "new 1\nnew ", 0).unwrap();
+        assert_eq!(follower.poll().unwrap(), vec!["new 1"]);
+        append(&log, "2\n", 0).unwrap();
+        assert_eq!(follower.poll().unwrap(), vec!["new 2"]);
+
+        // Rotated: the new, shorter log is read from the start
+        append(&log, "after rotation\n", 8).unwrap();
+        assert_eq!(follower.poll().unwrap(), vec!["after rotation"]);
+    }
+
+    #[test]
+    fn test_format_line() {
+        let text = format_line(LogFormat::Text, "skip", "SKIP: Rate limited", json!({}));
+        assert!(text.ends_with("Z SKIP: Rate limited\n"));
+
+        let line = format_line(
+            LogFormat::Json,
+            "response",
+            "Response in 2.5s, tokens=120",
+            json!({"session": "s.jsonl", "tokens": 120}),
+        );
+        assert!(line.ends_with('\n') && !line.trim_end().contains('\n'));
+        let record: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(record["level"], "info");
+        assert_eq!(record["event"], "response");
+        assert_eq!(record["session"], "s.jsonl");
+        assert_eq!(record["tokens"], 120);
+        assert!(record["timestamp"].as_str().unwrap().ends_with('Z'));
+
+        let record: Value = serde_json::from_str(&format_line(
+            LogFormat::Json,
+            "error",
+            "ERROR reading transcript: bad",
+            json!({}),
+        ))
+        .unwrap();
+        assert_eq!(record["level"], "error");
+        assert_eq!(record["message"], "reading transcript: bad");
+
+        let record: Value = serde_json::from_str(&format_line(
+            LogFormat::Json,
+            "warn",
+            "WARN: x",
+            Value::Null,
+        ))
+        .unwrap();
+        assert_eq!(
+            (record["level"].as_str(), record["message"].as_str()),
+            (Some("warn"), Some("x"))
+        );
+    }
+
+    #[test]
+    fn test_rotate_disabled_or_missing() {
+        let dir = tempdir().unwrap();
+        let log = dir.path().join("hook.log");
+        assert!(!rotate_if_needed(&log, 10).unwrap());
+
+        fs::write(&log, "x".repeat(100)).unwrap();
+        assert!(!rotate_if_needed(&log, 0).unwrap());
+        assert!(rotate_if_needed(&log, 10).unwrap());
+        assert!(!log.exists());
+    }
+}
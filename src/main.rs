@@ -1,24 +1,42 @@
 use clap::{Parser, Subcommand};
+use std::io::IsTerminal;
 use std::path::Path;
 
+mod atomic;
 mod audit;
-mod ba;
+mod batch;
 mod claude;
 mod codex_llm;
 mod config;
+mod costs;
 mod decision;
+mod diag;
+mod doctor;
 mod evaluate;
 mod feedback;
+mod heartbeat;
 mod hooks;
+mod ignore;
 mod init;
+mod llm;
+mod lock;
+mod logfile;
 mod migrate;
+mod notify;
 mod oh;
+mod overview;
 mod prompts;
 mod retro;
 mod review;
+mod sarif;
+mod serve;
+mod sessions;
 mod setup_oh;
+mod signals;
 mod state;
+mod tasks;
 mod transcript;
+mod update;
 
 #[derive(Parser)]
 #[command(name = "sg")]
@@ -28,6 +46,14 @@ mod transcript;
     about = "Superego - Metacognitive advisor for Claude Code"
 )]
 struct Cli {
+    /// Print only errors to stderr (no warnings or progress notes)
+    #[arg(long, short, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Also print debug detail to stderr (e.g. evaluation log lines as they're written)
+    #[arg(long, short, global = true)]
+    verbose: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -53,6 +79,31 @@ enum Commands {
         /// Maximum number of decisions to return
         #[arg(long, default_value = "10")]
         limit: usize,
+        /// Print each decision's full context
+        #[arg(long, conflicts_with = "short")]
+        full: bool,
+        /// Print one line of context per decision (default)
+        #[arg(long)]
+        short: bool,
+        /// Print decisions as a JSON array (full context)
+        #[arg(long)]
+        json: bool,
+        /// Only decisions on or after this date (YYYY-MM-DD, UTC)
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+        /// Only decisions on or before this date (YYYY-MM-DD, UTC)
+        #[arg(long)]
+        until: Option<chrono::NaiveDate>,
+    },
+
+    /// Show one journaled decision in full (every field, untruncated context)
+    Explain {
+        /// Decision file path, or its timestamp as shown by `sg history`
+        /// (e.g. "2025-01-15 10:30:00.123 UTC" or 2025-01-15T10-30-00Z)
+        decision: String,
+        /// Print the decision as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Check if there's pending feedback (instant, for hooks)
@@ -61,9 +112,27 @@ enum Commands {
     /// Get pending feedback and clear queue
     GetFeedback,
 
+    /// Show pending feedback without clearing the queue
+    PeekFeedback,
+
+    /// Pause evaluation without removing .superego/
+    Disable {
+        /// Re-enable automatically after N minutes
+        #[arg(long)]
+        minutes: Option<i64>,
+    },
+
+    /// Resume evaluation after `sg disable`
+    Enable,
+
     /// Reset superego state (recovery from corruption)
     Reset {
-        /// Also clear the superego Claude session
+        /// Only remove this session (.superego/sessions/<id>/), keeping everything else
+        #[arg(long, value_name = "ID")]
+        session: Option<String>,
+
+        /// Also delete saved evaluator session ids (Codex threads, legacy
+        /// superego_session files), forcing a cold start next time
         #[arg(long)]
         clear_session: bool,
     },
@@ -71,15 +140,40 @@ enum Commands {
     /// LLM-based evaluation with natural language feedback
     EvaluateLlm {
         /// Path to the transcript JSONL file
-        #[arg(long)]
-        transcript_path: String,
+        #[arg(long, required_unless_present = "stdin")]
+        transcript_path: Option<String>,
+        /// Read a Claude Code JSONL transcript from stdin instead of --transcript-path
+        #[arg(long, conflicts_with_all = ["transcript_path", "plain"])]
+        stdin: bool,
         /// Claude session ID (for per-session state isolation)
         #[arg(long)]
         session_id: Option<String>,
+        /// Topic to pay particular attention to (e.g. "test coverage", "security")
+        #[arg(long)]
+        focus: Option<String>,
+        /// Treat the transcript as a plain-text/markdown conversation export
+        #[arg(long)]
+        plain: bool,
+        /// On ALLOW, ask for a one-line rationale and journal it
+        #[arg(long)]
+        explain_decision: bool,
+        /// Keep queued feedback under N characters (journal keeps the full text)
+        #[arg(long)]
+        max_feedback_length: Option<usize>,
+        /// LLM CLI timeout in milliseconds (overrides config timeout_ms; default 5 min)
+        #[arg(long)]
+        timeout_ms: Option<u64>,
     },
 
     /// Check hooks and auto-update if outdated
-    Check,
+    Check {
+        /// Also check GitHub for a newer sg release (needs network; failures only warn)
+        #[arg(long)]
+        check_updates: bool,
+    },
+
+    /// Check external dependencies (claude, codex, ba, Open Horizons) and .superego/
+    Doctor,
 
     /// Output current evaluation mode (always or pull)
     Mode,
@@ -89,6 +183,29 @@ enum Commands {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+        /// Statistics only: skip the LLM analysis (no cost, works offline)
+        #[arg(long)]
+        no_llm: bool,
+        /// Only decisions on or after this date (YYYY-MM-DD, UTC)
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+        /// Only decisions on or before this date (YYYY-MM-DD, UTC)
+        #[arg(long)]
+        until: Option<chrono::NaiveDate>,
+    },
+
+    /// Show evaluation spend from .superego/costs.jsonl
+    Costs {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Follow the evaluation logs (codex, gemini, aider, hook) as they're written
+    Tail {
+        /// Lines of existing log to print first, per log
+        #[arg(long, short = 'n', default_value_t = 10)]
+        lines: usize,
     },
 
     /// Migrate from legacy hooks to plugin mode
@@ -98,7 +215,74 @@ enum Commands {
     SetupOh,
 
     /// Evaluate the most recent Codex session (for Codex skill)
-    EvaluateCodex,
+    EvaluateCodex {
+        /// Codex CLI timeout in milliseconds (overrides config timeout_ms; default 3 min)
+        #[arg(long)]
+        timeout_ms: Option<u64>,
+    },
+
+    /// Evaluate the most recent Gemini CLI session (evaluated with Claude)
+    EvaluateGemini,
+
+    /// Evaluate the latest aider session in the current directory (.aider.chat.history.md)
+    EvaluateAider,
+
+    /// Convert a Codex session JSONL into a Claude Code transcript
+    ImportCodex {
+        /// Codex session file (e.g. ~/.codex/sessions/.../rollout-*.jsonl)
+        session_file: std::path::PathBuf,
+        /// Where to write the converted transcript
+        #[arg(long, short)]
+        output: std::path::PathBuf,
+    },
+
+    /// Serve evaluate/review/status as JSON-RPC over stdin/stdout (for editor plugins)
+    Serve,
+
+    /// Report on long-running watch modes
+    Watch {
+        /// Show whether a watcher (`sg tail`) is running and its last heartbeat
+        #[arg(long)]
+        status: bool,
+    },
+
+    /// Delete old session directories (state, decisions, pending feedback)
+    #[command(group(clap::ArgGroup::new("criteria").required(true).multiple(true)))]
+    Prune {
+        /// Remove sessions not modified in this many days
+        #[arg(long, value_name = "DAYS", group = "criteria")]
+        older_than: Option<u32>,
+
+        /// Keep only the N most recently modified sessions (with --older-than: never prune these)
+        #[arg(long, value_name = "N", group = "criteria")]
+        keep: Option<usize>,
+
+        /// List what would be removed without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Manage per-session metadata
+    Sessions {
+        /// Defaults to `list`
+        #[command(subcommand)]
+        action: Option<SessionsAction>,
+    },
+
+    /// Evaluate every transcript in a directory (batch/CI review)
+    EvaluateDir {
+        /// Directory to search (recursively) for .jsonl transcripts
+        dir: String,
+        /// Only files modified since: RFC 3339, YYYY-MM-DD, or a duration (24h, 7d)
+        #[arg(long)]
+        since: Option<String>,
+        /// Output the aggregate report as JSON
+        #[arg(long)]
+        json: bool,
+        /// Maximum concurrent evaluations
+        #[arg(long, default_value = "4")]
+        concurrency: usize,
+    },
 
     /// Generate HTML retrospective visualization of a session
     Retro {
@@ -106,13 +290,21 @@ enum Commands {
         #[arg(long)]
         session: Option<String>,
 
+        /// Show LLM-curated key moments (the default)
+        #[arg(long, conflicts_with = "full")]
+        curated: bool,
+
         /// Show all decisions instead of LLM-curated key moments
         #[arg(long)]
         full: bool,
 
-        /// Output file path
-        #[arg(long, default_value = "retro.html")]
-        output: std::path::PathBuf,
+        /// Output format: "html" or "markdown"
+        #[arg(long, default_value = "html")]
+        format: String,
+
+        /// Output file path (default: .superego/retro.html, or retro.md for markdown)
+        #[arg(long)]
+        output: Option<std::path::PathBuf>,
 
         /// Open in browser after generating
         #[arg(long)]
@@ -121,6 +313,16 @@ enum Commands {
         /// Push retrospective data to Open Horizons
         #[arg(long)]
         push_oh: bool,
+
+        /// With --push-oh: print the OH payload as JSON instead of sending it
+        #[arg(long, requires = "push_oh")]
+        dry_run: bool,
+    },
+
+    /// Read or change .superego/config.yaml settings
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
     },
 
     /// Manage superego prompts (list, switch, show)
@@ -130,18 +332,77 @@ enum Commands {
     },
 
     /// Review changes with superego (on-demand evaluation)
-    Review {
-        /// What to review: "staged", "pr", or a file path (default: staged, fallback to uncommitted)
-        target: Option<String>,
+    Review(ReviewArgs),
+
+    /// Review changes using Codex LLM (for Codex skill; same as `review` with backend: codex)
+    ReviewCodex(ReviewArgs),
+}
+
+/// Arguments shared by `review` and `review-codex`
+#[derive(clap::Args)]
+struct ReviewArgs {
+    /// What to review: "staged", "pr", a commit range (a..b), or a file path (default: staged, fallback to uncommitted)
+    target: Option<String>,
+    /// Topic to pay particular attention to (e.g. "test coverage", "security")
+    #[arg(long)]
+    focus: Option<String>,
+    /// Review with this shipped prompt (e.g. "security") instead of prompt.md
+    #[arg(long)]
+    prompt: Option<String>,
+    /// Model for this review (overrides config review_model/model)
+    #[arg(long)]
+    model: Option<String>,
+    /// Output format: "text", "sarif", or "json" (findings array)
+    #[arg(long, default_value = "text")]
+    format: String,
+    /// For "pr": review only the branch's own commits, excluding changes
+    /// brought in by merging the base branch
+    #[arg(long)]
+    no_merges: bool,
+    /// For "pr": base branch to compare against (overrides config
+    /// review_base_branch and detection)
+    #[arg(long)]
+    base: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum SessionsAction {
+    /// List sessions with decision counts and activity, most recent first
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
 
-    /// Review changes using Codex LLM (for Codex skill)
-    ReviewCodex {
-        /// What to review: "staged", "pr", or a file path (default: staged, fallback to uncommitted)
-        target: Option<String>,
+    /// Set a human-readable label for a session (omit name to clear it)
+    Label {
+        /// Session id (directory name under .superego/sessions/)
+        id: String,
+        /// Label to set
+        name: Option<String>,
     },
 }
 
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print a key's effective value (default if not set in config.yaml)
+    Get {
+        /// Config key, e.g. model or oh_endeavor_id
+        key: String,
+    },
+
+    /// Set a key in .superego/config.yaml, keeping comments (empty value unsets)
+    Set {
+        /// Config key, e.g. model or oh_endeavor_id
+        key: String,
+        /// New value
+        value: String,
+    },
+
+    /// Report unknown keys, invalid values and out-of-range settings (exit 1 on errors)
+    Validate,
+}
+
 #[derive(Subcommand)]
 enum PromptAction {
     /// List available prompts
@@ -153,12 +414,280 @@ enum PromptAction {
         name: String,
     },
 
-    /// Show current prompt info
+    /// Show current prompt info (base, local modifications, backups)
+    #[command(alias = "info")]
     Show,
+
+    /// Show your changes to prompt.md relative to the shipped template
+    Diff {
+        /// Template to compare against (defaults to the current base prompt)
+        name: Option<String>,
+    },
+}
+
+/// `--since`/`--until` dates as a half-open UTC range: [since 00:00, until+1 00:00)
+type DateBounds = (
+    Option<chrono::DateTime<chrono::Utc>>,
+    Option<chrono::DateTime<chrono::Utc>>,
+);
+
+fn date_bounds(since: Option<chrono::NaiveDate>, until: Option<chrono::NaiveDate>) -> DateBounds {
+    let midnight = |d: chrono::NaiveDate| d.and_time(chrono::NaiveTime::MIN).and_utc();
+    (
+        since.map(midnight),
+        until.and_then(|d| d.succ_opt()).map(midnight),
+    )
+}
+
+/// `sg reset --clear-session`: delete saved evaluator session ids, reporting the count
+/// Only `session`'s ids if given, otherwise every session's and the root's.
+/// AIDEV-NOTE: Claude sessions are never persisted (--no-session-persistence), so
+/// there is nothing to drop on the Claude side; only ids we saved on disk remain.
+fn clear_evaluator_sessions(superego_dir: &Path, session: Option<&str>) {
+    let cleared = match session {
+        Some(id) => match sessions::clear_session_evaluator_sessions(superego_dir, id) {
+            // Reported by the removal that follows
+            Err(sessions::SessionError::NotFound(_)) => return,
+            result => result.map_err(|e| e.to_string()),
+        },
+        None => sessions::clear_evaluator_sessions(superego_dir).map_err(|e| e.to_string()),
+    };
+    match cleared {
+        Ok(0) => println!("No saved evaluator sessions to clear"),
+        Ok(n) => println!(
+            "Cleared {} saved evaluator session(s); the next evaluation starts cold",
+            n
+        ),
+        Err(e) => eprintln!("Failed to clear evaluator sessions: {}", e),
+    }
+}
+
+/// An agent whose latest session `sg evaluate-<name>` picks up
+struct SessionSource {
+    /// Command suffix and .superego log/lock file stem, e.g. "gemini"
+    name: &'static str,
+    /// Display name for logs
+    agent: &'static str,
+    find_latest: fn() -> Option<std::path::PathBuf>,
+    /// Printed when there is no session to evaluate
+    not_found: &'static str,
+}
+
+/// Evaluate an agent's latest session (shared by evaluate-gemini and evaluate-aider)
+fn evaluate_latest_session(source: &SessionSource) {
+    let superego_dir = Path::new(".superego");
+
+    // Log to .superego/<name>.log (rotated at log_max_bytes)
+    let log_max_bytes = config::Config::load(superego_dir).log_max_bytes;
+    let log = |msg: &str| {
+        diag::debug(msg);
+        let log_path = superego_dir.join(format!("{}.log", source.name));
+        let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ");
+        let line = format!("{} {}\n", timestamp, msg);
+        let _ = logfile::append(&log_path, &line, log_max_bytes);
+    };
+
+    // Recursion prevention - skip if this is superego's own LLM call
+    if std::env::var("SUPEREGO_DISABLED").as_deref() == Ok("1") {
+        log("SKIP: SUPEREGO_DISABLED=1");
+        println!(r#"{{"has_concerns": false, "skipped": true, "reason": "recursion_prevention"}}"#);
+        return;
+    }
+
+    log(&format!("evaluate-{} started", source.name));
+
+    if !superego_dir.exists() {
+        log("ERROR: .superego not initialized");
+        eprintln!("Superego not initialized. Run 'sg init' first.");
+        std::process::exit(1);
+    }
+
+    let lock_path = superego_dir.join(format!("{}.lock", source.name));
+    let Some(lock) = lock::ScopedLock::acquire(&lock_path, lock::STALE_AFTER, &log) else {
+        log("SKIP: Another evaluation in progress (lock file exists)");
+        eprintln!("Another evaluation in progress. Skipping.");
+        println!(r#"{{"has_concerns": false, "skipped": true}}"#);
+        return;
+    };
+
+    // Kill the Claude CLI if the caller cancels us
+    signals::install();
+
+    // Err carries the process exit code; the lock is released first
+    let result = lock.run(|| {
+        let session_path = match (source.find_latest)() {
+            Some(p) => p,
+            None => {
+                log(&format!("ERROR: No {} sessions found", source.agent));
+                eprintln!("{}", source.not_found);
+                return Err(1);
+            }
+        };
+
+        let session_name = session_path
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| session_path.display().to_string());
+        log(&format!("Session: {}", session_name));
+        eprintln!("Evaluating: {}", session_path.display());
+
+        log("Calling Claude...");
+        let start_time = std::time::Instant::now();
+
+        match evaluate::evaluate_llm(
+            &session_path,
+            superego_dir,
+            None,
+            &evaluate::EvaluateOptions::default(),
+        ) {
+            Ok(result) => {
+                log(&format!(
+                    "Response in {:.1}s, cost=${:.4}",
+                    start_time.elapsed().as_secs_f32(),
+                    result.cost_usd
+                ));
+                println!(
+                    r#"{{"has_concerns": {}, "cost_usd": {:.6}}}"#,
+                    result.has_concerns, result.cost_usd
+                );
+                if result.has_concerns {
+                    log("BLOCK - concerns found");
+                    eprintln!("Feedback:\n{}", result.feedback);
+                } else {
+                    log("ALLOW - no concerns");
+                    eprintln!("No concerns.");
+                }
+                Ok(())
+            }
+            Err(e) if e.interrupted().is_some() => {
+                let signal = e.interrupted().unwrap_or_default();
+                log(&format!("Interrupted (signal {})", signal));
+                eprintln!("Evaluation interrupted (signal {})", signal);
+                Err(signals::exit_code(signal))
+            }
+            Err(e) => {
+                log(&format!("ERROR: {}", e));
+                eprintln!("Evaluation failed: {}", e);
+                Err(1)
+            }
+        }
+    });
+    if let Err(code) = result {
+        std::process::exit(code);
+    }
+}
+
+/// `sg review` / `sg review-codex`: review a target and print it in the chosen format
+fn run_review(args: ReviewArgs, backend: Option<config::Backend>) {
+    let ReviewArgs {
+        target,
+        focus,
+        prompt,
+        model,
+        format,
+        no_merges,
+        base,
+    } = args;
+    let superego_dir = Path::new(".superego");
+
+    if !superego_dir.exists() {
+        eprintln!("No .superego directory found. Run 'sg init' first.");
+        std::process::exit(1);
+    }
+
+    let format = match review::OutputFormat::from_arg(&format) {
+        Some(f) => f,
+        None => {
+            eprintln!("Unknown format: {}", format);
+            eprintln!("Available: text, sarif, json");
+            std::process::exit(1);
+        }
+    };
+
+    let target = match review::ReviewTarget::from_arg(target.as_deref()).with_no_merges(no_merges) {
+        Some(t) => t,
+        None => {
+            eprintln!("--no-merges only applies to the 'pr' target");
+            std::process::exit(1);
+        }
+    };
+    let target = match target.with_base(base) {
+        Some(t) => t,
+        None => {
+            eprintln!("--base only applies to the 'pr' target");
+            std::process::exit(1);
+        }
+    };
+
+    let prompt = match prompt {
+        Some(name) => match prompts::PromptType::from_name(&name) {
+            Some(pt) => Some(pt),
+            None => {
+                eprintln!("Unknown prompt: {}", name);
+                eprintln!("Available: {}", prompts::PromptType::available_names());
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    let options = review::ReviewOptions {
+        focus,
+        prompt,
+        model,
+        structured: format == review::OutputFormat::Json,
+        backend,
+        // Show progress during long reviews, unless stderr is captured
+        stream: std::io::stderr().is_terminal(),
+    };
+
+    match backend {
+        Some(backend) => eprintln!("Reviewing ({})...", backend.as_str()),
+        None => eprintln!("Reviewing..."),
+    }
+
+    match review::review(superego_dir, target, &options) {
+        Ok(result) => match format {
+            review::OutputFormat::Text => {
+                println!("\n--- Review: {} ---\n", result.target_description);
+                println!("{}", result.feedback);
+            }
+            review::OutputFormat::Sarif => {
+                println!("{:#}", sarif::to_sarif(&result));
+            }
+            review::OutputFormat::Json => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&result.findings()).unwrap_or_default()
+                );
+            }
+        },
+        Err(review::ReviewError::NoDiff(msg)) => match format {
+            review::OutputFormat::Text => println!("Nothing to review: {}", msg),
+            review::OutputFormat::Sarif => {
+                eprintln!("Nothing to review: {}", msg);
+                println!("{:#}", sarif::empty_sarif());
+            }
+            review::OutputFormat::Json => {
+                eprintln!("Nothing to review: {}", msg);
+                println!("[]");
+            }
+        },
+        Err(e) => {
+            eprintln!("Review failed: {}", e);
+            std::process::exit(1);
+        }
+    }
 }
 
 fn main() {
     let cli = Cli::parse();
+    diag::set_verbosity(if cli.quiet {
+        diag::Verbosity::Quiet
+    } else if cli.verbose {
+        diag::Verbosity::Verbose
+    } else {
+        diag::Verbosity::Normal
+    });
 
     match cli.command {
         Commands::Init { force } => {
@@ -201,7 +730,12 @@ fn main() {
             }
 
             // Run LLM evaluation (no session_id for legacy command)
-            match evaluate::evaluate_llm(transcript, superego_dir, None) {
+            match evaluate::evaluate_llm(
+                transcript,
+                superego_dir,
+                None,
+                &evaluate::EvaluateOptions::default(),
+            ) {
                 Ok(result) => {
                     println!(
                         r#"{{"has_concerns": {}, "cost_usd": {:.6}}}"#,
@@ -220,26 +754,67 @@ fn main() {
                 }
             }
         }
-        Commands::History { limit } => {
+        Commands::History {
+            limit,
+            full,
+            short: _,
+            json,
+            since,
+            until,
+        } => {
             let superego_dir = Path::new(".superego");
+            let (start, end) = date_bounds(since, until);
 
-            match decision::read_all_sessions(superego_dir) {
+            match decision::read_sessions_between(superego_dir, start, end) {
                 Ok(decisions) => {
                     let start = decisions.len().saturating_sub(limit);
                     let recent: Vec<_> = decisions.into_iter().skip(start).collect();
 
-                    if recent.is_empty() {
+                    if json {
+                        // Fill in the session from the journal's directory when unset
+                        let decisions: Vec<decision::Decision> = recent
+                            .into_iter()
+                            .map(|(session, mut d)| {
+                                d.session_id.get_or_insert(session);
+                                d
+                            })
+                            .collect();
+                        println!("{}", decision::to_json_array(&decisions));
+                        return;
+                    }
+                    let labels = sessions::labels(superego_dir);
+
+                    if recent.is_empty() && (since.is_some() || until.is_some()) {
+                        println!("No decisions in that date range.");
+                    } else if recent.is_empty() {
                         println!("No decisions recorded yet.");
                     } else {
                         println!("Last {} decision(s):\n", recent.len());
-                        for d in recent {
+                        for (session, d) in recent {
                             println!("---");
                             println!("Timestamp: {}", d.timestamp);
+                            if let Some(label) = labels.get(&session) {
+                                println!(
+                                    "Session: {}",
+                                    sessions::display_name(&session, Some(label))
+                                );
+                            }
                             println!("Type: {:?}", d.decision_type);
+                            if let Some(confidence) = &d.confidence {
+                                println!("Confidence: {}", confidence);
+                            }
+                            if let Some(drift) = d.drift {
+                                println!("Drift: {}", if drift { "yes" } else { "no" });
+                            }
                             if let Some(trigger) = &d.trigger {
                                 println!("Trigger: {}", trigger);
                             }
-                            if let Some(ctx) = &d.context {
+                            let context = if full {
+                                d.context.clone()
+                            } else {
+                                d.short_context()
+                            };
+                            if let Some(ctx) = context {
                                 println!("Context: {}", ctx);
                             }
                         }
@@ -251,6 +826,57 @@ fn main() {
                 }
             }
         }
+        Commands::Explain { decision, json } => {
+            let superego_dir = Path::new(".superego");
+
+            let found = match decision::locate(superego_dir, &decision) {
+                Ok(found) => found,
+                Err(e) => {
+                    eprintln!("Error reading decisions: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let (session, path) = match found.as_slice() {
+                [] => {
+                    eprintln!(
+                        "No decision found for '{}'. Pass a decision file or a timestamp from 'sg history'.",
+                        decision
+                    );
+                    std::process::exit(1);
+                }
+                [one] => one,
+                several => {
+                    eprintln!(
+                        "'{}' matches several decisions; pass one of these files:",
+                        decision
+                    );
+                    for (_, path) in several {
+                        eprintln!("  {}", path.display());
+                    }
+                    std::process::exit(1);
+                }
+            };
+
+            let d = match decision::read_file(path) {
+                Ok(d) => d,
+                Err(e) => {
+                    eprintln!("Failed to read {}: {}", path.display(), e);
+                    std::process::exit(1);
+                }
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&d).unwrap_or_default());
+                return;
+            }
+            let session = session.as_ref().map(|id| {
+                sessions::display_name(
+                    id,
+                    sessions::labels(superego_dir).get(id).map(|l| l.as_str()),
+                )
+            });
+            println!("File: {}", path.display());
+            print!("{}", d.format_detail(session.as_deref()));
+        }
         Commands::HasFeedback => {
             let superego_dir = Path::new(".superego");
             let queue = feedback::FeedbackQueue::new(superego_dir);
@@ -276,7 +902,101 @@ fn main() {
                 }
             }
         }
-        Commands::Reset { clear_session: _ } => {
+        Commands::PeekFeedback => {
+            let superego_dir = Path::new(".superego");
+            let queue = feedback::FeedbackQueue::new(superego_dir);
+
+            match queue.peek() {
+                Some(content) => println!("{}", content),
+                None => println!("No pending feedback."),
+            }
+        }
+        Commands::Disable { minutes } => {
+            let superego_dir = Path::new(".superego");
+            if !superego_dir.exists() {
+                eprintln!("No .superego directory found. Run 'sg init' first.");
+                std::process::exit(1);
+            }
+
+            let until = match minutes {
+                Some(m) => match chrono::Duration::try_minutes(m)
+                    .filter(|d| *d > chrono::Duration::zero())
+                    .and_then(|d| chrono::Utc::now().checked_add_signed(d))
+                {
+                    Some(until) => Some(until),
+                    None => {
+                        eprintln!(
+                            "Invalid --minutes {}: expected a positive number of minutes",
+                            m
+                        );
+                        std::process::exit(1);
+                    }
+                },
+                None => None,
+            };
+            match state::StateManager::new(superego_dir).update(|s| s.disable(until)) {
+                Ok(_) => match until {
+                    Some(until) => println!(
+                        "Superego disabled until {} (run 'sg enable' to resume sooner)",
+                        until.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+                    ),
+                    None => println!("Superego disabled (run 'sg enable' to resume)"),
+                },
+                Err(e) => {
+                    eprintln!("Failed to update state: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Enable => {
+            let superego_dir = Path::new(".superego");
+            if !superego_dir.exists() {
+                eprintln!("No .superego directory found. Run 'sg init' first.");
+                std::process::exit(1);
+            }
+
+            let state_mgr = state::StateManager::new(superego_dir);
+            let was_disabled = state_mgr.load().map(|s| s.disabled).unwrap_or(false);
+            match state_mgr.update(|s| s.enable()) {
+                Ok(_) if was_disabled => println!("Superego enabled"),
+                Ok(_) => println!("Superego is already enabled"),
+                Err(e) => {
+                    eprintln!("Failed to update state: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Reset {
+            session: Some(id),
+            clear_session,
+        } => {
+            let superego_dir = Path::new(".superego");
+            if clear_session {
+                clear_evaluator_sessions(superego_dir, Some(&id));
+            }
+            match sessions::remove(superego_dir, &id) {
+                Ok(()) => println!("Removed .superego/sessions/{}/", id),
+                Err(sessions::SessionError::NotFound(_)) => {
+                    eprintln!(
+                        "No session '{}' in .superego/sessions/ (see 'sg sessions list')",
+                        id
+                    );
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    eprintln!("Failed to remove session {}: {}", id, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Reset {
+            session: None,
+            clear_session,
+        } => {
+            if clear_session {
+                clear_evaluator_sessions(Path::new(".superego"), None);
+            }
+
             // Remove .superego directory
             if Path::new(".superego").exists() {
                 if let Err(e) = std::fs::remove_dir_all(".superego") {
@@ -333,8 +1053,15 @@ fn main() {
         }
         Commands::EvaluateLlm {
             transcript_path,
+            stdin,
             session_id,
+            focus,
+            plain,
+            explain_decision,
+            max_feedback_length,
+            timeout_ms,
         } => {
+            let transcript_path = transcript_path.unwrap_or_else(|| "-".to_string());
             let transcript = Path::new(&transcript_path);
             let superego_dir = Path::new(".superego");
 
@@ -344,14 +1071,52 @@ fn main() {
                 std::process::exit(1);
             }
 
+            // Kill the Claude CLI and keep the read cursor if the hook is cancelled
+            signals::install();
+
+            // hook.log is appended by the plugin scripts - rotate it here
+            let log_max_bytes = config::Config::load(superego_dir).log_max_bytes;
+            let _ = logfile::rotate_if_needed(&superego_dir.join("hook.log"), log_max_bytes);
+
+            // The read cursor must not pass what stdin actually contained
+            let stdin_read_at = chrono::Utc::now();
+            let stdin_transcript = if stdin {
+                match transcript::read_transcript_from_reader(std::io::stdin().lock()) {
+                    Ok(entries) => Some(entries),
+                    Err(e) => {
+                        eprintln!("Failed to read transcript from stdin: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                None
+            };
+
             // Run LLM evaluation
-            match evaluate::evaluate_llm(transcript, superego_dir, session_id.as_deref()) {
+            let options = evaluate::EvaluateOptions {
+                focus,
+                plain,
+                explain_decision,
+                max_feedback_chars: max_feedback_length,
+                transcript_read_at: stdin_transcript.as_ref().map(|_| stdin_read_at),
+                transcript: stdin_transcript,
+                timeout_ms,
+                backend: None,
+            };
+            match evaluate::evaluate_llm(transcript, superego_dir, session_id.as_deref(), &options)
+            {
                 Ok(result) => {
                     // Output for hook/debugging
-                    println!(
-                        r#"{{"has_concerns": {}, "cost_usd": {:.6}}}"#,
-                        result.has_concerns, result.cost_usd
-                    );
+                    match result.second_opinion_cost_usd {
+                        Some(second) => println!(
+                            r#"{{"has_concerns": {}, "cost_usd": {:.6}, "second_opinion_cost_usd": {:.6}}}"#,
+                            result.has_concerns, result.cost_usd, second
+                        ),
+                        None => println!(
+                            r#"{{"has_concerns": {}, "cost_usd": {:.6}}}"#,
+                            result.has_concerns, result.cost_usd
+                        ),
+                    }
 
                     // Log feedback to stderr
                     if result.has_concerns {
@@ -360,31 +1125,60 @@ fn main() {
                         eprintln!("No concerns.");
                     }
                 }
+                Err(e) if e.interrupted().is_some() => {
+                    let signal = e.interrupted().unwrap_or_default();
+                    eprintln!("Evaluation interrupted (signal {})", signal);
+                    std::process::exit(signals::exit_code(signal));
+                }
                 Err(e) => {
                     eprintln!("Evaluation failed: {}", e);
                     std::process::exit(1);
                 }
             }
         }
-        Commands::Check => match hooks::check_and_update_hooks(Path::new(".")) {
-            Ok(result) => {
-                if result.updated.is_empty() {
-                    println!("Hooks up to date.");
-                } else {
-                    println!("Updated hooks: {}", result.updated.join(", "));
+        Commands::Doctor => {
+            let checks = doctor::run_checks(Path::new(".superego"));
+            print!("{}", doctor::format_report(&checks));
+            if doctor::has_failures(&checks) {
+                std::process::exit(1);
+            }
+        }
+        Commands::Check { check_updates } => {
+            match hooks::check_and_update_hooks(Path::new(".")) {
+                Ok(result) => {
+                    if result.updated.is_empty() {
+                        println!("Hooks up to date.");
+                    } else {
+                        println!("Updated hooks: {}", result.updated.join(", "));
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Failed to check hooks: {}", e);
+                    std::process::exit(1);
                 }
             }
-            Err(e) => {
-                eprintln!("Failed to check hooks: {}", e);
-                std::process::exit(1);
+
+            if check_updates {
+                match update::latest_version() {
+                    Ok(latest) if update::is_newer(&latest, update::CURRENT_VERSION) => {
+                        println!("{}", update::upgrade_hint(&latest));
+                    }
+                    Ok(_) => println!("sg {} is the latest release.", update::CURRENT_VERSION),
+                    Err(e) => diag::warn(&format!("could not check for updates: {}", e)),
+                }
             }
-        },
+        }
         Commands::Mode => {
             let superego_dir = Path::new(".superego");
             let cfg = config::Config::load(superego_dir);
             println!("{}", cfg.mode.as_str());
         }
-        Commands::Audit { json } => {
+        Commands::Audit {
+            json,
+            no_llm,
+            since,
+            until,
+        } => {
             let superego_dir = Path::new(".superego");
 
             if !superego_dir.exists() {
@@ -392,8 +1186,9 @@ fn main() {
                 std::process::exit(1);
             }
 
-            // Read all decisions across sessions
-            let decisions = match decision::read_all_sessions(superego_dir) {
+            // Read decisions across sessions, limited to --since/--until
+            let (start, end) = date_bounds(since, until);
+            let decisions = match decision::read_sessions_between(superego_dir, start, end) {
                 Ok(d) => d,
                 Err(e) => {
                     eprintln!("Failed to read decisions: {}", e);
@@ -403,18 +1198,26 @@ fn main() {
 
             if decisions.is_empty() {
                 if json {
-                    println!(
-                        r#"{{"stats":{{"total":0,"start_date":null,"end_date":null,"session_count":0}},"analysis":"No decisions recorded yet."}}"#
-                    );
+                    let empty = audit::AuditResult {
+                        stats: audit::calculate_stats(&[]),
+                        analysis: "No decisions recorded yet.".to_string(),
+                    };
+                    println!("{}", serde_json::to_string(&empty).unwrap_or_default());
+                } else if since.is_some() || until.is_some() {
+                    println!("No decisions in that date range.");
                 } else {
                     println!("No decisions recorded yet.");
                 }
                 return;
             }
 
-            // Run audit with LLM analysis
-            eprintln!("Analyzing {} decisions...", decisions.len());
-            match audit::run_audit(&decisions) {
+            // Run audit (with LLM analysis unless --no-llm)
+            if !no_llm {
+                eprintln!("Analyzing {} decisions...", decisions.len());
+            }
+            let labels = sessions::labels(superego_dir);
+            let tools = config::Config::load(superego_dir).superego_tools;
+            match audit::run_audit(&decisions, &labels, !no_llm, tools) {
                 Ok(result) => {
                     if json {
                         match serde_json::to_string_pretty(&result) {
@@ -439,8 +1242,34 @@ fn main() {
                             );
                         }
                         println!("Sessions: {}", result.stats.session_count);
-                        println!("\n--- Analysis ---\n");
-                        println!("{}", result.analysis);
+                        for s in &result.stats.per_session {
+                            println!(
+                                "  {}: {} decision(s), {} to {}",
+                                sessions::display_name(
+                                    &s.session_id,
+                                    labels.get(&s.session_id).map(|l| l.as_str())
+                                ),
+                                s.decision_count,
+                                s.first.format("%Y-%m-%d %H:%M"),
+                                s.last.format("%Y-%m-%d %H:%M")
+                            );
+                        }
+                        if result.stats.suppressed > 0 {
+                            println!(
+                                "Suppressed (below min_confidence): {}",
+                                result.stats.suppressed
+                            );
+                        }
+                        if result.stats.duplicates > 0 {
+                            println!(
+                                "Deduplicated (repeated recent feedback): {}",
+                                result.stats.duplicates
+                            );
+                        }
+                        if !no_llm {
+                            println!("\n--- Analysis ---\n");
+                            println!("{}", result.analysis);
+                        }
                     }
                 }
                 Err(e) => {
@@ -449,6 +1278,113 @@ fn main() {
                 }
             }
         }
+        Commands::Tail { lines } => {
+            let superego_dir = Path::new(".superego");
+
+            if !superego_dir.exists() {
+                eprintln!("No .superego directory found. Run 'sg init' first.");
+                std::process::exit(1);
+            }
+
+            // Lines are prefixed with their log's name when several exist
+            let paths: Vec<_> = logfile::EVAL_LOGS
+                .iter()
+                .map(|name| superego_dir.join(name))
+                .collect();
+            let existing = paths.iter().filter(|p| p.exists()).count();
+            let label = |path: &Path| {
+                if existing > 1 {
+                    format!(
+                        "[{}] ",
+                        path.file_name().unwrap_or_default().to_string_lossy()
+                    )
+                } else {
+                    String::new()
+                }
+            };
+            if existing == 0 {
+                eprintln!(
+                    "No evaluation logs yet; waiting for {}",
+                    logfile::EVAL_LOGS.join(", ")
+                );
+            }
+
+            for path in &paths {
+                for line in logfile::last_lines(path, lines).unwrap_or_default() {
+                    println!("{}{}", label(path), line);
+                }
+            }
+
+            // Liveness for `sg watch --status`; the file is removed when we stop on a signal
+            signals::install();
+            let heartbeat = heartbeat::Heartbeat::start(
+                &heartbeat::heartbeat_path(superego_dir),
+                heartbeat::DEFAULT_INTERVAL,
+            )
+            .inspect_err(|e| diag::warn(&format!("could not write heartbeat: {}", e)))
+            .ok();
+
+            let mut followers: Vec<_> = paths.iter().map(|p| logfile::Follower::new(p)).collect();
+            loop {
+                for (path, follower) in paths.iter().zip(followers.iter_mut()) {
+                    for line in follower.poll().unwrap_or_default() {
+                        println!("{}{}", label(path), line);
+                    }
+                }
+                if let Some(signal) = signals::interrupted() {
+                    drop(heartbeat);
+                    std::process::exit(signals::exit_code(signal));
+                }
+                std::thread::sleep(std::time::Duration::from_millis(500));
+            }
+        }
+        Commands::Costs { json } => {
+            let superego_dir = Path::new(".superego");
+
+            let records = match costs::read_all(superego_dir) {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("Failed to read {}: {}", costs::COSTS_FILE, e);
+                    std::process::exit(1);
+                }
+            };
+            let summary = costs::summarize(&records);
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&summary).unwrap_or_default()
+                );
+            } else if summary.evaluations == 0 {
+                println!("No costs recorded yet.");
+            } else {
+                let tokens: u64 = records.iter().filter_map(|r| r.tokens).sum();
+                println!(
+                    "Total: ${:.4} over {} evaluation(s)",
+                    summary.total_usd, summary.evaluations
+                );
+                if tokens > 0 {
+                    println!("Codex tokens (not priced): {}", tokens);
+                }
+                println!("\nBy day:");
+                for day in &summary.by_day {
+                    println!("  {}  ${:.4}  ({})", day.key, day.cost_usd, day.evaluations);
+                }
+                let labels = sessions::labels(superego_dir);
+                println!("\nBy session:");
+                for session in &summary.by_session {
+                    println!(
+                        "  ${:.4}  ({})  {}",
+                        session.cost_usd,
+                        session.evaluations,
+                        sessions::display_name(
+                            &session.key,
+                            labels.get(&session.key).map(|l| l.as_str())
+                        )
+                    );
+                }
+            }
+        }
         Commands::Migrate => {
             let base_dir = Path::new(".");
             match migrate::migrate(base_dir) {
@@ -475,83 +1411,106 @@ fn main() {
                 std::process::exit(1);
             }
         }
-        Commands::EvaluateCodex => {
+        Commands::Serve => {
             let superego_dir = Path::new(".superego");
+            let backend = serve::SgBackend::new(superego_dir);
+            let server = serve::Server::new(superego_dir, &backend);
+            if let Err(e) = server.run(std::io::stdin().lock(), std::io::stdout().lock()) {
+                eprintln!("serve: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Watch { status } => {
+            if !status {
+                eprintln!("Nothing to do: pass --status (watchers are started by 'sg tail')");
+                std::process::exit(2);
+            }
+            let watch_status =
+                heartbeat::status(Path::new(".superego"), heartbeat::DEFAULT_INTERVAL);
+            println!("{}", heartbeat::describe(&watch_status));
+            if matches!(watch_status, heartbeat::WatchStatus::Stale { .. }) {
+                std::process::exit(1);
+            }
+        }
+        Commands::ImportCodex {
+            session_file,
+            output,
+        } => {
+            let entries = match transcript::codex::read_codex_transcript(&session_file) {
+                Ok(e) => e,
+                Err(e) => {
+                    eprintln!("Failed to read Codex session: {}", e);
+                    std::process::exit(1);
+                }
+            };
 
-            // Log to .superego/codex.log
-            let log = |msg: &str| {
+            let fallback_id = session_file
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("codex");
+            let converted = transcript::codex::to_transcript_entries(&entries, fallback_id);
+
+            if let Err(e) = transcript::codex::write_transcript(&converted, &output) {
+                eprintln!("Failed to write transcript: {}", e);
+                std::process::exit(1);
+            }
+
+            println!(
+                "Converted {} entries to {}",
+                converted.len(),
+                output.display()
+            );
+        }
+        Commands::EvaluateCodex { timeout_ms } => {
+            let superego_dir = Path::new(".superego");
+
+            // Log to .superego/codex.log (rotated at log_max_bytes)
+            let cfg = config::Config::load(superego_dir);
+            let log_max_bytes = cfg.log_max_bytes;
+            // AIDEV-NOTE: `event` and `fields` only show up with log_format: json
+            let log_event = |event: &str, msg: &str, fields: serde_json::Value| {
+                diag::debug(msg);
                 let log_path = superego_dir.join("codex.log");
-                let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ");
-                let line = format!("{} {}\n", timestamp, msg);
-                let _ = std::fs::OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(&log_path)
-                    .and_then(|mut f| std::io::Write::write_all(&mut f, line.as_bytes()));
+                let line = logfile::format_line(cfg.log_format, event, msg, fields);
+                let _ = logfile::append(&log_path, &line, log_max_bytes);
             };
 
             // Recursion prevention - skip if this is superego's own Codex call
             if std::env::var("SUPEREGO_DISABLED").as_deref() == Ok("1") {
-                log("SKIP: SUPEREGO_DISABLED=1");
+                log_event(
+                    "skip",
+                    "SKIP: SUPEREGO_DISABLED=1",
+                    serde_json::json!({"reason": "recursion_prevention"}),
+                );
                 println!(
                     r#"{{"has_concerns": false, "skipped": true, "reason": "recursion_prevention"}}"#
                 );
                 return;
             }
 
-            log("evaluate-codex started");
+            log_event("start", "evaluate-codex started", serde_json::json!({}));
 
             // Check if superego is initialized
             if !superego_dir.exists() {
-                log("ERROR: .superego not initialized");
+                log_event(
+                    "error",
+                    "ERROR: .superego not initialized",
+                    serde_json::json!({}),
+                );
                 eprintln!("Superego not initialized. Run 'sg init' first.");
                 std::process::exit(1);
             }
 
-            // Check for lock file to prevent concurrent evals
-            let lock_path = superego_dir.join("codex.lock");
-            // Match codex exec timeout (3 min) - locks older than this are from crashed processes
-            let lock_timeout = std::time::Duration::from_secs(180);
-
-            if lock_path.exists() {
-                if let Ok(meta) = lock_path.metadata() {
-                    if let Ok(modified) = meta.modified() {
-                        if modified.elapsed().unwrap_or(lock_timeout) < lock_timeout {
-                            log("SKIP: Another evaluation in progress (lock file exists)");
-                            eprintln!("Another evaluation in progress. Skipping.");
-                            println!(r#"{{"has_concerns": false, "skipped": true}}"#);
-                            return;
-                        }
-                    }
-                }
-                // Lock is >3min old - probably a crash, safe to remove
-                log("Removing stale lock (>3min old)");
-                let _ = std::fs::remove_file(&lock_path);
-            }
-
-            // Create lock file
-            if let Err(e) = std::fs::write(&lock_path, chrono::Utc::now().to_rfc3339()) {
-                log(&format!("WARN: Could not create lock file: {}", e));
-            }
-
-            // Ensure lock is removed on exit (scope guard)
-            struct LockGuard<'a>(&'a Path);
-            impl<'a> Drop for LockGuard<'a> {
-                fn drop(&mut self) {
-                    let _ = std::fs::remove_file(self.0);
-                }
-            }
-            let _lock_guard = LockGuard(&lock_path);
-
             // Find the most recent Codex session
-            let session_path = match transcript::codex::find_latest_codex_session() {
-                Some(p) => p,
-                None => {
-                    log("ERROR: No Codex sessions found");
-                    eprintln!("No Codex sessions found in ~/.codex/sessions/");
-                    eprintln!("Make sure you have an active Codex session.");
-                    std::process::exit(1);
-                }
+            let Some(session_path) = transcript::codex::find_latest_codex_session() else {
+                log_event(
+                    "error",
+                    "ERROR: No Codex sessions found",
+                    serde_json::json!({}),
+                );
+                eprintln!("No Codex sessions found in ~/.codex/sessions/");
+                eprintln!("Make sure you have an active Codex session.");
+                std::process::exit(1);
             };
 
             // Log just the filename, not full path
@@ -559,93 +1518,106 @@ fn main() {
                 .file_name()
                 .map(|s| s.to_string_lossy().to_string())
                 .unwrap_or_else(|| session_path.display().to_string());
-            log(&format!("Session: {}", session_name));
+            log_event(
+                "session",
+                &format!("Session: {}", session_name),
+                serde_json::json!({"session": session_name}),
+            );
             eprintln!("Evaluating: {}", session_path.display());
 
-            // Read and format transcript
-            let entries = match transcript::codex::read_codex_transcript(&session_path) {
-                Ok(e) => e,
+            // State (lock, saved Codex thread) lives under sessions/<Codex session id>
+            let session_id = match transcript::codex::read_codex_transcript(&session_path) {
+                Ok(entries) => transcript::codex::codex_session_id(&entries),
                 Err(e) => {
-                    log(&format!("ERROR reading transcript: {}", e));
+                    log_event(
+                        "error",
+                        &format!("ERROR reading transcript: {}", e),
+                        serde_json::json!({"session": session_name}),
+                    );
                     eprintln!("Failed to read transcript: {}", e);
                     std::process::exit(1);
                 }
-            };
-
-            if entries.is_empty() {
-                log("No entries in transcript");
-                println!(r#"{{"has_concerns": false, "tokens": 0}}"#);
-                eprintln!("No concerns.");
-                return;
             }
-
-            let context = transcript::codex::format_codex_context(&entries);
-            let context_kb = context.len() / 1024;
-            log(&format!(
-                "Context: {} entries, {}KB",
-                entries.len(),
-                context_kb
-            ));
-
-            // Load system prompt (respect config-based prompt selection)
-            let prompt_path = superego_dir.join("prompt.md");
-            let system_prompt = if prompt_path.exists() {
-                std::fs::read_to_string(&prompt_path).unwrap_or_else(|_| {
-                    // Fallback to embedded prompt based on config
-                    let prompt_type = prompts::get_current_base(superego_dir)
-                        .unwrap_or(prompts::PromptType::Code);
-                    prompt_type.content().to_string()
-                })
-            } else {
-                // No prompt.md - use embedded prompt based on config
-                let prompt_type =
-                    prompts::get_current_base(superego_dir).unwrap_or(prompts::PromptType::Code);
-                prompt_type.content().to_string()
+            .unwrap_or_else(|| {
+                session_path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_else(|| session_name.clone())
+            });
+
+            // Kill the Codex CLI and keep the read cursor if the hook is cancelled
+            signals::install();
+
+            // AIDEV-NOTE: Thin alias - the evaluation itself is evaluate_llm with
+            // the Codex backend, whatever config `backend` says.
+            let options = evaluate::EvaluateOptions {
+                timeout_ms,
+                backend: Some(config::Backend::Codex),
+                ..Default::default()
             };
-
-            // Get ba task context (only include if there IS a task - for drift detection)
-            let ba_context = match ba::evaluate() {
-                Ok(eval) => {
-                    if let Some(task) = eval.current_task {
-                        format!("CURRENT TASK: {} - {}\n\n", task.id, task.title)
-                    } else {
-                        String::new() // No task = no context (don't prime workflow concerns)
+            match evaluate::evaluate_llm(&session_path, superego_dir, Some(&session_id), &options) {
+                Ok(result) => {
+                    if let Some(reason) = result.skip_reason {
+                        log_event(
+                            "skip",
+                            &format!("SKIP: {}", result.feedback),
+                            serde_json::json!({"session": session_name, "reason": reason}),
+                        );
+                        eprintln!("{}", result.feedback);
+                        println!(
+                            r#"{{"has_concerns": false, "skipped": true, "reason": "{}"}}"#,
+                            reason
+                        );
+                        return;
                     }
-                }
-                Err(_) => String::new(),
-            };
-
-            let message = format!(
-                "Review the following Codex conversation and provide feedback.\n\n\
-                {}--- CONVERSATION ---\n{}\n--- END CONVERSATION ---",
-                ba_context, context
-            );
-
-            log("Calling Codex LLM...");
-            let start_time = std::time::Instant::now();
 
-            // Use Codex LLM (not Claude) for evaluation
-            match codex_llm::invoke(&system_prompt, &message, None) {
-                Ok(response) => {
-                    let elapsed = start_time.elapsed().as_secs_f32();
-                    log(&format!(
-                        "Response in {:.1}s, tokens={}",
-                        elapsed, response.total_tokens
-                    ));
-
-                    // Parse decision from response
-                    let has_concerns = !response.result.contains("DECISION: ALLOW");
+                    let context_kb = result.context_bytes / 1024;
+                    log_event(
+                        "context",
+                        &format!(
+                            "Context: {} entries, {}KB",
+                            result.context_entries, context_kb
+                        ),
+                        serde_json::json!({
+                            "session": session_name,
+                            "entries": result.context_entries,
+                            "context_kb": context_kb,
+                        }),
+                    );
+                    let elapsed = result.elapsed_secs.unwrap_or_default();
+                    log_event(
+                        "response",
+                        &format!(
+                            "Response in {:.1}s, tokens={}",
+                            elapsed,
+                            result.tokens.unwrap_or(0)
+                        ),
+                        serde_json::json!({
+                            "session": session_name,
+                            "elapsed_secs": elapsed,
+                            "tokens": result.tokens,
+                        }),
+                    );
 
                     println!(
                         r#"{{"has_concerns": {}, "tokens": {}}}"#,
-                        has_concerns, response.total_tokens
+                        result.has_concerns,
+                        result.tokens.unwrap_or(0)
                     );
 
-                    if has_concerns {
-                        log("BLOCK - concerns found");
-                        eprintln!("Feedback:\n{}", response.result);
+                    let decision_fields = serde_json::json!({
+                        "session": session_name,
+                        "decision": if result.has_concerns { "block" } else { "allow" },
+                        "tokens": result.tokens,
+                        "elapsed_secs": elapsed,
+                        "entries": result.context_entries,
+                        "context_kb": context_kb,
+                    });
+                    if result.has_concerns {
+                        log_event("decision", "BLOCK - concerns found", decision_fields);
+                        eprintln!("Feedback:\n{}", result.feedback);
                     } else {
-                        log("ALLOW - no concerns");
+                        log_event("decision", "ALLOW - no concerns", decision_fields);
                         eprintln!("No concerns.");
                     }
 
@@ -659,32 +1631,207 @@ fn main() {
                             .spawn();
                     }
                 }
-                Err(codex_llm::CodexLlmError::RateLimited { resets_in_seconds }) => {
-                    let msg = if let Some(secs) = resets_in_seconds {
-                        format!("SKIP: Rate limited (resets in {} min)", secs / 60)
-                    } else {
-                        "SKIP: Rate limited".to_string()
-                    };
-                    log(&msg);
-                    eprintln!("{}", msg);
-                    println!(
-                        r#"{{"has_concerns": false, "skipped": true, "reason": "rate_limited"}}"#
+                Err(e) if e.interrupted().is_some() => {
+                    let signal = e.interrupted().unwrap_or_default();
+                    log_event(
+                        "interrupted",
+                        &format!("Interrupted (signal {})", signal),
+                        serde_json::json!({"session": session_name, "signal": signal}),
                     );
-                    // Don't exit with error - this is expected behavior
+                    eprintln!("Evaluation interrupted (signal {})", signal);
+                    std::process::exit(signals::exit_code(signal));
                 }
                 Err(e) => {
-                    log(&format!("ERROR: {}", e));
+                    log_event(
+                        "error",
+                        &format!("ERROR: {}", e),
+                        serde_json::json!({"session": session_name}),
+                    );
                     eprintln!("Evaluation failed: {}", e);
                     std::process::exit(1);
                 }
             }
         }
+        Commands::EvaluateGemini => evaluate_latest_session(&SessionSource {
+            name: "gemini",
+            agent: "Gemini",
+            find_latest: transcript::gemini::find_latest_gemini_session,
+            not_found: "No Gemini sessions found in ~/.gemini/tmp/*/chats/\n\
+                        Make sure you have an active Gemini CLI session.",
+        }),
+        Commands::EvaluateAider => evaluate_latest_session(&SessionSource {
+            name: "aider",
+            agent: "aider",
+            find_latest: transcript::aider::find_latest_aider_session,
+            not_found: "No .aider.chat.history.md in the current directory\n\
+                        Run this from the directory aider was started in.",
+        }),
+        Commands::Sessions { action } => {
+            let superego_dir = Path::new(".superego");
+
+            if !superego_dir.exists() {
+                eprintln!("No .superego directory found. Run 'sg init' first.");
+                std::process::exit(1);
+            }
+
+            match action.unwrap_or(SessionsAction::List { json: false }) {
+                SessionsAction::List { json } => match sessions::list(superego_dir) {
+                    Ok(list) if json => match serde_json::to_string_pretty(&list) {
+                        Ok(out) => println!("{}", out),
+                        Err(e) => {
+                            eprintln!("Failed to serialize sessions: {}", e);
+                            std::process::exit(1);
+                        }
+                    },
+                    Ok(list) if list.is_empty() => println!("No sessions found."),
+                    Ok(list) => print!("{}", sessions::format_list(&list)),
+                    Err(e) => {
+                        eprintln!("Failed to list sessions: {}", e);
+                        std::process::exit(1);
+                    }
+                },
+                SessionsAction::Label { id, name } => {
+                    match sessions::set_label(superego_dir, &id, name.as_deref()) {
+                        Ok(()) => match name {
+                            Some(name) => println!("Labeled session {} as '{}'", id, name),
+                            None => println!("Cleared label for session {}", id),
+                        },
+                        Err(e) => {
+                            eprintln!("Failed to label session: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+            }
+        }
+        Commands::Prune {
+            older_than,
+            keep,
+            dry_run,
+        } => {
+            let superego_dir = Path::new(".superego");
+
+            if !superego_dir.exists() {
+                eprintln!("No .superego directory found. Run 'sg init' first.");
+                std::process::exit(1);
+            }
+
+            let list = match sessions::list(superego_dir) {
+                Ok(list) => list,
+                Err(e) => {
+                    eprintln!("Failed to list sessions: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let pruned = sessions::prune_candidates(
+                &list,
+                chrono::Utc::now(),
+                older_than.map(i64::from),
+                keep,
+            );
+
+            let mut failed = false;
+            for s in &pruned {
+                let modified = s
+                    .modified
+                    .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|| "unknown".to_string());
+                let name = sessions::display_name(&s.id, s.label.as_deref());
+                if dry_run {
+                    println!("Would remove {} (last modified {})", name, modified);
+                    continue;
+                }
+                match sessions::remove(superego_dir, &s.id) {
+                    Ok(()) => println!("Removed {} (last modified {})", name, modified),
+                    Err(e) => {
+                        eprintln!("Failed to remove session {}: {}", s.id, e);
+                        failed = true;
+                    }
+                }
+            }
+
+            let verb = if dry_run { "would be pruned" } else { "pruned" };
+            println!("{} of {} session(s) {}", pruned.len(), list.len(), verb);
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        Commands::EvaluateDir {
+            dir,
+            since,
+            json,
+            concurrency,
+        } => {
+            let superego_dir = Path::new(".superego");
+
+            let since = match since.map(|s| batch::parse_since(&s, chrono::Utc::now())) {
+                Some(Ok(ts)) => Some(ts),
+                Some(Err(e)) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+                None => None,
+            };
+
+            let report = match batch::evaluate_dir(
+                superego_dir,
+                Path::new(&dir),
+                since,
+                concurrency,
+                &batch::llm_backend(&config::Config::load(superego_dir)),
+            ) {
+                Ok(report) => report,
+                Err(e) => {
+                    eprintln!("Batch evaluation failed: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&report).unwrap_or_default()
+                );
+            } else {
+                for file in &report.files {
+                    let status = if file.error.is_some() {
+                        "ERROR"
+                    } else if file.has_concerns {
+                        "BLOCK"
+                    } else {
+                        "ALLOW"
+                    };
+                    println!("[{}] {}", status, file.path);
+                    if let Some(err) = &file.error {
+                        println!("  {}", err);
+                    } else if file.has_concerns {
+                        for line in file.feedback.lines() {
+                            println!("  {}", line);
+                        }
+                    }
+                }
+                println!(
+                    "\n{} file(s), {} with concerns, {} error(s), cost ${:.4}",
+                    report.total_files,
+                    report.files_with_concerns,
+                    report.errors,
+                    report.total_cost_usd
+                );
+            }
+
+            if report.errors > 0 {
+                std::process::exit(1);
+            }
+        }
         Commands::Retro {
             session,
+            curated: _,
             full,
+            format,
             output,
             open,
             push_oh,
+            dry_run,
         } => {
             let superego_dir = Path::new(".superego");
 
@@ -693,16 +1840,29 @@ fn main() {
                 std::process::exit(1);
             }
 
+            let format = match retro::RetroFormat::from_arg(&format) {
+                Some(f) => f,
+                None => {
+                    eprintln!("Unknown format: {}", format);
+                    eprintln!("Available: html, markdown");
+                    std::process::exit(1);
+                }
+            };
+
             // Default is curated mode; --full disables curation
             let curated = !full;
-            match retro::run(
-                superego_dir,
-                session.as_deref(),
+            let output = output
+                .unwrap_or_else(|| superego_dir.join(format!("retro.{}", format.extension())));
+            let options = retro::RetroOptions {
+                session_id: session,
                 curated,
-                &output,
+                format,
+                output,
                 open,
                 push_oh,
-            ) {
+                oh_dry_run: dry_run,
+            };
+            match retro::run(superego_dir, &options) {
                 Ok(()) => {}
                 Err(e) => {
                     eprintln!("Retro failed: {}", e);
@@ -710,6 +1870,65 @@ fn main() {
                 }
             }
         }
+        Commands::Config { action } => {
+            let superego_dir = Path::new(".superego");
+
+            if !superego_dir.exists() {
+                eprintln!("No .superego directory found. Run 'sg init' first.");
+                std::process::exit(1);
+            }
+
+            let result = match action {
+                ConfigAction::Get { key } => {
+                    config::Config::load(superego_dir)
+                        .value(&key)
+                        .map(|value| match value {
+                            Some(v) => println!("{}", v),
+                            None => eprintln!("{} is not set", key),
+                        })
+                }
+                ConfigAction::Set { key, value } => config::set_value(superego_dir, &key, &value)
+                    .map(|()| {
+                        eprintln!("Set {} in .superego/config.yaml", key);
+                    }),
+                ConfigAction::Validate => {
+                    match std::fs::read_to_string(superego_dir.join("config.yaml")) {
+                        Ok(content) => {
+                            let issues = config::validate(&content);
+                            for issue in &issues {
+                                println!("{}", issue);
+                            }
+                            let errors = issues
+                                .iter()
+                                .filter(|i| i.severity == config::IssueSeverity::Error)
+                                .count();
+                            if issues.is_empty() {
+                                println!("config.yaml is valid.");
+                            } else {
+                                println!(
+                                    "{} error(s), {} warning(s)",
+                                    errors,
+                                    issues.len() - errors
+                                );
+                            }
+                            if errors > 0 {
+                                std::process::exit(1);
+                            }
+                            Ok(())
+                        }
+                        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                            println!("No config.yaml; using defaults.");
+                            Ok(())
+                        }
+                        Err(e) => Err(e.into()),
+                    }
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
         Commands::Prompt { action } => {
             let superego_dir = Path::new(".superego");
 
@@ -734,7 +1953,7 @@ fn main() {
                         Some(pt) => pt,
                         None => {
                             eprintln!("Unknown prompt: {}", name);
-                            eprintln!("Available: code, writing");
+                            eprintln!("Available: {}", prompts::PromptType::available_names());
                             std::process::exit(1);
                         }
                     };
@@ -789,6 +2008,20 @@ fn main() {
                                     info.available_backups.iter().map(|pt| pt.name()).collect();
                                 println!("Backups available: {}", backup_names.join(", "));
                             }
+
+                            let layers = prompts::prompt_layers(superego_dir).unwrap_or_default();
+                            if !layers.is_empty() {
+                                let layer_names: Vec<_> = layers
+                                    .iter()
+                                    .filter_map(|p| p.file_name())
+                                    .map(|n| n.to_string_lossy())
+                                    .collect();
+                                println!(
+                                    "Layers ({}/): {}",
+                                    prompts::LAYERS_DIR,
+                                    layer_names.join(", ")
+                                );
+                            }
                         }
                         Err(e) => {
                             eprintln!("Failed to get prompt info: {}", e);
@@ -796,59 +2029,31 @@ fn main() {
                         }
                     }
                 }
-            }
-        }
-        Commands::Review { target } => {
-            let superego_dir = Path::new(".superego");
-
-            if !superego_dir.exists() {
-                eprintln!("No .superego directory found. Run 'sg init' first.");
-                std::process::exit(1);
-            }
-
-            let target = review::ReviewTarget::from_arg(target.as_deref());
-
-            eprintln!("Reviewing...");
-
-            match review::review(superego_dir, target) {
-                Ok(result) => {
-                    println!("\n--- Review: {} ---\n", result.target_description);
-                    println!("{}", result.feedback);
-                }
-                Err(review::ReviewError::NoDiff(msg)) => {
-                    println!("Nothing to review: {}", msg);
-                }
-                Err(e) => {
-                    eprintln!("Review failed: {}", e);
-                    std::process::exit(1);
-                }
-            }
-        }
-        Commands::ReviewCodex { target } => {
-            let superego_dir = Path::new(".superego");
-
-            if !superego_dir.exists() {
-                eprintln!("No .superego directory found. Run 'sg init' first.");
-                std::process::exit(1);
-            }
-
-            let target = review::ReviewTarget::from_arg(target.as_deref());
-
-            eprintln!("Reviewing (Codex)...");
+                PromptAction::Diff { name } => {
+                    let against = match name {
+                        Some(name) => match prompts::PromptType::from_name(&name) {
+                            Some(pt) => Some(pt),
+                            None => {
+                                eprintln!("Unknown prompt: {}", name);
+                                eprintln!("Available: {}", prompts::PromptType::available_names());
+                                std::process::exit(1);
+                            }
+                        },
+                        None => None,
+                    };
 
-            match review::review_codex(superego_dir, target) {
-                Ok(result) => {
-                    println!("\n--- Review: {} ---\n", result.target_description);
-                    println!("{}", result.feedback);
-                }
-                Err(review::ReviewError::NoDiff(msg)) => {
-                    println!("Nothing to review: {}", msg);
-                }
-                Err(e) => {
-                    eprintln!("Review failed: {}", e);
-                    std::process::exit(1);
+                    match prompts::diff(superego_dir, against) {
+                        Ok(diff) if diff.is_empty() => println!("No local modifications."),
+                        Ok(diff) => print!("{}", diff),
+                        Err(e) => {
+                            eprintln!("Failed to diff prompt: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
                 }
             }
         }
+        Commands::Review(args) => run_review(args, None),
+        Commands::ReviewCodex(args) => run_review(args, Some(config::Backend::Codex)),
     }
 }
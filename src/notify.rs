@@ -0,0 +1,125 @@
+//! Webhook notification when feedback is queued
+//!
+//! The queue is only read when the agent's next hook fires, which is easy to
+//! miss during long unattended runs. With `notify_webhook_url` set, each queued
+//! concern is also POSTed as JSON (Slack/ntfy/custom relay).
+//! AIDEV-NOTE: Best-effort - a failed POST is a stderr warning, never an
+//! evaluation failure. The short timeout keeps a dead endpoint from stalling hooks.
+
+use std::time::Duration;
+
+use attohttpc::ProxySettings;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Timeout for the webhook POST
+const TIMEOUT_SECS: u64 = 5;
+
+/// Error type for webhook delivery
+#[derive(Debug)]
+pub enum NotifyError {
+    RequestFailed(String),
+    HttpError { status: u16 },
+}
+
+impl std::fmt::Display for NotifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotifyError::RequestFailed(msg) => write!(f, "Webhook request failed: {}", msg),
+            NotifyError::HttpError { status } => write!(f, "Webhook returned HTTP {}", status),
+        }
+    }
+}
+
+impl std::error::Error for NotifyError {}
+
+/// JSON body POSTed to the webhook
+#[derive(Debug, Serialize)]
+pub struct FeedbackNotification<'a> {
+    /// Always "feedback_queued" (room for other events later)
+    pub event: &'static str,
+    /// Agent session the feedback was queued for (None = unnamespaced)
+    pub session_id: Option<&'a str>,
+    pub confidence: Option<String>,
+    /// Full feedback text (not truncated by max_feedback_chars)
+    pub feedback: &'a str,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl<'a> FeedbackNotification<'a> {
+    pub fn new(
+        session_id: Option<&'a str>,
+        confidence: Option<String>,
+        feedback: &'a str,
+        timestamp: DateTime<Utc>,
+    ) -> Self {
+        FeedbackNotification {
+            event: "feedback_queued",
+            session_id,
+            confidence,
+            feedback,
+            timestamp,
+        }
+    }
+}
+
+/// POST a notification to the webhook
+pub fn send_webhook(url: &str, notification: &FeedbackNotification) -> Result<(), NotifyError> {
+    let response = attohttpc::post(url)
+        .timeout(Duration::from_secs(TIMEOUT_SECS))
+        .proxy_settings(ProxySettings::from_env())
+        .json(notification)
+        .map_err(|e| NotifyError::RequestFailed(e.to_string()))?
+        .send()
+        .map_err(|e| NotifyError::RequestFailed(e.to_string()))?;
+
+    if !response.is_success() {
+        return Err(NotifyError::HttpError {
+            status: response.status().as_u16(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_notification_json() {
+        let timestamp = DateTime::parse_from_rfc3339("2025-01-15T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let notification =
+            FeedbackNotification::new(Some("abc-123"), Some("HIGH".to_string()), "Stop", timestamp);
+        let json: serde_json::Value = serde_json::to_value(&notification).unwrap();
+        assert_eq!(json["event"], "feedback_queued");
+        assert_eq!(json["session_id"], "abc-123");
+        assert_eq!(json["confidence"], "HIGH");
+        assert_eq!(json["feedback"], "Stop");
+        assert_eq!(json["timestamp"], "2025-01-15T10:00:00Z");
+    }
+
+    #[test]
+    fn test_send_webhook_reports_http_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let n = stream.read(&mut buf).unwrap();
+            stream
+                .write_all(b"HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_string()
+        });
+
+        let notification = FeedbackNotification::new(None, None, "Stop", Utc::now());
+        let err = send_webhook(&url, &notification).unwrap_err();
+        assert!(matches!(err, NotifyError::HttpError { status: 500 }));
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /hook"));
+    }
+}
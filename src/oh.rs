@@ -7,18 +7,36 @@
 //! superego works exactly as before. The integration enables higher-level
 //! coordination by connecting metacognitive feedback to strategic context.
 
+use attohttpc::{ProxySettings, RequestBuilder};
+use chrono::{DateTime, Utc};
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::CertificateDer;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::diag;
 
 /// OH API configuration from environment
 #[derive(Debug, Clone)]
 pub struct OhConfig {
     pub api_url: String,
     pub api_key: String,
+    /// PEM bundle of extra root certificates to trust (corporate TLS proxies)
+    pub ca_cert: Option<PathBuf>,
+    /// Timeout for every request; None = per-request defaults
+    pub timeout_secs: Option<u64>,
 }
 
+/// Default request timeout
+const DEFAULT_TIMEOUT_SECS: u64 = 5;
+
+/// Default timeout for the (larger) retrospective POST
+const RETRO_TIMEOUT_SECS: u64 = 10;
+
 impl OhConfig {
     /// Try to load configuration from environment variables
     /// Returns None if OH_API_KEY is not set (OH_API_URL has default)
@@ -26,7 +44,12 @@ impl OhConfig {
         let api_key = env::var("OH_API_KEY").ok()?;
         let api_url =
             env::var("OH_API_URL").unwrap_or_else(|_| "https://app.openhorizons.me".to_string());
-        Some(OhConfig { api_url, api_key })
+        Some(OhConfig {
+            api_url,
+            api_key,
+            ca_cert: env_ca_cert(),
+            timeout_secs: None,
+        })
     }
 
     /// Try to load configuration from global config file
@@ -52,45 +75,84 @@ impl OhConfig {
             .map(|s| s.to_string())
             .unwrap_or_else(|| "https://app.openhorizons.me".to_string());
 
-        Some(OhConfig { api_url, api_key })
+        Some(OhConfig {
+            api_url,
+            api_key,
+            ca_cert: env_ca_cert(),
+            timeout_secs: None,
+        })
     }
 
     /// Try to load configuration from .superego/config.yaml
     /// Priority: env vars > project config > global config
     pub fn from_config(superego_dir: &Path) -> Option<Self> {
-        // First, check env vars
-        if let Some(config) = Self::from_env() {
-            return Some(config);
-        }
+        let config = Config::load(superego_dir);
+
+        // First env vars, then project config.yaml, then the global config
+        // (~/.config/openhorizons/config.json)
+        let found = Self::from_env()
+            .or_else(|| {
+                config.oh_api_key.clone().map(|api_key| OhConfig {
+                    api_url: config
+                        .oh_api_url
+                        .clone()
+                        .unwrap_or_else(|| "https://app.openhorizons.me".to_string()),
+                    api_key,
+                    ca_cert: env_ca_cert(),
+                    timeout_secs: None,
+                })
+            })
+            .or_else(Self::from_global_config)?;
+
+        Some(found.with_project_settings(&config))
+    }
 
-        // Then check project config.yaml
-        let config_path = superego_dir.join("config.yaml");
-        if let Ok(content) = fs::read_to_string(&config_path) {
-            // Parse oh_api_key and oh_api_url from config
-            if let Some(api_key) = parse_config_value(&content, "oh_api_key") {
-                let api_url = parse_config_value(&content, "oh_api_url")
-                    .unwrap_or_else(|| "https://app.openhorizons.me".to_string());
-                return Some(OhConfig { api_url, api_key });
-            }
+    /// Apply `oh_timeout_seconds`, and `oh_ca_cert` when OH_CA_CERT isn't set,
+    /// from config.yaml
+    pub fn with_project_settings(mut self, config: &Config) -> Self {
+        if self.ca_cert.is_none() {
+            self.ca_cert = config.oh_ca_cert.clone();
         }
+        self.timeout_secs = config.oh_timeout_seconds;
+        self
+    }
+}
 
-        // Finally, check global config (~/.config/openhorizons/config.json)
-        Self::from_global_config()
+/// CA bundle path from the OH_CA_CERT environment variable
+fn env_ca_cert() -> Option<PathBuf> {
+    env::var("OH_CA_CERT")
+        .ok()
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Load every certificate in a PEM bundle
+fn load_ca_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, OhError> {
+    let invalid = |msg: String| OhError::InvalidCaCert(format!("{}: {}", path.display(), msg));
+    let pem = fs::read(path).map_err(|e| invalid(e.to_string()))?;
+    let certs = CertificateDer::pem_slice_iter(&pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| invalid(format!("{:?}", e)))?;
+    if certs.is_empty() {
+        return Err(invalid("no PEM certificates found".to_string()));
     }
+    Ok(certs)
 }
 
-/// Parse a string value from config file content
-fn parse_config_value(content: &str, key: &str) -> Option<String> {
-    for line in content.lines() {
-        let line = line.trim();
-        if let Some(value) = line.strip_prefix(key).and_then(|s| s.strip_prefix(':')) {
-            let value = value.trim().trim_matches('"').trim_matches('\'');
-            if !value.is_empty() {
-                return Some(value.to_string());
-            }
+/// Classify a failed send: couldn't reach the server vs. anything else
+/// AIDEV-NOTE: Connection, proxy and TLS failures get their own variant so a
+/// proxy problem doesn't read like a bad API key.
+fn send_error(url: &str, e: attohttpc::Error) -> OhError {
+    match e.kind() {
+        attohttpc::ErrorKind::Io(_)
+        | attohttpc::ErrorKind::ConnectError { .. }
+        | attohttpc::ErrorKind::ConnectNotSupported
+        | attohttpc::ErrorKind::Tls(_)
+        | attohttpc::ErrorKind::InvalidDNSName(_) => {
+            OhError::ConnectionFailed(format!("{}: {}", url, e))
         }
+        _ => OhError::RequestFailed(e.to_string()),
     }
-    None
 }
 
 /// Error type for OH operations
@@ -98,6 +160,10 @@ fn parse_config_value(content: &str, key: &str) -> Option<String> {
 pub enum OhError {
     /// HTTP request failed
     RequestFailed(String),
+    /// Couldn't reach the server (network, proxy or TLS)
+    ConnectionFailed(String),
+    /// `oh_ca_cert` / OH_CA_CERT couldn't be loaded
+    InvalidCaCert(String),
     /// Failed to parse response
     ParseError(String),
     /// OH not configured (not an error, just skip)
@@ -110,6 +176,12 @@ impl std::fmt::Display for OhError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             OhError::RequestFailed(msg) => write!(f, "OH request failed: {}", msg),
+            OhError::ConnectionFailed(msg) => write!(
+                f,
+                "Could not connect to OH ({}); check HTTPS_PROXY/HTTP_PROXY and oh_ca_cert",
+                msg
+            ),
+            OhError::InvalidCaCert(msg) => write!(f, "Invalid OH CA certificate: {}", msg),
             OhError::ParseError(msg) => write!(f, "Failed to parse OH response: {}", msg),
             OhError::NotConfigured => write!(f, "OH not configured"),
             OhError::ApiError(status, msg) => write!(f, "OH API error ({}): {}", status, msg),
@@ -120,7 +192,7 @@ impl std::fmt::Display for OhError {
 impl std::error::Error for OhError {}
 
 /// Full endeavor details from GET /api/endeavors/:id
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OhEndeavorFull {
     pub id: String,
     pub title: String,
@@ -149,7 +221,7 @@ struct GetLogsResponse {
 
 /// Guardrail from GET /api/endeavors/:id/extensions
 #[allow(dead_code)]
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OhGuardrail {
     pub id: String,
     pub title: String,
@@ -167,7 +239,7 @@ pub struct OhGuardrail {
 
 /// Metis entry from GET /api/endeavors/:id/extensions
 #[allow(dead_code)]
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OhMetis {
     pub id: String,
     pub title: String,
@@ -183,7 +255,7 @@ pub struct OhMetis {
 
 /// Extensions response from GET /api/endeavors/:id/extensions
 #[allow(dead_code)]
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OhExtensions {
     pub endeavor_id: String,
     pub guardrails: Vec<OhGuardrail>,
@@ -205,19 +277,102 @@ pub struct LogEntry {
 #[derive(Debug, Clone)]
 pub struct OhClient {
     config: OhConfig,
+    /// Extra trusted roots loaded from `config.ca_cert`
+    ca_certs: Vec<CertificateDer<'static>>,
+    /// Print write requests to stdout instead of sending them
+    dry_run: bool,
 }
 
-impl OhClient {
-    /// Create a new OH client if configuration is available (env vars only)
-    pub fn new() -> Result<Self, OhError> {
-        let config = OhConfig::from_env().ok_or(OhError::NotConfigured)?;
-        Ok(OhClient { config })
-    }
+/// Log ID returned for requests printed by a dry-run client
+pub const DRY_RUN_LOG_ID: &str = "dry-run";
 
+impl OhClient {
     /// Create a new OH client from config file (with env var override)
     pub fn from_config(superego_dir: &Path) -> Result<Self, OhError> {
-        let config = OhConfig::from_config(superego_dir).ok_or(OhError::NotConfigured)?;
-        Ok(OhClient { config })
+        Self::with_config(OhConfig::from_config(superego_dir).ok_or(OhError::NotConfigured)?)
+    }
+
+    /// Create a client for an explicit configuration (loads its CA bundle)
+    pub fn with_config(config: OhConfig) -> Result<Self, OhError> {
+        let ca_certs = match &config.ca_cert {
+            Some(path) => load_ca_certs(path)?,
+            None => Vec::new(),
+        };
+        Ok(OhClient {
+            config,
+            ca_certs,
+            dry_run: false,
+        })
+    }
+
+    /// Apply auth, timeout, proxy and CA settings shared by every request
+    /// AIDEV-NOTE: Proxies come from HTTPS_PROXY/HTTP_PROXY/ALL_PROXY/NO_PROXY
+    /// (either case). attohttpc reads them by default too; set explicitly so
+    /// the behavior doesn't depend on that default.
+    /// `default_timeout_secs` applies unless `oh_timeout_seconds` is set.
+    fn prepare<B>(
+        &self,
+        builder: RequestBuilder<B>,
+        default_timeout_secs: u64,
+    ) -> RequestBuilder<B> {
+        let timeout_secs = self.config.timeout_secs.unwrap_or(default_timeout_secs);
+        let mut builder = builder
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .header("Content-Type", "application/json")
+            .timeout(std::time::Duration::from_secs(timeout_secs))
+            .proxy_settings(ProxySettings::from_env());
+        for cert in &self.ca_certs {
+            builder = builder.add_root_certificate(cert.clone());
+        }
+        builder
+    }
+
+    /// Print POSTs (URL to stderr, JSON body to stdout) instead of sending them;
+    /// reads still hit the API
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// POST a JSON body to /api/logs and return the new log's ID
+    fn post_log<T: Serialize>(
+        &self,
+        body: &T,
+        default_timeout_secs: u64,
+    ) -> Result<String, OhError> {
+        let url = format!("{}/api/logs", self.config.api_url);
+
+        if self.dry_run {
+            let json = serde_json::to_string_pretty(body)
+                .map_err(|e| OhError::ParseError(e.to_string()))?;
+            eprintln!("POST {}", url);
+            println!("{}", json);
+            return Ok(DRY_RUN_LOG_ID.to_string());
+        }
+
+        let response = self
+            .prepare(attohttpc::post(&url), default_timeout_secs)
+            .json(body)
+            .map_err(|e| OhError::RequestFailed(e.to_string()))?
+            .send()
+            .map_err(|e| send_error(&url, e))?;
+
+        if !response.is_success() {
+            let status = response.status().as_u16();
+            let body = response.text().unwrap_or_default();
+            return Err(OhError::ApiError(status, body));
+        }
+
+        let body = response
+            .text()
+            .map_err(|e| OhError::ParseError(e.to_string()))?;
+        let log_response: LogResponse = serde_json::from_str(&body)
+            .map_err(|e| OhError::ParseError(format!("{}: {}", e, body)))?;
+
+        Ok(log_response
+            .log
+            .map(|l| l.id)
+            .unwrap_or_else(|| "unknown".to_string()))
     }
 
     /// Log a decision to an endeavor
@@ -227,8 +382,6 @@ impl OhClient {
         content: &str,
         log_date: Option<&str>,
     ) -> Result<String, OhError> {
-        let url = format!("{}/api/logs", self.config.api_url);
-
         let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
         let date = log_date.unwrap_or(&today);
 
@@ -249,31 +402,22 @@ impl OhClient {
             log_date: date,
         };
 
-        let response = attohttpc::post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .header("Content-Type", "application/json")
-            .timeout(std::time::Duration::from_secs(5))
-            .json(&request)
-            .map_err(|e| OhError::RequestFailed(e.to_string()))?
-            .send()
-            .map_err(|e| OhError::RequestFailed(e.to_string()))?;
+        self.post_log(&request, DEFAULT_TIMEOUT_SECS)
+    }
 
+    /// Cheap authenticated GET to confirm the API is reachable and the key works
+    pub fn check_connection(&self) -> Result<(), OhError> {
+        let url = format!("{}/api/contexts", self.config.api_url);
+        let response = self
+            .prepare(attohttpc::get(&url), DEFAULT_TIMEOUT_SECS)
+            .send()
+            .map_err(|e| send_error(&url, e))?;
         if !response.is_success() {
             let status = response.status().as_u16();
             let body = response.text().unwrap_or_default();
             return Err(OhError::ApiError(status, body));
         }
-
-        let body = response
-            .text()
-            .map_err(|e| OhError::ParseError(e.to_string()))?;
-        let log_response: LogResponse = serde_json::from_str(&body)
-            .map_err(|e| OhError::ParseError(format!("{}: {}", e, body)))?;
-
-        Ok(log_response
-            .log
-            .map(|l| l.id)
-            .unwrap_or_else(|| "unknown".to_string()))
+        Ok(())
     }
 
     /// Get a single endeavor by ID
@@ -284,12 +428,10 @@ impl OhClient {
             urlencoding::encode(endeavor_id)
         );
 
-        let response = attohttpc::get(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .header("Content-Type", "application/json")
-            .timeout(std::time::Duration::from_secs(5))
+        let response = self
+            .prepare(attohttpc::get(&url), DEFAULT_TIMEOUT_SECS)
             .send()
-            .map_err(|e| OhError::RequestFailed(e.to_string()))?;
+            .map_err(|e| send_error(&url, e))?;
 
         if !response.is_success() {
             let status = response.status().as_u16();
@@ -314,33 +456,7 @@ impl OhClient {
         &self,
         payload: &crate::retro::RetrospectivePayload,
     ) -> Result<String, OhError> {
-        let url = format!("{}/api/logs", self.config.api_url);
-
-        let response = attohttpc::post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .header("Content-Type", "application/json")
-            .timeout(std::time::Duration::from_secs(10))
-            .json(payload)
-            .map_err(|e| OhError::RequestFailed(e.to_string()))?
-            .send()
-            .map_err(|e| OhError::RequestFailed(e.to_string()))?;
-
-        if !response.is_success() {
-            let status = response.status().as_u16();
-            let body = response.text().unwrap_or_default();
-            return Err(OhError::ApiError(status, body));
-        }
-
-        let body = response
-            .text()
-            .map_err(|e| OhError::ParseError(e.to_string()))?;
-        let log_response: LogResponse = serde_json::from_str(&body)
-            .map_err(|e| OhError::ParseError(format!("{}: {}", e, body)))?;
-
-        Ok(log_response
-            .log
-            .map(|l| l.id)
-            .unwrap_or_else(|| "unknown".to_string()))
+        self.post_log(payload, RETRO_TIMEOUT_SECS)
     }
 
     /// Get recent logs for an endeavor
@@ -358,12 +474,10 @@ impl OhClient {
             urlencoding::encode(&end_date)
         );
 
-        let response = attohttpc::get(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .header("Content-Type", "application/json")
-            .timeout(std::time::Duration::from_secs(5))
+        let response = self
+            .prepare(attohttpc::get(&url), DEFAULT_TIMEOUT_SECS)
             .send()
-            .map_err(|e| OhError::RequestFailed(e.to_string()))?;
+            .map_err(|e| send_error(&url, e))?;
 
         if !response.is_success() {
             let status = response.status().as_u16();
@@ -389,12 +503,10 @@ impl OhClient {
             urlencoding::encode(endeavor_id)
         );
 
-        let response = attohttpc::get(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .header("Content-Type", "application/json")
-            .timeout(std::time::Duration::from_secs(5))
+        let response = self
+            .prepare(attohttpc::get(&url), DEFAULT_TIMEOUT_SECS)
             .send()
-            .map_err(|e| OhError::RequestFailed(e.to_string()))?;
+            .map_err(|e| send_error(&url, e))?;
 
         if !response.is_success() {
             let status = response.status().as_u16();
@@ -412,23 +524,6 @@ impl OhClient {
     }
 }
 
-/// Parse oh_endeavor_id from config file content
-/// Extracted for testability (avoids env var interference in tests)
-fn parse_config_for_endeavor_id(content: &str) -> Option<String> {
-    for line in content.lines() {
-        let line = line.trim();
-        if line.starts_with("oh_endeavor_id:") {
-            if let Some(value) = line.strip_prefix("oh_endeavor_id:") {
-                let value = value.trim().trim_matches('"').trim_matches('\'');
-                if !value.is_empty() {
-                    return Some(value.to_string());
-                }
-            }
-        }
-    }
-    None
-}
-
 /// Get the configured OH endeavor ID from environment or config file
 ///
 /// Priority:
@@ -445,14 +540,133 @@ pub fn get_endeavor_id(superego_dir: &Path) -> Option<String> {
     }
 
     // Then check config.yaml
-    let config_path = superego_dir.join("config.yaml");
-    if config_path.exists() {
-        if let Ok(content) = fs::read_to_string(&config_path) {
-            return parse_config_for_endeavor_id(&content);
+    Config::load(superego_dir).oh_endeavor_id
+}
+
+/// Endeavor for the current change, using `oh_endeavor_map` when configured
+///
+/// Priority:
+/// 1. OH_ENDEAVOR_ID environment variable (for overrides)
+/// 2. oh_endeavor_map, matched against files changed in the working tree
+/// 3. oh_endeavor_id in .superego/config.yaml
+pub fn resolve_endeavor_id(superego_dir: &Path) -> Option<String> {
+    if let Ok(id) = env::var("OH_ENDEAVOR_ID") {
+        if !id.is_empty() {
+            return Some(id);
         }
     }
 
-    None
+    let config = Config::load(superego_dir);
+    if !config.oh_endeavor_map.is_empty() {
+        let repo = superego_dir
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        if let Some(id) = endeavor_for_files(&config.oh_endeavor_map, &changed_files(repo)) {
+            return Some(id);
+        }
+    }
+    config.oh_endeavor_id
+}
+
+/// The mapped endeavor covering the most files (ties go to the earlier mapping)
+/// AIDEV-NOTE: Each file counts toward its first matching pattern only, so
+/// a catch-all pattern listed last doesn't outvote specific ones.
+fn endeavor_for_files(map: &[(String, String)], files: &[String]) -> Option<String> {
+    let mut counts = vec![0usize; map.len()];
+    for file in files {
+        if let Some(i) = map
+            .iter()
+            .position(|(pattern, _)| crate::ignore::path_matches(pattern, file))
+        {
+            counts[i] += 1;
+        }
+    }
+    let mut best: Option<usize> = None;
+    for (i, &n) in counts.iter().enumerate() {
+        if n > 0 && best.is_none_or(|b| n > counts[b]) {
+            best = Some(i);
+        }
+    }
+    best.map(|i| map[i].1.clone())
+}
+
+/// Repo-relative paths with uncommitted changes (tracked or untracked)
+fn changed_files(repo: &Path) -> Vec<String> {
+    let git = |args: &[&str]| -> Vec<String> {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(repo)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| {
+                String::from_utf8_lossy(&o.stdout)
+                    .lines()
+                    .filter(|l| !l.is_empty())
+                    .map(|l| l.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let mut files = git(&["diff", "--name-only", "HEAD"]);
+    // --full-name: repo-root relative like `git diff`, even from a subdirectory
+    files.extend(git(&[
+        "ls-files",
+        "--others",
+        "--exclude-standard",
+        "--full-name",
+    ]));
+    files
+}
+
+/// Format a timestamp as an OH `log_date` (YYYY-MM-DD, UTC)
+pub fn log_date(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d").to_string()
+}
+
+/// Endeavor cache file within .superego/
+const CACHE_FILE: &str = "oh_cache.json";
+
+/// Endeavor details and extensions as last fetched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedEndeavor {
+    fetched_at: DateTime<Utc>,
+    endeavor: OhEndeavorFull,
+    extensions: OhExtensions,
+}
+
+/// Contents of `.superego/oh_cache.json`, keyed by endeavor ID
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OhCache {
+    endeavors: HashMap<String, CachedEndeavor>,
+}
+
+/// Cached entry for `endeavor_id` if younger than `ttl`
+fn load_cached(
+    cache_path: &Path,
+    endeavor_id: &str,
+    ttl: chrono::Duration,
+    now: DateTime<Utc>,
+) -> Option<CachedEndeavor> {
+    let content = fs::read_to_string(cache_path).ok()?;
+    let mut cache: OhCache = serde_json::from_str(&content).ok()?;
+    let entry = cache.endeavors.remove(endeavor_id)?;
+    (now - entry.fetched_at < ttl).then_some(entry)
+}
+
+/// Write an entry back, keeping other endeavors' entries (best effort)
+fn store_cached(cache_path: &Path, endeavor_id: &str, entry: CachedEndeavor) {
+    let mut cache: OhCache = fs::read_to_string(cache_path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default();
+    cache.endeavors.insert(endeavor_id.to_string(), entry);
+    if let Ok(json) = serde_json::to_string_pretty(&cache) {
+        if let Err(e) = fs::write(cache_path, json) {
+            diag::warn(&format!("failed to write OH cache: {}", e));
+        }
+    }
 }
 
 /// Full OH integration configuration
@@ -461,44 +675,86 @@ pub fn get_endeavor_id(superego_dir: &Path) -> Option<String> {
 pub struct OhIntegration {
     pub client: OhClient,
     pub endeavor_id: String,
+    /// `.superego/oh_cache.json`
+    cache_path: PathBuf,
+    /// How long cached endeavor details/extensions stay fresh (0 = no cache)
+    cache_ttl: chrono::Duration,
 }
 
 impl OhIntegration {
     /// Try to create a fully configured OH integration
     /// Returns None if either API is not configured or endeavor ID is not set
     pub fn new(superego_dir: &Path) -> Option<Self> {
-        let client = OhClient::new().ok()?;
-        let endeavor_id = get_endeavor_id(superego_dir)?;
+        let config = Config::load(superego_dir);
+        let oh_config = OhConfig::from_env()?.with_project_settings(&config);
+        let client = match OhClient::with_config(oh_config) {
+            Ok(client) => client,
+            Err(e) => {
+                diag::warn(&format!("OH integration disabled: {}", e));
+                return None;
+            }
+        };
+        let endeavor_id = resolve_endeavor_id(superego_dir)?;
+        let ttl_minutes = config.oh_cache_ttl_minutes;
         Some(OhIntegration {
             client,
             endeavor_id,
+            cache_path: superego_dir.join(CACHE_FILE),
+            cache_ttl: chrono::Duration::minutes(ttl_minutes),
         })
     }
 
     /// Log superego feedback to the configured endeavor
-    pub fn log_feedback(&self, feedback: &str) -> Result<String, OhError> {
+    /// `at` is when the decision was made, so backfilled logs keep their date.
+    pub fn log_feedback(&self, feedback: &str, at: DateTime<Utc>) -> Result<String, OhError> {
         let content = format!("## Superego Feedback\n\n{}", feedback);
-        self.client.log_decision(&self.endeavor_id, &content, None)
+        self.client
+            .log_decision(&self.endeavor_id, &content, Some(&log_date(at)))
     }
 
-    /// Get formatted endeavor context for evaluation
-    /// Returns empty string if fetching fails (graceful degradation)
-    pub fn get_endeavor_context(&self) -> String {
-        // Fetch endeavor details
-        let endeavor = match self.client.get_endeavor(&self.endeavor_id) {
-            Ok(e) => e,
+    /// Endeavor details and extensions, from the cache while fresh
+    /// AIDEV-NOTE: Only complete fetches are cached, so a failed extensions
+    /// call is retried on the next evaluation rather than cached as missing.
+    fn endeavor_and_extensions(&self) -> Result<(OhEndeavorFull, Option<OhExtensions>), OhError> {
+        let now = Utc::now();
+        if let Some(cached) = load_cached(&self.cache_path, &self.endeavor_id, self.cache_ttl, now)
+        {
+            return Ok((cached.endeavor, Some(cached.extensions)));
+        }
+
+        let endeavor = self.client.get_endeavor(&self.endeavor_id)?;
+        let extensions = match self.client.get_extensions(&self.endeavor_id) {
+            Ok(ext) => ext,
             Err(e) => {
-                eprintln!("Warning: failed to fetch OH endeavor: {}", e);
-                return String::new();
+                diag::warn(&format!("failed to fetch OH extensions: {}", e));
+                return Ok((endeavor, None));
             }
         };
 
-        // Fetch extensions (guardrails + metis)
-        let extensions = match self.client.get_extensions(&self.endeavor_id) {
-            Ok(ext) => Some(ext),
+        if self.cache_ttl > chrono::Duration::zero() {
+            store_cached(
+                &self.cache_path,
+                &self.endeavor_id,
+                CachedEndeavor {
+                    fetched_at: now,
+                    endeavor: endeavor.clone(),
+                    extensions: extensions.clone(),
+                },
+            );
+        }
+        Ok((endeavor, Some(extensions)))
+    }
+
+    /// Get formatted endeavor context for evaluation
+    /// Endeavor details and guardrails come from `.superego/oh_cache.json` while
+    /// fresh (`oh_cache_ttl_minutes`); recent logs are always fetched live.
+    /// Returns empty string if fetching fails (graceful degradation)
+    pub fn get_endeavor_context(&self) -> String {
+        let (endeavor, extensions) = match self.endeavor_and_extensions() {
+            Ok(fetched) => fetched,
             Err(e) => {
-                eprintln!("Warning: failed to fetch OH extensions: {}", e);
-                None
+                diag::warn(&format!("failed to fetch OH endeavor: {}", e));
+                return String::new();
             }
         };
 
@@ -506,7 +762,7 @@ impl OhIntegration {
         let logs = match self.client.get_logs(&self.endeavor_id, 7) {
             Ok(l) => l,
             Err(e) => {
-                eprintln!("Warning: failed to fetch OH logs: {}", e);
+                diag::warn(&format!("failed to fetch OH logs: {}", e));
                 Vec::new() // Continue with endeavor info even if logs fail
             }
         };
@@ -619,65 +875,140 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_config_from_env_missing() {
-        // Clear env vars for test
-        env::remove_var("OH_API_KEY");
-        env::remove_var("OH_API_URL");
+    fn test_log_date_uses_given_timestamp() {
+        let yesterday = chrono::Utc::now() - chrono::Duration::days(1);
+        assert_eq!(
+            log_date(yesterday),
+            yesterday.format("%Y-%m-%d").to_string()
+        );
 
-        assert!(OhConfig::from_env().is_none());
+        let past = DateTime::parse_from_rfc3339("2024-11-05T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(log_date(past), "2024-11-05");
     }
 
     #[test]
-    fn test_client_new_fails_when_not_configured() {
-        env::remove_var("OH_API_KEY");
-        env::remove_var("OH_API_URL");
-
-        let result = OhClient::new();
-        assert!(matches!(result, Err(OhError::NotConfigured)));
+    fn test_endeavor_for_files() {
+        let map = vec![
+            ("crates/api/**".to_string(), "initiative:api".to_string()),
+            ("web/".to_string(), "initiative:web".to_string()),
+            ("**".to_string(), "initiative:all".to_string()),
+        ];
+        let files =
+            |paths: &[&str]| -> Vec<String> { paths.iter().map(|p| p.to_string()).collect() };
+
+        assert_eq!(endeavor_for_files(&map, &[]), None);
+        assert_eq!(
+            endeavor_for_files(&map, &files(&["web/src/app.ts", "crates/api/src/lib.rs"])),
+            Some("initiative:api".to_string()),
+            "tie goes to the earlier mapping"
+        );
+        assert_eq!(
+            endeavor_for_files(
+                &map,
+                &files(&["web/a.ts", "web/b.ts", "crates/api/src/lib.rs"])
+            ),
+            Some("initiative:web".to_string())
+        );
+        assert_eq!(
+            endeavor_for_files(&map, &files(&["README.md", "web/a.ts"])),
+            Some("initiative:web".to_string()),
+            "catch-all ties with web and is listed later"
+        );
+        assert_eq!(
+            endeavor_for_files(&map[..2], &files(&["README.md"])),
+            None,
+            "no match falls back to oh_endeavor_id"
+        );
     }
 
-    // Tests for parse_config_for_endeavor_id (no env var interference)
-
     #[test]
-    fn test_parse_config_extracts_endeavor_id() {
-        let content = "# Config\nmode: always\noh_endeavor_id: my-endeavor-123\n";
-        let result = parse_config_for_endeavor_id(content);
-        assert_eq!(result, Some("my-endeavor-123".to_string()));
+    fn test_connection_errors_are_distinct() {
+        let client = OhClient::with_config(OhConfig {
+            api_url: "http://127.0.0.1:9".to_string(),
+            api_key: "key".to_string(),
+            ca_cert: None,
+            timeout_secs: None,
+        })
+        .unwrap();
+        match client.get_endeavor("initiative:abc") {
+            Err(OhError::ConnectionFailed(msg)) => assert!(msg.contains("127.0.0.1:9"), "{}", msg),
+            other => panic!("expected ConnectionFailed, got {:?}", other),
+        }
     }
 
     #[test]
-    fn test_parse_config_strips_double_quotes() {
-        let content = "oh_endeavor_id: \"quoted-value\"";
-        let result = parse_config_for_endeavor_id(content);
-        assert_eq!(result, Some("quoted-value".to_string()));
-    }
+    fn test_project_settings() {
+        let config = Config {
+            oh_ca_cert: Some(PathBuf::from("/etc/ssl/corp.pem")),
+            oh_timeout_seconds: Some(30),
+            ..Config::default()
+        };
+        let base = OhConfig {
+            api_url: "https://oh.example".to_string(),
+            api_key: "key".to_string(),
+            ca_cert: None,
+            timeout_secs: None,
+        };
 
-    #[test]
-    fn test_parse_config_strips_single_quotes() {
-        let content = "oh_endeavor_id: 'single-quoted'";
-        let result = parse_config_for_endeavor_id(content);
-        assert_eq!(result, Some("single-quoted".to_string()));
+        let applied = base.clone().with_project_settings(&config);
+        assert_eq!(applied.timeout_secs, Some(30));
+        assert_eq!(applied.ca_cert, config.oh_ca_cert);
+
+        // OH_CA_CERT (already on the config) wins over the project file
+        let from_env = OhConfig {
+            ca_cert: Some(PathBuf::from("/env.pem")),
+            ..base
+        }
+        .with_project_settings(&config);
+        assert_eq!(from_env.ca_cert, Some(PathBuf::from("/env.pem")));
     }
 
     #[test]
-    fn test_parse_config_returns_none_when_missing() {
-        let content = "mode: always\nmodel: opus";
-        let result = parse_config_for_endeavor_id(content);
-        assert!(result.is_none());
+    fn test_invalid_ca_cert() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ca.pem");
+        fs::write(&path, "not a certificate").unwrap();
+        let config = |ca_cert: PathBuf| OhConfig {
+            api_url: "https://oh.example".to_string(),
+            api_key: "key".to_string(),
+            ca_cert: Some(ca_cert),
+            timeout_secs: None,
+        };
+
+        let err = OhClient::with_config(config(path)).unwrap_err();
+        assert!(matches!(err, OhError::InvalidCaCert(_)), "{:?}", err);
+        let err = OhClient::with_config(config(dir.path().join("missing.pem"))).unwrap_err();
+        assert!(err.to_string().contains("missing.pem"), "{}", err);
     }
 
     #[test]
-    fn test_parse_config_returns_none_for_empty_value() {
-        let content = "oh_endeavor_id: ";
-        let result = parse_config_for_endeavor_id(content);
-        assert!(result.is_none());
+    fn test_dry_run_skips_network() {
+        // Nothing listens on the discard port; a real request would fail
+        let client = OhClient::with_config(OhConfig {
+            api_url: "http://127.0.0.1:9".to_string(),
+            api_key: "key".to_string(),
+            ca_cert: None,
+            timeout_secs: None,
+        })
+        .unwrap()
+        .with_dry_run(true);
+        assert_eq!(
+            client
+                .log_decision("initiative:abc", "feedback", Some("2025-01-01"))
+                .unwrap(),
+            DRY_RUN_LOG_ID
+        );
     }
 
     #[test]
-    fn test_parse_config_handles_whitespace() {
-        let content = "  oh_endeavor_id:   spaced-value  \n";
-        let result = parse_config_for_endeavor_id(content);
-        assert_eq!(result, Some("spaced-value".to_string()));
+    fn test_config_from_env_missing() {
+        // Clear env vars for test
+        env::remove_var("OH_API_KEY");
+        env::remove_var("OH_API_URL");
+
+        assert!(OhConfig::from_env().is_none());
     }
 
     #[test]
@@ -713,10 +1044,63 @@ mod tests {
         assert_eq!(response.logs[1].log_date, "2025-12-19");
     }
 
+    #[test]
+    fn test_endeavor_cache_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join(CACHE_FILE);
+        let fetched_at = Utc::now();
+        let entry = |id: &str| CachedEndeavor {
+            fetched_at,
+            endeavor: OhEndeavorFull {
+                id: id.to_string(),
+                title: "Ship it".to_string(),
+                description: None,
+                status: None,
+            },
+            extensions: OhExtensions {
+                endeavor_id: id.to_string(),
+                guardrails: Vec::new(),
+                metis: Vec::new(),
+            },
+        };
+        store_cached(&cache_path, "e1", entry("e1"));
+        store_cached(&cache_path, "e2", entry("e2"));
+
+        let ttl = chrono::Duration::minutes(10);
+        let fresh = load_cached(
+            &cache_path,
+            "e1",
+            ttl,
+            fetched_at + chrono::Duration::minutes(9),
+        );
+        assert_eq!(fresh.unwrap().endeavor.title, "Ship it");
+        assert!(load_cached(&cache_path, "e2", ttl, fetched_at).is_some());
+        assert!(load_cached(&cache_path, "e1", ttl, fetched_at + ttl).is_none());
+        assert!(load_cached(&cache_path, "e3", ttl, fetched_at).is_none());
+    }
+
     #[test]
     fn test_parse_logs_response_empty() {
         let json = r#"{"logs":[]}"#;
         let response: GetLogsResponse = serde_json::from_str(json).unwrap();
         assert!(response.logs.is_empty());
     }
+
+    #[test]
+    fn test_changed_files_repo_relative_from_subdirectory() {
+        let (dir, git) = crate::review::temp_git_repo();
+        let repo = dir.path();
+
+        git(&["init", "-q"]);
+        std::fs::create_dir_all(repo.join("api")).unwrap();
+        std::fs::write(repo.join("api/handler.rs"), "v1\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+        std::fs::write(repo.join("api/handler.rs"), "v2\n").unwrap();
+        std::fs::write(repo.join("api/new.rs"), "new\n").unwrap();
+
+        let mut files = changed_files(&repo.join("api"));
+        files.sort();
+        assert_eq!(files, vec!["api/handler.rs", "api/new.rs"]);
+    }
 }
@@ -0,0 +1,151 @@
+//! One-time repository overview for a session's first evaluation
+//!
+//! Gives superego some grounding (layout, README intro, language/framework)
+//! before it has seen any of the project's history.
+//! AIDEV-NOTE: Generated once and cached in `.superego/repo_overview.md`;
+//! delete the file to regenerate it.
+
+use std::fs;
+use std::path::Path;
+
+/// Cache file within .superego/
+const CACHE_FILE: &str = "repo_overview.md";
+
+/// Non-empty README lines included in the overview
+const README_LINES: usize = 5;
+
+/// Top-level directories listed at most
+const MAX_DIRS: usize = 20;
+
+/// Marker files and the language/tooling they indicate
+const LANGUAGE_MARKERS: &[(&str, &str)] = &[
+    ("Cargo.toml", "Rust"),
+    ("package.json", "JavaScript/TypeScript"),
+    ("pyproject.toml", "Python"),
+    ("requirements.txt", "Python"),
+    ("go.mod", "Go"),
+    ("Gemfile", "Ruby"),
+    ("pom.xml", "Java (Maven)"),
+    ("build.gradle", "JVM (Gradle)"),
+    ("build.gradle.kts", "JVM (Gradle)"),
+    ("mix.exs", "Elixir"),
+    ("composer.json", "PHP"),
+];
+
+/// package.json dependencies that identify a framework
+const JS_FRAMEWORKS: &[(&str, &str)] = &[
+    ("next", "Next.js"),
+    ("react", "React"),
+    ("vue", "Vue"),
+    ("svelte", "Svelte"),
+    ("@angular/core", "Angular"),
+    ("express", "Express"),
+];
+
+/// Detected languages and frameworks, in marker order
+fn detect_stack(repo_root: &Path) -> Vec<String> {
+    let mut stack: Vec<String> = Vec::new();
+    for (marker, language) in LANGUAGE_MARKERS {
+        if repo_root.join(marker).exists() && !stack.iter().any(|s| s == language) {
+            stack.push(language.to_string());
+        }
+    }
+
+    let package_json = fs::read_to_string(repo_root.join("package.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok());
+    if let Some(package) = package_json {
+        for (dep, framework) in JS_FRAMEWORKS {
+            let has_dep = ["dependencies", "devDependencies"]
+                .iter()
+                .any(|section| package[section].get(dep).is_some());
+            if has_dep {
+                stack.push(framework.to_string());
+            }
+        }
+    }
+
+    stack
+}
+
+/// First non-empty lines of the README, if there is one
+fn readme_intro(repo_root: &Path) -> Option<String> {
+    let content = ["README.md", "README", "README.rst", "README.txt"]
+        .iter()
+        .find_map(|name| fs::read_to_string(repo_root.join(name)).ok())?;
+    let lines: Vec<&str> = content
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .take(README_LINES)
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Build a concise overview of the repository at `repo_root`
+pub fn repo_overview(repo_root: &Path) -> String {
+    let mut dirs: Vec<String> = fs::read_dir(repo_root)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
+                .filter(|name| !name.starts_with('.'))
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    dirs.truncate(MAX_DIRS);
+
+    let mut out = String::new();
+    let stack = detect_stack(repo_root);
+    if !stack.is_empty() {
+        out.push_str(&format!("Stack: {}\n", stack.join(", ")));
+    }
+    if !dirs.is_empty() {
+        out.push_str(&format!("Top-level directories: {}\n", dirs.join(", ")));
+    }
+    if let Some(intro) = readme_intro(repo_root) {
+        out.push_str("README (start):\n");
+        out.push_str(&intro);
+        out.push('\n');
+    }
+    out
+}
+
+/// Overview from cache, generating and caching it on first use
+pub fn cached_overview(superego_dir: &Path, repo_root: &Path) -> std::io::Result<String> {
+    let cache_path = superego_dir.join(CACHE_FILE);
+    if let Ok(cached) = fs::read_to_string(&cache_path) {
+        return Ok(cached);
+    }
+    let overview = repo_overview(repo_root);
+    fs::write(&cache_path, &overview)?;
+    Ok(overview)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_overview() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::create_dir(root.join("web")).unwrap();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]\n").unwrap();
+        fs::write(
+            root.join("package.json"),
+            r#"{"dependencies":{"react":"^18"}}"#,
+        )
+        .unwrap();
+        fs::write(root.join("README.md"), "# Widget\n\nMakes widgets.\n").unwrap();
+
+        let overview = repo_overview(root);
+        assert!(overview.contains("Stack: Rust, JavaScript/TypeScript, React"));
+        assert!(overview.contains("Top-level directories: src, web\n"));
+        assert!(overview.contains("# Widget\nMakes widgets."));
+    }
+}
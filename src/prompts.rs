@@ -1,9 +1,12 @@
 //! Prompt management for superego
 //!
-//! Handles multiple prompt templates (code, writing, learning) with switching and backup.
+//! Handles multiple prompt templates (code, writing, learning, security) with switching and backup.
+//! Layers in `.superego/prompt.d/*.md` are appended to prompt.md (see `with_layers`).
 
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
 
 /// Available prompt types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -11,12 +14,27 @@ pub enum PromptType {
     Code,
     Writing,
     Learning,
+    Security,
 }
 
 impl PromptType {
     /// All available prompt types
     pub fn all() -> &'static [PromptType] {
-        &[PromptType::Code, PromptType::Writing, PromptType::Learning]
+        &[
+            PromptType::Code,
+            PromptType::Writing,
+            PromptType::Learning,
+            PromptType::Security,
+        ]
+    }
+
+    /// Comma-separated names of all prompt types (for usage messages)
+    pub fn available_names() -> String {
+        Self::all()
+            .iter()
+            .map(|pt| pt.name())
+            .collect::<Vec<_>>()
+            .join(", ")
     }
 
     /// Get prompt type from string name
@@ -25,6 +43,7 @@ impl PromptType {
             "code" => Some(PromptType::Code),
             "writing" => Some(PromptType::Writing),
             "learning" => Some(PromptType::Learning),
+            "security" => Some(PromptType::Security),
             _ => None,
         }
     }
@@ -35,6 +54,7 @@ impl PromptType {
             PromptType::Code => "code",
             PromptType::Writing => "writing",
             PromptType::Learning => "learning",
+            PromptType::Security => "security",
         }
     }
 
@@ -44,6 +64,7 @@ impl PromptType {
             PromptType::Code => "Metacognitive advisor for coding agents",
             PromptType::Writing => "Co-author reviewer for writing and content creation",
             PromptType::Learning => "Learning coach for teaching approaches - ensures learning is hands-on and verifiable",
+            PromptType::Security => "Security reviewer - injection, secrets in diffs, unsafe deserialization, auth bypasses",
         }
     }
 
@@ -53,6 +74,7 @@ impl PromptType {
             PromptType::Code => include_str!("../default_prompt.md"),
             PromptType::Writing => include_str!("prompts/writing.md"),
             PromptType::Learning => include_str!("prompts/learning.md"),
+            PromptType::Security => include_str!("prompts/security.md"),
         }
     }
 }
@@ -87,23 +109,95 @@ impl From<std::io::Error> for PromptError {
     }
 }
 
-/// Get the current base prompt from config
-pub fn get_current_base(superego_dir: &Path) -> Option<PromptType> {
-    let config_path = superego_dir.join("config.yaml");
-    if !config_path.exists() {
-        return Some(PromptType::Code); // Default
+/// Append a focus instruction to a system prompt (base prompt is kept intact)
+pub fn with_focus(system_prompt: &str, focus: Option<&str>) -> String {
+    match focus.map(str::trim).filter(|f| !f.is_empty()) {
+        Some(topic) => format!(
+            "{}\n\n## Focus\n\nPay particular attention to: {}\n",
+            system_prompt.trim_end(),
+            topic
+        ),
+        None => system_prompt.to_string(),
     }
+}
 
-    let content = fs::read_to_string(&config_path).ok()?;
-    for line in content.lines() {
-        let line = line.trim();
-        if line.starts_with("base_prompt:") {
-            let value = line.strip_prefix("base_prompt:")?.trim();
-            return PromptType::from_name(value);
+/// Directory (in .superego) of prompt layers appended to prompt.md
+pub const LAYERS_DIR: &str = "prompt.d";
+
+/// The `.md` files in prompt.d, sorted by file name (empty if there's no prompt.d)
+/// AIDEV-NOTE: Name layers like `10-team.md`, `20-project.md` to order them.
+pub fn prompt_layers(superego_dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(superego_dir.join(LAYERS_DIR)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut layers: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    layers.sort();
+    Ok(layers)
+}
+
+/// A system prompt followed by every prompt.d layer, blank-line separated
+/// Layers extend the base prompt; `sg prompt switch` and backups never touch them.
+pub fn with_layers(superego_dir: &Path, system_prompt: &str) -> std::io::Result<String> {
+    let mut prompt = system_prompt.to_string();
+    for layer in prompt_layers(superego_dir)? {
+        let content = fs::read_to_string(&layer)?;
+        if !content.trim().is_empty() {
+            prompt = format!("{}\n\n{}\n", prompt.trim_end(), content.trim());
         }
     }
+    Ok(prompt)
+}
 
-    Some(PromptType::Code) // Default if not specified
+/// Replace `{{name}}` tokens with their `prompt_vars` values
+///
+/// Returns the prompt and the names of tokens with no value (left as written,
+/// each reported once). Only `{{` + letters, digits, `_`, `-` or `.` + `}}`
+/// is a token, so other brace pairs (e.g. code samples) are never touched.
+pub fn substitute_vars(prompt: &str, vars: &[(String, String)]) -> (String, Vec<String>) {
+    let mut out = String::with_capacity(prompt.len());
+    let mut unknown: Vec<String> = Vec::new();
+    let mut rest = prompt;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let name = after.find("}}").map(|end| &after[..end]).filter(|name| {
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        });
+        let Some(name) = name else {
+            out.push_str("{{");
+            rest = after;
+            continue;
+        };
+        // Later entries win, as with repeated config keys
+        match vars.iter().rev().find(|(k, _)| k == name) {
+            Some((_, value)) => out.push_str(value),
+            None => {
+                out.push_str(&rest[start..start + name.len() + 4]);
+                if !unknown.iter().any(|u| u == name) {
+                    unknown.push(name.to_string());
+                }
+            }
+        }
+        rest = &after[name.len() + 2..];
+    }
+    out.push_str(rest);
+    (out, unknown)
+}
+
+/// Get the current base prompt from config
+pub fn get_current_base(superego_dir: &Path) -> Option<PromptType> {
+    match Config::load(superego_dir).base_prompt {
+        Some(name) => PromptType::from_name(&name),
+        None => Some(PromptType::Code), // Default if not specified
+    }
 }
 
 /// Set the base prompt in config
@@ -223,6 +317,131 @@ pub fn switch(superego_dir: &Path, target: PromptType) -> Result<SwitchResult, P
     Ok(result)
 }
 
+/// Lines of unchanged context around each diff hunk
+const DIFF_CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffOp {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Line-level edit script via longest common subsequence
+/// AIDEV-NOTE: O(n*m) is fine for prompt-sized inputs (a few hundred lines).
+fn diff_ops<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(DiffOp, &'a str)> {
+    let (n, m) = (old.len(), new.len());
+    // lcs[i][j] = LCS length of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            ops.push((DiffOp::Equal, old[i]));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            // Prefer deletions first so replacements read "-old" then "+new"
+            ops.push((DiffOp::Delete, old[i]));
+            i += 1;
+        } else {
+            ops.push((DiffOp::Insert, new[j]));
+            j += 1;
+        }
+    }
+    ops
+}
+
+/// Unified diff between two texts (empty string if identical)
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_ops(&old_lines, &new_lines);
+
+    let changes: Vec<usize> = (0..ops.len())
+        .filter(|&k| ops[k].0 != DiffOp::Equal)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // Group changes whose surrounding context would overlap into one hunk
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    for &k in &changes {
+        match groups.last_mut() {
+            Some((_, last)) if k - *last <= 2 * DIFF_CONTEXT => *last = k,
+            _ => groups.push((k, k)),
+        }
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for (first, last) in groups {
+        let start = first.saturating_sub(DIFF_CONTEXT);
+        let end = (last + DIFF_CONTEXT + 1).min(ops.len());
+
+        let count = |range: &[(DiffOp, &str)], skip: DiffOp| {
+            range.iter().filter(|(op, _)| *op != skip).count()
+        };
+        let old_start = count(&ops[..start], DiffOp::Insert);
+        let new_start = count(&ops[..start], DiffOp::Delete);
+        let old_count = count(&ops[start..end], DiffOp::Insert);
+        let new_count = count(&ops[start..end], DiffOp::Delete);
+        // Unified format: line numbers are 1-based, except for empty ranges
+        let line_no = |start: usize, count: usize| if count == 0 { start } else { start + 1 };
+
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            line_no(old_start, old_count),
+            old_count,
+            line_no(new_start, new_count),
+            new_count
+        ));
+        for (op, line) in &ops[start..end] {
+            let prefix = match op {
+                DiffOp::Equal => ' ',
+                DiffOp::Delete => '-',
+                DiffOp::Insert => '+',
+            };
+            out.push_str(&format!("{}{}\n", prefix, line));
+        }
+    }
+    out
+}
+
+/// Diff prompt.md against the shipped template (current base if not specified)
+pub fn diff(superego_dir: &Path, against: Option<PromptType>) -> Result<String, PromptError> {
+    if !superego_dir.exists() {
+        return Err(PromptError::NotInitialized);
+    }
+
+    let base = against
+        .or_else(|| get_current_base(superego_dir))
+        .unwrap_or(PromptType::Code);
+    let prompt_path = superego_dir.join("prompt.md");
+    let current = if prompt_path.exists() {
+        fs::read_to_string(&prompt_path)?
+    } else {
+        String::new()
+    };
+
+    Ok(unified_diff(
+        base.content(),
+        &current,
+        &format!("{} (template)", base.name()),
+        "prompt.md",
+    ))
+}
+
 /// Result of a prompt switch operation
 #[derive(Debug)]
 pub struct SwitchResult {
@@ -282,6 +501,115 @@ mod tests {
         assert_eq!(PromptType::from_name("CODE"), Some(PromptType::Code));
         assert_eq!(PromptType::from_name("writing"), Some(PromptType::Writing));
         assert_eq!(PromptType::from_name("unknown"), None);
+        assert_eq!(
+            PromptType::from_name("Security"),
+            Some(PromptType::Security)
+        );
+        assert_eq!(
+            PromptType::available_names(),
+            "code, writing, learning, security"
+        );
+    }
+
+    #[test]
+    fn test_diff_unmodified_prompt_is_empty() {
+        let dir = setup_superego_dir();
+        let superego = dir.path().join(".superego");
+        assert_eq!(diff(&superego, None).unwrap(), "");
+    }
+
+    #[test]
+    fn test_diff_modified_prompt() {
+        let dir = setup_superego_dir();
+        let superego = dir.path().join(".superego");
+        let modified = format!("{}\nAlways check migrations.\n", PromptType::Code.content());
+        fs::write(superego.join("prompt.md"), modified).unwrap();
+
+        let output = diff(&superego, None).unwrap();
+        assert!(output.starts_with("--- code (template)\n+++ prompt.md\n"));
+        assert!(output.contains("\n+Always check migrations.\n"));
+        assert!(!output
+            .lines()
+            .any(|l| l.starts_with('-') && !l.starts_with("---")));
+
+        // Against a different template, everything differs
+        let output = diff(&superego, Some(PromptType::Writing)).unwrap();
+        assert!(output
+            .lines()
+            .any(|l| l.starts_with('-') && !l.starts_with("---")));
+    }
+
+    #[test]
+    fn test_unified_diff_hunks() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+        let out = unified_diff(old, new, "old", "new");
+        assert_eq!(
+            out,
+            "--- old\n+++ new\n\
+             @@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n\
+             @@ -10,3 +10,4 @@\n j\n k\n l\n+m\n"
+        );
+    }
+
+    #[test]
+    fn test_with_focus_appends_instruction() {
+        let prompt = with_focus("Base prompt.", Some("security"));
+        assert!(prompt.starts_with("Base prompt."));
+        assert!(prompt.contains("Pay particular attention to: security"));
+
+        assert_eq!(with_focus("Base prompt.", None), "Base prompt.");
+        assert_eq!(with_focus("Base prompt.", Some("  ")), "Base prompt.");
+    }
+
+    #[test]
+    fn test_with_layers_appends_prompt_d_in_order() {
+        let dir = setup_superego_dir();
+        let superego = dir.path().join(".superego");
+        assert_eq!(with_layers(&superego, "Base.").unwrap(), "Base.");
+
+        let layers = superego.join(LAYERS_DIR);
+        fs::create_dir(&layers).unwrap();
+        fs::write(layers.join("20-project.md"), "Project rules.\n").unwrap();
+        fs::write(layers.join("10-team.md"), "Team conventions.").unwrap();
+        fs::write(layers.join("15-empty.md"), "  \n").unwrap();
+        fs::write(layers.join("notes.txt"), "Not a layer.").unwrap();
+
+        assert_eq!(
+            with_layers(&superego, "Base.\n").unwrap(),
+            "Base.\n\nTeam conventions.\n\nProject rules.\n"
+        );
+        assert_eq!(prompt_layers(&superego).unwrap().len(), 3);
+
+        // Layers apply on top of the embedded prompt when prompt.md is missing
+        fs::remove_file(superego.join("prompt.md")).unwrap();
+        let prompt = crate::evaluate::load_system_prompt(&superego).unwrap();
+        assert!(prompt.starts_with(PromptType::Code.content().trim_end()));
+        assert!(prompt.ends_with("Project rules.\n"));
+    }
+
+    #[test]
+    fn test_substitute_vars() {
+        let vars = vec![
+            ("project".to_string(), "billing-api".to_string()),
+            (
+                "standards_url".to_string(),
+                "https://example.com/std".to_string(),
+            ),
+            ("project".to_string(), "payments".to_string()),
+        ];
+        let (prompt, unknown) = substitute_vars(
+            "Project {{project}} follows {{standards_url}}. {{owner}} and {{owner}} review.\n\
+             Code: fn f() {{ x }} and {{ not a token }}, trailing {{",
+            &vars,
+        );
+        assert_eq!(
+            prompt,
+            "Project payments follows https://example.com/std. {{owner}} and {{owner}} review.\n\
+             Code: fn f() {{ x }} and {{ not a token }}, trailing {{"
+        );
+        assert_eq!(unknown, vec!["owner"]);
+        assert_eq!(substitute_vars("No tokens.", &[]).0, "No tokens.");
     }
 
     #[test]
@@ -6,11 +6,15 @@
 //! - Curated: LLM picks key moments with generated summaries
 
 use crate::claude::{self, ClaudeOptions};
-use crate::decision::{Decision, DecisionType};
+use crate::config::Config;
+use crate::decision::{self, Decision, DecisionType};
+use crate::diag;
+use crate::sessions;
+use crate::state::StateManager;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// Severity levels for timeline events
@@ -34,6 +38,35 @@ impl Severity {
     }
 }
 
+/// Output format for the retrospective
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RetroFormat {
+    /// Self-contained HTML page (default)
+    #[default]
+    Html,
+    /// Markdown timeline for PR descriptions and issue comments
+    Markdown,
+}
+
+impl RetroFormat {
+    /// Parse format from string argument
+    pub fn from_arg(arg: &str) -> Option<Self> {
+        match arg.to_lowercase().as_str() {
+            "html" => Some(RetroFormat::Html),
+            "markdown" | "md" => Some(RetroFormat::Markdown),
+            _ => None,
+        }
+    }
+
+    /// File extension for the default output path
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RetroFormat::Html => "html",
+            RetroFormat::Markdown => "md",
+        }
+    }
+}
+
 /// A moment in the timeline
 #[derive(Debug, Clone, Serialize)]
 pub struct Moment {
@@ -47,11 +80,15 @@ pub struct Moment {
     pub accepted: Option<bool>,
     /// Claude's reaction/reasoning (curated mode only)
     pub reaction: Option<String>,
+    /// What the agent was doing when the feedback landed, e.g. "Edit(foo.rs)"
+    pub tool_uses: Vec<String>,
 }
 
 /// Session metadata for the report header
 struct SessionMeta {
     session_id: String,
+    /// Human-readable label from `sg sessions label`
+    label: Option<String>,
     date: String,
     decision_count: usize,
     /// Executive summary from LLM curation (empty for default mode)
@@ -65,6 +102,8 @@ pub enum RetroError {
     SessionNotFound(String),
     IoError(std::io::Error),
     DecisionError(String),
+    /// Custom template is missing a required placeholder
+    TemplateError(String),
 }
 
 impl std::fmt::Display for RetroError {
@@ -74,6 +113,7 @@ impl std::fmt::Display for RetroError {
             RetroError::SessionNotFound(id) => write!(f, "Session not found: {}", id),
             RetroError::IoError(e) => write!(f, "IO error: {}", e),
             RetroError::DecisionError(e) => write!(f, "Decision error: {}", e),
+            RetroError::TemplateError(e) => write!(f, "Invalid retro template: {}", e),
         }
     }
 }
@@ -122,23 +162,15 @@ fn load_decisions(session_dir: &Path) -> Result<Vec<Decision>, RetroError> {
         return Ok(Vec::new());
     }
 
-    let mut decisions = Vec::new();
-
+    let mut paths = Vec::new();
     for entry in fs::read_dir(&decisions_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-
+        let path = entry?.path();
         if path.extension().is_some_and(|ext| ext == "json") {
-            let content = fs::read_to_string(&path)?;
-            match serde_json::from_str::<Decision>(&content) {
-                Ok(decision) => decisions.push(decision),
-                Err(e) => {
-                    eprintln!("Warning: skipping {:?}: {}", path, e);
-                }
-            }
+            paths.push(path);
         }
     }
 
+    let mut decisions = decision::read_files(&paths)?;
     decisions.sort_by_key(|d| d.timestamp);
     Ok(decisions)
 }
@@ -267,6 +299,7 @@ fn decisions_to_moments(decisions: Vec<Decision>) -> Vec<Moment> {
                 tag: infer_tag(context),
                 accepted: None, // Not available in default mode
                 reaction: None,
+                tool_uses: d.tool_uses.clone(),
             })
         })
         .collect()
@@ -315,6 +348,44 @@ pub struct CurationResult {
 
 // === OH Integration Payload ===
 
+/// How many moments fall under each severity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SeverityCounts {
+    pub error: usize,
+    pub warning: usize,
+    pub success: usize,
+    pub info: usize,
+}
+
+impl SeverityCounts {
+    /// Tally the severities of these moments
+    pub fn from_moments(moments: &[Moment]) -> Self {
+        let mut counts = SeverityCounts::default();
+        for moment in moments {
+            match moment.severity {
+                Severity::Error => counts.error += 1,
+                Severity::Warning => counts.warning += 1,
+                Severity::Success => counts.success += 1,
+                Severity::Info => counts.info += 1,
+            }
+        }
+        counts
+    }
+
+    /// Non-zero counts with their severity, most severe first
+    fn nonzero(&self) -> Vec<(Severity, usize)> {
+        [
+            (Severity::Error, self.error),
+            (Severity::Warning, self.warning),
+            (Severity::Success, self.success),
+            (Severity::Info, self.info),
+        ]
+        .into_iter()
+        .filter(|(_, n)| *n > 0)
+        .collect()
+    }
+}
+
 /// Statistics for the retrospective
 #[derive(Debug, Serialize)]
 pub struct RetrospectiveStats {
@@ -326,6 +397,8 @@ pub struct RetrospectiveStats {
     pub accepted_count: usize,
     /// Number of moments where Claude dismissed feedback
     pub dismissed_count: usize,
+    /// Curated moments by severity
+    pub severity: SeverityCounts,
 }
 
 /// Metadata payload for OH log entry
@@ -352,11 +425,13 @@ pub struct RetrospectivePayload {
 }
 
 /// Format retrospective data as OH log payload
+/// `session_date` is when the session happened (its first decision), used as log_date
 pub fn format_oh_payload(
     session_id: &str,
     endeavor_id: &str,
     total_decisions: usize,
     result: &CurationResult,
+    session_date: DateTime<Utc>,
 ) -> RetrospectivePayload {
     // Count acceptance stats
     let accepted_count = result
@@ -385,14 +460,12 @@ pub fn format_oh_payload(
             .join("\n")
     );
 
-    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
-
     RetrospectivePayload {
         entity_type: "endeavor".to_string(),
         entity_id: endeavor_id.to_string(),
         content,
         content_type: "markdown".to_string(),
-        log_date: today,
+        log_date: crate::oh::log_date(session_date),
         metadata: RetrospectiveMetadata {
             payload_type: "superego_retrospective".to_string(),
             version: 1,
@@ -403,6 +476,7 @@ pub fn format_oh_payload(
                 curated_count: result.moments.len(),
                 accepted_count,
                 dismissed_count,
+                severity: SeverityCounts::from_moments(&result.moments),
             },
             moments: result.moments.clone(),
         },
@@ -410,7 +484,10 @@ pub fn format_oh_payload(
 }
 
 /// Curate moments using LLM (picks key moments, generates summaries)
-fn curate_moments(decisions: Vec<Decision>) -> Result<CurationResult, RetroError> {
+fn curate_moments(
+    decisions: Vec<Decision>,
+    tools: Option<Vec<String>>,
+) -> Result<CurationResult, RetroError> {
     // Filter to feedback decisions and format for LLM
     let feedback_decisions: Vec<_> = decisions
         .iter()
@@ -476,11 +553,12 @@ Rules:
         context
     );
 
-    eprintln!("Calling LLM to curate moments...");
+    diag::info("Calling LLM to curate moments...");
 
     let options = ClaudeOptions {
         model: Some("haiku".to_string()), // Fast and cheap for this task
         no_session_persistence: true,
+        tools,
         ..Default::default()
     };
 
@@ -530,11 +608,14 @@ Rules:
                 tag: cm.tag,
                 accepted: cm.accepted,
                 reaction: cm.reaction,
+                tool_uses: matching_decision
+                    .map(|d| d.tool_uses.clone())
+                    .unwrap_or_default(),
             }
         })
         .collect();
 
-    eprintln!("LLM selected {} key moments", moments.len());
+    diag::info(&format!("LLM selected {} key moments", moments.len()));
     Ok(CurationResult {
         executive_summary: curated.executive_summary,
         moments,
@@ -542,6 +623,37 @@ Rules:
 }
 
 /// HTML template with placeholders
+/// Custom HTML template within .superego/ (overrides HTML_TEMPLATE)
+pub const TEMPLATE_FILE: &str = "retro-template.html";
+
+/// Placeholders every HTML template must contain
+const TEMPLATE_PLACEHOLDERS: &[&str] = &["{{SUBTITLE}}", "{{DECISION_COUNT}}", "{{EVENTS}}"];
+
+/// Load `.superego/retro-template.html` if present, else the embedded default
+/// AIDEV-NOTE: Validated up front so a broken template fails before the
+/// (paid) curation call rather than after.
+fn load_template(superego_dir: &Path) -> Result<String, RetroError> {
+    let path = superego_dir.join(TEMPLATE_FILE);
+    if !path.exists() {
+        return Ok(HTML_TEMPLATE.to_string());
+    }
+    let template = fs::read_to_string(&path)?;
+    let missing: Vec<&str> = TEMPLATE_PLACEHOLDERS
+        .iter()
+        .copied()
+        .filter(|p| !template.contains(p))
+        .collect();
+    if !missing.is_empty() {
+        return Err(RetroError::TemplateError(format!(
+            "{} is missing {}",
+            path.display(),
+            missing.join(", ")
+        )));
+    }
+    diag::info(&format!("Using custom template: {}", path.display()));
+    Ok(template)
+}
+
 const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
 <html lang="en">
 <head>
@@ -576,6 +688,9 @@ const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
     .stats { display: flex; justify-content: center; gap: 2rem; margin-top: 1.5rem; }
     .stat { text-align: center; }
     .stat-value { font-size: 1.5rem; font-weight: 600; color: var(--accent); }
+    .stat.error .stat-value { color: var(--error); }
+    .stat.warning .stat-value { color: var(--warning); }
+    .stat.success .stat-value { color: var(--success); }
     .stat-label { font-size: 0.8rem; color: var(--text-muted); text-transform: uppercase; letter-spacing: 0.05em; }
     .timeline { position: relative; padding-left: 2rem; }
     .timeline::before {
@@ -637,6 +752,7 @@ const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
     .event.info .event-tag { background: rgba(88, 166, 255, 0.2); color: var(--accent); }
     .event-title { font-size: 1rem; font-weight: 600; margin-bottom: 0.5rem; }
     .event-summary { font-size: 0.9rem; color: var(--text-muted); }
+    .event-tools { margin-top: 0.4rem; font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 0.75rem; color: var(--text-muted); overflow-wrap: anywhere; }
     .event-detail {
       display: none;
       margin-top: 1rem;
@@ -668,7 +784,7 @@ const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
           <div class="stat-value">{{DECISION_COUNT}}</div>
           <div class="stat-label">Decisions</div>
         </div>
-      </div>
+{{SEVERITY_STATS}}      </div>
     </header>
     <div class="timeline">
 {{EVENTS}}
@@ -723,6 +839,15 @@ fn generate_event_html(moment: &Moment) -> String {
         })
         .unwrap_or_default();
 
+    let tools_html = if moment.tool_uses.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "        <div class=\"event-tools\">{}</div>\n",
+            escape_html(&moment.tool_uses.join(", "))
+        )
+    };
+
     format!(
         r#"      <div class="event {}">
         <div class="event-header">
@@ -731,7 +856,7 @@ fn generate_event_html(moment: &Moment) -> String {
         </div>
         <div class="event-title">{}</div>
         <div class="event-summary">{}</div>
-{}        <div class="event-detail">{}</div>
+{}{}        <div class="event-detail">{}</div>
       </div>
 "#,
         severity_class,
@@ -739,34 +864,131 @@ fn generate_event_html(moment: &Moment) -> String {
         escape_html(&moment.tag),
         escape_html(&moment.title),
         escape_html(&moment.summary),
+        tools_html,
         reaction_html,
         escape_html(&moment.detail)
     )
 }
 
+/// Short session name for report headers (label if set)
+fn session_display(meta: &SessionMeta) -> String {
+    let short = meta.session_id.get(..8).unwrap_or(&meta.session_id);
+    sessions::display_name(short, meta.label.as_deref())
+}
+
+/// One `.stat` block per severity present (empty when there are no moments)
+fn generate_severity_stats_html(counts: &SeverityCounts) -> String {
+    counts
+        .nonzero()
+        .into_iter()
+        .map(|(severity, n)| {
+            format!(
+                r#"        <div class="stat {}">
+          <div class="stat-value">{}</div>
+          <div class="stat-label">{}</div>
+        </div>
+"#,
+                severity.css_class(),
+                n,
+                severity.css_class()
+            )
+        })
+        .collect()
+}
+
 /// Generate the full HTML report
-fn generate_html(moments: Vec<Moment>, meta: SessionMeta) -> String {
+fn generate_html(template: &str, moments: &[Moment], meta: &SessionMeta) -> String {
     let events_html: String = moments.iter().map(generate_event_html).collect();
 
     // Include executive summary in subtitle if present
+    let session = session_display(meta);
     let subtitle = match &meta.executive_summary {
         Some(summary) if !summary.is_empty() => {
-            format!(
-                "Session {} • {} • {}",
-                &meta.session_id[..8],
-                meta.date,
-                summary
-            )
+            format!("Session {} • {} • {}", session, meta.date, summary)
         }
-        _ => format!("Session {} • {}", &meta.session_id[..8], meta.date),
+        _ => format!("Session {} • {}", session, meta.date),
     };
 
-    HTML_TEMPLATE
+    template
         .replace("{{SUBTITLE}}", &subtitle)
         .replace("{{DECISION_COUNT}}", &meta.decision_count.to_string())
+        .replace(
+            "{{SEVERITY_STATS}}",
+            &generate_severity_stats_html(&SeverityCounts::from_moments(moments)),
+        )
         .replace("{{EVENTS}}", &events_html)
 }
 
+/// Render one moment as a markdown timeline entry
+fn generate_event_markdown(moment: &Moment) -> String {
+    let mut out = format!(
+        "### {} · `{}` · {}\n\n{}\n",
+        moment.timestamp.format("%H:%M"),
+        moment.tag,
+        moment.title,
+        moment.summary
+    );
+
+    if !moment.tool_uses.is_empty() {
+        let tools: Vec<String> = moment
+            .tool_uses
+            .iter()
+            .map(|t| format!("`{}`", t.replace('`', "'")))
+            .collect();
+        out.push_str(&format!("\n{}\n", tools.join(", ")));
+    }
+
+    if let Some(reaction) = &moment.reaction {
+        let icon = match moment.accepted {
+            Some(true) => "✓",
+            Some(false) => "✗",
+            None => "?",
+        };
+        out.push_str(&format!("\n*{} {}*\n", icon, reaction));
+    }
+
+    if !moment.detail.is_empty() {
+        let quoted: Vec<String> = moment
+            .detail
+            .lines()
+            .map(|l| {
+                if l.is_empty() {
+                    ">".to_string()
+                } else {
+                    format!("> {}", l)
+                }
+            })
+            .collect();
+        out.push_str(&format!(
+            "\n<details><summary>Detail</summary>\n\n{}\n\n</details>\n",
+            quoted.join("\n")
+        ));
+    }
+
+    out.push('\n');
+    out
+}
+
+/// Generate the report as a markdown timeline
+/// AIDEV-NOTE: Same moments as the HTML report; the executive summary becomes
+/// an H2 so the output pastes cleanly into a PR description.
+fn generate_markdown(moments: &[Moment], meta: &SessionMeta) -> String {
+    let mut out = String::from("# Superego Session Retrospective\n\n");
+    if let Some(summary) = meta.executive_summary.as_deref().filter(|s| !s.is_empty()) {
+        out.push_str(&format!("## {}\n\n", summary));
+    }
+    out.push_str(&format!(
+        "Session {} • {} • {} events\n\n",
+        session_display(meta),
+        meta.date,
+        meta.decision_count
+    ));
+    for moment in moments {
+        out.push_str(&generate_event_markdown(moment));
+    }
+    out
+}
+
 /// Open file in default browser
 fn open_browser(path: &Path) -> Result<(), RetroError> {
     #[cfg(target_os = "macos")]
@@ -787,21 +1009,47 @@ fn open_browser(path: &Path) -> Result<(), RetroError> {
     Ok(())
 }
 
+/// Options for `sg retro`
+#[derive(Debug, Clone, Default)]
+pub struct RetroOptions {
+    /// Session to report on (None = latest)
+    pub session_id: Option<String>,
+    /// LLM-curated key moments instead of every decision
+    pub curated: bool,
+    pub format: RetroFormat,
+    /// Report file to write
+    pub output: PathBuf,
+    /// Open the report after writing it
+    pub open: bool,
+    /// Push the curated retrospective to Open Horizons
+    pub push_oh: bool,
+    /// Print the OH payload instead of sending it
+    pub oh_dry_run: bool,
+}
+
 /// Main entry point for the retro command
-pub fn run(
-    superego_dir: &Path,
-    session_id: Option<&str>,
-    curated: bool,
-    output: &Path,
-    open: bool,
-    push_oh: bool,
-) -> Result<(), RetroError> {
+pub fn run(superego_dir: &Path, options: &RetroOptions) -> Result<(), RetroError> {
+    let RetroOptions {
+        session_id,
+        curated,
+        format,
+        output,
+        open,
+        push_oh,
+        oh_dry_run,
+    } = options;
+
+    let template = match format {
+        RetroFormat::Html => Some(load_template(superego_dir)?),
+        RetroFormat::Markdown => None,
+    };
+
     // Find session
     let session_id = match session_id {
-        Some(id) => id.to_string(),
+        Some(id) => id.clone(),
         None => {
             let id = find_latest_session(superego_dir)?;
-            eprintln!("Using latest session: {}", id);
+            diag::info(&format!("Using latest session: {}", id));
             id
         }
     };
@@ -814,25 +1062,27 @@ pub fn run(
     // Load decisions
     let decisions = load_decisions(&session_dir)?;
     if decisions.is_empty() {
-        eprintln!("No decisions found in session.");
+        diag::info("No decisions found in session.");
         return Ok(());
     }
 
     let total_decisions = decisions.len();
-    eprintln!("Found {} decisions", total_decisions);
+    diag::info(&format!("Found {} decisions", total_decisions));
 
     // Get date from first decision
-    let date = decisions
+    let session_started = decisions
         .first()
-        .map(|d| d.timestamp.format("%b %d, %Y").to_string())
-        .unwrap_or_default();
+        .map(|d| d.timestamp)
+        .unwrap_or_else(Utc::now);
+    let date = session_started.format("%b %d, %Y").to_string();
 
     // Determine processing mode - curate if either flag is set
-    let need_curation = curated || push_oh;
+    let need_curation = *curated || *push_oh;
 
     // Process decisions (moves ownership into one path, no cloning)
     let (moments, executive_summary, curation_for_oh) = if need_curation {
-        let result = curate_moments(decisions)?;
+        let tools = Config::load(superego_dir).superego_tools;
+        let result = curate_moments(decisions, tools)?;
         let summary = result.executive_summary.clone();
         let moments = result.moments.clone();
         (moments, Some(summary), Some(result))
@@ -841,35 +1091,49 @@ pub fn run(
     };
 
     if moments.is_empty() {
-        eprintln!("No feedback decisions to display.");
+        diag::info("No feedback decisions to display.");
         return Ok(());
     }
 
-    eprintln!("Generated {} timeline events", moments.len());
+    diag::info(&format!("Generated {} timeline events", moments.len()));
 
+    let label = StateManager::new(&session_dir)
+        .load()
+        .ok()
+        .and_then(|s| s.label);
     let meta = SessionMeta {
         session_id: session_id.clone(),
+        label,
         date,
         decision_count: moments.len(),
         executive_summary,
     };
 
-    // Generate HTML
-    let html = generate_html(moments, meta);
+    let report = match template {
+        Some(template) => generate_html(&template, &moments, &meta),
+        None => generate_markdown(&moments, &meta),
+    };
 
     // Write to file
-    fs::write(output, &html)?;
-    eprintln!("Written to: {}", output.display());
+    fs::write(output, &report)?;
+    diag::info(&format!("Written to: {}", output.display()));
 
-    // Open in browser if requested
-    if open {
+    // Open in browser (or the default viewer for markdown) if requested
+    if *open {
         open_browser(output)?;
     }
 
     // Push to Open Horizons if requested
-    if push_oh {
+    if *push_oh {
         if let Some(ref result) = curation_for_oh {
-            push_to_oh(superego_dir, &session_id, total_decisions, result)?;
+            push_to_oh(
+                superego_dir,
+                &session_id,
+                total_decisions,
+                result,
+                session_started,
+                *oh_dry_run,
+            )?;
         }
     }
 
@@ -882,6 +1146,8 @@ fn push_to_oh(
     session_id: &str,
     total_decisions: usize,
     result: &CurationResult,
+    session_started: DateTime<Utc>,
+    dry_run: bool,
 ) -> Result<(), RetroError> {
     use crate::oh::{get_endeavor_id, OhClient};
 
@@ -889,36 +1155,189 @@ fn push_to_oh(
     let endeavor_id = match get_endeavor_id(superego_dir) {
         Some(id) => id,
         None => {
-            eprintln!("OH push skipped: no oh_endeavor_id configured in .superego/config.yaml");
+            diag::warn("OH push skipped: no oh_endeavor_id configured in .superego/config.yaml");
             return Ok(());
         }
     };
 
     let client = match OhClient::from_config(superego_dir) {
-        Ok(c) => c,
+        Ok(c) => c.with_dry_run(dry_run),
         Err(e) => {
-            eprintln!(
+            diag::warn(&format!(
                 "OH push skipped: {} (set oh_api_key in config.yaml or OH_API_KEY env var)",
                 e
-            );
+            ));
             return Ok(());
         }
     };
 
     // Format payload
-    let payload = format_oh_payload(session_id, &endeavor_id, total_decisions, result);
+    let payload = format_oh_payload(
+        session_id,
+        &endeavor_id,
+        total_decisions,
+        result,
+        session_started,
+    );
 
     // Push to OH
-    eprintln!("Pushing retrospective to OH endeavor: {}", endeavor_id);
+    if dry_run {
+        diag::info(&format!(
+            "Dry run: retrospective for OH endeavor {} (not sent)",
+            endeavor_id
+        ));
+    } else {
+        diag::info(&format!(
+            "Pushing retrospective to OH endeavor: {}",
+            endeavor_id
+        ));
+    }
     match client.log_retrospective(&payload) {
+        Ok(_) if dry_run => {}
         Ok(log_id) => {
-            eprintln!("Successfully pushed to OH (log_id: {})", log_id);
+            diag::info(&format!("Successfully pushed to OH (log_id: {})", log_id));
         }
         Err(e) => {
-            eprintln!("Failed to push to OH: {}", e);
             // Don't fail the command, just warn
+            diag::warn(&format!("Failed to push to OH: {}", e));
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oh_payload_uses_session_date() {
+        let result = CurationResult {
+            executive_summary: "Auth refactor".to_string(),
+            moments: Vec::new(),
+        };
+        let session_date = DateTime::parse_from_rfc3339("2025-03-02T23:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let payload = format_oh_payload("sess-1", "end-1", 3, &result, session_date);
+        assert_eq!(payload.log_date, "2025-03-02");
+        assert_eq!(payload.metadata.stats.severity, SeverityCounts::default());
+    }
+
+    #[test]
+    fn test_severity_counts() {
+        let moment = |severity| Moment {
+            timestamp: Utc::now(),
+            title: String::new(),
+            summary: String::new(),
+            detail: String::new(),
+            severity,
+            tag: String::new(),
+            accepted: None,
+            reaction: None,
+            tool_uses: Vec::new(),
+        };
+        let moments = vec![
+            moment(Severity::Error),
+            moment(Severity::Warning),
+            moment(Severity::Warning),
+            moment(Severity::Info),
+        ];
+        let counts = SeverityCounts::from_moments(&moments);
+        assert_eq!(
+            counts,
+            SeverityCounts {
+                error: 1,
+                warning: 2,
+                success: 0,
+                info: 1
+            }
+        );
+
+        let html = generate_severity_stats_html(&counts);
+        assert!(html.contains(r#"<div class="stat warning">"#));
+        assert!(html.contains(r#"<div class="stat-value">2</div>"#));
+        assert!(!html.contains("success"));
+        assert_eq!(generate_severity_stats_html(&SeverityCounts::default()), "");
+    }
+
+    #[test]
+    fn test_event_html_shows_tool_uses() {
+        let mut moment = Moment {
+            timestamp: Utc::now(),
+            title: "Missing tests".to_string(),
+            summary: "No test for the parser".to_string(),
+            detail: String::new(),
+            severity: Severity::Warning,
+            tag: "testing".to_string(),
+            accepted: None,
+            reaction: None,
+            tool_uses: vec![
+                "Edit(src/parser.rs)".to_string(),
+                "Bash(cargo test && echo <ok>)".to_string(),
+            ],
+        };
+        let html = generate_event_html(&moment);
+        assert!(html.contains(
+            r#"<div class="event-tools">Edit(src/parser.rs), Bash(cargo test &amp;&amp; echo &lt;ok&gt;)</div>"#
+        ));
+
+        moment.tool_uses.clear();
+        assert!(!generate_event_html(&moment).contains("event-tools"));
+    }
+
+    #[test]
+    fn test_custom_template() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load_template(dir.path()).unwrap(), HTML_TEMPLATE);
+
+        let custom = "<h1>Acme</h1><p>{{SUBTITLE}} ({{DECISION_COUNT}})</p>{{EVENTS}}";
+        fs::write(dir.path().join(TEMPLATE_FILE), custom).unwrap();
+        let template = load_template(dir.path()).unwrap();
+        let meta = SessionMeta {
+            session_id: "abcdef1234".to_string(),
+            label: None,
+            date: "Mar 02, 2025".to_string(),
+            decision_count: 0,
+            executive_summary: None,
+        };
+        assert_eq!(
+            generate_html(&template, &[], &meta),
+            "<h1>Acme</h1><p>Session abcdef12 • Mar 02, 2025 (0)</p>"
+        );
+
+        fs::write(dir.path().join(TEMPLATE_FILE), "<p>{{SUBTITLE}}</p>").unwrap();
+        let err = load_template(dir.path()).unwrap_err().to_string();
+        assert!(err.contains("{{DECISION_COUNT}}, {{EVENTS}}"), "{}", err);
+    }
+
+    #[test]
+    fn test_markdown_report() {
+        let moment = Moment {
+            timestamp: "2025-03-02T14:05:00Z".parse().unwrap(),
+            title: "Missing tests".to_string(),
+            summary: "No test for the parser".to_string(),
+            detail: "Add a test.\n\nCover the error path.".to_string(),
+            severity: Severity::Warning,
+            tag: "testing".to_string(),
+            accepted: Some(true),
+            reaction: Some("Added tests".to_string()),
+            tool_uses: vec!["Edit(src/parser.rs)".to_string()],
+        };
+        let meta = SessionMeta {
+            session_id: "abcdef1234".to_string(),
+            label: None,
+            date: "Mar 02, 2025".to_string(),
+            decision_count: 1,
+            executive_summary: Some("Parser refactor".to_string()),
+        };
+
+        let md = generate_markdown(&[moment], &meta);
+        assert!(md.starts_with("# Superego Session Retrospective\n\n## Parser refactor\n"));
+        assert!(md.contains("### 14:05 · `testing` · Missing tests\n\nNo test for the parser\n"));
+        assert!(md.contains("`Edit(src/parser.rs)`"));
+        assert!(md.contains("*✓ Added tests*"));
+        assert!(md.contains("> Add a test.\n>\n> Cover the error path."));
+    }
+}
@@ -2,16 +2,28 @@
 //!
 //! Allows users to proactively request superego review of changes.
 
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Output};
 
-use crate::claude;
-use crate::codex_llm;
-use crate::prompts;
+use crate::config::{Backend, Config};
+use crate::diag;
+use crate::evaluate;
+use crate::ignore::IgnorePatterns;
+use crate::llm::{self, InvokeOptions};
+use crate::prompts::{self, PromptType};
+use crate::sarif;
 
 /// Run a git command and check for errors
 fn run_git(args: &[&str]) -> Result<Output, ReviewError> {
+    run_git_in(Path::new("."), args)
+}
+
+/// Run a git command in a specific repository directory
+fn run_git_in(repo: &Path, args: &[&str]) -> Result<Output, ReviewError> {
     let output = Command::new("git")
+        .current_dir(repo)
         .args(args)
         .output()
         .map_err(|e| ReviewError::GitError(e.to_string()))?;
@@ -32,9 +44,17 @@ pub enum ReviewTarget {
     /// Staged changes (git diff --cached)
     Staged,
     /// PR diff vs base branch
-    Pr,
+    /// With `no_merges`, only the branch's own (first-parent, non-merge)
+    /// commits are reviewed, so changes pulled in by merging base are excluded.
+    /// `base` (`--base`) skips base branch detection.
+    Pr {
+        no_merges: bool,
+        base: Option<String>,
+    },
     /// Specific file
     File(String),
+    /// Commit range, e.g. `HEAD~3..HEAD` or `abc123..def456`
+    Range(String),
 }
 
 impl ReviewTarget {
@@ -43,10 +63,81 @@ impl ReviewTarget {
         match arg {
             None => ReviewTarget::Staged,
             Some("staged") => ReviewTarget::Staged,
-            Some("pr") => ReviewTarget::Pr,
+            Some("pr") => ReviewTarget::Pr {
+                no_merges: false,
+                base: None,
+            },
+            // A file literally named like "../x" stays a file
+            Some(range) if range.contains("..") && !Path::new(range).exists() => {
+                ReviewTarget::Range(range.to_string())
+            }
             Some(path) => ReviewTarget::File(path.to_string()),
         }
     }
+
+    /// Apply `--no-merges`; None if the target doesn't support it (only `pr` does)
+    pub fn with_no_merges(self, no_merges: bool) -> Option<Self> {
+        match (self, no_merges) {
+            (ReviewTarget::Pr { base, .. }, true) => Some(ReviewTarget::Pr {
+                no_merges: true,
+                base,
+            }),
+            (_, true) => None,
+            (target, false) => Some(target),
+        }
+    }
+
+    /// Apply `--base`; None if the target doesn't support it (only `pr` does)
+    pub fn with_base(self, base: Option<String>) -> Option<Self> {
+        match (self, base) {
+            (ReviewTarget::Pr { no_merges, .. }, Some(base)) => Some(ReviewTarget::Pr {
+                no_merges,
+                base: Some(base),
+            }),
+            (_, Some(_)) => None,
+            (target, None) => Some(target),
+        }
+    }
+}
+
+/// Output format for review results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text (default)
+    Text,
+    /// SARIF 2.1.0 JSON for code-scanning tools
+    Sarif,
+    /// JSON array of findings (file, line, severity, message)
+    Json,
+}
+
+impl OutputFormat {
+    /// Parse format from string argument
+    pub fn from_arg(arg: &str) -> Option<Self> {
+        match arg.to_lowercase().as_str() {
+            "text" => Some(OutputFormat::Text),
+            "sarif" => Some(OutputFormat::Sarif),
+            "json" => Some(OutputFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Options for a review
+#[derive(Debug, Clone, Default)]
+pub struct ReviewOptions {
+    /// Topic to pay particular attention to (overrides config `default_focus`)
+    pub focus: Option<String>,
+    /// Use this shipped prompt instead of .superego/prompt.md, for this review only
+    pub prompt: Option<PromptType>,
+    /// Model for this review (overrides config `review_model` and `model`)
+    pub model: Option<String>,
+    /// Ask the LLM for a JSON findings array instead of prose (`--format json`)
+    pub structured: bool,
+    /// LLM backend for this review (overrides config `backend`; `sg review-codex`)
+    pub backend: Option<Backend>,
+    /// Echo the LLM's progress to stderr while it works (Claude backend only)
+    pub stream: bool,
 }
 
 /// Result of a review
@@ -54,6 +145,191 @@ impl ReviewTarget {
 pub struct ReviewResult {
     pub feedback: String,
     pub target_description: String,
+    /// The diff that was reviewed (used to locate findings)
+    pub diff: String,
+}
+
+impl ReviewResult {
+    /// Discrete findings: the JSON array if the LLM returned one (structured
+    /// reviews), else one per paragraph, located against the diff
+    pub fn findings(&self) -> Vec<Finding> {
+        parse_structured_findings(&self.feedback)
+            .unwrap_or_else(|| sarif::infer_findings(&self.feedback, &self.diff))
+    }
+}
+
+/// How serious a finding is (SARIF levels)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    /// Lenient parse of the LLM's label; anything unrecognized is a note
+    pub fn from_label(label: &str) -> Self {
+        match label.trim().to_lowercase().as_str() {
+            "error" | "critical" | "high" | "block" => Severity::Error,
+            "warning" | "warn" | "medium" => Severity::Warning,
+            _ => Severity::Note,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        }
+    }
+}
+
+/// One discrete review finding
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    /// Repo-relative path, if the finding points at a file
+    pub file: Option<String>,
+    pub line: Option<u64>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// A finding as the LLM writes it (every field but the message optional)
+#[derive(Debug, Deserialize)]
+struct RawFinding {
+    #[serde(default)]
+    file: Option<String>,
+    #[serde(default)]
+    line: Option<u64>,
+    #[serde(default)]
+    severity: Option<String>,
+    message: String,
+}
+
+/// Output instruction for structured reviews
+const FINDINGS_INSTRUCTION: &str = "Respond with ONLY a JSON array of findings, no prose:\n\
+    [{\"file\": \"path/in/diff.rs\", \"line\": 42, \"severity\": \"error|warning|note\", \"message\": \"...\"}]\n\
+    Use the new-side line number from the diff; use null for file/line when a finding is general. \
+    Return [] if there is nothing worth raising.";
+
+/// User message for a review of `diff`
+fn review_message(description: &str, diff: &str, structured: bool) -> String {
+    let format_instruction = if structured {
+        FINDINGS_INSTRUCTION
+    } else {
+        "Provide constructive feedback - no DECISION/BLOCK format needed, just helpful observations."
+    };
+    format!(
+        "Review the following changes and provide feedback.\n\n\
+        This is an on-demand review requested by the user (not a hook evaluation).\n\
+        {}\n\n\
+        --- CHANGES ({}) ---\n{}\n--- END CHANGES ---",
+        format_instruction, description, diff
+    )
+}
+
+/// Part of a diff reviewed in one LLM call (whole files only)
+#[derive(Debug, PartialEq)]
+struct DiffChunk {
+    files: Vec<String>,
+    diff: String,
+}
+
+/// Split a diff by file into chunks of at most `max_bytes` where possible
+/// AIDEV-NOTE: A file's diff is never split, so one file larger than
+/// max_bytes gets a chunk of its own. Text before the first `diff --git`
+/// header (e.g. a `--no-merges` commit line) stays with the first file.
+fn split_diff(diff: &str, max_bytes: usize) -> Vec<DiffChunk> {
+    let mut files: Vec<(Option<String>, String)> = Vec::new();
+    for line in diff.split_inclusive('\n') {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            let file = header
+                .trim_end()
+                .rsplit_once(" b/")
+                .map_or(header.trim_end(), |(_, b)| b);
+            match files.last_mut() {
+                // Preamble only: this file adopts it
+                Some((name @ None, _)) => *name = Some(file.to_string()),
+                _ => files.push((Some(file.to_string()), String::new())),
+            }
+        } else if files.is_empty() {
+            files.push((None, String::new()));
+        }
+        files.last_mut().unwrap().1.push_str(line);
+    }
+
+    let mut chunks: Vec<DiffChunk> = Vec::new();
+    for (file, text) in files {
+        match chunks.last_mut() {
+            Some(chunk) if chunk.diff.len() + text.len() <= max_bytes => {
+                chunk.files.extend(file);
+                chunk.diff.push_str(&text);
+            }
+            _ => chunks.push(DiffChunk {
+                files: file.into_iter().collect(),
+                diff: text,
+            }),
+        }
+    }
+    chunks
+}
+
+/// Feedback of a review done in parts: a heading per part naming its files,
+/// or for structured reviews one merged findings array
+fn combine_chunk_feedback(chunks: &[DiffChunk], feedback: &[String], structured: bool) -> String {
+    if structured {
+        let findings: Option<Vec<Vec<Finding>>> = feedback
+            .iter()
+            .map(|f| parse_structured_findings(f))
+            .collect();
+        if let Some(findings) = findings {
+            let merged: Vec<Finding> = findings.into_iter().flatten().collect();
+            return serde_json::to_string_pretty(&merged).unwrap_or_default();
+        }
+    }
+    chunks
+        .iter()
+        .zip(feedback)
+        .enumerate()
+        .map(|(i, (chunk, feedback))| {
+            format!(
+                "### Part {}/{}: {}\n\n{}",
+                i + 1,
+                chunks.len(),
+                chunk.files.join(", "),
+                feedback.trim()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Parse the JSON findings array from a structured review response
+/// AIDEV-NOTE: Takes the outermost [...] so code fences or a stray sentence
+/// around the array don't matter. None (not an array) means free-form feedback.
+fn parse_structured_findings(feedback: &str) -> Option<Vec<Finding>> {
+    let start = feedback.find('[')?;
+    let end = feedback.rfind(']')?;
+    if end < start {
+        return None;
+    }
+    let raw: Vec<RawFinding> = serde_json::from_str(&feedback[start..=end]).ok()?;
+    Some(
+        raw.into_iter()
+            .map(|r| Finding {
+                file: r.file.filter(|f| !f.is_empty()),
+                line: r.line.filter(|l| *l > 0),
+                severity: r
+                    .severity
+                    .as_deref()
+                    .map(Severity::from_label)
+                    .unwrap_or(Severity::Note),
+                message: r.message,
+            })
+            .collect(),
+    )
 }
 
 /// Error type for review operations
@@ -79,7 +355,7 @@ impl std::fmt::Display for ReviewError {
 impl std::error::Error for ReviewError {}
 
 /// Get diff content based on target
-fn get_diff(target: &ReviewTarget) -> Result<(String, String), ReviewError> {
+fn get_diff(target: &ReviewTarget, config: &Config) -> Result<(String, String), ReviewError> {
     let (diff, description) = match target {
         ReviewTarget::Staged => {
             let output = run_git(&["diff", "--cached"])?;
@@ -88,10 +364,11 @@ fn get_diff(target: &ReviewTarget) -> Result<(String, String), ReviewError> {
             // If nothing staged, fall back to uncommitted
             if diff.trim().is_empty() {
                 let output = run_git(&["diff", "HEAD"])?;
-                let diff = String::from_utf8_lossy(&output.stdout).to_string();
+                let mut diff = String::from_utf8_lossy(&output.stdout).to_string();
+                diff.push_str(&untracked_diff(Path::new("."), config.max_diff_bytes)?);
                 if diff.trim().is_empty() {
                     return Err(ReviewError::NoDiff(
-                        "no staged or uncommitted changes".to_string(),
+                        "no staged, uncommitted or untracked changes".to_string(),
                     ));
                 }
                 (diff, "uncommitted changes (nothing staged)".to_string())
@@ -99,20 +376,31 @@ fn get_diff(target: &ReviewTarget) -> Result<(String, String), ReviewError> {
                 (diff, "staged changes".to_string())
             }
         }
-        ReviewTarget::Pr => {
-            // Get the base branch (usually main or master)
-            let base = get_base_branch()?;
-            let diff_ref = format!("{}...HEAD", base);
+        ReviewTarget::Pr { no_merges, base } => {
+            // Get the base branch (--base, config, else usually main or master)
+            let explicit = base.as_deref().or(config.review_base_branch.as_deref());
+            let base = get_base_branch(Path::new("."), explicit)?;
 
-            let output = run_git(&["diff", &diff_ref])?;
-            let diff = String::from_utf8_lossy(&output.stdout).to_string();
+            let (diff, description) = if *no_merges {
+                (
+                    branch_commit_patches(Path::new("."), &base)?,
+                    format!("PR commits vs {} (merge commits excluded)", base),
+                )
+            } else {
+                let diff_ref = format!("{}...HEAD", base);
+                let output = run_git(&["diff", &diff_ref])?;
+                (
+                    String::from_utf8_lossy(&output.stdout).to_string(),
+                    format!("PR changes vs {}", base),
+                )
+            };
             if diff.trim().is_empty() {
                 return Err(ReviewError::NoDiff(format!(
                     "no changes vs {} branch",
                     base
                 )));
             }
-            (diff, format!("PR changes vs {}", base))
+            (diff, description)
         }
         ReviewTarget::File(path) => {
             // Try staged first, then unstaged
@@ -130,124 +418,344 @@ fn get_diff(target: &ReviewTarget) -> Result<(String, String), ReviewError> {
                 (diff, format!("changes in {}", path))
             }
         }
+        ReviewTarget::Range(range) => {
+            let diff = range_diff(Path::new("."), range)?;
+            if diff.trim().is_empty() {
+                return Err(ReviewError::NoDiff(format!("no changes in {}", range)));
+            }
+            (diff, format!("changes in {}", range))
+        }
     };
 
+    // Drop files matched by .superego-ignore (generated code etc.)
+    let ignore = IgnorePatterns::discover(Path::new("."));
+    let (diff, ignored) = filter_diff(&diff, &ignore);
+    if diff.trim().is_empty() && ignored > 0 {
+        return Err(ReviewError::NoDiff(format!(
+            "all {} changed file(s) matched {}",
+            ignored,
+            crate::ignore::IGNORE_FILE
+        )));
+    }
+
     Ok((diff, description))
 }
 
-/// Get the base branch for PR comparison
-fn get_base_branch() -> Result<String, ReviewError> {
-    // Try to get the default branch from git
-    let output = Command::new("git")
-        .args(["symbolic-ref", "refs/remotes/origin/HEAD"])
-        .output();
-
-    if let Ok(output) = output {
-        if output.status.success() {
-            let branch = String::from_utf8_lossy(&output.stdout)
-                .trim()
-                .replace("refs/remotes/origin/", "");
-            if !branch.is_empty() {
-                return Ok(branch);
+/// Path a `diff --git a/<old> b/<new>` section applies to
+fn diff_section_path(header: &str) -> Option<&str> {
+    let rest = header.strip_prefix("diff --git ")?;
+    rest.rfind(" b/").map(|i| &rest[i + 3..])
+}
+
+/// Remove per-file sections whose path is ignored; returns the filtered diff
+/// and how many files were dropped
+/// AIDEV-NOTE: `commit <hash> <subject>` lines (from --no-merges patches)
+/// start a new, never-ignored section so they survive when the file diff
+/// before them is dropped.
+fn filter_diff(diff: &str, ignore: &IgnorePatterns) -> (String, usize) {
+    let mut out = String::with_capacity(diff.len());
+    let mut ignored = 0;
+    let mut skipping = false;
+
+    for line in diff.split_inclusive('\n') {
+        if line.starts_with("diff --git ") {
+            skipping = diff_section_path(line.trim_end()).is_some_and(|p| ignore.is_ignored(p));
+            if skipping {
+                ignored += 1;
             }
+        } else if line.starts_with("commit ") {
+            skipping = false;
+        }
+        if !skipping {
+            out.push_str(line);
         }
     }
 
-    // Fall back to checking for main or master
-    let output = Command::new("git")
-        .args(["branch", "-l", "main", "master"])
-        .output()
-        .map_err(|e| ReviewError::GitError(e.to_string()))?;
+    (out, ignored)
+}
 
-    let branches = String::from_utf8_lossy(&output.stdout);
-    if branches.contains("main") {
-        Ok("main".to_string())
-    } else if branches.contains("master") {
-        Ok("master".to_string())
-    } else {
-        Err(ReviewError::GitError(
-            "could not determine base branch".to_string(),
-        ))
+/// Top-level directory of the repository containing `repo`
+fn repo_root(repo: &Path) -> Result<PathBuf, ReviewError> {
+    let output = run_git_in(repo, &["rev-parse", "--show-toplevel"])?;
+    Ok(PathBuf::from(
+        String::from_utf8_lossy(&output.stdout).trim(),
+    ))
+}
+
+/// Untracked files, relative to the repository root
+/// AIDEV-NOTE: `git status` already excludes anything matched by .gitignore
+/// (node_modules, build output), so we never walk the directory ourselves.
+fn untracked_files(root: &Path) -> Result<Vec<String>, ReviewError> {
+    let output = run_git_in(
+        root,
+        &["status", "--porcelain", "-z", "--untracked-files=all"],
+    )?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter_map(|entry| entry.strip_prefix("?? "))
+        .map(|path| path.to_string())
+        .collect())
+}
+
+/// Bytes sniffed for a NUL to tell binary files apart (as git does)
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Why an untracked file's contents are left out of the review, if they are
+fn omit_reason(path: &Path, max_file_bytes: usize) -> Option<String> {
+    let len = path.metadata().map(|m| m.len()).unwrap_or(0);
+    if max_file_bytes > 0 && len > max_file_bytes as u64 {
+        return Some(format!("{} bytes, over max_diff_bytes", len));
     }
+    let mut head = Vec::with_capacity(BINARY_SNIFF_BYTES);
+    let file = std::fs::File::open(path).ok()?;
+    file.take(BINARY_SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+    head.contains(&0).then(|| "binary".to_string())
 }
 
-/// Run a review
-pub fn review(superego_dir: &Path, target: ReviewTarget) -> Result<ReviewResult, ReviewError> {
-    if !superego_dir.exists() {
-        return Err(ReviewError::NotInitialized);
+/// New-file diffs for untracked files not excluded by `.superego-ignore`
+/// Binary files and files over `max_file_bytes` (0 = no cap) are listed
+/// without their contents.
+fn untracked_diff(repo: &Path, max_file_bytes: usize) -> Result<String, ReviewError> {
+    let root = repo_root(repo)?;
+    let ignore = IgnorePatterns::discover(&root);
+    let mut diff = String::new();
+    for path in untracked_files(&root)? {
+        if ignore.is_ignored(&path) {
+            continue;
+        }
+        if let Some(reason) = omit_reason(&root.join(&path), max_file_bytes) {
+            diff.push_str(&format!(
+                "diff --git a/{0} b/{0}\nnew file, contents omitted ({1})\n",
+                path, reason
+            ));
+            continue;
+        }
+        // AIDEV-NOTE: Not run_git_in - `--no-index` exits 1 whenever the files
+        // differ (always, here), and its stderr is only warnings (e.g. CRLF)
+        let output = Command::new("git")
+            .current_dir(&root)
+            .args(["diff", "--no-index", "--", "/dev/null", &path])
+            .output()
+            .map_err(|e| ReviewError::GitError(e.to_string()))?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !matches!(output.status.code(), Some(0 | 1)) {
+            return Err(ReviewError::GitError(stderr.trim().to_string()));
+        }
+        if !stderr.trim().is_empty() {
+            diag::warn(stderr.trim());
+        }
+        diff.push_str(&String::from_utf8_lossy(&output.stdout));
     }
+    Ok(diff)
+}
 
-    // Get the diff
-    let (diff, description) = get_diff(&target)?;
+/// Patches of the branch's own commits since `base`, oldest first
+/// AIDEV-NOTE: `--first-parent` keeps only commits made on the branch itself
+/// (not those brought in by merging base), `--no-merges` drops the merge
+/// commits and their conflict resolutions. The result is a sequence of
+/// per-commit patches rather than one combined diff.
+fn branch_commit_patches(repo: &Path, base: &str) -> Result<String, ReviewError> {
+    let range = format!("{}..HEAD", base);
+    let output = run_git_in(
+        repo,
+        &[
+            "log",
+            "--first-parent",
+            "--no-merges",
+            "--reverse",
+            "-p",
+            "--format=commit %h %s",
+            &range,
+        ],
+    )?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
 
-    // Load the current prompt
-    let prompt_path = superego_dir.join("prompt.md");
-    let system_prompt = if prompt_path.exists() {
-        std::fs::read_to_string(&prompt_path)
-            .unwrap_or_else(|_| prompts::PromptType::Code.content().to_string())
-    } else {
-        prompts::PromptType::Code.content().to_string()
+/// Diff of a commit range (`a..b` or `a...b`; an empty side means HEAD)
+/// Each end is checked with `git rev-parse` first, so a typo gives a clean
+/// "invalid revision" error instead of git's usage dump.
+fn range_diff(repo: &Path, range: &str) -> Result<String, ReviewError> {
+    let (from, to) = range
+        .split_once("...")
+        .or_else(|| range.split_once(".."))
+        .ok_or_else(|| ReviewError::GitError(format!("not a commit range: '{}'", range)))?;
+
+    for rev in [from, to].into_iter().filter(|r| !r.is_empty()) {
+        if !is_commit(repo, rev) {
+            return Err(ReviewError::GitError(format!(
+                "invalid revision '{}' in range '{}'",
+                rev, range
+            )));
+        }
+    }
+
+    let output = run_git_in(repo, &["diff", range, "--"])?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Whether `rev` names a commit in `repo` (options like "--output=x" never do)
+fn is_commit(repo: &Path, rev: &str) -> bool {
+    let commit = format!("{}^{{commit}}", rev);
+    // --quiet fails with an exit status but no stderr, which run_git_in passes through
+    !rev.starts_with('-')
+        && run_git_in(repo, &["rev-parse", "--verify", "--quiet", &commit])
+            .is_ok_and(|out| out.status.success())
+}
+
+/// Get the base branch for PR comparison
+/// An explicit base (`--base` / config `review_base_branch`) skips detection
+/// but must exist locally. Otherwise: origin/HEAD, then `init.defaultBranch`,
+/// then main or master - the latter two only if the local branch exists.
+fn get_base_branch(repo: &Path, explicit: Option<&str>) -> Result<String, ReviewError> {
+    if let Some(base) = explicit {
+        if !is_commit(repo, base) {
+            return Err(ReviewError::GitError(format!(
+                "base branch '{}' does not exist locally (fetch it, or fix --base / review_base_branch)",
+                base
+            )));
+        }
+        return Ok(base.to_string());
+    }
+
+    let git_stdout = |args: &[&str]| {
+        run_git_in(repo, args)
+            .ok()
+            .filter(|out| out.status.success())
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+            .filter(|s| !s.is_empty())
     };
 
-    // Prepare the message
-    let message = format!(
-        "Review the following changes and provide feedback.\n\n\
-        This is an on-demand review requested by the user (not a hook evaluation).\n\
-        Provide constructive feedback - no DECISION/BLOCK format needed, just helpful observations.\n\n\
-        --- CHANGES ({}) ---\n{}\n--- END CHANGES ---",
-        description, diff
-    );
+    // Try to get the default branch from git
+    if let Some(head) = git_stdout(&["symbolic-ref", "refs/remotes/origin/HEAD"]) {
+        return Ok(head.replace("refs/remotes/origin/", ""));
+    }
 
-    // Call the LLM
-    let response = claude::invoke(&system_prompt, &message, claude::ClaudeOptions::default())
-        .map_err(|e| ReviewError::LlmError(e.to_string()))?;
+    let configured = git_stdout(&["config", "init.defaultBranch"]);
+    configured
+        .into_iter()
+        .chain(["main".to_string(), "master".to_string()])
+        .find(|branch| is_commit(repo, &format!("refs/heads/{}", branch)))
+        .ok_or_else(|| {
+            ReviewError::GitError(
+                "could not determine base branch (pass --base or set review_base_branch)"
+                    .to_string(),
+            )
+        })
+}
 
-    Ok(ReviewResult {
-        feedback: response.result,
-        target_description: description,
-    })
+/// System prompt for a review: the requested template, else the evaluation
+/// prompt (prompt.md, prompt.d layers and prompt_vars), plus focus
+fn review_system_prompt(superego_dir: &Path, config: &Config, options: &ReviewOptions) -> String {
+    let system_prompt = match options.prompt {
+        Some(prompt) => prompt.content().to_string(),
+        None => evaluate::load_system_prompt(superego_dir)
+            .unwrap_or_else(|_| PromptType::Code.content().to_string()),
+    };
+    let focus = options.focus.as_deref().or(config.default_focus.as_deref());
+    prompts::with_focus(&system_prompt, focus)
 }
 
-/// Run a review using Codex LLM (for Codex skill)
-pub fn review_codex(
+/// Model for a review: --model, else config `review_model`, else `model`
+/// AIDEV-NOTE: The config model keys name models for the configured `backend`;
+/// when a review overrides the backend, only an explicit --model applies.
+fn review_model(config: &Config, options: &ReviewOptions) -> Option<String> {
+    if options.backend.is_some_and(|b| b != config.backend) {
+        return options.model.clone();
+    }
+    options
+        .model
+        .clone()
+        .or_else(|| config.review_model.clone())
+        .or_else(|| config.model.clone())
+}
+
+/// Run a review
+pub fn review(
     superego_dir: &Path,
     target: ReviewTarget,
+    options: &ReviewOptions,
 ) -> Result<ReviewResult, ReviewError> {
     if !superego_dir.exists() {
         return Err(ReviewError::NotInitialized);
     }
 
+    let config = Config::load(superego_dir);
+
     // Get the diff
-    let (diff, description) = get_diff(&target)?;
+    let (diff, description) = get_diff(&target, &config)?;
+    let system_prompt = review_system_prompt(superego_dir, &config, options);
 
-    // Load the current prompt
-    let prompt_path = superego_dir.join("prompt.md");
-    let system_prompt = if prompt_path.exists() {
-        std::fs::read_to_string(&prompt_path)
-            .unwrap_or_else(|_| prompts::PromptType::Code.content().to_string())
-    } else {
-        prompts::PromptType::Code.content().to_string()
+    // Call the LLM
+    let backend = llm::for_backend(options.backend.unwrap_or(config.backend));
+    let invoke_options = InvokeOptions {
+        model: review_model(&config, options),
+        timeout_ms: config.timeout_ms,
+        disable_prompt_cache: !config.prompt_cache,
+        max_retries: config.max_retries,
+        tools: config.superego_tools.clone(),
+        stream: options.stream,
+        ..Default::default()
+    };
+    let invoke = |description: &str, diff: &str| {
+        let message = review_message(description, diff, options.structured);
+        backend
+            .invoke(&system_prompt, &message, &invoke_options)
+            .map(|response| response.result)
+            .map_err(|e| ReviewError::LlmError(e.to_string()))
     };
 
-    // Prepare the message
-    let message = format!(
-        "Review the following changes and provide feedback.\n\n\
-        This is an on-demand review requested by the user (not a hook evaluation).\n\
-        Provide constructive feedback - no DECISION/BLOCK format needed, just helpful observations.\n\n\
-        --- CHANGES ({}) ---\n{}\n--- END CHANGES ---",
-        description, diff
-    );
-
-    // Call Codex LLM
-    let response = codex_llm::invoke(&system_prompt, &message, None)
-        .map_err(|e| ReviewError::LlmError(e.to_string()))?;
+    // Diffs over max_diff_bytes are reviewed file-by-file in parts
+    let chunks = if config.max_diff_bytes > 0 && diff.len() > config.max_diff_bytes {
+        split_diff(&diff, config.max_diff_bytes)
+    } else {
+        Vec::new()
+    };
+    if chunks.len() <= 1 {
+        return Ok(ReviewResult {
+            feedback: invoke(&description, &diff)?,
+            target_description: description,
+            diff,
+        });
+    }
 
+    let mut feedback = Vec::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let part = format!("{}, part {}/{}", description, i + 1, chunks.len());
+        feedback.push(invoke(&part, &chunk.diff)?);
+    }
+    let file_count: usize = chunks.iter().map(|c| c.files.len()).sum();
     Ok(ReviewResult {
-        feedback: response.result,
-        target_description: description,
+        feedback: combine_chunk_feedback(&chunks, &feedback, options.structured),
+        target_description: format!(
+            "{} ({} files in {} parts)",
+            description,
+            file_count,
+            chunks.len()
+        ),
+        diff,
     })
 }
 
+/// A temporary directory for a test git repo, and a runner for git commands
+/// in it (with a committer identity set); asserts each command succeeds
+#[cfg(test)]
+pub(crate) fn temp_git_repo() -> (tempfile::TempDir, impl Fn(&[&str])) {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path().to_path_buf();
+    let git = move |args: &[&str]| {
+        let output = Command::new("git")
+            .current_dir(&repo)
+            .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+    };
+    (dir, git)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -261,11 +769,385 @@ mod tests {
         ));
         assert!(matches!(
             ReviewTarget::from_arg(Some("pr")),
-            ReviewTarget::Pr
+            ReviewTarget::Pr {
+                no_merges: false,
+                base: None
+            }
         ));
         assert!(matches!(
             ReviewTarget::from_arg(Some("foo.rs")),
             ReviewTarget::File(_)
         ));
     }
+
+    #[test]
+    fn test_with_no_merges_only_for_pr() {
+        assert!(matches!(
+            ReviewTarget::from_arg(Some("pr")).with_no_merges(true),
+            Some(ReviewTarget::Pr {
+                no_merges: true,
+                base: None
+            })
+        ));
+        assert!(ReviewTarget::Staged.with_no_merges(true).is_none());
+        assert!(matches!(
+            ReviewTarget::Staged.with_no_merges(false),
+            Some(ReviewTarget::Staged)
+        ));
+    }
+
+    #[test]
+    fn test_split_diff_packs_whole_files() {
+        let file = |name: &str, body: &str| {
+            format!(
+                "diff --git a/{0} b/{0}\n--- a/{0}\n+++ b/{0}\n@@ -1 +1 @@\n{1}",
+                name, body
+            )
+        };
+        let big = format!("+{}\n", "x".repeat(200));
+        let diff = format!(
+            "commit abc123 Add things\n{}{}{}",
+            file("a.rs", "+a\n"),
+            file("b.rs", "+b\n"),
+            file("big.rs", &big)
+        );
+
+        let chunks = split_diff(&diff, 150);
+        let files: Vec<_> = chunks.iter().map(|c| c.files.clone()).collect();
+        assert_eq!(files, vec![vec!["a.rs", "b.rs"], vec!["big.rs"]]);
+        assert!(chunks[0].diff.starts_with("commit abc123"));
+        assert!(
+            chunks[1].diff.len() > 150,
+            "an oversized file is kept whole"
+        );
+        let rejoined: String = chunks.iter().map(|c| c.diff.as_str()).collect();
+        assert_eq!(rejoined, diff);
+
+        assert_eq!(split_diff(&diff, diff.len()).len(), 1);
+    }
+
+    #[test]
+    fn test_combine_chunk_feedback() {
+        let chunks = vec![
+            DiffChunk {
+                files: vec!["a.rs".to_string(), "b.rs".to_string()],
+                diff: String::new(),
+            },
+            DiffChunk {
+                files: vec!["c.rs".to_string()],
+                diff: String::new(),
+            },
+        ];
+        let text = combine_chunk_feedback(
+            &chunks,
+            &["Looks fine.\n".to_string(), "Missing test.".to_string()],
+            false,
+        );
+        assert_eq!(
+            text,
+            "### Part 1/2: a.rs, b.rs\n\nLooks fine.\n\n### Part 2/2: c.rs\n\nMissing test."
+        );
+
+        // Structured parts merge into one findings array
+        let json = combine_chunk_feedback(
+            &chunks,
+            &[
+                "[]".to_string(),
+                r#"[{"file":"c.rs","line":3,"severity":"error","message":"Panics"}]"#.to_string(),
+            ],
+            true,
+        );
+        let findings = parse_structured_findings(&json).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].file.as_deref(), Some("c.rs"));
+        assert_eq!(findings[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_with_base_only_for_pr() {
+        assert!(matches!(
+            ReviewTarget::from_arg(Some("pr"))
+                .with_no_merges(true)
+                .and_then(|t| t.with_base(Some("develop".to_string()))),
+            Some(ReviewTarget::Pr {
+                no_merges: true,
+                base: Some(b)
+            }) if b == "develop"
+        ));
+        assert!(ReviewTarget::Staged
+            .with_base(Some("develop".to_string()))
+            .is_none());
+        assert!(matches!(
+            ReviewTarget::Staged.with_base(None),
+            Some(ReviewTarget::Staged)
+        ));
+    }
+
+    #[test]
+    fn test_get_base_branch() {
+        let (dir, git) = temp_git_repo();
+        let repo = dir.path();
+        git(&["init", "-q", "-b", "trunk"]);
+        std::fs::write(repo.join("a.txt"), "a\n").unwrap();
+        git(&["add", "a.txt"]);
+        git(&["commit", "-q", "-m", "a"]);
+
+        // Neither origin/HEAD nor main/master: init.defaultBranch names it
+        git(&["config", "init.defaultBranch", "trunk"]);
+        assert_eq!(get_base_branch(repo, None).unwrap(), "trunk");
+
+        // An explicit base skips detection, but must exist
+        git(&["branch", "develop"]);
+        assert_eq!(get_base_branch(repo, Some("develop")).unwrap(), "develop");
+        match get_base_branch(repo, Some("release")) {
+            Err(ReviewError::GitError(msg)) => {
+                assert!(msg.starts_with("base branch 'release' does not exist locally"))
+            }
+            other => panic!("expected GitError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_branch_commit_patches_excludes_merged_base_changes() {
+        let (dir, git) = temp_git_repo();
+        let repo = dir.path();
+        let commit = |file: &str| {
+            std::fs::write(repo.join(file), format!("{}\n", file)).unwrap();
+            git(&["add", file]);
+            git(&["commit", "-q", "-m", file]);
+        };
+
+        git(&["init", "-q", "-b", "main"]);
+        commit("base.txt");
+        git(&["checkout", "-q", "-b", "feature"]);
+        commit("feature_one.txt");
+        git(&["checkout", "-q", "main"]);
+        commit("main_only.txt");
+        git(&["checkout", "-q", "feature"]);
+        git(&["merge", "-q", "--no-ff", "--no-edit", "main"]);
+        commit("feature_two.txt");
+
+        let patches = branch_commit_patches(repo, "main").unwrap();
+        assert!(patches.contains("+++ b/feature_one.txt"));
+        assert!(patches.contains("+++ b/feature_two.txt"));
+        assert!(!patches.contains("main_only.txt"), "got: {}", patches);
+        assert!(!patches.contains("Merge branch"));
+    }
+
+    #[test]
+    fn test_range_target() {
+        assert!(matches!(
+            ReviewTarget::from_arg(Some("HEAD~3..HEAD")),
+            ReviewTarget::Range(r) if r == "HEAD~3..HEAD"
+        ));
+        assert!(ReviewTarget::from_arg(Some("HEAD~1.."))
+            .with_no_merges(true)
+            .is_none());
+
+        let (dir, git) = temp_git_repo();
+        let repo = dir.path();
+        git(&["init", "-q"]);
+        for file in ["one.txt", "two.txt", "three.txt"] {
+            std::fs::write(repo.join(file), format!("{}\n", file)).unwrap();
+            git(&["add", file]);
+            git(&["commit", "-q", "-m", file]);
+        }
+
+        let diff = range_diff(repo, "HEAD~2..HEAD").unwrap();
+        assert!(diff.contains("+++ b/two.txt") && diff.contains("+++ b/three.txt"));
+        assert!(!diff.contains("one.txt"));
+        assert!(range_diff(repo, "HEAD~1..").unwrap().contains("three.txt"));
+
+        match range_diff(repo, "nope..HEAD") {
+            Err(ReviewError::GitError(msg)) => {
+                assert_eq!(msg, "invalid revision 'nope' in range 'nope..HEAD'")
+            }
+            other => panic!("expected GitError, got {:?}", other),
+        }
+        assert!(matches!(
+            range_diff(repo, "--output=x..HEAD"),
+            Err(ReviewError::GitError(_))
+        ));
+    }
+
+    #[test]
+    fn test_untracked_diff_respects_gitignore_and_superego_ignore() {
+        let (dir, git) = temp_git_repo();
+        let repo = dir.path();
+        let write = |path: &str| {
+            let path = repo.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "content\n").unwrap();
+        };
+
+        git(&["init", "-q"]);
+        std::fs::write(repo.join(".gitignore"), "node_modules/\n").unwrap();
+        std::fs::write(repo.join(".superego-ignore"), "generated/\n").unwrap();
+        write("node_modules/pkg/index.js");
+        write("generated/client.rs");
+        write("src/new.rs");
+
+        let diff = untracked_diff(repo, 0).unwrap();
+        assert!(diff.contains("+++ b/src/new.rs"), "got: {}", diff);
+        assert!(!diff.contains("node_modules/pkg"), "got: {}", diff);
+        assert!(!diff.contains("generated/client.rs"), "got: {}", diff);
+    }
+
+    #[test]
+    fn test_untracked_diff_tolerates_warnings_and_omits_binary_and_large_files() {
+        let (dir, git) = temp_git_repo();
+        let repo = dir.path();
+
+        git(&["init", "-q"]);
+        // LF files then make `git diff` warn about CRLF conversion on stderr
+        git(&["config", "core.autocrlf", "true"]);
+        std::fs::write(repo.join("notes.txt"), "hello\n").unwrap();
+        std::fs::write(repo.join("logo.png"), b"\x89PNG\0\0secret").unwrap();
+        std::fs::write(repo.join("dump.sql"), "x".repeat(500)).unwrap();
+
+        let diff = untracked_diff(repo, 100).unwrap();
+        assert!(diff.contains("+hello"), "got: {}", diff);
+        assert!(
+            diff.contains("logo.png\nnew file, contents omitted (binary)"),
+            "got: {}",
+            diff
+        );
+        assert!(!diff.contains("secret"), "got: {}", diff);
+        assert!(
+            diff.contains("dump.sql\nnew file, contents omitted (500 bytes, over max_diff_bytes)"),
+            "got: {}",
+            diff
+        );
+        assert!(!diff.contains("xxxx"), "got: {}", diff);
+    }
+
+    #[test]
+    fn test_review_prompt_override_leaves_prompt_md_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("prompt.md"), "my code prompt").unwrap();
+        let config = Config::default();
+
+        let default = review_system_prompt(dir.path(), &config, &ReviewOptions::default());
+        assert_eq!(default, "my code prompt");
+
+        let options = ReviewOptions {
+            prompt: Some(PromptType::Security),
+            focus: Some("auth".to_string()),
+            ..Default::default()
+        };
+        let security = review_system_prompt(dir.path(), &config, &options);
+        assert!(security.starts_with("# Security Reviewer System Prompt"));
+        assert!(security.ends_with("Pay particular attention to: auth\n"));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("prompt.md")).unwrap(),
+            "my code prompt"
+        );
+    }
+
+    #[test]
+    fn test_review_model_precedence() {
+        let mut config = Config {
+            model: Some("sonnet".to_string()),
+            ..Default::default()
+        };
+        let flag = ReviewOptions {
+            model: Some("haiku".to_string()),
+            ..Default::default()
+        };
+        let none = ReviewOptions::default();
+
+        assert_eq!(review_model(&config, &none).as_deref(), Some("sonnet"));
+        config.review_model = Some("opus".to_string());
+        assert_eq!(review_model(&config, &none).as_deref(), Some("opus"));
+        assert_eq!(review_model(&config, &flag).as_deref(), Some("haiku"));
+
+        // Overriding the backend drops the other backend's config models
+        let codex = ReviewOptions {
+            backend: Some(Backend::Codex),
+            ..Default::default()
+        };
+        assert_eq!(review_model(&config, &codex), None);
+        config.backend = Backend::Codex;
+        assert_eq!(review_model(&config, &codex).as_deref(), Some("opus"));
+    }
+
+    #[test]
+    fn test_filter_diff_drops_ignored_files() {
+        let diff = "\
+commit abc123 Add client
+diff --git a/src/api/generated.rs b/src/api/generated.rs
+--- a/src/api/generated.rs
++++ b/src/api/generated.rs
+@@ -1 +1 @@
+-old
++new
+commit def456 Use client
+diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1 +1 @@
++use api;
+";
+        let ignore = IgnorePatterns::parse("generated.rs\n");
+        let (filtered, ignored) = filter_diff(diff, &ignore);
+        assert_eq!(ignored, 1);
+        assert!(!filtered.contains("generated.rs"));
+        assert!(filtered.contains("commit abc123 Add client\ncommit def456"));
+        assert!(filtered.contains("+use api;"));
+
+        let (unchanged, ignored) = filter_diff(diff, &IgnorePatterns::default());
+        assert_eq!((unchanged.as_str(), ignored), (diff, 0));
+    }
+
+    #[test]
+    fn test_findings_from_structured_and_prose_feedback() {
+        let diff =
+            "--- a/src/db.rs\n+++ b/src/db.rs\n@@ -3,2 +3,3 @@\n+query(&format!(\"{}\", id));\n";
+        let structured = ReviewResult {
+            feedback: "```json\n[\n\
+                {\"file\": \"src/db.rs\", \"line\": 4, \"severity\": \"high\", \"message\": \"SQL built with format!\"},\n\
+                {\"file\": null, \"line\": null, \"message\": \"No tests for the new query.\"}\n\
+                ]\n```"
+                .to_string(),
+            target_description: "staged changes".to_string(),
+            diff: diff.to_string(),
+        };
+        assert_eq!(
+            structured.findings(),
+            vec![
+                Finding {
+                    file: Some("src/db.rs".to_string()),
+                    line: Some(4),
+                    severity: Severity::Error,
+                    message: "SQL built with format!".to_string(),
+                },
+                Finding {
+                    file: None,
+                    line: None,
+                    severity: Severity::Note,
+                    message: "No tests for the new query.".to_string(),
+                },
+            ]
+        );
+
+        // Free-form feedback: one note per paragraph, located via the diff
+        let prose = ReviewResult {
+            feedback: "The query in src/db.rs is injectable.\n\nLooks fine otherwise.".to_string(),
+            ..structured
+        };
+        let findings = prose.findings();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].file.as_deref(), Some("src/db.rs"));
+        assert_eq!(findings[0].line, Some(3));
+        assert_eq!(findings[1].file, None);
+        assert!(review_message("staged", "diff", true).contains("JSON array"));
+    }
+
+    #[test]
+    fn test_output_format_from_arg() {
+        assert_eq!(OutputFormat::from_arg("text"), Some(OutputFormat::Text));
+        assert_eq!(OutputFormat::from_arg("SARIF"), Some(OutputFormat::Sarif));
+        assert_eq!(OutputFormat::from_arg("json"), Some(OutputFormat::Json));
+        assert_eq!(OutputFormat::from_arg("xml"), None);
+    }
 }
@@ -0,0 +1,239 @@
+//! SARIF output for review results
+//!
+//! Converts free-form review feedback into a minimal SARIF 2.1.0 log so
+//! `sg review` can feed code-scanning dashboards (e.g. GitHub code scanning).
+//! AIDEV-NOTE: Structured reviews (`--format json`) carry their own findings.
+//! Otherwise feedback is natural language: each paragraph becomes one result,
+//! located by matching file paths from the diff (and `path:line` references).
+
+use serde_json::{json, Value};
+
+use crate::review::{Finding, ReviewResult, Severity};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const RULE_ID: &str = "superego/review";
+
+/// A file touched by the diff, with the new-side start line of each hunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffFile {
+    pub path: String,
+    pub hunk_starts: Vec<u64>,
+}
+
+/// Parse changed files and hunk start lines from a unified diff
+pub fn parse_diff_files(diff: &str) -> Vec<DiffFile> {
+    let mut files: Vec<DiffFile> = Vec::new();
+    // Whether hunks currently belong to the last entry in `files`
+    let mut in_file = false;
+
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            // Deleted files have "+++ /dev/null" - nothing to point at
+            in_file = path != "/dev/null";
+            if in_file {
+                let path = path.strip_prefix("b/").unwrap_or(path);
+                files.push(DiffFile {
+                    path: path.to_string(),
+                    hunk_starts: Vec::new(),
+                });
+            }
+        } else if let Some(rest) = line.strip_prefix("@@ ") {
+            // "@@ -a,b +c,d @@" - we want c
+            let start = rest
+                .split_whitespace()
+                .find_map(|part| part.strip_prefix('+'))
+                .and_then(|range| range.split(',').next())
+                .and_then(|n| n.parse::<u64>().ok());
+            if let (true, Some(file), Some(start)) = (in_file, files.last_mut(), start) {
+                file.hunk_starts.push(start);
+            }
+        }
+    }
+
+    files
+}
+
+/// Split feedback into individual findings (one per paragraph)
+fn split_findings(feedback: &str) -> Vec<String> {
+    feedback
+        .split("\n\n")
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        // Skip bare headings like "## Summary" - they carry no finding
+        .filter(|p| p.lines().count() > 1 || !p.starts_with('#'))
+        .map(|p| p.to_string())
+        .collect()
+}
+
+/// Parse an explicit line number following `path:` in text
+fn explicit_line(text: &str, path: &str) -> Option<u64> {
+    let needle = format!("{}:", path);
+    let idx = text.find(&needle)?;
+    let digits: String = text[idx + needle.len()..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok().filter(|n| *n > 0)
+}
+
+/// Find the best location for a finding among the diff files
+/// Prefers the longest matching path so `src/a/mod.rs` beats `mod.rs`.
+fn locate(finding: &str, files: &[DiffFile]) -> Option<(String, Option<u64>)> {
+    let file = files
+        .iter()
+        .filter(|f| finding.contains(&f.path))
+        .max_by_key(|f| f.path.len())?;
+
+    let line = explicit_line(finding, &file.path).or_else(|| file.hunk_starts.first().copied());
+    Some((file.path.clone(), line))
+}
+
+/// Findings from free-form feedback: one note per paragraph, located by
+/// matching diff paths in its text
+pub fn infer_findings(feedback: &str, diff: &str) -> Vec<Finding> {
+    let files = parse_diff_files(diff);
+    split_findings(feedback)
+        .into_iter()
+        .map(|text| {
+            let (file, line) = match locate(&text, &files) {
+                Some((path, line)) => (Some(path), line),
+                None => (None, None),
+            };
+            Finding {
+                file,
+                line,
+                severity: Severity::Note,
+                message: text,
+            }
+        })
+        .collect()
+}
+
+/// Build a SARIF result for a single finding
+fn sarif_result(finding: &Finding) -> Value {
+    let mut result = json!({
+        "ruleId": RULE_ID,
+        "level": finding.severity.as_str(),
+        "message": { "text": finding.message },
+    });
+
+    if let Some(path) = &finding.file {
+        let mut physical = json!({
+            "artifactLocation": { "uri": path },
+        });
+        if let Some(line) = finding.line {
+            physical["region"] = json!({ "startLine": line });
+        }
+        result["locations"] = json!([{ "physicalLocation": physical }]);
+    }
+
+    result
+}
+
+/// Convert a review result into a SARIF 2.1.0 log
+pub fn to_sarif(result: &ReviewResult) -> Value {
+    let results: Vec<Value> = result.findings().iter().map(sarif_result).collect();
+    sarif_log(results)
+}
+
+/// Empty SARIF log (nothing to review) - keeps output machine-readable
+pub fn empty_sarif() -> Value {
+    sarif_log(Vec::new())
+}
+
+fn sarif_log(results: Vec<Value>) -> Value {
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "superego",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/cloud-atlas-ai/superego",
+                    "rules": [{
+                        "id": RULE_ID,
+                        "shortDescription": { "text": "Superego review feedback" },
+                    }],
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_DIFF: &str = "\
+diff --git a/src/main.rs b/src/main.rs
+index 111..222 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -10,3 +10,4 @@ fn main() {
++    let x = 1;
+@@ -40,2 +41,3 @@ fn other() {
++    todo!();
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1 +1,2 @@
++pub mod foo;
+diff --git a/old.rs b/old.rs
+--- a/old.rs
++++ /dev/null
+@@ -1 +0,0 @@
+-gone
+";
+
+    #[test]
+    fn test_parse_diff_files() {
+        let files = parse_diff_files(SAMPLE_DIFF);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "src/main.rs");
+        assert_eq!(files[0].hunk_starts, vec![10, 41]);
+        assert_eq!(files[1].path, "src/lib.rs");
+        assert_eq!(files[1].hunk_starts, vec![1]);
+    }
+
+    #[test]
+    fn test_to_sarif_maps_findings_to_locations() {
+        let result = ReviewResult {
+            feedback: "## Review\n\nThe `todo!()` in src/main.rs:42 will panic.\n\n\
+                       New module in src/lib.rs has no tests.\n\n\
+                       Overall the approach is reasonable."
+                .to_string(),
+            target_description: "staged changes".to_string(),
+            diff: SAMPLE_DIFF.to_string(),
+        };
+
+        let sarif = to_sarif(&result);
+        assert_eq!(sarif["version"], "2.1.0");
+
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3, "heading paragraph should be skipped");
+
+        let loc = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(loc["artifactLocation"]["uri"], "src/main.rs");
+        assert_eq!(loc["region"]["startLine"], 42);
+
+        // No explicit line - falls back to first hunk start
+        let loc = &results[1]["locations"][0]["physicalLocation"];
+        assert_eq!(loc["artifactLocation"]["uri"], "src/lib.rs");
+        assert_eq!(loc["region"]["startLine"], 1);
+
+        // No file mentioned - result without location
+        assert!(results[2].get("locations").is_none());
+        assert_eq!(
+            results[2]["message"]["text"],
+            "Overall the approach is reasonable."
+        );
+    }
+
+    #[test]
+    fn test_empty_sarif_has_no_results() {
+        let sarif = empty_sarif();
+        assert!(sarif["runs"][0]["results"].as_array().unwrap().is_empty());
+    }
+}
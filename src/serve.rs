@@ -0,0 +1,269 @@
+//! JSON-RPC 2.0 server over stdin/stdout for editor plugins
+//!
+//! `sg serve` reads one JSON-RPC request per line on stdin and writes one
+//! response per line on stdout, so an editor can keep a single process around
+//! instead of spawning `sg` for every evaluation.
+//!
+//! Methods:
+//! - `evaluate` - params `{transcript_path, session_id?, focus?}` ->
+//!   `{has_concerns, feedback, confidence, cost_usd, second_opinion_cost_usd}`
+//! - `review` - params `{target?, focus?}` -> `{target, feedback}`
+//! - `status` - no params -> `{version, initialized, mode}`
+//!
+//! AIDEV-NOTE: The process (not the config) is what's reused. `evaluate` and
+//! `review` go through `evaluate_llm`/`review::review`, which reload config and
+//! the prompt and pick the backend per request, so edits under .superego apply
+//! without a restart; only `status` reads the config loaded at startup.
+//! Requests without an `id` are notifications and get no reply.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::evaluate::{self, EvaluateOptions};
+use crate::review::{self, ReviewTarget};
+
+/// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Implementation-defined: the evaluation/review itself failed
+const BACKEND_ERROR: i64 = -32000;
+
+/// Params for the `evaluate` method
+#[derive(Debug, Deserialize)]
+pub struct EvaluateParams {
+    pub transcript_path: String,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub focus: Option<String>,
+}
+
+/// Params for the `review` method
+#[derive(Debug, Default, Deserialize)]
+pub struct ReviewParams {
+    /// "staged", "pr", or a file path (default: staged)
+    #[serde(default)]
+    pub target: Option<String>,
+    #[serde(default)]
+    pub focus: Option<String>,
+}
+
+/// Does the actual work behind `evaluate` and `review`
+pub trait RpcBackend {
+    fn evaluate(&self, params: EvaluateParams) -> Result<Value, String>;
+    fn review(&self, params: ReviewParams) -> Result<Value, String>;
+}
+
+/// Backend running the same evaluation/review as the CLI commands
+pub struct SgBackend {
+    superego_dir: PathBuf,
+}
+
+impl SgBackend {
+    pub fn new(superego_dir: &Path) -> Self {
+        SgBackend {
+            superego_dir: superego_dir.to_path_buf(),
+        }
+    }
+}
+
+impl RpcBackend for SgBackend {
+    fn evaluate(&self, params: EvaluateParams) -> Result<Value, String> {
+        let options = EvaluateOptions {
+            focus: params.focus,
+            ..Default::default()
+        };
+        let result = evaluate::evaluate_llm(
+            Path::new(&params.transcript_path),
+            &self.superego_dir,
+            params.session_id.as_deref(),
+            &options,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(json!({
+            "has_concerns": result.has_concerns,
+            "feedback": result.feedback,
+            "confidence": result.confidence.map(|c| c.to_string()),
+            "cost_usd": result.cost_usd,
+            "second_opinion_cost_usd": result.second_opinion_cost_usd,
+        }))
+    }
+
+    fn review(&self, params: ReviewParams) -> Result<Value, String> {
+        let target = ReviewTarget::from_arg(params.target.as_deref());
+        let options = review::ReviewOptions {
+            focus: params.focus,
+            ..Default::default()
+        };
+        let result =
+            review::review(&self.superego_dir, target, &options).map_err(|e| e.to_string())?;
+        Ok(json!({
+            "target": result.target_description,
+            "feedback": result.feedback,
+        }))
+    }
+}
+
+/// A JSON-RPC server bound to one .superego directory
+pub struct Server<'a> {
+    superego_dir: PathBuf,
+    config: Config,
+    backend: &'a dyn RpcBackend,
+}
+
+impl<'a> Server<'a> {
+    pub fn new(superego_dir: &Path, backend: &'a dyn RpcBackend) -> Self {
+        Server {
+            superego_dir: superego_dir.to_path_buf(),
+            config: Config::load(superego_dir),
+            backend,
+        }
+    }
+
+    /// Serve requests until EOF on `input`
+    pub fn run<R: BufRead, W: Write>(&self, input: R, mut output: W) -> std::io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_line(&line) {
+                writeln!(output, "{}", response)?;
+                output.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle one request line; None for notifications
+    pub fn handle_line(&self, line: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(v) => v,
+            Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+
+        let id = request.get("id").cloned();
+        let method = match request.get("method").and_then(|m| m.as_str()) {
+            Some(m) => m,
+            None => {
+                return Some(error_response(
+                    id.unwrap_or(Value::Null),
+                    INVALID_REQUEST,
+                    "missing method",
+                ))
+            }
+        };
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = self.dispatch(method, params);
+        let id = id?;
+        Some(match result {
+            Ok(value) => json!({ "jsonrpc": "2.0", "id": id, "result": value }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    fn dispatch(&self, method: &str, params: Value) -> Result<Value, (i64, String)> {
+        match method {
+            "evaluate" => {
+                let params = parse_params(params)?;
+                self.backend
+                    .evaluate(params)
+                    .map_err(|e| (BACKEND_ERROR, e))
+            }
+            "review" => {
+                let params = if params.is_null() {
+                    ReviewParams::default()
+                } else {
+                    parse_params(params)?
+                };
+                self.backend.review(params).map_err(|e| (BACKEND_ERROR, e))
+            }
+            "status" => Ok(json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "initialized": self.superego_dir.exists(),
+                "mode": self.config.mode.as_str(),
+            })),
+            _ => Err((METHOD_NOT_FOUND, format!("unknown method: {}", method))),
+        }
+    }
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, (i64, String)> {
+    serde_json::from_value(params).map_err(|e| (INVALID_PARAMS, e.to_string()))
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MockBackend;
+
+    impl RpcBackend for MockBackend {
+        fn evaluate(&self, params: EvaluateParams) -> Result<Value, String> {
+            if params.transcript_path == "missing.jsonl" {
+                return Err("transcript not found".to_string());
+            }
+            Ok(json!({ "has_concerns": true, "feedback": params.transcript_path }))
+        }
+
+        fn review(&self, params: ReviewParams) -> Result<Value, String> {
+            Ok(json!({ "target": params.target.unwrap_or_else(|| "staged".to_string()) }))
+        }
+    }
+
+    #[test]
+    fn test_serve_loop_with_canned_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = MockBackend;
+        let server = Server::new(dir.path(), &backend);
+
+        let input = [
+            r#"{"jsonrpc":"2.0","id":1,"method":"status"}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"evaluate","params":{"transcript_path":"t.jsonl"}}"#,
+            "",
+            r#"{"jsonrpc":"2.0","id":3,"method":"review"}"#,
+            r#"{"jsonrpc":"2.0","method":"review","params":{"target":"pr"}}"#,
+            r#"{"jsonrpc":"2.0","id":4,"method":"evaluate","params":{}}"#,
+            r#"{"jsonrpc":"2.0","id":5,"method":"evaluate","params":{"transcript_path":"missing.jsonl"}}"#,
+            r#"{"jsonrpc":"2.0","id":6,"method":"explode"}"#,
+            "not json",
+        ]
+        .join("\n");
+
+        let mut output = Vec::new();
+        server.run(input.as_bytes(), &mut output).unwrap();
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+
+        // The notification (no id) gets no response
+        assert_eq!(responses.len(), 7);
+
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[0]["result"]["mode"], "always");
+        assert_eq!(responses[1]["result"]["feedback"], "t.jsonl");
+        assert_eq!(responses[2]["result"]["target"], "staged");
+        assert_eq!(responses[3]["error"]["code"], INVALID_PARAMS);
+        assert_eq!(responses[4]["error"]["code"], BACKEND_ERROR);
+        assert_eq!(responses[4]["error"]["message"], "transcript not found");
+        assert_eq!(responses[5]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(responses[6]["error"]["code"], PARSE_ERROR);
+        assert_eq!(responses[6]["id"], Value::Null);
+    }
+}
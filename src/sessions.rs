@@ -0,0 +1,428 @@
+//! Per-session metadata for superego
+//!
+//! Sessions live in `.superego/sessions/<id>/`. Ids are opaque (Claude session
+//! UUIDs), so users can attach a human-readable label stored in the session's
+//! state.json and shown by history, retro and audit.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::decision::Journal;
+use crate::state::{StateError, StateManager};
+
+/// Summary of a session directory
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub id: String,
+    pub label: Option<String>,
+    /// Entries in the session's decision journal
+    pub decision_count: usize,
+    /// Read cursor from the session's state.json
+    pub last_evaluated: Option<DateTime<Utc>>,
+    /// Newest modification time of anything in the session directory
+    pub modified: Option<DateTime<Utc>>,
+}
+
+/// Error type for session operations
+#[derive(Debug)]
+pub enum SessionError {
+    NotFound(String),
+    State(StateError),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionError::NotFound(id) => write!(f, "Session not found: {}", id),
+            SessionError::State(e) => write!(f, "State error: {}", e),
+            SessionError::Io(e) => write!(f, "IO error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+impl From<StateError> for SessionError {
+    fn from(e: StateError) -> Self {
+        SessionError::State(e)
+    }
+}
+
+impl From<std::io::Error> for SessionError {
+    fn from(e: std::io::Error) -> Self {
+        SessionError::Io(e)
+    }
+}
+
+/// Directory of an existing session
+/// Ids are single path components, so "../x" can't reach outside sessions/.
+fn session_dir(superego_dir: &Path, id: &str) -> Result<PathBuf, SessionError> {
+    let mut components = Path::new(id).components();
+    let is_name = matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    );
+    let dir = superego_dir.join("sessions").join(id);
+    if !is_name || !dir.is_dir() {
+        return Err(SessionError::NotFound(id.to_string()));
+    }
+    Ok(dir)
+}
+
+/// Newest modification time under `path` (the path itself included)
+fn last_modified(path: &Path) -> Option<DateTime<Utc>> {
+    let own = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(DateTime::<Utc>::from);
+    let children = fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| last_modified(&entry.path()));
+    own.into_iter().chain(children).max()
+}
+
+/// List all session directories with their stats, most recently modified first
+pub fn list(superego_dir: &Path) -> std::io::Result<Vec<SessionInfo>> {
+    let sessions_dir = superego_dir.join("sessions");
+    if !sessions_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut sessions = Vec::new();
+    for entry in fs::read_dir(&sessions_dir)? {
+        let entry = entry?;
+        if !entry.path().is_dir() {
+            continue;
+        }
+        let path = entry.path();
+        let state = StateManager::new(&path).load().unwrap_or_default();
+        sessions.push(SessionInfo {
+            id: entry.file_name().to_string_lossy().to_string(),
+            label: state.label,
+            decision_count: Journal::new(&path).read_all().map(|d| d.len()).unwrap_or(0),
+            last_evaluated: state.last_evaluated,
+            modified: last_modified(&path),
+        });
+    }
+
+    sessions.sort_by(|a, b| b.modified.cmp(&a.modified).then(a.id.cmp(&b.id)));
+    Ok(sessions)
+}
+
+/// Map of session id -> label, for sessions that have one
+pub fn labels(superego_dir: &Path) -> HashMap<String, String> {
+    list(superego_dir)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|s| s.label.map(|l| (s.id, l)))
+        .collect()
+}
+
+/// Set (or clear, with None/empty) a session's label
+pub fn set_label(superego_dir: &Path, id: &str, label: Option<&str>) -> Result<(), SessionError> {
+    let session_dir = session_dir(superego_dir, id)?;
+    let label = label.map(str::trim).filter(|l| !l.is_empty());
+    StateManager::new(&session_dir).update(|s| s.label = label.map(str::to_string))?;
+    Ok(())
+}
+
+/// Files that carry an evaluator conversation from one evaluation to the next:
+/// the Codex thread (`codex_resume_thread`) and, from versions that resumed
+/// Claude sessions, `superego_session`
+const EVALUATOR_SESSION_FILES: &[&str] = &[crate::codex_llm::THREAD_FILE, "superego_session"];
+
+/// Delete saved evaluator session ids (root and every session), so the next
+/// evaluation starts cold. Returns how many were removed.
+pub fn clear_evaluator_sessions(superego_dir: &Path) -> std::io::Result<usize> {
+    let mut dirs = vec![superego_dir.to_path_buf()];
+    let sessions_dir = superego_dir.join("sessions");
+    if sessions_dir.is_dir() {
+        for entry in fs::read_dir(&sessions_dir)? {
+            dirs.push(entry?.path());
+        }
+    }
+
+    let mut removed = 0;
+    for dir in dirs {
+        removed += clear_evaluator_files(&dir)?;
+    }
+    Ok(removed)
+}
+
+/// Delete one session's saved evaluator session ids; other sessions and the
+/// root are left alone. Returns how many were removed.
+pub fn clear_session_evaluator_sessions(
+    superego_dir: &Path,
+    id: &str,
+) -> Result<usize, SessionError> {
+    Ok(clear_evaluator_files(&session_dir(superego_dir, id)?)?)
+}
+
+/// Delete the evaluator session files directly in `dir`
+fn clear_evaluator_files(dir: &Path) -> std::io::Result<usize> {
+    let mut removed = 0;
+    for name in EVALUATOR_SESSION_FILES {
+        let path = dir.join(name);
+        if path.is_file() {
+            fs::remove_file(path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Delete one session's directory (state, decisions, pending feedback)
+/// Other sessions, the prompt and config are left alone.
+pub fn remove(superego_dir: &Path, id: &str) -> Result<(), SessionError> {
+    fs::remove_dir_all(session_dir(superego_dir, id)?)?;
+    Ok(())
+}
+
+/// Sessions `sg prune` would delete from a `list()` (most recent first)
+/// With only `older_than_days`: every session last modified before the cutoff.
+/// With only `keep`: everything after the N most recent. With both, the N most
+/// recent are kept even if older than the cutoff. Sessions whose modification
+/// time is unknown are never pruned by age.
+pub fn prune_candidates(
+    sessions: &[SessionInfo],
+    now: DateTime<Utc>,
+    older_than_days: Option<i64>,
+    keep: Option<usize>,
+) -> Vec<&SessionInfo> {
+    let cutoff = older_than_days.map(|days| now - chrono::Duration::days(days));
+    sessions
+        .iter()
+        .enumerate()
+        .filter(|(i, s)| {
+            let beyond_keep = keep.is_none_or(|keep| *i >= keep);
+            let too_old = cutoff.is_none_or(|cutoff| s.modified.is_some_and(|m| m < cutoff));
+            beyond_keep && too_old
+        })
+        .map(|(_, s)| s)
+        .collect()
+}
+
+/// `sg sessions list` table: name, decision count, last evaluated, last modified
+pub fn format_list(sessions: &[SessionInfo]) -> String {
+    let time = |t: Option<DateTime<Utc>>| {
+        t.map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let names: Vec<String> = sessions
+        .iter()
+        .map(|s| display_name(&s.id, s.label.as_deref()))
+        .collect();
+    let width = names
+        .iter()
+        .map(|n| n.chars().count())
+        .chain(["SESSION".len()])
+        .max()
+        .unwrap_or(0);
+
+    let mut out = format!(
+        "{:width$}  {:>9}  {:16}  {}\n",
+        "SESSION",
+        "DECISIONS",
+        "LAST EVALUATED",
+        "MODIFIED",
+        width = width
+    );
+    for (s, name) in sessions.iter().zip(&names) {
+        out.push_str(&format!(
+            "{:width$}  {:>9}  {:16}  {}\n",
+            name,
+            s.decision_count,
+            time(s.last_evaluated),
+            time(s.modified),
+            width = width
+        ));
+    }
+    out
+}
+
+/// Display form of a session: "label (id)" when labeled, else the id
+pub fn display_name(id: &str, label: Option<&str>) -> String {
+    match label {
+        Some(label) => format!("{} ({})", label, id),
+        None => id.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_labeled_session_surfaces_in_listing() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sessions/aaa")).unwrap();
+        fs::create_dir_all(dir.path().join("sessions/bbb")).unwrap();
+
+        set_label(dir.path(), "bbb", Some("auth refactor")).unwrap();
+
+        let sessions = list(dir.path()).unwrap();
+        assert_eq!(sessions.len(), 2);
+        let bbb = sessions.iter().find(|s| s.id == "bbb").unwrap();
+        assert_eq!(bbb.label.as_deref(), Some("auth refactor"));
+        assert_eq!(
+            display_name(&bbb.id, bbb.label.as_deref()),
+            "auth refactor (bbb)"
+        );
+        assert_eq!(labels(dir.path()).len(), 1);
+
+        // Labels are editable and clearable
+        set_label(dir.path(), "bbb", None).unwrap();
+        assert!(labels(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_list_stats_most_recent_first() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sessions/old")).unwrap();
+        fs::create_dir_all(dir.path().join("sessions/new")).unwrap();
+        let evaluated = DateTime::parse_from_rfc3339("2025-01-15T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        StateManager::new(&dir.path().join("sessions/new"))
+            .update(|s| s.mark_evaluated_at(evaluated))
+            .unwrap();
+        Journal::new(&dir.path().join("sessions/new"))
+            .write(&crate::decision::Decision::feedback_delivered(
+                None,
+                "Concern".to_string(),
+            ))
+            .unwrap();
+        // Backdate the old session so ordering doesn't depend on timing
+        let old = fs::File::open(dir.path().join("sessions/old")).unwrap();
+        old.set_modified(std::time::SystemTime::UNIX_EPOCH).unwrap();
+
+        let sessions = list(dir.path()).unwrap();
+        let ids: Vec<_> = sessions.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["new", "old"]);
+        assert_eq!(sessions[0].decision_count, 1);
+        assert_eq!(sessions[0].last_evaluated, Some(evaluated));
+        assert_eq!(sessions[1].decision_count, 0);
+
+        let table = format_list(&sessions);
+        let lines: Vec<_> = table.lines().collect();
+        assert!(lines[0].starts_with("SESSION  DECISIONS  LAST EVALUATED"));
+        assert!(lines[1].starts_with("new              1  2025-01-15 10:00"));
+        assert!(lines[2].starts_with("old              0  -               "));
+    }
+
+    #[test]
+    fn test_prune_candidates() {
+        let now = Utc::now();
+        let session = |id: &str, days_ago: Option<i64>| SessionInfo {
+            id: id.to_string(),
+            label: None,
+            decision_count: 0,
+            last_evaluated: None,
+            modified: days_ago.map(|d| now - chrono::Duration::days(d)),
+        };
+        // As list() returns them: most recent first, unknown mtime last
+        let sessions = vec![
+            session("a", Some(1)),
+            session("b", Some(10)),
+            session("c", Some(40)),
+            session("d", Some(90)),
+            session("e", None),
+        ];
+        let ids = |pruned: Vec<&SessionInfo>| -> Vec<String> {
+            pruned.into_iter().map(|s| s.id.clone()).collect()
+        };
+
+        assert_eq!(
+            ids(prune_candidates(&sessions, now, Some(30), None)),
+            ["c", "d"]
+        );
+        assert_eq!(
+            ids(prune_candidates(&sessions, now, None, Some(2))),
+            ["c", "d", "e"]
+        );
+        assert_eq!(
+            ids(prune_candidates(&sessions, now, Some(30), Some(3))),
+            ["d"]
+        );
+        assert!(prune_candidates(&sessions, now, None, Some(10)).is_empty());
+    }
+
+    #[test]
+    fn test_set_label_unknown_session() {
+        let dir = tempdir().unwrap();
+        let err = set_label(dir.path(), "missing", Some("x")).unwrap_err();
+        assert!(matches!(err, SessionError::NotFound(_)));
+    }
+
+    #[test]
+    fn test_clear_evaluator_sessions() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sessions/aaa")).unwrap();
+        fs::create_dir_all(dir.path().join("sessions/bbb")).unwrap();
+        fs::write(dir.path().join("superego_session"), "old").unwrap();
+        fs::write(dir.path().join("sessions/aaa/codex_thread"), "t1").unwrap();
+        fs::write(dir.path().join("sessions/bbb/state.json"), "{}").unwrap();
+
+        assert_eq!(clear_evaluator_sessions(dir.path()).unwrap(), 2);
+        assert!(!dir.path().join("sessions/aaa/codex_thread").exists());
+        assert!(dir.path().join("sessions/bbb/state.json").exists());
+        assert_eq!(clear_evaluator_sessions(dir.path()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_clear_session_evaluator_sessions_only_that_session() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sessions/aaa")).unwrap();
+        fs::create_dir_all(dir.path().join("sessions/bbb")).unwrap();
+        fs::write(dir.path().join("superego_session"), "old").unwrap();
+        fs::write(dir.path().join("sessions/aaa/codex_thread"), "t1").unwrap();
+        fs::write(dir.path().join("sessions/bbb/codex_thread"), "t2").unwrap();
+
+        assert_eq!(
+            clear_session_evaluator_sessions(dir.path(), "aaa").unwrap(),
+            1
+        );
+        assert!(!dir.path().join("sessions/aaa/codex_thread").exists());
+        assert!(dir.path().join("sessions/bbb/codex_thread").exists());
+        assert!(dir.path().join("superego_session").exists());
+        assert!(matches!(
+            clear_session_evaluator_sessions(dir.path(), "missing"),
+            Err(SessionError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_remove_only_that_session() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sessions/aaa/decisions")).unwrap();
+        fs::create_dir_all(dir.path().join("sessions/bbb")).unwrap();
+        fs::write(dir.path().join("prompt.md"), "prompt").unwrap();
+
+        remove(dir.path(), "aaa").unwrap();
+        let ids: Vec<_> = list(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(ids, vec!["bbb"]);
+        assert!(dir.path().join("prompt.md").exists());
+
+        assert!(matches!(
+            remove(dir.path(), "aaa"),
+            Err(SessionError::NotFound(_))
+        ));
+        assert!(matches!(
+            remove(dir.path(), "../sessions"),
+            Err(SessionError::NotFound(_))
+        ));
+        assert!(dir.path().join("sessions/bbb").exists());
+    }
+}
@@ -0,0 +1,105 @@
+//! Graceful SIGTERM/SIGINT handling during evaluation
+//!
+//! The handler only records which signal arrived. The Claude and Codex wait
+//! loops poll `interrupted()`, kill the CLI's whole process group and return
+//! an `Interrupted` error, and `evaluate_llm` records the read-time cursor
+//! before the process exits.
+//! AIDEV-NOTE: Without this a cancelled hook left `claude` (and its children)
+//! orphaned, and last_evaluated unchanged, so the next run redid the context.
+
+use std::process::Child;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// Signal number received, 0 if none
+static INTERRUPTED: AtomicI32 = AtomicI32::new(0);
+
+#[cfg(unix)]
+extern "C" fn on_signal(signal: libc::c_int) {
+    // Only async-signal-safe work here
+    INTERRUPTED.store(signal, Ordering::SeqCst);
+}
+
+/// Install SIGTERM/SIGINT handlers for the rest of the process
+pub fn install() {
+    #[cfg(unix)]
+    unsafe {
+        let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        libc::signal(libc::SIGTERM, handler);
+        libc::signal(libc::SIGINT, handler);
+    }
+}
+
+/// The signal received since `install()`, if any
+pub fn interrupted() -> Option<i32> {
+    match INTERRUPTED.load(Ordering::SeqCst) {
+        0 => None,
+        signal => Some(signal),
+    }
+}
+
+/// Conventional exit status for a process ended by `signal`
+pub fn exit_code(signal: i32) -> i32 {
+    128 + signal
+}
+
+/// Kill a child started in its own process group, including its children,
+/// and reap it
+pub fn kill_process_group(child: &mut Child) {
+    #[cfg(unix)]
+    unsafe {
+        // Negative pid = the whole group (the child is its leader)
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+    use std::time::{Duration, Instant};
+
+    /// Whether a pid is gone (or a zombie awaiting its new parent)
+    fn is_dead(pid: &str) -> bool {
+        match std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+            Err(_) => true,
+            Ok(stat) => stat
+                .rsplit(')')
+                .next()
+                .is_some_and(|rest| rest.trim_start().starts_with('Z')),
+        }
+    }
+
+    #[test]
+    fn test_simulated_signal_kills_process_group() {
+        let mut child = Command::new("sh")
+            .args(["-c", "sleep 30 & echo $!; wait"])
+            .stdout(Stdio::piped())
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let mut grandchild = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut grandchild)
+            .unwrap();
+        let grandchild = grandchild.trim().to_string();
+
+        // Simulate delivery
+        on_signal(libc::SIGTERM);
+        assert_eq!(interrupted(), Some(libc::SIGTERM));
+        assert_eq!(exit_code(libc::SIGTERM), 143);
+        INTERRUPTED.store(0, Ordering::SeqCst);
+
+        kill_process_group(&mut child);
+        assert!(child.try_wait().unwrap().is_some(), "child reaped");
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while !is_dead(&grandchild) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(is_dead(&grandchild), "grandchild {} orphaned", grandchild);
+    }
+}
@@ -6,16 +6,27 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::fs::{self, File};
-use std::io::{BufReader, BufWriter};
+use std::fs::{self, File, OpenOptions};
+use std::io::BufReader;
 use std::path::{Path, PathBuf};
 
+use crate::atomic;
+
 /// Current superego state
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct State {
     pub last_evaluated: Option<DateTime<Utc>>,
     #[serde(default)]
     pub disabled: bool,
+    /// When a timed `sg disable --minutes N` ends (None = until `sg enable`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disabled_until: Option<DateTime<Utc>>,
+    /// Human-readable session label (set via `sg sessions label`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// When feedback was last delivered (BLOCK), for `feedback_cooldown_minutes`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_blocked: Option<DateTime<Utc>>,
 }
 
 impl State {
@@ -25,6 +36,38 @@ impl State {
     pub fn mark_evaluated_at(&mut self, timestamp: DateTime<Utc>) {
         self.last_evaluated = Some(timestamp);
     }
+
+    /// Pause evaluation, optionally only until `until`
+    pub fn disable(&mut self, until: Option<DateTime<Utc>>) {
+        self.disabled = true;
+        self.disabled_until = until;
+    }
+
+    /// Resume evaluation
+    pub fn enable(&mut self) {
+        self.disabled = false;
+        self.disabled_until = None;
+    }
+
+    /// Clear a timed disable whose deadline has passed
+    fn expire_disable(&mut self, now: DateTime<Utc>) {
+        if self.disabled_until.is_some_and(|until| now >= until) {
+            self.enable();
+        }
+    }
+
+    /// Record that feedback was delivered at `timestamp`
+    pub fn mark_blocked_at(&mut self, timestamp: DateTime<Utc>) {
+        self.last_blocked = Some(timestamp);
+    }
+
+    /// Whether `now` falls within `minutes` of the last BLOCK (0 = never)
+    pub fn in_feedback_cooldown(&self, now: DateTime<Utc>, minutes: i64) -> bool {
+        minutes > 0
+            && self
+                .last_blocked
+                .is_some_and(|blocked| now < blocked + chrono::Duration::minutes(minutes))
+    }
 }
 
 /// Error type for state operations
@@ -78,28 +121,45 @@ impl StateManager {
 
         let file = File::open(&self.state_path)?;
         let reader = BufReader::new(file);
-        let state = serde_json::from_reader(reader)?;
+        let mut state: State = serde_json::from_reader(reader)?;
+        // AIDEV-NOTE: Timed disables lapse here, so every reader sees the same
+        // answer without anything having to rewrite state.json on expiry.
+        state.expire_disable(Utc::now());
         Ok(state)
     }
 
-    /// Save state to disk
+    /// Save state to disk (atomically: a crash mid-write keeps the old state.json)
     pub fn save(&self, state: &State) -> Result<(), StateError> {
         // Ensure parent directory exists
         if let Some(parent) = self.state_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
-        let file = File::create(&self.state_path)?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, state)?;
+        let json = serde_json::to_vec_pretty(state)?;
+        atomic::write(&self.state_path, &json)?;
         Ok(())
     }
 
     /// Load, modify, and save state atomically
+    /// AIDEV-NOTE: An exclusive advisory lock on `state.json.lock` is held for
+    /// the whole read-modify-write, so two concurrent evaluations can't both
+    /// read the same old state and have the later save silently drop the
+    /// other's change. The lock file is left in place (deleting it would race).
     pub fn update<F>(&self, f: F) -> Result<State, StateError>
     where
         F: FnOnce(&mut State),
     {
+        if let Some(parent) = self.state_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.state_path.with_extension("json.lock"))?;
+        // Released when `lock` is closed at the end of this scope
+        lock.lock()?;
+
         let mut state = self.load()?;
         f(&mut state);
         self.save(&state)?;
@@ -118,6 +178,21 @@ mod tests {
         assert!(!state.disabled);
     }
 
+    #[test]
+    fn test_feedback_cooldown_window() {
+        let blocked = Utc::now();
+        let state = State {
+            last_blocked: Some(blocked),
+            ..Default::default()
+        };
+        let later = |m| blocked + chrono::Duration::minutes(m);
+
+        assert!(state.in_feedback_cooldown(later(4), 5));
+        assert!(!state.in_feedback_cooldown(later(5), 5));
+        assert!(!state.in_feedback_cooldown(later(1), 0), "0 disables");
+        assert!(!State::default().in_feedback_cooldown(blocked, 5));
+    }
+
     #[test]
     fn test_save_and_load() {
         let dir = tempdir().unwrap();
@@ -134,6 +209,49 @@ mod tests {
         assert!(loaded.disabled);
     }
 
+    #[test]
+    fn test_concurrent_updates_not_lost() {
+        let dir = tempdir().unwrap();
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let path = dir.path().to_path_buf();
+                std::thread::spawn(move || {
+                    let manager = StateManager::new(&path);
+                    for _ in 0..20 {
+                        manager
+                            .update(|s| s.label.get_or_insert_with(String::new).push('x'))
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        let label = StateManager::new(dir.path()).load().unwrap().label.unwrap();
+        assert_eq!(label.len(), 8 * 20, "every update survives");
+    }
+
+    #[test]
+    fn test_crash_mid_save_keeps_previous_state() {
+        let dir = tempdir().unwrap();
+        let manager = StateManager::new(dir.path());
+        manager
+            .update(|s| s.label = Some("before".to_string()))
+            .unwrap();
+
+        // A save interrupted after the temp file got half its bytes
+        let partial = dir.path().join(".state.json.99999.tmp");
+        fs::write(&partial, "{\"label\": \"af").unwrap();
+
+        assert_eq!(manager.load().unwrap().label.as_deref(), Some("before"));
+        manager
+            .update(|s| s.label = Some("after".to_string()))
+            .unwrap();
+        assert_eq!(manager.load().unwrap().label.as_deref(), Some("after"));
+    }
+
     #[test]
     fn test_load_missing_returns_default() {
         let dir = tempdir().unwrap();
@@ -158,6 +276,27 @@ mod tests {
         assert!(loaded.disabled);
     }
 
+    #[test]
+    fn test_timed_disable_expires_on_load() {
+        let dir = tempdir().unwrap();
+        let manager = StateManager::new(dir.path());
+
+        manager
+            .update(|s| s.disable(Some(Utc::now() + chrono::Duration::minutes(30))))
+            .unwrap();
+        assert!(manager.load().unwrap().disabled);
+
+        manager
+            .update(|s| s.disable(Some(Utc::now() - chrono::Duration::minutes(1))))
+            .unwrap();
+        let loaded = manager.load().unwrap();
+        assert!(!loaded.disabled);
+        assert!(loaded.disabled_until.is_none());
+
+        manager.update(|s| s.disable(None)).unwrap();
+        assert!(manager.load().unwrap().disabled, "untimed stays disabled");
+    }
+
     #[test]
     fn test_mark_evaluated_at_stores_exact_timestamp() {
         // AIDEV-NOTE: This tests the race condition fix.
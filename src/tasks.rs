@@ -0,0 +1,214 @@
+//! Task tracker integration (ba, bd) for drift detection
+//!
+//! Task state comes from the tracker, not LLM conversation analysis. Each
+//! tracker is a CLI that lists in-progress issues as JSON; all of them (up to
+//! MAX_CURRENT_TASKS) are the current tasks, since several can legitimately be
+//! in flight. Config `task_provider` picks the tracker (default: auto).
+//! AIDEV-NOTE: Adding a tracker = one `TaskProvider` impl plus a
+//! `TaskProviderKind` variant. Only id/title are read from its output.
+
+use serde::Deserialize;
+use std::process::Command;
+
+use crate::config::TaskProviderKind;
+
+/// Cap on in-progress tasks shown to the evaluator
+pub const MAX_CURRENT_TASKS: usize = 5;
+
+/// Issue from a tracker's JSON list output
+#[derive(Debug, Clone, Deserialize)]
+pub struct Task {
+    pub id: String,
+    pub title: String,
+}
+
+/// Message section naming the current tasks for the evaluator (empty if none)
+/// A single task keeps the one-line "CURRENT TASK:" form.
+pub fn context(tasks: &[Task]) -> String {
+    match tasks {
+        [] => String::new(),
+        [task] => format!("CURRENT TASK: {} - {}\n\n", task.id, task.title),
+        _ => {
+            let mut out = String::from("CURRENT TASKS:\n");
+            for task in tasks {
+                out.push_str(&format!("- {} - {}\n", task.id, task.title));
+            }
+            out.push('\n');
+            out
+        }
+    }
+}
+
+/// Error type for task tracker operations
+#[derive(Debug)]
+pub enum TaskError {
+    CommandFailed(String),
+    ParseError(String),
+    NotInitialized,
+}
+
+impl std::fmt::Display for TaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskError::CommandFailed(msg) => write!(f, "task command failed: {}", msg),
+            TaskError::ParseError(msg) => write!(f, "Failed to parse task list: {}", msg),
+            TaskError::NotInitialized => write!(f, "task tracker not initialized in this project"),
+        }
+    }
+}
+
+impl std::error::Error for TaskError {}
+
+/// A task tracker CLI
+pub trait TaskProvider {
+    /// CLI name (for messages)
+    fn name(&self) -> &'static str;
+
+    /// Whether the CLI is installed and set up in this project
+    fn is_initialized(&self) -> bool;
+
+    /// Issues currently in progress
+    fn in_progress(&self) -> Result<Vec<Task>, TaskError>;
+
+    /// In-progress tasks, up to MAX_CURRENT_TASKS (errors = no tasks)
+    fn current_tasks(&self) -> Vec<Task> {
+        let mut tasks = self.in_progress().unwrap_or_default();
+        tasks.truncate(MAX_CURRENT_TASKS);
+        tasks
+    }
+}
+
+/// ba: `ba --json list --status in_progress`
+pub struct Ba;
+
+impl TaskProvider for Ba {
+    fn name(&self) -> &'static str {
+        "ba"
+    }
+
+    fn is_initialized(&self) -> bool {
+        succeeds("ba", &["list"])
+    }
+
+    fn in_progress(&self) -> Result<Vec<Task>, TaskError> {
+        list_json("ba", &["--json", "list", "--status", "in_progress"])
+    }
+}
+
+/// bd (beads): `bd list --status in_progress --json`
+pub struct Bd;
+
+impl TaskProvider for Bd {
+    fn name(&self) -> &'static str {
+        "bd"
+    }
+
+    fn is_initialized(&self) -> bool {
+        succeeds("bd", &["list"])
+    }
+
+    fn in_progress(&self) -> Result<Vec<Task>, TaskError> {
+        list_json("bd", &["list", "--status", "in_progress", "--json"])
+    }
+}
+
+/// Providers `auto` tries, in order
+const AUTO_PROVIDERS: [&dyn TaskProvider; 2] = [&Ba, &Bd];
+
+/// The provider for `kind`; auto = the first initialized one (None if neither is)
+pub fn provider(kind: TaskProviderKind) -> Option<&'static dyn TaskProvider> {
+    match kind {
+        TaskProviderKind::Auto => AUTO_PROVIDERS.into_iter().find(|p| p.is_initialized()),
+        TaskProviderKind::Ba => Some(&Ba),
+        TaskProviderKind::Bd => Some(&Bd),
+    }
+}
+
+/// The current tasks from the configured tracker (empty if none)
+pub fn current_tasks(kind: TaskProviderKind) -> Vec<Task> {
+    provider(kind)
+        .map(|p| p.current_tasks())
+        .unwrap_or_default()
+}
+
+/// Whether `program args` runs and exits successfully
+fn succeeds(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Run a tracker's JSON list command
+fn list_json(program: &str, args: &[&str]) -> Result<Vec<Task>, TaskError> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| TaskError::CommandFailed(format!("{}: {}", program, e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("not initialized") || stderr.contains("No database") {
+            return Err(TaskError::NotInitialized);
+        }
+        return Err(TaskError::CommandFailed(stderr.to_string()));
+    }
+
+    parse_tasks(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse a JSON issue list (empty output = no tasks)
+fn parse_tasks(stdout: &str) -> Result<Vec<Task>, TaskError> {
+    if stdout.trim().is_empty() || stdout.trim() == "[]" {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(stdout).map_err(|e| TaskError::ParseError(format!("{}: {}", e, stdout)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_initialized() {
+        // Depends on whether ba/bd are installed and initialized
+        // Just verify the calls don't panic
+        for provider in AUTO_PROVIDERS {
+            let _ = provider.is_initialized();
+        }
+    }
+
+    #[test]
+    fn test_parse_tasks() {
+        assert!(parse_tasks("").unwrap().is_empty());
+        assert!(parse_tasks("[]\n").unwrap().is_empty());
+        // Extra fields (status, priority, ...) are ignored
+        let tasks =
+            parse_tasks(r#"[{"id":"sg-12","title":"Add drift","status":"in_progress"}]"#).unwrap();
+        assert_eq!(context(&tasks), "CURRENT TASK: sg-12 - Add drift\n\n");
+        assert!(matches!(
+            parse_tasks("not json"),
+            Err(TaskError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_context_lists_every_task() {
+        let task = |id: &str, title: &str| Task {
+            id: id.to_string(),
+            title: title.to_string(),
+        };
+        assert_eq!(context(&[]), "");
+        assert_eq!(
+            context(&[task("sg-1", "Fix parser"), task("sg-2", "Update docs")]),
+            "CURRENT TASKS:\n- sg-1 - Fix parser\n- sg-2 - Update docs\n\n"
+        );
+    }
+
+    #[test]
+    fn test_explicit_provider() {
+        assert_eq!(provider(TaskProviderKind::Bd).unwrap().name(), "bd");
+        assert_eq!(provider(TaskProviderKind::Ba).unwrap().name(), "ba");
+    }
+}
@@ -0,0 +1,222 @@
+//! Aider chat history parser
+//!
+//! Aider appends every session to `.aider.chat.history.md` in the directory it
+//! runs in. It is markdown, not JSON: `# aider chat started at <time>` opens a
+//! session, user input lines start with `#### `, aider's own output (edits
+//! applied, commits, command results) is quoted with `> `, and everything else
+//! is the model's reply.
+//!
+//! AIDEV-NOTE: Markers only count outside fenced code blocks, so a `####` or
+//! `>` line inside a reply's ``` fence stays part of the reply verbatim. Only
+//! the latest session in the file is returned.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::reader::TranscriptError;
+use super::{truncate_chars, ContextLimits};
+
+/// Aider's chat history file name
+pub const HISTORY_FILE: &str = ".aider.chat.history.md";
+
+/// Line that starts a new session in the history file
+const SESSION_MARKER: &str = "# aider chat started at";
+
+/// One block of an aider session
+#[derive(Debug, Clone, PartialEq)]
+pub enum AiderEntry {
+    User(String),
+    Assistant(String),
+    /// Aider's own `> ` output
+    Output(String),
+}
+
+impl AiderEntry {
+    fn new(kind: Kind, text: String) -> Self {
+        match kind {
+            Kind::User => AiderEntry::User(text),
+            Kind::Assistant => AiderEntry::Assistant(text),
+            Kind::Output => AiderEntry::Output(text),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    User,
+    Assistant,
+    Output,
+}
+
+/// Text after a line marker (`#### ` or `> `), or None if the line lacks it
+fn strip_marker<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    if line == marker.trim_end() {
+        return Some("");
+    }
+    line.strip_prefix(marker)
+}
+
+/// Whether a line opens or closes a fenced code block
+fn is_fence(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+/// Parse the latest session of an aider chat history
+pub fn parse_aider_history(content: &str) -> Vec<AiderEntry> {
+    let mut entries = Vec::new();
+    let mut current: Option<(Kind, Vec<&str>)> = None;
+    let mut in_fence = false;
+
+    let flush = |current: &mut Option<(Kind, Vec<&str>)>, entries: &mut Vec<AiderEntry>| {
+        if let Some((kind, lines)) = current.take() {
+            let text = lines.join("\n").trim().to_string();
+            if !text.is_empty() {
+                entries.push(AiderEntry::new(kind, text));
+            }
+        }
+    };
+
+    for line in content.lines() {
+        if in_fence {
+            in_fence = !is_fence(line);
+            if let Some((_, lines)) = current.as_mut() {
+                lines.push(line);
+            }
+            continue;
+        }
+
+        if line.starts_with(SESSION_MARKER) {
+            current = None;
+            entries.clear();
+            continue;
+        }
+
+        let (kind, text) = if let Some(text) = strip_marker(line, "#### ") {
+            (Kind::User, text)
+        } else if let Some(text) = strip_marker(line, "> ") {
+            (Kind::Output, text)
+        } else if line.trim().is_empty() {
+            // Blank lines separate user/output blocks but are part of replies
+            match current.as_mut() {
+                Some((Kind::Assistant, lines)) => lines.push(line),
+                _ => flush(&mut current, &mut entries),
+            }
+            continue;
+        } else {
+            in_fence = is_fence(line);
+            (Kind::Assistant, line)
+        };
+
+        match current.as_mut() {
+            Some((k, lines)) if *k == kind => lines.push(text),
+            _ => {
+                flush(&mut current, &mut entries);
+                current = Some((kind, vec![text]));
+            }
+        }
+    }
+    flush(&mut current, &mut entries);
+
+    entries
+}
+
+/// Read and parse an aider chat history file (latest session)
+pub fn read_aider_transcript(path: &Path) -> Result<Vec<AiderEntry>, TranscriptError> {
+    Ok(parse_aider_history(&fs::read_to_string(path)?))
+}
+
+/// Format aider entries for evaluation context
+/// Uses the same USER/OUTPUT/ASSISTANT layout as `format_gemini_context`.
+pub fn format_aider_context(entries: &[AiderEntry], limits: &ContextLimits) -> String {
+    let mut output = String::new();
+
+    for entry in entries {
+        let (label, text, max) = match entry {
+            AiderEntry::User(text) => ("USER", text, limits.max_message_chars),
+            AiderEntry::Assistant(text) => ("ASSISTANT", text, limits.max_message_chars),
+            AiderEntry::Output(text) => ("OUTPUT", text, limits.max_tool_output_chars),
+        };
+        output.push_str(label);
+        output.push_str(": ");
+        output.push_str(&truncate_chars(text, max));
+        output.push_str("\n\n");
+    }
+
+    output
+}
+
+/// Detect an aider chat history (by file name, or its session header)
+pub fn is_aider_format(path: &Path) -> bool {
+    if path.file_name().is_some_and(|n| n == HISTORY_FILE) {
+        return true;
+    }
+    if path.extension().map(|e| e != "md").unwrap_or(true) {
+        return false;
+    }
+    fs::read_to_string(path)
+        .map(|c| c.trim_start().starts_with(SESSION_MARKER))
+        .unwrap_or(false)
+}
+
+/// The aider chat history in the current directory, if there is one
+pub fn find_latest_aider_session() -> Option<PathBuf> {
+    let path = std::env::current_dir().ok()?.join(HISTORY_FILE);
+    path.is_file().then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::{assert_golden, fixture_path};
+
+    #[test]
+    fn test_format_aider_context_golden() {
+        let path = fixture_path("aider_session.md");
+        assert!(is_aider_format(&path));
+        let entries = read_aider_transcript(&path).unwrap();
+        assert_golden(
+            "aider_session.golden.txt",
+            &format_aider_context(&entries, &ContextLimits::default()),
+        );
+    }
+
+    #[test]
+    fn test_fenced_markers_stay_in_reply() {
+        let history = "\
+# aider chat started at 2025-01-15 10:00:00
+
+#### Add a heading to the README
+#### and quote the license
+
+Here is the change:
+
+```markdown
+#### Installation
+> Licensed under MIT
+```
+
+> Applied edit to README.md
+";
+        assert_eq!(
+            parse_aider_history(history),
+            vec![
+                AiderEntry::User("Add a heading to the README\nand quote the license".to_string()),
+                AiderEntry::Assistant(
+                    "Here is the change:\n\n```markdown\n#### Installation\n> Licensed under MIT\n```"
+                        .to_string()
+                ),
+                AiderEntry::Output("Applied edit to README.md".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_not_aider_format() {
+        assert!(!is_aider_format(&fixture_path("claude_session.jsonl")));
+        let dir = tempfile::tempdir().unwrap();
+        let readme = dir.path().join("README.md");
+        fs::write(&readme, "# Project\n").unwrap();
+        assert!(!is_aider_format(&readme));
+    }
+}
@@ -6,11 +6,19 @@
 //! Format validated against actual session files (codex-cli 0.77.0)
 
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
+use crate::diag;
+
 use super::reader::TranscriptError;
+use super::types::{
+    AssistantContentBlock, AssistantMessage, TranscriptEntry, UserContent, UserContentBlock,
+    UserMessage,
+};
+use super::{truncate_chars, ContextLimits};
 
 /// Top-level entry in a Codex session JSONL file
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -224,11 +232,11 @@ pub fn read_codex_transcript(path: &Path) -> Result<Vec<CodexEntry>, TranscriptE
         match serde_json::from_str::<CodexEntry>(&line) {
             Ok(entry) => entries.push(entry),
             Err(e) => {
-                eprintln!(
-                    "Warning: skipping malformed Codex line {} in transcript: {}",
+                diag::warn(&format!(
+                    "skipping malformed Codex line {} in transcript: {}",
                     line_num + 1,
                     e
-                );
+                ));
             }
         }
     }
@@ -236,32 +244,63 @@ pub fn read_codex_transcript(path: &Path) -> Result<Vec<CodexEntry>, TranscriptE
     Ok(entries)
 }
 
+/// How many emitted user messages may await their second copy
+const RECENT_USER_MSGS: usize = 8;
+
+/// An emitted user message whose other copy (response_item vs event_msg) is still to come
+type UnpairedUserMsg = (String, String);
+
+/// Normalize user text for duplicate comparison (collapse whitespace)
+fn normalize_user_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Check if a response_item user message is Codex-injected context, not user input
+fn is_injected_context(text: &str) -> bool {
+    let trimmed = text.trim_start();
+    trimmed.starts_with("<environment_context>") || trimmed.starts_with("<user_instructions>")
+}
+
+/// User text for a user-message entry, or None if it is not one, is injected
+/// context, or is the second copy of a message already emitted
+/// AIDEV-NOTE: A copy only pairs with an unpaired message of the *other* entry
+/// type, and consumes it - so a message the user really sent twice (two
+/// response_item + event_msg pairs) is emitted twice.
+fn dedup_user_text(entry: &CodexEntry, unpaired: &mut VecDeque<UnpairedUserMsg>) -> Option<String> {
+    if !entry.is_user_message() {
+        return None;
+    }
+    let text = entry
+        .user_text()
+        .filter(|t| entry.entry_type == "event_msg" || !is_injected_context(t))?;
+    let normalized = normalize_user_text(&text);
+    if let Some(idx) = unpaired
+        .iter()
+        .position(|(entry_type, t)| *entry_type != entry.entry_type && *t == normalized)
+    {
+        unpaired.remove(idx);
+        return None;
+    }
+    if unpaired.len() == RECENT_USER_MSGS {
+        unpaired.pop_front();
+    }
+    unpaired.push_back((entry.entry_type.clone(), normalized));
+    Some(text)
+}
+
 /// Format Codex entries for evaluation context
-pub fn format_codex_context(entries: &[CodexEntry]) -> String {
+pub fn format_codex_context(entries: &[CodexEntry], limits: &ContextLimits) -> String {
     let mut output = String::new();
-    let mut seen_user_msg: Option<String> = None;
+    // AIDEV-NOTE: Codex records each user message twice (response_item + event_msg),
+    // not necessarily adjacent - reasoning/tool entries can interleave. Emit whichever
+    // copy comes first and drop its pair when it shows up.
+    let mut recent_user_msgs: VecDeque<UnpairedUserMsg> = VecDeque::with_capacity(RECENT_USER_MSGS);
 
     for entry in entries {
-        // User message (prefer event_msg version to avoid duplicates)
-        if entry.entry_type == "event_msg" && entry.is_user_message() {
-            if let Some(text) = entry.user_text() {
-                // Skip duplicate if same as recent response_item
-                if seen_user_msg.as_ref() != Some(&text) {
-                    output.push_str("USER: ");
-                    // Truncate very long messages
-                    let truncated = if text.len() > 2000 {
-                        format!("{}... [truncated]", &text[..2000])
-                    } else {
-                        text.clone()
-                    };
-                    output.push_str(&truncated);
-                    output.push_str("\n\n");
-                }
-            }
-        } else if entry.entry_type == "response_item" && entry.is_user_message() {
-            if let Some(text) = entry.user_text() {
-                seen_user_msg = Some(text); // Track for dedup
-            }
+        if let Some(text) = dedup_user_text(entry, &mut recent_user_msgs) {
+            output.push_str("USER: ");
+            output.push_str(&truncate_chars(&text, limits.max_message_chars));
+            output.push_str("\n\n");
         }
 
         // Reasoning
@@ -291,25 +330,15 @@ pub fn format_codex_context(entries: &[CodexEntry]) -> String {
 
         // Function outputs (truncated)
         if let Some(out) = entry.function_output() {
-            let truncated = if out.len() > 500 {
-                format!("{}... [truncated]", &out[..500])
-            } else {
-                out
-            };
             output.push_str("OUTPUT: ");
-            output.push_str(&truncated);
+            output.push_str(&truncate_chars(&out, limits.max_tool_output_chars));
             output.push_str("\n\n");
         }
 
         // Agent text responses
         if let Some(text) = entry.agent_text() {
             output.push_str("ASSISTANT: ");
-            let truncated = if text.len() > 2000 {
-                format!("{}... [truncated]", &text[..2000])
-            } else {
-                text
-            };
-            output.push_str(&truncated);
+            output.push_str(&truncate_chars(&text, limits.max_message_chars));
             output.push_str("\n\n");
         }
     }
@@ -420,6 +449,124 @@ pub fn find_latest_codex_session() -> Option<std::path::PathBuf> {
     latest.map(|(_, p)| p)
 }
 
+/// Session id recorded in a Codex session_meta entry, if any
+pub fn codex_session_id(entries: &[CodexEntry]) -> Option<String> {
+    entries
+        .iter()
+        .find(|e| e.entry_type == "session_meta")
+        .and_then(|e| e.payload.get("id")?.as_str().map(|s| s.to_string()))
+}
+
+/// Convert Codex entries into Claude Code transcript entries
+///
+/// Maps user messages to `user`, reasoning/tool calls/agent text to `assistant`
+/// (thinking/tool_use/text blocks) and tool outputs to `user` tool_result blocks,
+/// chained via uuid/parentUuid. Uuids are synthetic (`codex-<n>`).
+/// AIDEV-NOTE: Used by `sg import-codex` so Claude-format tooling can consume
+/// Codex sessions without a per-backend implementation.
+pub fn to_transcript_entries(
+    entries: &[CodexEntry],
+    fallback_session_id: &str,
+) -> Vec<TranscriptEntry> {
+    let session_id = codex_session_id(entries).unwrap_or_else(|| fallback_session_id.to_string());
+    let mut recent_user_msgs: VecDeque<UnpairedUserMsg> = VecDeque::with_capacity(RECENT_USER_MSGS);
+    let mut converted: Vec<TranscriptEntry> = Vec::new();
+
+    let assistant_block = |block_type: &str| AssistantContentBlock {
+        block_type: block_type.to_string(),
+        text: None,
+        thinking: None,
+        name: None,
+        input: None,
+    };
+
+    for entry in entries {
+        let user_content = if let Some(text) = dedup_user_text(entry, &mut recent_user_msgs) {
+            Some(UserContent::Text(text))
+        } else {
+            entry.function_output().map(|out| {
+                UserContent::Blocks(vec![UserContentBlock {
+                    block_type: "tool_result".to_string(),
+                    text: None,
+                    tool_use_id: entry
+                        .payload
+                        .get("call_id")
+                        .and_then(|c| c.as_str())
+                        .map(|c| c.to_string()),
+                    content: Some(serde_json::Value::String(out)),
+                }])
+            })
+        };
+
+        let assistant_content = if user_content.is_some() {
+            None
+        } else if let Some(text) = entry.agent_text() {
+            Some(AssistantContentBlock {
+                text: Some(text),
+                ..assistant_block("text")
+            })
+        } else if let Some((name, args)) = entry.function_call() {
+            let input = serde_json::from_str(&args).unwrap_or(serde_json::Value::String(args));
+            Some(AssistantContentBlock {
+                name: Some(name),
+                input: Some(input),
+                ..assistant_block("tool_use")
+            })
+        } else if entry.is_reasoning() {
+            entry.reasoning_text().map(|text| AssistantContentBlock {
+                thinking: Some(text),
+                ..assistant_block("thinking")
+            })
+        } else {
+            None
+        };
+
+        let uuid = format!("codex-{}", converted.len() + 1);
+        let parent_uuid = (!converted.is_empty()).then(|| format!("codex-{}", converted.len()));
+        let timestamp = entry.timestamp.clone();
+
+        if let Some(content) = user_content {
+            converted.push(TranscriptEntry::User {
+                uuid,
+                parent_uuid,
+                session_id: Some(session_id.clone()),
+                timestamp,
+                is_sidechain: false,
+                message: UserMessage {
+                    role: "user".to_string(),
+                    content,
+                },
+            });
+        } else if let Some(block) = assistant_content {
+            converted.push(TranscriptEntry::Assistant {
+                uuid,
+                parent_uuid,
+                session_id: Some(session_id.clone()),
+                timestamp,
+                is_sidechain: false,
+                message: AssistantMessage {
+                    role: "assistant".to_string(),
+                    content: vec![block],
+                    model: None,
+                },
+            });
+        }
+    }
+
+    converted
+}
+
+/// Write transcript entries as Claude Code JSONL
+pub fn write_transcript(entries: &[TranscriptEntry], path: &Path) -> Result<(), TranscriptError> {
+    let mut out = String::new();
+    for entry in entries {
+        out.push_str(&serde_json::to_string(entry).map_err(std::io::Error::other)?);
+        out.push('\n');
+    }
+    std::fs::write(path, out)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -472,4 +619,134 @@ mod tests {
             Some("file1.txt\nfile2.txt".to_string())
         );
     }
+
+    #[test]
+    fn test_format_codex_context_golden() {
+        let entries =
+            read_codex_transcript(&crate::transcript::fixture_path("codex_session.jsonl")).unwrap();
+        crate::transcript::assert_golden(
+            "codex_session.golden.txt",
+            &format_codex_context(&entries, &ContextLimits::default()),
+        );
+    }
+
+    #[test]
+    fn test_import_roundtrip_keeps_key_turns() {
+        let lines = [
+            r#"{"timestamp":"2025-11-04T00:16:00.000Z","type":"session_meta","payload":{"id":"codex-sess","cwd":"/test"}}"#,
+            r#"{"timestamp":"2025-11-04T00:16:01.000Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"Fix the flaky test"}]}}"#,
+            r#"{"timestamp":"2025-11-04T00:16:01.100Z","type":"event_msg","payload":{"type":"user_message","message":"Fix the flaky test"}}"#,
+            r#"{"timestamp":"2025-11-04T00:16:02.000Z","type":"event_msg","payload":{"type":"agent_reasoning","text":"Check the timing"}}"#,
+            r#"{"timestamp":"2025-11-04T00:16:03.000Z","type":"response_item","payload":{"type":"function_call","name":"shell","arguments":"{\"command\":[\"cargo\",\"test\"]}","call_id":"call_1"}}"#,
+            r#"{"timestamp":"2025-11-04T00:16:04.000Z","type":"response_item","payload":{"type":"function_call_output","call_id":"call_1","output":"1 failed"}}"#,
+            r#"{"timestamp":"2025-11-04T00:16:05.000Z","type":"response_item","payload":{"type":"message","role":"assistant","content":[{"type":"output_text","text":"Added a retry."}]}}"#,
+        ];
+        let entries: Vec<CodexEntry> = lines
+            .iter()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+
+        let converted = to_transcript_entries(&entries, "fallback");
+        assert_eq!(converted.len(), 5, "duplicate user message dropped");
+        assert_eq!(converted[0].session_id(), Some("codex-sess"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("imported.jsonl");
+        write_transcript(&converted, &path).unwrap();
+
+        let read_back = crate::transcript::read_transcript(&path).unwrap();
+        let messages = crate::transcript::get_messages_since(&read_back, None, None);
+        let context = crate::transcript::format_context(&messages);
+
+        assert_eq!(context.matches("USER: Fix the flaky test").count(), 1);
+        assert!(context.contains("THINKING: Check the timing"));
+        assert!(context.contains("TOOLS: shell"));
+        assert!(context.contains("TOOL_RESULT: 1 failed"));
+        assert!(context.contains("ASSISTANT: Added a retry."));
+    }
+
+    #[test]
+    fn test_format_dedupes_interleaved_user_messages() {
+        let lines = [
+            r#"{"type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"Fix the bug"}]}}"#,
+            r#"{"type":"event_msg","payload":{"type":"agent_reasoning","text":"Thinking"}}"#,
+            r#"{"type":"response_item","payload":{"type":"function_call","name":"shell","arguments":"{}"}}"#,
+            r#"{"type":"event_msg","payload":{"type":"user_message","message":"Fix  the bug\n"}}"#,
+            r#"{"type":"event_msg","payload":{"type":"user_message","message":"Now add tests"}}"#,
+        ];
+        let entries: Vec<CodexEntry> = lines
+            .iter()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+
+        let context = format_codex_context(&entries, &ContextLimits::default());
+        assert_eq!(context.matches("USER: Fix").count(), 1, "{}", context);
+        assert_eq!(context.matches("USER: Now add tests").count(), 1);
+    }
+
+    #[test]
+    fn test_format_keeps_repeated_user_turns() {
+        let lines = [
+            r#"{"type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"continue"}]}}"#,
+            r#"{"type":"event_msg","payload":{"type":"user_message","message":"continue"}}"#,
+            r#"{"type":"response_item","payload":{"type":"message","role":"assistant","content":[{"type":"output_text","text":"Done with step 1."}]}}"#,
+            r#"{"type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"continue"}]}}"#,
+            r#"{"type":"event_msg","payload":{"type":"user_message","message":"continue"}}"#,
+        ];
+        let entries: Vec<CodexEntry> = lines
+            .iter()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+
+        let context = format_codex_context(&entries, &ContextLimits::default());
+        assert_eq!(context.matches("USER: continue").count(), 2, "{}", context);
+        let converted = to_transcript_entries(&entries, "fallback");
+        assert_eq!(converted.len(), 3);
+    }
+
+    #[test]
+    fn test_format_skips_injected_environment_context() {
+        let json = r#"{"type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"<environment_context>cwd</environment_context>"}]}}"#;
+        let entries = vec![serde_json::from_str::<CodexEntry>(json).unwrap()];
+        assert!(!format_codex_context(&entries, &ContextLimits::default()).contains("USER:"));
+    }
+
+    #[test]
+    fn test_format_truncates_on_char_boundary() {
+        // 4-byte emoji straddling every byte limit would panic with byte slicing
+        let output = "🦀".repeat(10);
+        let message = format!("a{}", "é".repeat(10));
+        let lines = [
+            format!(
+                r#"{{"type":"response_item","payload":{{"type":"function_call_output","call_id":"c","output":"{}"}}}}"#,
+                output
+            ),
+            format!(
+                r#"{{"type":"event_msg","payload":{{"type":"user_message","message":"{}"}}}}"#,
+                message
+            ),
+        ];
+        let entries: Vec<CodexEntry> = lines
+            .iter()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let limits = ContextLimits {
+            max_tool_output_chars: 3,
+            max_message_chars: 4,
+        };
+
+        let context = format_codex_context(&entries, &limits);
+        assert!(
+            context.contains("OUTPUT: 🦀🦀🦀... [truncated]"),
+            "{}",
+            context
+        );
+        assert!(context.contains("USER: aééé... [truncated]"), "{}", context);
+
+        let unlimited = ContextLimits {
+            max_tool_output_chars: 0,
+            max_message_chars: 0,
+        };
+        assert!(format_codex_context(&entries, &unlimited).contains(&output));
+    }
 }
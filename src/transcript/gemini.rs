@@ -0,0 +1,346 @@
+//! Gemini CLI transcript parser
+//!
+//! Parses Google Gemini CLI chat sessions. Unlike Claude/Codex these are a
+//! single JSON document, not JSONL. Sessions are stored in
+//! ~/.gemini/tmp/<project-hash>/chats/session-*.json
+//!
+//! AIDEV-NOTE: Two shapes are accepted and flattened into `GeminiEntry`:
+//! - chat recordings: `{"sessionId", "messages": [{"type": "user"|"gemini", ...}]}`
+//! - raw API history (`/chat save` checkpoints): `[{"role", "parts": [...]}]`
+
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::reader::TranscriptError;
+use super::{truncate_chars, ContextLimits};
+
+/// One conversation event from a Gemini session
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeminiEntry {
+    User(String),
+    Assistant(String),
+    Thinking(String),
+    ToolCall { name: String, args: Value },
+    ToolResult { name: String, output: String },
+}
+
+/// Text of a message `content`: a string, or a list of strings/`{text}` parts
+fn content_text(content: &Value) -> Option<String> {
+    let text = match content {
+        Value::String(s) => s.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|p| p.as_str().or_else(|| p.get("text")?.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => return None,
+    };
+    (!text.trim().is_empty()).then_some(text)
+}
+
+/// Output of a `functionResponse` part (`response.output`, else the whole response)
+fn function_response_output(response: &Value) -> String {
+    match response.get("output").or_else(|| response.get("error")) {
+        Some(Value::String(s)) => s.clone(),
+        Some(v) => v.to_string(),
+        None => response.to_string(),
+    }
+}
+
+/// Entries from a chat-recording `messages` array
+fn entries_from_messages(messages: &[Value]) -> Vec<GeminiEntry> {
+    let mut entries = Vec::new();
+    for message in messages {
+        match message.get("type").and_then(|t| t.as_str()) {
+            Some("user") => {
+                if let Some(text) = message.get("content").and_then(content_text) {
+                    entries.push(GeminiEntry::User(text));
+                }
+            }
+            Some("gemini") => {
+                for thought in message["thoughts"].as_array().into_iter().flatten() {
+                    let subject = thought["subject"].as_str().unwrap_or("");
+                    let description = thought["description"].as_str().unwrap_or("");
+                    let text = match (subject.is_empty(), description.is_empty()) {
+                        (false, false) => format!("{}: {}", subject, description),
+                        (false, true) => subject.to_string(),
+                        _ => description.to_string(),
+                    };
+                    if !text.is_empty() {
+                        entries.push(GeminiEntry::Thinking(text));
+                    }
+                }
+                if let Some(text) = message.get("content").and_then(content_text) {
+                    entries.push(GeminiEntry::Assistant(text));
+                }
+                for call in message["toolCalls"].as_array().into_iter().flatten() {
+                    let name = call["name"].as_str().unwrap_or("unknown").to_string();
+                    entries.push(GeminiEntry::ToolCall {
+                        name: name.clone(),
+                        args: call["args"].clone(),
+                    });
+                    let output = match &call["resultDisplay"] {
+                        Value::String(s) => Some(s.clone()),
+                        _ => call["result"].as_array().and_then(|parts| {
+                            parts.iter().find_map(|p| {
+                                p.get("functionResponse")
+                                    .map(|r| function_response_output(&r["response"]))
+                            })
+                        }),
+                    };
+                    if let Some(output) = output {
+                        entries.push(GeminiEntry::ToolResult { name, output });
+                    }
+                }
+            }
+            // info/error/warning messages are CLI chrome, not conversation
+            _ => {}
+        }
+    }
+    entries
+}
+
+/// Entries from raw API history (`{"role", "parts"}` contents)
+fn entries_from_contents(contents: &[Value]) -> Vec<GeminiEntry> {
+    let mut entries = Vec::new();
+    for content in contents {
+        let is_model = content["role"].as_str() == Some("model");
+        let mut texts: Vec<&str> = Vec::new();
+        let mut rest: Vec<GeminiEntry> = Vec::new();
+
+        for part in content["parts"].as_array().into_iter().flatten() {
+            if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
+                if part["thought"].as_bool() == Some(true) {
+                    rest.push(GeminiEntry::Thinking(text.to_string()));
+                } else if !text.trim().is_empty() {
+                    texts.push(text);
+                }
+            } else if let Some(call) = part.get("functionCall") {
+                rest.push(GeminiEntry::ToolCall {
+                    name: call["name"].as_str().unwrap_or("unknown").to_string(),
+                    args: call["args"].clone(),
+                });
+            } else if let Some(response) = part.get("functionResponse") {
+                rest.push(GeminiEntry::ToolResult {
+                    name: response["name"].as_str().unwrap_or("unknown").to_string(),
+                    output: function_response_output(&response["response"]),
+                });
+            }
+        }
+
+        // Thoughts come first, then the text, then tool calls/results
+        let (thoughts, others): (Vec<_>, Vec<_>) = rest
+            .into_iter()
+            .partition(|e| matches!(e, GeminiEntry::Thinking(_)));
+        entries.extend(thoughts);
+        if !texts.is_empty() {
+            let text = texts.join("\n");
+            entries.push(if is_model {
+                GeminiEntry::Assistant(text)
+            } else {
+                GeminiEntry::User(text)
+            });
+        }
+        entries.extend(others);
+    }
+    entries
+}
+
+/// Read and parse a Gemini CLI session file
+pub fn read_gemini_transcript(path: &Path) -> Result<Vec<GeminiEntry>, TranscriptError> {
+    let content = fs::read_to_string(path)?;
+    let doc: Value = serde_json::from_str(&content)?;
+
+    let entries = match &doc {
+        Value::Object(_) => doc["messages"]
+            .as_array()
+            .map(|m| entries_from_messages(m))
+            .unwrap_or_default(),
+        Value::Array(contents) => entries_from_contents(contents),
+        _ => Vec::new(),
+    };
+    Ok(entries)
+}
+
+/// Format Gemini entries for evaluation context
+/// Uses the same USER/THINKING/TOOL/OUTPUT/ASSISTANT layout as `format_codex_context`.
+pub fn format_gemini_context(entries: &[GeminiEntry], limits: &ContextLimits) -> String {
+    let mut output = String::new();
+
+    for entry in entries {
+        match entry {
+            GeminiEntry::User(text) => {
+                output.push_str("USER: ");
+                output.push_str(&truncate_chars(text, limits.max_message_chars));
+                output.push_str("\n\n");
+            }
+            GeminiEntry::Thinking(text) => {
+                output.push_str("THINKING: ");
+                output.push_str(text);
+                output.push_str("\n\n");
+            }
+            GeminiEntry::ToolCall { name, args } => {
+                output.push_str("TOOL: ");
+                output.push_str(name);
+                // Shell command or file path, whichever the tool takes
+                let detail = ["command", "file_path", "absolute_path", "path"]
+                    .iter()
+                    .find_map(|key| args.get(key)?.as_str());
+                if let Some(detail) = detail {
+                    output.push(' ');
+                    output.push_str(detail);
+                }
+                output.push('\n');
+            }
+            GeminiEntry::ToolResult { output: out, .. } => {
+                output.push_str("OUTPUT: ");
+                output.push_str(&truncate_chars(out, limits.max_tool_output_chars));
+                output.push_str("\n\n");
+            }
+            GeminiEntry::Assistant(text) => {
+                output.push_str("ASSISTANT: ");
+                output.push_str(&truncate_chars(text, limits.max_message_chars));
+                output.push_str("\n\n");
+            }
+        }
+    }
+
+    output
+}
+
+/// Detect if a file is a Gemini CLI session (vs Claude Code/Codex JSONL)
+pub fn is_gemini_format(path: &Path) -> bool {
+    let path_str = path.to_string_lossy();
+    if path_str.contains(".gemini/") {
+        return true;
+    }
+    // Gemini sessions are a single JSON document; don't slurp JSONL files
+    if path.extension().map(|e| e != "json").unwrap_or(true) {
+        return false;
+    }
+
+    let doc: Value = match fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+    {
+        Some(v) => v,
+        None => return false,
+    };
+    match &doc {
+        Value::Object(_) => doc["messages"].is_array(),
+        Value::Array(contents) => contents
+            .first()
+            .is_some_and(|c| c.get("role").is_some() && c["parts"].is_array()),
+        _ => false,
+    }
+}
+
+/// Find the most recently modified Gemini CLI chat session
+pub fn find_latest_gemini_session() -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    find_latest_session_in(&Path::new(&home).join(".gemini/tmp"))
+}
+
+/// Newest `<project-hash>/chats/*.json` under `tmp_dir`
+fn find_latest_session_in(tmp_dir: &Path) -> Option<PathBuf> {
+    let mut latest: Option<(std::time::SystemTime, PathBuf)> = None;
+
+    for project in fs::read_dir(tmp_dir).ok()?.flatten() {
+        let Ok(chats) = fs::read_dir(project.path().join("chats")) else {
+            continue;
+        };
+        for chat in chats.flatten() {
+            let path = chat.path();
+            if path.extension().map(|e| e != "json").unwrap_or(true) {
+                continue;
+            }
+            if let Ok(modified) = chat.metadata().and_then(|m| m.modified()) {
+                if latest.as_ref().is_none_or(|(t, _)| modified > *t) {
+                    latest = Some((modified, path));
+                }
+            }
+        }
+    }
+
+    latest.map(|(_, p)| p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::{assert_golden, fixture_path};
+
+    #[test]
+    fn test_format_gemini_context_golden() {
+        let path = fixture_path("gemini_session.json");
+        assert!(is_gemini_format(&path));
+        let entries = read_gemini_transcript(&path).unwrap();
+        assert_golden(
+            "gemini_session.golden.txt",
+            &format_gemini_context(&entries, &ContextLimits::default()),
+        );
+    }
+
+    #[test]
+    fn test_parse_api_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint-wip.json");
+        fs::write(
+            &path,
+            r#"[
+  {"role":"user","parts":[{"text":"Rename the config flag"}]},
+  {"role":"model","parts":[{"text":"Looking for usages","thought":true},{"text":"I'll grep first."},{"functionCall":{"name":"run_shell_command","args":{"command":"grep -rn old_flag src"}}}]},
+  {"role":"user","parts":[{"functionResponse":{"name":"run_shell_command","response":{"output":"src/main.rs:3: old_flag"}}}]}
+]"#,
+        )
+        .unwrap();
+
+        assert!(is_gemini_format(&path));
+        let entries = read_gemini_transcript(&path).unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                GeminiEntry::User("Rename the config flag".to_string()),
+                GeminiEntry::Thinking("Looking for usages".to_string()),
+                GeminiEntry::Assistant("I'll grep first.".to_string()),
+                GeminiEntry::ToolCall {
+                    name: "run_shell_command".to_string(),
+                    args: serde_json::json!({"command": "grep -rn old_flag src"}),
+                },
+                GeminiEntry::ToolResult {
+                    name: "run_shell_command".to_string(),
+                    output: "src/main.rs:3: old_flag".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_not_gemini_format() {
+        assert!(!is_gemini_format(&fixture_path("claude_session.jsonl")));
+        assert!(!is_gemini_format(&fixture_path("codex_session.jsonl")));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("package.json");
+        fs::write(&path, r#"{"name":"x"}"#).unwrap();
+        assert!(!is_gemini_format(&path));
+    }
+
+    #[test]
+    fn test_find_latest_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let chats = dir.path().join("abc123/chats");
+        fs::create_dir_all(&chats).unwrap();
+        fs::write(chats.join("session-1.json"), "{}").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(chats.join("session-2.json"), "{}").unwrap();
+        fs::write(dir.path().join("abc123/logs.json"), "[]").unwrap();
+
+        assert_eq!(
+            find_latest_session_in(dir.path()),
+            Some(chats.join("session-2.json"))
+        );
+        assert_eq!(find_latest_session_in(&dir.path().join("missing")), None);
+    }
+}
@@ -1,5 +1,65 @@
+pub mod aider;
 pub mod codex;
+pub mod gemini;
 pub mod reader;
 mod types;
 
 pub use reader::*;
+pub use types::TranscriptEntry;
+
+/// Truncation limits for Codex/Gemini context, in characters (0 = unlimited)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextLimits {
+    /// Cap on each tool output (default: 500)
+    pub max_tool_output_chars: usize,
+    /// Cap on each user/assistant message (default: 2000)
+    pub max_message_chars: usize,
+}
+
+impl Default for ContextLimits {
+    fn default() -> Self {
+        ContextLimits {
+            max_tool_output_chars: 500,
+            max_message_chars: 2000,
+        }
+    }
+}
+
+/// Cut `text` to at most `max` characters, marking the cut; 0 = no limit
+/// AIDEV-NOTE: Counts chars, not bytes - slicing at a byte offset panics when
+/// it lands inside a multi-byte character (emoji, CJK).
+pub fn truncate_chars(text: &str, max: usize) -> String {
+    if max == 0 {
+        return text.to_string();
+    }
+    match text.char_indices().nth(max) {
+        Some((idx, _)) => format!("{}... [truncated]", &text[..idx]),
+        None => text.to_string(),
+    }
+}
+
+/// Compare `actual` against a golden file in `src/transcript/testdata/`
+/// Run with `UPDATE_GOLDEN=1` to rewrite the golden file instead.
+#[cfg(test)]
+pub(crate) fn assert_golden(name: &str, actual: &str) {
+    let path = fixture_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("missing golden file {}: {}", path.display(), e));
+    assert_eq!(
+        actual, expected,
+        "{} differs (rerun with UPDATE_GOLDEN=1 if intended)",
+        name
+    );
+}
+
+/// Path of a test fixture in `src/transcript/testdata/`
+#[cfg(test)]
+pub(crate) fn fixture_path(name: &str) -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("src/transcript/testdata")
+        .join(name)
+}
@@ -3,18 +3,22 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
+use crate::diag;
 use crate::transcript::types::TranscriptEntry;
 
 /// Error type for transcript reading
 #[derive(Debug)]
 pub enum TranscriptError {
     IoError(std::io::Error),
+    /// A whole-document JSON transcript (Gemini) failed to parse
+    JsonError(serde_json::Error),
 }
 
 impl std::fmt::Display for TranscriptError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             TranscriptError::IoError(e) => write!(f, "IO error: {}", e),
+            TranscriptError::JsonError(e) => write!(f, "JSON error: {}", e),
         }
     }
 }
@@ -27,12 +31,23 @@ impl From<std::io::Error> for TranscriptError {
     }
 }
 
+impl From<serde_json::Error> for TranscriptError {
+    fn from(e: serde_json::Error) -> Self {
+        TranscriptError::JsonError(e)
+    }
+}
+
 /// Read and parse a transcript JSONL file
 ///
 /// Skips malformed lines rather than failing entirely
 pub fn read_transcript(path: &Path) -> Result<Vec<TranscriptEntry>, TranscriptError> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
+    read_transcript_from_reader(BufReader::new(File::open(path)?))
+}
+
+/// Read a Claude Code JSONL transcript from any reader (e.g. stdin)
+pub fn read_transcript_from_reader(
+    reader: impl BufRead,
+) -> Result<Vec<TranscriptEntry>, TranscriptError> {
     let mut entries = Vec::new();
 
     for (line_num, line_result) in reader.lines().enumerate() {
@@ -45,11 +60,11 @@ pub fn read_transcript(path: &Path) -> Result<Vec<TranscriptEntry>, TranscriptEr
             Ok(entry) => entries.push(entry),
             Err(e) => {
                 // Log warning but continue - don't fail on malformed lines
-                eprintln!(
-                    "Warning: skipping malformed line {} in transcript: {}",
+                diag::warn(&format!(
+                    "skipping malformed line {} in transcript: {}",
                     line_num + 1,
                     e
-                );
+                ));
             }
         }
     }
@@ -57,6 +72,20 @@ pub fn read_transcript(path: &Path) -> Result<Vec<TranscriptEntry>, TranscriptEr
     Ok(entries)
 }
 
+/// Read a plain-text/markdown conversation export as a single context blob
+///
+/// Returns None if the file is empty. Lines that happen to be JSON (pasted
+/// snippets, logs) are kept as text.
+/// AIDEV-NOTE: Only used behind `--plain`, which is honored as is - no format
+/// guessing. Plain exports have no timestamps, so the whole file is evaluated every time.
+pub fn read_plain_context(path: &Path) -> Result<Option<String>, TranscriptError> {
+    let content = std::fs::read_to_string(path)?;
+    if content.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(content.trim().to_string()))
+}
+
 /// Get messages in a time window, optionally filtered by session
 /// AIDEV-NOTE: Used for carryover context - get messages from a time range
 /// (e.g., last 5 minutes before current evaluation window).
@@ -135,6 +164,70 @@ pub fn get_messages_since<'a>(
     }
 }
 
+/// Drop sub-agent (Task tool) turns, keeping the primary conversation
+/// AIDEV-NOTE: The Claude Code counterpart of codex's `is_user_initiated_session`.
+/// Applied by callers (gated on `exclude_subagents`) rather than inside
+/// `get_messages_since`, which every other context selection shares.
+pub fn exclude_subagents(messages: Vec<&TranscriptEntry>) -> Vec<&TranscriptEntry> {
+    messages.into_iter().filter(|e| !e.is_sidechain()).collect()
+}
+
+/// Text Claude Code injects while plan mode is active
+const PLAN_MODE_MARKER: &str = "plan mode is active";
+
+/// Tools that change files - using one means the agent is past planning
+const EDIT_TOOLS: &[&str] = &["Edit", "MultiEdit", "Write", "NotebookEdit"];
+
+/// Whether the agent is still in plan mode at the end of these messages
+///
+/// True if a plan-mode reminder appears and no ExitPlanMode call or file edit
+/// follows it.
+pub fn in_plan_mode(messages: &[&TranscriptEntry]) -> bool {
+    let mut planning = false;
+    for entry in messages {
+        if entry
+            .user_text()
+            .is_some_and(|t| t.to_lowercase().contains(PLAN_MODE_MARKER))
+        {
+            planning = true;
+        }
+        if entry
+            .tool_uses()
+            .iter()
+            .any(|(name, _)| *name == "ExitPlanMode" || EDIT_TOOLS.contains(name))
+        {
+            planning = false;
+        }
+    }
+    planning
+}
+
+/// Character budget for each entry of `recent_tool_uses`
+const TOOL_USE_SUMMARY_CHARS: usize = 60;
+
+/// The last `limit` tool calls in these messages, oldest first, as "Name(target)"
+pub fn recent_tool_uses(messages: &[&TranscriptEntry], limit: usize) -> Vec<String> {
+    let all: Vec<String> = messages
+        .iter()
+        .flat_map(|entry| entry.tool_uses())
+        .map(|(name, input)| {
+            let summary = tool_summary(name, input);
+            if summary.is_empty() {
+                name.to_string()
+            } else {
+                let first_line = summary.lines().next().unwrap_or("");
+                let mut short: String = first_line.chars().take(TOOL_USE_SUMMARY_CHARS).collect();
+                if short.len() < summary.len() {
+                    short.push('…');
+                }
+                format!("{}({})", name, short)
+            }
+        })
+        .collect();
+    let skip = all.len().saturating_sub(limit);
+    all.into_iter().skip(skip).collect()
+}
+
 /// Keep only the last <system-reminder>...</system-reminder> block, strip others
 /// AIDEV-NOTE: System reminders are injected by Claude Code for workflow/context.
 /// We keep the last one as a signal to superego that guidance exists, but dedupe
@@ -205,72 +298,189 @@ fn tool_summary(name: &str, input: Option<&serde_json::Value>) -> String {
 
 /// Format messages for context (for sending to superego LLM)
 pub fn format_context(messages: &[&TranscriptEntry]) -> String {
+    format_context_within(messages, 0, &FeedbackEchoes::default()).0
+}
+
+/// Paragraphs shorter than this (normalized) are never treated as echoes
+const MIN_ECHO_CHARS: usize = 30;
+
+/// Left in place of removed echoes, so the evaluator knows feedback was there
+const ECHO_MARKER: &str = "[superego feedback omitted]";
+
+/// Superego's own delivered feedback, to strip from transcript text
+/// AIDEV-NOTE: The Stop hook's block reason and the agent quoting it back put
+/// our feedback into the transcript; evaluating it again grades our own advice
+/// (an echo). Matching is per paragraph on normalized text (whitespace, `>`
+/// quote markers and a truncation `…` ignored), so truncated or quoted copies
+/// are caught while the agent's own words around them are kept.
+#[derive(Debug, Default)]
+pub struct FeedbackEchoes {
+    feedback: Vec<String>,
+}
+
+impl FeedbackEchoes {
+    pub fn new<'a>(feedback: impl IntoIterator<Item = &'a str>) -> Self {
+        FeedbackEchoes {
+            feedback: feedback.into_iter().map(normalize_echo).collect(),
+        }
+    }
+
+    fn is_echo(&self, paragraph: &str) -> bool {
+        let normalized = normalize_echo(paragraph);
+        normalized.chars().count() >= MIN_ECHO_CHARS
+            && self.feedback.iter().any(|f| f.contains(&normalized))
+    }
+
+    /// `text` with echoed paragraphs replaced by a marker (one per run)
+    pub fn strip(&self, text: &str) -> String {
+        if self.feedback.is_empty() {
+            return text.to_string();
+        }
+        let mut kept: Vec<&str> = Vec::new();
+        for paragraph in text.split("\n\n") {
+            if !self.is_echo(paragraph) {
+                kept.push(paragraph);
+            } else if kept.last() != Some(&ECHO_MARKER) {
+                kept.push(ECHO_MARKER);
+            }
+        }
+        kept.join("\n\n")
+    }
+}
+
+fn normalize_echo(text: &str) -> String {
+    text.lines()
+        .map(|l| l.trim_start().trim_start_matches('>'))
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches('…')
+        .to_string()
+}
+
+/// Like `format_context`, but trimmed to about `max_chars` characters (0 = no limit)
+/// Returns the context and how many characters were trimmed.
+/// AIDEV-NOTE: Oldest TOOL_RESULT bodies go first (they're the bulk and the
+/// least informative), then whole messages oldest-first. Summaries and the most
+/// recent message are always kept, so the result can still exceed `max_chars`.
+pub fn format_context_within(
+    messages: &[&TranscriptEntry],
+    max_chars: usize,
+    echoes: &FeedbackEchoes,
+) -> (String, usize) {
+    let mut blocks: Vec<String> = messages
+        .iter()
+        .map(|e| format_entry(e, true, echoes))
+        .collect();
+    let full: usize = blocks.iter().map(|b| b.chars().count()).sum();
+    if max_chars == 0 || full <= max_chars {
+        return (blocks.concat(), 0);
+    }
+
+    let mut total = full;
+    for (block, entry) in blocks.iter_mut().zip(messages) {
+        if total <= max_chars {
+            break;
+        }
+        if !entry.tool_results().is_empty() {
+            let without = format_entry(entry, false, echoes);
+            total = total - block.chars().count() + without.chars().count();
+            *block = without;
+        }
+    }
+
+    let last = blocks.len().saturating_sub(1);
+    for (i, (block, entry)) in blocks.iter_mut().zip(messages).enumerate() {
+        if total <= max_chars {
+            break;
+        }
+        if i != last && !entry.is_summary() {
+            total -= block.chars().count();
+            block.clear();
+        }
+    }
+
+    let trimmed = full - total;
+    let mut output = format!("[{} characters of older context trimmed]\n\n", trimmed);
+    output.push_str(&blocks.concat());
+    (output, trimmed)
+}
+
+/// One entry as formatted by `format_context`; without tool results, their
+/// bodies are replaced by a marker
+fn format_entry(
+    entry: &TranscriptEntry,
+    with_tool_results: bool,
+    echoes: &FeedbackEchoes,
+) -> String {
     let mut output = String::new();
 
-    for entry in messages {
-        match entry {
-            TranscriptEntry::Summary { .. } => {
-                if let Some(text) = entry.summary_text() {
-                    output.push_str("SUMMARY: ");
-                    output.push_str(text);
-                    output.push_str("\n\n");
-                }
+    match entry {
+        TranscriptEntry::Summary { .. } => {
+            if let Some(text) = entry.summary_text() {
+                output.push_str("SUMMARY: ");
+                output.push_str(text);
+                output.push_str("\n\n");
             }
-            TranscriptEntry::User { .. } => {
-                // Include tool results (what Claude read/executed)
-                let tool_results = entry.tool_results();
-                if !tool_results.is_empty() {
-                    for (_id, content) in &tool_results {
-                        output.push_str("TOOL_RESULT: ");
+        }
+        TranscriptEntry::User { .. } => {
+            // Include tool results (what Claude read/executed)
+            let tool_results = entry.tool_results();
+            if !tool_results.is_empty() {
+                for (_id, content) in &tool_results {
+                    output.push_str("TOOL_RESULT: ");
+                    if with_tool_results {
                         output.push_str(content);
-                        output.push_str("\n\n");
-                    }
-                }
-
-                if let Some(text) = entry.user_text() {
-                    let cleaned = dedupe_system_reminders(&text);
-                    if !cleaned.is_empty() {
-                        output.push_str("USER: ");
-                        output.push_str(&cleaned);
-                        output.push_str("\n\n");
+                    } else {
+                        output.push_str("[trimmed]");
                     }
+                    output.push_str("\n\n");
                 }
             }
-            TranscriptEntry::Assistant { .. } => {
-                let tool_uses = entry.tool_uses();
 
-                // Include thinking if present (shows Claude's reasoning)
-                if let Some(thinking) = entry.assistant_thinking() {
-                    output.push_str("THINKING: ");
-                    output.push_str(&thinking);
+            if let Some(text) = entry.user_text() {
+                let cleaned = echoes.strip(&dedupe_system_reminders(&text));
+                if !cleaned.is_empty() {
+                    output.push_str("USER: ");
+                    output.push_str(&cleaned);
                     output.push_str("\n\n");
                 }
+            }
+        }
+        TranscriptEntry::Assistant { .. } => {
+            let tool_uses = entry.tool_uses();
+
+            // Include thinking if present (shows Claude's reasoning)
+            if let Some(thinking) = entry.assistant_thinking() {
+                output.push_str("THINKING: ");
+                output.push_str(&thinking);
+                output.push_str("\n\n");
+            }
 
-                if !tool_uses.is_empty() {
-                    output.push_str("TOOLS: ");
-                    for (name, input) in &tool_uses {
-                        output.push_str(name);
-                        let summary = tool_summary(name, *input);
-                        if !summary.is_empty() {
-                            output.push('(');
-                            output.push_str(&summary);
-                            output.push(')');
-                        }
-                        output.push(' ');
+            if !tool_uses.is_empty() {
+                output.push_str("TOOLS: ");
+                for (name, input) in &tool_uses {
+                    output.push_str(name);
+                    let summary = tool_summary(name, *input);
+                    if !summary.is_empty() {
+                        output.push('(');
+                        output.push_str(&summary);
+                        output.push(')');
                     }
-                    output.push('\n');
+                    output.push(' ');
                 }
+                output.push('\n');
+            }
 
-                if let Some(text) = entry.assistant_text() {
-                    output.push_str("ASSISTANT: ");
-                    output.push_str(&text);
-                    output.push_str("\n\n");
-                } else if !tool_uses.is_empty() {
-                    output.push('\n');
-                }
+            if let Some(text) = entry.assistant_text() {
+                output.push_str("ASSISTANT: ");
+                output.push_str(&echoes.strip(&text));
+                output.push_str("\n\n");
+            } else if !tool_uses.is_empty() {
+                output.push('\n');
             }
-            _ => {}
         }
+        _ => {}
     }
 
     output
@@ -280,6 +490,137 @@ pub fn format_context(messages: &[&TranscriptEntry]) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_format_context_golden() {
+        let entries =
+            read_transcript(&crate::transcript::fixture_path("claude_session.jsonl")).unwrap();
+
+        let messages = get_messages_since(&entries, None, Some("sess-a"));
+        crate::transcript::assert_golden("claude_session.golden.txt", &format_context(&messages));
+
+        // Without a session filter: summaries and every session's messages
+        let messages = get_messages_since(&entries, None, None);
+        crate::transcript::assert_golden(
+            "claude_session_all.golden.txt",
+            &format_context(&messages),
+        );
+
+        // Cutoff keeps only messages strictly after it
+        let cutoff = DateTime::parse_from_rfc3339("2025-01-15T10:00:04Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let messages = get_messages_since(&entries, Some(cutoff), Some("sess-a"));
+        crate::transcript::assert_golden(
+            "claude_session_since.golden.txt",
+            &format_context(&messages),
+        );
+    }
+
+    #[test]
+    fn test_feedback_echoes_strip() {
+        let echoes = FeedbackEchoes::new([
+            "Add a test for the empty-input case.\n\nAlso check that the parser rejects trailing commas.",
+        ]);
+        let text =
+            "Good catch.\n\n> Also check that the parser\n> rejects trailing commas.\n\nDone.";
+        assert_eq!(
+            echoes.strip(text),
+            "Good catch.\n\n[superego feedback omitted]\n\nDone."
+        );
+        // Short paragraphs are never treated as echoes
+        assert_eq!(echoes.strip("Add a test"), "Add a test");
+        assert_eq!(FeedbackEchoes::default().strip(text), text);
+    }
+
+    #[test]
+    fn test_format_context_within_trims_oldest_first() {
+        let entries =
+            read_transcript(&crate::transcript::fixture_path("claude_session.jsonl")).unwrap();
+        let messages = get_messages_since(&entries, None, None);
+        let full = format_context(&messages);
+
+        let none = FeedbackEchoes::default();
+        assert_eq!(
+            format_context_within(&messages, 0, &none),
+            (full.clone(), 0)
+        );
+        assert_eq!(
+            format_context_within(&messages, full.len(), &none),
+            (full, 0)
+        );
+
+        // Just over budget: only the oldest tool result body goes
+        let (text, trimmed) = format_context_within(&messages, 480, &none);
+        assert!(trimmed > 0);
+        assert!(text.starts_with(&format!("[{} characters", trimmed)));
+        assert!(text.contains("TOOL_RESULT: [trimmed]"));
+        assert!(!text.contains("pub fn fetch"));
+        assert!(text.contains("test result: ok"));
+        assert!(text.contains("USER: Unrelated work"));
+
+        // Far over budget: older messages go too, summary and latest stay
+        let (text, _) = format_context_within(&messages, 50, &none);
+        assert!(text.contains("SUMMARY: Adding retry logic"));
+        assert!(text.contains("ASSISTANT: Other session reply"));
+        assert!(!text.contains("USER: Unrelated work"));
+    }
+
+    #[test]
+    fn test_read_transcript_from_reader() {
+        let input = "{\"type\":\"summary\",\"summary\":\"x\"}\n\nnot json\n{\"type\":\"user\",\"uuid\":\"u\",\"message\":{\"role\":\"user\",\"content\":\"hi\"}}\n";
+        let entries = read_transcript_from_reader(std::io::Cursor::new(input)).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].is_summary());
+        assert!(entries[1].is_user());
+    }
+
+    #[test]
+    fn test_exclude_subagents() {
+        let parse = |json: &str| -> TranscriptEntry { serde_json::from_str(json).unwrap() };
+        let main = parse(
+            r#"{"type":"user","uuid":"u","sessionId":"s","message":{"role":"user","content":"Refactor the parser"}}"#,
+        );
+        let task = parse(
+            r#"{"type":"user","uuid":"t","sessionId":"s","isSidechain":true,"message":{"role":"user","content":"Find all callers of parse()"}}"#,
+        );
+        let task_reply = parse(
+            r#"{"type":"assistant","uuid":"r","sessionId":"s","isSidechain":true,"message":{"role":"assistant","content":[{"type":"text","text":"Found 3 callers"}]}}"#,
+        );
+        assert!(!main.is_sidechain());
+        assert!(task.is_sidechain() && task_reply.is_sidechain());
+
+        let kept = exclude_subagents(vec![&main, &task, &task_reply]);
+        assert_eq!(format_context(&kept), "USER: Refactor the parser\n\n");
+    }
+
+    #[test]
+    fn test_in_plan_mode() {
+        let parse = |json: &str| -> TranscriptEntry { serde_json::from_str(json).unwrap() };
+        let reminder = parse(
+            r#"{"type":"user","uuid":"u","sessionId":"s","message":{"role":"user","content":"<system-reminder>Plan mode is active. Do not make edits.</system-reminder>Plan the refactor"}}"#,
+        );
+        let read = parse(
+            r#"{"type":"assistant","uuid":"a","sessionId":"s","message":{"role":"assistant","content":[{"type":"tool_use","name":"Read","input":{"file_path":"src/lib.rs"}}]}}"#,
+        );
+        let exit = parse(
+            r#"{"type":"assistant","uuid":"b","sessionId":"s","message":{"role":"assistant","content":[{"type":"tool_use","name":"ExitPlanMode","input":{"plan":"1. Split module"}}]}}"#,
+        );
+        let edit = parse(
+            r#"{"type":"assistant","uuid":"c","sessionId":"s","message":{"role":"assistant","content":[{"type":"tool_use","name":"Edit","input":{"file_path":"src/lib.rs"}}]}}"#,
+        );
+
+        assert!(in_plan_mode(&[&reminder, &read]));
+        assert!(!in_plan_mode(&[&reminder, &read, &exit]));
+        assert!(!in_plan_mode(&[&reminder, &edit]));
+        assert!(!in_plan_mode(&[&read]));
+
+        assert_eq!(
+            recent_tool_uses(&[&reminder, &read, &exit, &edit], 2),
+            vec!["ExitPlanMode", "Edit(src/lib.rs)"]
+        );
+        assert!(recent_tool_uses(&[&reminder], 5).is_empty());
+    }
+
     #[test]
     fn test_parse_user_entry() {
         let json = r#"{"type":"user","uuid":"abc","parentUuid":null,"sessionId":"sess-1","timestamp":"2025-01-15T10:00:00Z","message":{"role":"user","content":"hello"}}"#;
@@ -304,6 +645,37 @@ mod tests {
         assert!(matches!(entry, TranscriptEntry::Unknown));
     }
 
+    #[test]
+    fn test_read_plain_context_markdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chat.md");
+        std::fs::write(&path, "## User\n\nFix the bug\n\n## Assistant\n\nDone.\n").unwrap();
+
+        let context = read_plain_context(&path).unwrap();
+        assert_eq!(
+            context.as_deref(),
+            Some("## User\n\nFix the bug\n\n## Assistant\n\nDone.")
+        );
+    }
+
+    #[test]
+    fn test_read_plain_context_keeps_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chat.log");
+        std::fs::write(
+            &path,
+            "User: the API returns\n{\"error\":\"not found\"}\nwhy?\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_plain_context(&path).unwrap().as_deref(),
+            Some("User: the API returns\n{\"error\":\"not found\"}\nwhy?")
+        );
+
+        std::fs::write(&path, "  \n").unwrap();
+        assert!(read_plain_context(&path).unwrap().is_none());
+    }
+
     #[test]
     fn test_dedupe_system_reminders_single() {
         // Single reminder is kept
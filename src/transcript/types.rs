@@ -24,6 +24,13 @@ pub enum TranscriptEntry {
         #[serde(rename = "sessionId")]
         session_id: Option<String>,
         timestamp: Option<String>,
+        /// Sub-agent (Task tool) turn rather than the primary conversation
+        #[serde(
+            rename = "isSidechain",
+            default,
+            skip_serializing_if = "std::ops::Not::not"
+        )]
+        is_sidechain: bool,
         message: UserMessage,
     },
     /// Assistant message
@@ -34,6 +41,13 @@ pub enum TranscriptEntry {
         #[serde(rename = "sessionId")]
         session_id: Option<String>,
         timestamp: Option<String>,
+        /// Sub-agent (Task tool) turn rather than the primary conversation
+        #[serde(
+            rename = "isSidechain",
+            default,
+            skip_serializing_if = "std::ops::Not::not"
+        )]
+        is_sidechain: bool,
         message: AssistantMessage,
     },
     /// Catch-all for unknown types
@@ -108,6 +122,15 @@ impl TranscriptEntry {
         }
     }
 
+    /// Check if this came from a sub-agent (Claude Code's `isSidechain`)
+    pub fn is_sidechain(&self) -> bool {
+        match self {
+            TranscriptEntry::User { is_sidechain, .. } => *is_sidechain,
+            TranscriptEntry::Assistant { is_sidechain, .. } => *is_sidechain,
+            _ => false,
+        }
+    }
+
     /// Check if this is a user message
     pub fn is_user(&self) -> bool {
         matches!(self, TranscriptEntry::User { .. })
@@ -0,0 +1,148 @@
+//! Release check for `sg check --check-updates`
+//!
+//! Compares the running version against the latest GitHub release and
+//! suggests an upgrade when it's behind.
+//! AIDEV-NOTE: Opt-in and best-effort - `sg check` must keep working offline,
+//! so a failed lookup is a warning and never changes the exit code.
+
+use std::time::Duration;
+
+use attohttpc::ProxySettings;
+use serde::Deserialize;
+
+/// GitHub API endpoint for the latest superego release
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/cloud-atlas-ai/superego/releases/latest";
+
+/// Timeout for the release lookup
+const TIMEOUT_SECS: u64 = 5;
+
+/// Version of the running binary
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Error type for the release lookup
+#[derive(Debug)]
+pub enum UpdateError {
+    RequestFailed(String),
+    HttpError { status: u16 },
+    ParseError(String),
+}
+
+impl std::fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpdateError::RequestFailed(msg) => write!(f, "Release lookup failed: {}", msg),
+            UpdateError::HttpError { status } => {
+                write!(f, "Release lookup returned HTTP {}", status)
+            }
+            UpdateError::ParseError(msg) => write!(f, "Failed to parse release: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for UpdateError {}
+
+/// The fields of a GitHub release we read
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+}
+
+/// Latest published version (tag without the leading "v")
+pub fn latest_version() -> Result<String, UpdateError> {
+    latest_version_from(LATEST_RELEASE_URL)
+}
+
+fn latest_version_from(url: &str) -> Result<String, UpdateError> {
+    let response = attohttpc::get(url)
+        .timeout(Duration::from_secs(TIMEOUT_SECS))
+        .proxy_settings(ProxySettings::from_env())
+        // GitHub rejects API requests without a User-Agent
+        .header("User-Agent", format!("superego/{}", CURRENT_VERSION))
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .map_err(|e| UpdateError::RequestFailed(e.to_string()))?;
+
+    if !response.is_success() {
+        return Err(UpdateError::HttpError {
+            status: response.status().as_u16(),
+        });
+    }
+    let release: Release = response
+        .json()
+        .map_err(|e| UpdateError::ParseError(e.to_string()))?;
+    Ok(release.tag_name.trim_start_matches('v').to_string())
+}
+
+/// Numeric components of a version ("0.9.7" -> [0, 9, 7]); None if not numeric
+/// A pre-release/build suffix ("1.0.0-rc1") is ignored.
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let core = version
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default();
+    core.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// Whether `latest` is a newer version than `current` (unparseable = no)
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// How to upgrade, for the check output
+pub fn upgrade_hint(latest: &str) -> String {
+    format!(
+        "sg {} is available (running {}). Upgrade with:\n  \
+         brew upgrade superego        # Homebrew\n  \
+         cargo install superego       # Cargo",
+        latest, CURRENT_VERSION
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("0.10.0", "0.9.7"));
+        assert!(is_newer("v1.0.0", "0.9.7"));
+        assert!(is_newer("0.9.8-rc1", "0.9.7"));
+        assert!(!is_newer("0.9.7", "0.9.7"));
+        assert!(!is_newer("0.9.6", "0.9.7"));
+        assert!(!is_newer("nightly", "0.9.7"));
+    }
+
+    #[test]
+    fn test_latest_version_reads_tag() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/releases/latest", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let n = stream.read(&mut buf).unwrap();
+            let body = r#"{"tag_name":"v0.10.1","name":"0.10.1"}"#;
+            stream
+                .write_all(
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                    .as_bytes(),
+                )
+                .unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_string()
+        });
+
+        assert_eq!(latest_version_from(&url).unwrap(), "0.10.1");
+        let request = server.join().unwrap().to_lowercase();
+        assert!(request.contains("user-agent: superego/"));
+    }
+}